@@ -0,0 +1,97 @@
+/*
+ * Copyright (c) 2015, <daggerbot@gmail.com>
+ * This software is available under the terms of the zlib license.
+ * See COPYING.TXT for more information.
+ *
+ * C API of the termbox crate, available when it is built with the `capi` feature. See the
+ * documentation of `termbox::capi` for details.
+ */
+
+#ifndef TERMBOX_RS_H
+#define TERMBOX_RS_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define TBRS_EVENT_NONE 0
+#define TBRS_EVENT_KEY 1
+#define TBRS_EVENT_RESIZE 2
+#define TBRS_EVENT_MOUSE 3
+#define TBRS_EVENT_PASTE_START 4
+#define TBRS_EVENT_PASTE_END 5
+#define TBRS_EVENT_SIGNAL 6
+#define TBRS_EVENT_RESUMED 7
+#define TBRS_EVENT_RESUME_FAILED 8
+#define TBRS_EVENT_OTHER 9
+
+#define TBRS_MOUSE_LEFT 0
+#define TBRS_MOUSE_RIGHT 1
+#define TBRS_MOUSE_MIDDLE 2
+#define TBRS_MOUSE_RELEASE 3
+#define TBRS_MOUSE_WHEEL_UP 4
+#define TBRS_MOUSE_WHEEL_DOWN 5
+#define TBRS_MOUSE_WHEEL_LEFT 6
+#define TBRS_MOUSE_WHEEL_RIGHT 7
+
+#define TBRS_INPUT_ESC 1
+#define TBRS_INPUT_ALT 2
+
+#define TBRS_OUTPUT_NORMAL 1
+#define TBRS_OUTPUT_256 2
+#define TBRS_OUTPUT_216 3
+#define TBRS_OUTPUT_GRAYSCALE 4
+
+#define TBRS_SIGNAL_INTERRUPT 0
+#define TBRS_SIGNAL_TERMINATE 1
+
+#define TBRS_EUNSUPPORTED_TERMINAL -1
+#define TBRS_EFAILED_TO_OPEN_TTY -2
+#define TBRS_EPIPE_TRAP_ERROR -3
+#define TBRS_ELOCKED -4
+
+/* Opaque handle returned by tbrs_open. */
+typedef struct tbrs tbrs;
+
+typedef struct tbrs_event {
+  int kind;
+  uint16_t key;
+  uint32_t ch;
+  int alt;
+  int w;
+  int h;
+  int x;
+  int y;
+  int button;
+  int signal;
+  int shift;
+  int ctrl;
+} tbrs_event;
+
+void tbrs_change_cell(tbrs *tb, int x, int y, uint32_t ch, uint16_t fg, uint16_t bg);
+void tbrs_clear(tbrs *tb);
+void tbrs_close(tbrs *tb);
+int tbrs_height(tbrs *tb);
+tbrs *tbrs_open(int *error);
+int tbrs_peek_event(tbrs *tb, tbrs_event *event, int timeout);
+int tbrs_poll_event(tbrs *tb, tbrs_event *event);
+void tbrs_present(tbrs *tb);
+int tbrs_put_str(tbrs *tb, int x, int y, const char *msg, uint16_t fg, uint16_t bg);
+uint16_t tbrs_rgb(tbrs *tb, uint8_t r, uint8_t g, uint8_t b);
+/* Does nothing unless the crate is built with the `pure-rust` feature. */
+void tbrs_set_bracketed_paste(tbrs *tb, int enabled);
+void tbrs_set_clear_attributes(tbrs *tb, uint16_t fg, uint16_t bg);
+int tbrs_set_clipboard(tbrs *tb, const char *text);
+void tbrs_set_cursor(tbrs *tb, int x, int y);
+void tbrs_set_input_mode(tbrs *tb, int mode);
+void tbrs_set_mouse_enabled(tbrs *tb, int enabled);
+void tbrs_set_output_mode(tbrs *tb, int mode);
+int tbrs_width(tbrs *tb);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
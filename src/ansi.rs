@@ -0,0 +1,303 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Imports text colored with SGR escape sequences, such as the output of `ls --color` or of a
+//! syntax highlighter, into cells. Colors are translated into the closest ones the output mode can
+//! show, including 24-bit colors. Escape sequences other than SGR, such as cursor movement or
+//! hyperlinks, are skipped.
+//!
+//! ~~~
+//! use termbox::{Backend, OutputMode, BOLD, RED};
+//! use termbox::ansi;
+//! use termbox::headless::TestTermbox;
+//!
+//! let text = "\x1b[1;31merror:\x1b[0m not found\n";
+//! let lines = ansi::parse(text, OutputMode::Normal);
+//! assert_eq!(lines.len(), 1);
+//! assert_eq!(lines[0][0].fg(), RED | BOLD);
+//!
+//! let mut tb = TestTermbox::new(20, 2);
+//! assert_eq!(ansi::draw(&mut tb, 0, 1, text), 1);
+//! assert_eq!(tb.snapshot().to_plain_text(), "\nerror: not found\n");
+//! ~~~
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use width;
+use {
+  Attribute,
+  Backend,
+  Cell,
+  Color,
+  Coord,
+  OutputMode,
+  BLINK,
+  BOLD,
+  DEFAULT,
+  DIM,
+  ITALIC,
+  REVERSE,
+  STRIKETHROUGH,
+  UNDERLINE,
+};
+
+// Tab stops are this many columns apart, as in most terminals.
+const TAB_WIDTH: usize = 8;
+
+#[derive(Clone, Copy)]
+enum SgrColor {
+  Default,
+  Indexed(u8),
+  Rgb(u8, u8, u8),
+}
+
+// The graphic rendition selected by the escape sequences read so far.
+#[derive(Clone, Copy)]
+struct Rendition {
+  fg: SgrColor,
+  bg: SgrColor,
+  // Attribute flags such as `BOLD`, applied to the foreground.
+  flags: Attribute,
+}
+
+impl Rendition {
+  fn new () -> Rendition {
+    Rendition {
+      fg: SgrColor::Default,
+      bg: SgrColor::Default,
+      flags: 0,
+    }
+  }
+
+  // Applies the parameters of an SGR sequence.
+  fn apply (&mut self, params: &[Vec<u16>]) {
+    if params.is_empty() {
+      *self = Rendition::new();
+      return;
+    }
+
+    let mut i = 0;
+    while i < params.len() {
+      let code = params[i].first().cloned().unwrap_or(0);
+      match code {
+        0 => { *self = Rendition::new(); },
+        1 => { self.flags |= BOLD; },
+        2 => { self.flags |= DIM; },
+        3 => { self.flags |= ITALIC; },
+        4 => { self.flags |= UNDERLINE; },
+        5 | 6 => { self.flags |= BLINK; },
+        7 => { self.flags |= REVERSE; },
+        9 => { self.flags |= STRIKETHROUGH; },
+        22 => { self.flags &= !(BOLD | DIM); },
+        23 => { self.flags &= !ITALIC; },
+        24 => { self.flags &= !UNDERLINE; },
+        25 => { self.flags &= !BLINK; },
+        27 => { self.flags &= !REVERSE; },
+        29 => { self.flags &= !STRIKETHROUGH; },
+        30..=37 => { self.fg = SgrColor::Indexed((code - 30) as u8); },
+        39 => { self.fg = SgrColor::Default; },
+        40..=47 => { self.bg = SgrColor::Indexed((code - 40) as u8); },
+        49 => { self.bg = SgrColor::Default; },
+        90..=97 => { self.fg = SgrColor::Indexed((code - 90 + 8) as u8); },
+        100..=107 => { self.bg = SgrColor::Indexed((code - 100 + 8) as u8); },
+        38 | 48 => {
+          let (color, used) = extended_color(params, i);
+          if let Some(color) = color {
+            if code == 38 { self.fg = color; } else { self.bg = color; }
+          }
+          i += used;
+        },
+        _ => {},
+      }
+      i += 1;
+    }
+  }
+
+  fn cell (&self, ch: char, mode: OutputMode) -> Cell {
+    Cell::new(ch, color_attribute(self.fg, mode) | self.flags, color_attribute(self.bg, mode))
+  }
+}
+
+
+//
+// Functions
+//
+
+
+/// Draws `text` with its top left corner at `x`, `y`, translating its colors into the output mode
+/// of `tb`. Each line of the text starts at `x`. Cells outside of the output buffer are left out.
+/// Returns the number of lines drawn.
+pub fn draw<B: Backend> (tb: &mut B, x: Coord, y: Coord, text: &str) -> Coord {
+  let lines = parse(text, tb.output_mode());
+  for (row, line) in lines.iter().enumerate() {
+    for (col, &cell) in line.iter().enumerate() {
+      let (cell_x, cell_y) = (x + col as Coord, y + row as Coord);
+      if tb.get_cell(cell_x, cell_y).is_some() {
+        tb.put_cell(cell_x, cell_y, cell);
+      }
+    }
+  }
+  return lines.len() as Coord;
+}
+
+/// Splits `text` into lines of cells, one cell per column, with colors translated into `mode`.
+/// A wide character is followed by a blank cell in the same colors, which the terminal draws the
+/// character over. Carriage returns move back to the start of the line, so that later text
+/// overwrites earlier text as in a terminal, and tabs advance to the next multiple of eight
+/// columns. Other control characters are skipped. A final line break doesn't start another line.
+pub fn parse (text: &str, mode: OutputMode) -> Vec<Vec<Cell>> {
+  let mut lines = Vec::new();
+  let mut line: Vec<Cell> = Vec::new();
+  let mut col = 0;
+  let mut rendition = Rendition::new();
+  let mut chars = text.chars().peekable();
+
+  while let Some(ch) = chars.next() {
+    match ch {
+      '\x1b' => {
+        if let Some(params) = read_escape(&mut chars) {
+          rendition.apply(&params);
+        }
+      },
+      '\n' => {
+        lines.push(line.split_off(0));
+        col = 0;
+      },
+      '\r' => { col = 0; },
+      '\t' => {
+        let next = (col / TAB_WIDTH + 1) * TAB_WIDTH;
+        while col < next {
+          put(&mut line, col, rendition.cell(' ', mode));
+          col += 1;
+        }
+      },
+      ch if ch.is_control() => {},
+      ch => {
+        let cell = rendition.cell(ch, mode);
+        put(&mut line, col, cell);
+        col += 1;
+        if width::char_width(ch) > 1 {
+          put(&mut line, col, Cell::new(' ', cell.fg(), cell.bg()));
+          col += 1;
+        }
+      },
+    }
+  }
+
+  if !line.is_empty() {
+    lines.push(line);
+  }
+  return lines;
+}
+
+// Returns the attribute that shows `color` in `mode`, approximating colors the mode can't show.
+fn color_attribute (color: SgrColor, mode: OutputMode) -> Attribute {
+  match color {
+    SgrColor::Default => DEFAULT,
+    SgrColor::Indexed(index) => {
+      let color = Color::indexed(index);
+      match color.attribute(mode) {
+        Some(attr) => attr,
+        None => {
+          let (r, g, b) = color.to_rgb();
+          Color::from_rgb_approx(r, g, b, mode)
+        },
+      }
+    },
+    SgrColor::Rgb(r, g, b) => Color::from_rgb_approx(r, g, b, mode),
+  }
+}
+
+// Reads the color selected by the 38 or 48 parameter at `params[i]`, in either the form with
+// semicolons, `38;5;n` and `38;2;r;g;b`, or the one with colons, `38:5:n` and `38:2::r:g:b`.
+// Returns the color, if valid, and the number of parameters after `i` that it took up.
+fn extended_color (params: &[Vec<u16>], i: usize) -> (Option<SgrColor>, usize) {
+  let to_u8 = |value: u16| if value <= 255 { Some(value as u8) } else { None };
+
+  if params[i].len() > 1 {
+    let sub = &params[i][1..];
+    let color = match sub.first() {
+      Some(&5) if sub.len() >= 2 => to_u8(sub[1]).map(SgrColor::Indexed),
+      Some(&2) if sub.len() >= 4 => {
+        // The color space id between the 2 and the components is optional.
+        let rgb = &sub[sub.len() - 3..];
+        match (to_u8(rgb[0]), to_u8(rgb[1]), to_u8(rgb[2])) {
+          (Some(r), Some(g), Some(b)) => Some(SgrColor::Rgb(r, g, b)),
+          _ => None,
+        }
+      },
+      _ => None,
+    };
+    return (color, 0);
+  }
+
+  let arg = |n: usize| params.get(i + n).and_then(|param| param.first().cloned());
+  match arg(1) {
+    Some(5) => (arg(2).and_then(to_u8).map(SgrColor::Indexed), 2),
+    Some(2) => {
+      match (arg(2).and_then(to_u8), arg(3).and_then(to_u8), arg(4).and_then(to_u8)) {
+        (Some(r), Some(g), Some(b)) => (Some(SgrColor::Rgb(r, g, b)), 4),
+        _ => (None, 4),
+      }
+    },
+    _ => (None, 0),
+  }
+}
+
+// Writes `cell` at column `col` of `line`, padding the line with blanks if it is shorter.
+fn put (line: &mut Vec<Cell>, col: usize, cell: Cell) {
+  let blank = Cell::new(' ', DEFAULT, DEFAULT);
+  while line.len() < col {
+    line.push(blank);
+  }
+  if col < line.len() {
+    line[col] = cell;
+  } else {
+    line.push(cell);
+  }
+}
+
+// Reads the rest of an escape sequence after ESC. Returns the parameters of an SGR sequence, each
+// split into its colon-separated parts, or `None` for any other sequence.
+fn read_escape (chars: &mut Peekable<Chars>) -> Option<Vec<Vec<u16>>> {
+  match chars.next() {
+    Some('[') => {},
+    Some(']') | Some('P') | Some('_') | Some('^') => {
+      // Strings such as OSC hyperlinks end with BEL or with ST, which is ESC \.
+      while let Some(ch) = chars.next() {
+        if ch == '\x07' {
+          break;
+        }
+        if ch == '\x1b' && chars.peek() == Some(&'\\') {
+          chars.next();
+          break;
+        }
+      }
+      return None;
+    },
+    _ => { return None; },
+  }
+
+  let mut body = String::new();
+  for ch in chars.by_ref() {
+    match ch {
+      '\x40'..='\x7e' => {
+        if ch != 'm' || body.starts_with(|c: char| ('<'..='?').contains(&c)) {
+          return None;
+        }
+        if body.is_empty() {
+          return Some(Vec::new());
+        }
+        let params = body.split(';')
+          .map(|param| param.split(':').map(|part| part.parse().unwrap_or(0)).collect())
+          .collect();
+        return Some(params);
+      },
+      _ => { body.push(ch); },
+    }
+  }
+
+  return None;
+}
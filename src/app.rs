@@ -0,0 +1,557 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! A ready-made shell for small tools. `minimal` returns an `App` with a title bar, a status bar,
+//! a command palette, notifications and a help overlay listing the key bindings, around a single
+//! `Page` that the program supplies. The page only has to draw itself and handle the commands and
+//! events meant for it.
+//!
+//! ~~~
+//! use termbox::{Backend, Event, KeyEvent, KeyState, Rect};
+//! use termbox::app::{self, App, Page};
+//! use termbox::headless::TestTermbox;
+//! use termbox::keys::*;
+//! use termbox::theme::{Role, Theme};
+//!
+//! struct Counter {
+//!   count: u32,
+//! }
+//!
+//! impl Page for Counter {
+//!   fn render<B: Backend> (&mut self, tb: &mut B, rect: Rect, theme: &Theme) {
+//!     let (fg, bg) = theme.get(Role::Normal, tb.output_mode()).attributes();
+//!     tb.put_str(rect.x, rect.y, &format!("count: {}", self.count), fg, bg);
+//!   }
+//!
+//!   fn run_command (&mut self, app: &mut App, name: &str) {
+//!     if name == "increment" {
+//!       self.count += 1;
+//!       app.notify("incremented", Role::Success);
+//!     }
+//!   }
+//! }
+//!
+//! let mut app = app::minimal("counter", Theme::dark());
+//! app.add_command("increment", "Add one to the count", Some("+"));
+//!
+//! let key = |key, ch| Event::Key(KeyEvent { key: key, ch: ch, alt: false,
+//!                                           state: KeyState::Press });
+//! let mut tb = TestTermbox::new(40, 4);
+//! tb.push_event(key(0, Some('+')));
+//! tb.push_event(key(KEY_CTRL_Q, None));
+//!
+//! let mut page = Counter { count: 0 };
+//! app.run(&mut tb, &mut page);
+//! assert_eq!(page.count, 1);
+//! assert!(tb.presented().unwrap().to_plain_text().starts_with("counter\ncount: 1"));
+//! ~~~
+
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use box_drawing::{
+  self,
+  LineStyle,
+};
+use keymap::KeyMap;
+use theme::{
+  Role,
+  Theme,
+};
+use widgets::{
+  HelpView,
+  LineEditor,
+  ListView,
+};
+use width;
+use {
+  Attribute,
+  Backend,
+  Coord,
+  Event,
+  KeyEvent,
+  KeyState,
+  Rect,
+  Time,
+};
+use keys::*;
+
+// How long a notification stays on screen.
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(4);
+
+// Notifications beyond this many push out the oldest one.
+const MAX_NOTIFICATIONS: usize = 4;
+
+// The widest the command palette gets, including its border.
+const PALETTE_WIDTH: Coord = 60;
+
+// The most commands the palette lists at once.
+const PALETTE_ROWS: Coord = 10;
+
+// Shown on the right of the status bar while no key sequence is being typed.
+const STATUS_HINT: &str = "F1 Help  Ctrl-P Commands";
+
+// What a key binding or palette entry does.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Action {
+  Command(String),
+  Help,
+  Palette,
+  Quit,
+}
+
+// An entry of the command palette.
+struct Command {
+  name: String,
+  description: String,
+  action: Action,
+}
+
+struct Notification {
+  text: String,
+  role: Role,
+  expires: Instant,
+}
+
+// The command palette while it is open, with the indices of the commands that match the filter.
+struct Palette {
+  editor: LineEditor,
+  list: ListView,
+  matches: Vec<usize>,
+}
+
+
+//
+// Page
+//
+
+
+/// The content of an `App`, drawn between its title bar and its status bar.
+pub trait Page {
+  /// Draws the page into `rect`. Colors should come from `theme`, so that the page matches the
+  /// rest of the app.
+  fn render<B: Backend> (&mut self, tb: &mut B, rect: Rect, theme: &Theme);
+
+  /// Handles an event that the app didn't use itself: keys that aren't bound, mouse events,
+  /// resizes and everything else. Returns `false` if the page didn't use it either.
+  fn handle_event (&mut self, _app: &mut App, _event: &Event) -> bool {
+    false
+  }
+
+  /// Runs a command added with `App::add_command`, chosen from the palette or with its keys.
+  fn run_command (&mut self, _app: &mut App, _name: &str) {}
+
+  /// Returns the text shown on the left of the status bar.
+  fn status (&self) -> String {
+    String::new()
+  }
+}
+
+
+//
+// App
+//
+
+
+/// An application shell around a `Page`, returned by `minimal`.
+///
+/// Ctrl-P opens the command palette, which lists the commands added with `add_command` along with
+/// the built-in `help` and `quit`. Typing filters the list, Up/Down choose a command, Enter runs it
+/// and Esc closes the palette. F1 shows the help overlay, which lists every key binding that has a
+/// description, and Ctrl-Q quits.
+pub struct App {
+  title: String,
+  theme: Theme,
+  keymap: KeyMap<Action>,
+  commands: Vec<Command>,
+  notifications: Vec<Notification>,
+  palette: Option<Palette>,
+  help: Option<HelpView>,
+  quit: bool,
+}
+
+impl App {
+  /// Adds a command to the palette, which passes `name` to `Page::run_command`. If `keys` is
+  /// given, the command is also bound to that key sequence and listed in the help. Returns
+  /// `false` if `keys` could not be parsed, in which case the command is not added.
+  pub fn add_command (&mut self, name: &str, description: &str, keys: Option<&str>) -> bool {
+    let action = Action::Command(name.to_string());
+    if let Some(keys) = keys {
+      if !self.keymap.bind(keys, action.clone()) {
+        return false;
+      }
+      self.keymap.set_help(keys, "Commands", description);
+    }
+
+    self.commands.push(Command {
+      name: name.to_string(),
+      description: description.to_string(),
+      action: action,
+    });
+    return true;
+  }
+
+  /// Handles an event as `run` does. Returns `false` if neither the app nor the page used it.
+  /// This is for programs that run their own event loop, together with `render`.
+  pub fn handle_event<P: Page> (&mut self, page: &mut P, event: &Event) -> bool {
+    let key = match *event {
+      Event::Key(ref key) if key.state != KeyState::Release => key,
+      _ => { return page.handle_event(self, event); },
+    };
+
+    if self.help.is_some() {
+      self.handle_help_key(key);
+      return true;
+    }
+    if self.palette.is_some() {
+      self.handle_palette_key(page, key);
+      return true;
+    }
+
+    if let Some(action) = self.keymap.feed(key) {
+      self.perform(page, action);
+      return true;
+    }
+    if !self.keymap.pending().is_empty() {
+      return true;
+    }
+    return page.handle_event(self, event);
+  }
+
+  /// Determines whether the app has been told to quit, with `quit` or by the user.
+  pub fn is_quitting (&self) -> bool {
+    self.quit
+  }
+
+  /// Shows a message above the status bar for a few seconds, in the style of `role`.
+  pub fn notify (&mut self, text: &str, role: Role) {
+    if self.notifications.len() >= MAX_NOTIFICATIONS {
+      self.notifications.remove(0);
+    }
+    self.notifications.push(Notification {
+      text: text.to_string(),
+      role: role,
+      expires: Instant::now() + NOTIFICATION_DURATION,
+    });
+  }
+
+  /// Makes `run` return once the current event has been handled.
+  pub fn quit (&mut self) {
+    self.quit = true;
+  }
+
+  /// Draws the whole app into the output buffer, with `page` in the main area. The buffer is
+  /// cleared first. This is for programs that run their own event loop; `run` calls it itself.
+  pub fn render<B: Backend, P: Page> (&mut self, tb: &mut B, page: &mut P) {
+    let now = Instant::now();
+    self.notifications.retain(|notification| notification.expires > now);
+
+    let (width, height) = (tb.width(), tb.height());
+    let mode = tb.output_mode();
+    let (fg, bg) = self.theme.get(Role::Normal, mode).attributes();
+    tb.set_clear_attributes(fg, bg);
+    tb.clear();
+    tb.hide_cursor();
+    if width <= 0 || height <= 0 {
+      return;
+    }
+
+    let (fg, bg) = self.theme.get(Role::Title, mode).attributes();
+    put_padded(tb, 0, 0, width, &self.title, fg, bg);
+
+    let main = Rect::new(0, 1, width, (height - 2).max(0));
+    page.render(tb, main, &self.theme);
+
+    if height > 1 {
+      let status = page.status();
+      let hint = if self.keymap.pending().is_empty() {
+        STATUS_HINT.to_string()
+      } else {
+        let pending: Vec<String> = self.keymap.pending().iter().map(|key| key.to_string()).collect();
+        pending.join(" ")
+      };
+      let (fg, bg) = self.theme.get(Role::Status, mode).attributes();
+      let hint_width = text_width(&hint).min(width);
+      put_padded(tb, 0, height - 1, width - hint_width, &status, fg, bg);
+      put_padded(tb, width - hint_width, height - 1, hint_width, &hint, fg, bg);
+    }
+
+    self.render_notifications(tb, main);
+    if self.palette.is_some() {
+      self.render_palette(tb);
+    }
+    if self.help.is_some() {
+      self.render_help(tb);
+    }
+  }
+
+  /// Runs the event loop until the app is told to quit, drawing the app and `page` after every
+  /// event.
+  pub fn run<B: Backend, P: Page> (&mut self, tb: &mut B, page: &mut P) {
+    self.quit = false;
+    while !self.quit {
+      self.render(tb, page);
+      tb.present();
+
+      // Wake up when the oldest notification expires, to take it off the screen.
+      let expires = self.notifications.iter().map(|notification| notification.expires).min();
+      let event = match expires {
+        Some(expires) => {
+          let timeout = expires.saturating_duration_since(Instant::now());
+          match tb.peek_event(timeout.as_millis().min(Time::MAX as u128) as Time) {
+            Some(event) => event,
+            None => { continue; },
+          }
+        },
+        None => tb.poll_event(),
+      };
+      self.handle_event(page, &event);
+    }
+  }
+
+  /// Replaces the theme that the app and its page are drawn with.
+  pub fn set_theme (&mut self, theme: Theme) {
+    self.theme = theme;
+  }
+
+  /// Replaces the text shown in the title bar.
+  pub fn set_title (&mut self, title: &str) {
+    self.title = title.to_string();
+  }
+
+  /// Returns the theme that the app and its page are drawn with.
+  pub fn theme (&self) -> &Theme {
+    &self.theme
+  }
+
+  /// Returns the text shown in the title bar.
+  pub fn title (&self) -> &str {
+    &self.title
+  }
+
+  // Closes the help on Esc, F1 or q, and scrolls it with other keys.
+  fn handle_help_key (&mut self, key: &KeyEvent) {
+    if matches!((key.ch, key.key), (None, KEY_ESC) | (None, KEY_F1) | (Some('q'), _)) {
+      self.help = None;
+    } else if let Some(ref mut help) = self.help {
+      help.handle_key(key);
+    }
+  }
+
+  // Edits the filter, moves the selection or runs the selected command.
+  fn handle_palette_key<P: Page> (&mut self, page: &mut P, key: &KeyEvent) {
+    match (key.ch, key.key) {
+      (None, KEY_ESC) => { self.palette = None; },
+      (None, KEY_ENTER) => {
+        let action = self.palette.take().and_then(|palette| {
+          let selected = palette.list.selected()?;
+          return Some(self.commands[palette.matches[selected]].action.clone());
+        });
+        if let Some(action) = action {
+          self.perform(page, action);
+        }
+      },
+      _ => {
+        let mut palette = match self.palette.take() {
+          Some(palette) => palette,
+          None => { return; },
+        };
+        if !palette.list.handle_key(key) && palette.editor.handle_key(key) {
+          self.filter_palette(&mut palette);
+        }
+        self.palette = Some(palette);
+      },
+    }
+  }
+
+  // Lists the commands whose name or description contains the filter, ignoring case.
+  fn filter_palette (&self, palette: &mut Palette) {
+    let filter = palette.editor.text().to_lowercase();
+    palette.matches = (0..self.commands.len()).filter(|&index| {
+      let command = &self.commands[index];
+      command.name.to_lowercase().contains(&filter)
+        || command.description.to_lowercase().contains(&filter)
+    }).collect();
+
+    let name_width = palette.matches.iter()
+      .map(|&index| text_width(&self.commands[index].name))
+      .max()
+      .unwrap_or(0);
+    let items: Vec<String> = palette.matches.iter().map(|&index| {
+      let command = &self.commands[index];
+      let padding = name_width - text_width(&command.name) + 2;
+      format!("{}{}{}", command.name, " ".repeat(padding as usize), command.description)
+    }).collect();
+    palette.list.set_items(items);
+    palette.list.select(0);
+  }
+
+  fn perform<P: Page> (&mut self, page: &mut P, action: Action) {
+    match action {
+      Action::Command(name) => { page.run_command(self, &name); },
+      Action::Help => {
+        self.palette = None;
+        self.help = Some(HelpView::new(&self.keymap));
+      },
+      Action::Palette => {
+        let mut palette = Palette {
+          editor: LineEditor::new(),
+          list: ListView::new(),
+          matches: Vec::new(),
+        };
+        self.filter_palette(&mut palette);
+        self.palette = Some(palette);
+      },
+      Action::Quit => { self.quit(); },
+    }
+  }
+
+  fn render_help<B: Backend> (&mut self, tb: &mut B) {
+    let (width, height) = (tb.width(), tb.height());
+    let rect = Rect::new(2, 1, width - 4, height - 2);
+    if rect.w < 4 || rect.h < 3 {
+      return;
+    }
+
+    let mode = tb.output_mode();
+    let normal = self.theme.get(Role::Normal, mode);
+    let heading = self.theme.get(Role::Title, mode);
+    let key = self.theme.get(Role::Highlight, mode);
+    draw_frame(tb, rect, " Help ", &self.theme);
+    if let Some(ref mut help) = self.help {
+      help.set_style(normal);
+      help.set_heading_style(heading);
+      help.set_key_style(key);
+      help.render(tb, Rect::new(rect.x + 1, rect.y + 1, rect.w - 2, rect.h - 2));
+    }
+  }
+
+  // Draws each notification on a row of its own at the bottom right of `rect`, newest last.
+  fn render_notifications<B: Backend> (&self, tb: &mut B, rect: Rect) {
+    let mode = tb.output_mode();
+    let count = (self.notifications.len() as Coord).min(rect.h);
+    let skip = self.notifications.len() - count as usize;
+    for (row, notification) in self.notifications[skip..].iter().enumerate() {
+      let text = format!(" {} ", notification.text);
+      let w = text_width(&text).min(rect.w);
+      let y = rect.y + rect.h - count + row as Coord;
+      let (fg, bg) = self.theme.get(notification.role, mode).attributes();
+      put_padded(tb, rect.x + rect.w - w, y, w, &text, fg, bg);
+    }
+  }
+
+  fn render_palette<B: Backend> (&mut self, tb: &mut B) {
+    let (width, height) = (tb.width(), tb.height());
+    let palette = match self.palette {
+      Some(ref mut palette) => palette,
+      None => { return; },
+    };
+    let rows = (palette.matches.len() as Coord).clamp(1, PALETTE_ROWS);
+    let w = PALETTE_WIDTH.min(width - 4);
+    let h = (rows + 3).min(height - 2);
+    if w < 6 || h < 4 {
+      return;
+    }
+    let rect = Rect::new((width - w) / 2, 1, w, h);
+
+    let mode = tb.output_mode();
+    draw_frame(tb, rect, " Commands ", &self.theme);
+    palette.list.set_style(self.theme.get(Role::Normal, mode));
+    palette.list.set_highlight_style(self.theme.get(Role::Selection, mode));
+    palette.list.render(tb, Rect::new(rect.x + 1, rect.y + 2, rect.w - 2, rect.h - 3));
+
+    let (fg, bg) = self.theme.get(Role::Normal, mode).attributes();
+    tb.put_str(rect.x + 1, rect.y + 1, "> ", fg, bg);
+    palette.editor.render(tb, rect.x + 3, rect.y + 1, rect.w - 4, fg, bg);
+  }
+}
+
+/// Returns an app with the given title and theme, with Ctrl-P bound to the command palette, F1 to
+/// the help and Ctrl-Q to quit. Add commands with `App::add_command`, and start it with `App::run`.
+pub fn minimal (title: &str, theme: Theme) -> App {
+  let mut app = App {
+    title: title.to_string(),
+    theme: theme,
+    keymap: KeyMap::new(),
+    commands: Vec::new(),
+    notifications: Vec::new(),
+    palette: None,
+    help: None,
+    quit: false,
+  };
+
+  let builtins = [
+    ("Ctrl-P", "Show the commands", Action::Palette, None),
+    ("F1", "Show the key bindings", Action::Help, Some("help")),
+    ("Ctrl-Q", "Quit", Action::Quit, Some("quit")),
+  ];
+  for &(keys, description, ref action, name) in &builtins {
+    app.keymap.bind(keys, action.clone());
+    app.keymap.set_help(keys, "General", description);
+    if let Some(name) = name {
+      app.commands.push(Command {
+        name: name.to_string(),
+        description: description.to_string(),
+        action: action.clone(),
+      });
+    }
+  }
+  return app;
+}
+
+
+//
+// Functions
+//
+
+
+// Clears `rect` and draws a border around it, with `title` at the top left.
+fn draw_frame<B: Backend> (tb: &mut B, rect: Rect, title: &str, theme: &Theme) {
+  let mode = tb.output_mode();
+  let (fg, bg) = theme.get(Role::Normal, mode).attributes();
+  for y in rect.y..rect.y + rect.h {
+    put_padded(tb, rect.x, y, rect.w, "", fg, bg);
+  }
+
+  let (fg, bg) = theme.get(Role::Border, mode).attributes();
+  box_drawing::draw_box(tb, rect.x, rect.y, rect.w, rect.h, LineStyle::Light, fg, bg);
+  let (fg, bg) = theme.get(Role::Title, mode).attributes();
+  put_padded(tb, rect.x + 2, rect.y, text_width(title).min(rect.w - 4), title, fg, bg);
+}
+
+// Writes `text` into `w` cells at (`x`, `y`), cutting it off or filling the rest with spaces.
+// Cells outside of the output buffer are skipped.
+fn put_padded<B: Backend> (tb: &mut B, x: Coord, y: Coord, w: Coord, text: &str, fg: Attribute,
+                           bg: Attribute)
+{
+  if y < 0 || y >= tb.height() {
+    return;
+  }
+
+  let end = (x + w).min(tb.width());
+  let mut col = x;
+  for ch in text.chars() {
+    let ch_width = (width::char_width(ch) as Coord).max(1);
+    if col + ch_width > end {
+      break;
+    }
+    if col >= 0 {
+      tb.change_cell(col, y, ch, fg, bg);
+    }
+    col += ch_width;
+  }
+  while col < end {
+    if col >= 0 {
+      tb.change_cell(col, y, ' ', fg, bg);
+    }
+    col += 1;
+  }
+}
+
+// Returns the number of cells taken by `text`.
+fn text_width (text: &str) -> Coord {
+  text.chars().map(|ch| width::char_width(ch).max(1) as Coord).sum()
+}
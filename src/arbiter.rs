@@ -0,0 +1,80 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::ops::Drop;
+
+use {
+  Released,
+  Result,
+  Termbox,
+};
+
+
+//
+// TtyArbiter
+//
+
+
+/// Lends the terminal to another program, such as an editor or a child user interface, and takes
+/// it back afterwards. Releasing saves the output buffer and the input and output modes, and puts
+/// the terminal back into the state it was in before termbox was initialized. Restoring
+/// reinitializes termbox with the saved modes and presents the saved output buffer again, clipped
+/// to the terminal's size at that time. If the size changed, the next call to `peek_event` or
+/// `poll_event` reports a resize event.
+///
+/// The terminal is restored when the arbiter is dropped, but errors can only be handled by calling
+/// `restore` explicitly.
+///
+/// ~~~no_run
+/// use std::process::Command;
+/// use termbox::Termbox;
+/// use termbox::arbiter::TtyArbiter;
+///
+/// let mut tb = Termbox::open().unwrap();
+/// let status = TtyArbiter::with_released(&mut tb, || {
+///   Command::new("vi").arg("notes.txt").status()
+/// }).unwrap();
+/// ~~~
+pub struct TtyArbiter<'a> {
+  tb: &'a mut Termbox,
+  released: Option<Released>,
+}
+
+impl<'a> TtyArbiter<'a> {
+  /// Saves the state of termbox and releases the terminal.
+  pub fn release (tb: &'a mut Termbox) -> TtyArbiter<'a> {
+    let released = tb.release();
+
+    TtyArbiter {
+      tb: tb,
+      released: Some(released),
+    }
+  }
+
+  /// Takes the terminal back, restoring the saved state. Returns an error if termbox can't be
+  /// reinitialized, in which case the `Termbox` should be dropped.
+  pub fn restore (mut self) -> Result<()> {
+    let released = self.released.take().unwrap();
+    return self.tb.reacquire(released);
+  }
+
+  /// Releases the terminal, runs `f`, and takes the terminal back. `f` typically starts another
+  /// program in the foreground and waits for it to exit.
+  pub fn with_released<F, T> (tb: &mut Termbox, f: F) -> Result<T>
+    where F: FnOnce() -> T
+  {
+    let arbiter = TtyArbiter::release(tb);
+    let result = f();
+    arbiter.restore()?;
+    return Ok(result);
+  }
+}
+
+impl<'a> Drop for TtyArbiter<'a> {
+  fn drop (&mut self) {
+    if let Some(released) = self.released.take() {
+      let _ = self.tb.reacquire(released);
+    }
+  }
+}
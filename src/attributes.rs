@@ -23,3 +23,168 @@ pub const BOLD: Attribute = ::ffi::TB_BOLD;
 /// Put an underline under the displayed character if the terminal supports it.
 pub const UNDERLINE: Attribute = ::ffi::TB_UNDERLINE;
 pub const REVERSE: Attribute = ::ffi::TB_REVERSE;
+
+// The following attributes are not known to the termbox C library, which ignores them. They are
+// only displayed by the `pure-rust` backend, and by `Snapshot::to_ansi` and `Snapshot::to_html`.
+// Like `UNDERLINE`, they only have an effect on the foreground attribute.
+
+/// Slant the displayed character if the terminal supports it.
+pub const ITALIC: Attribute = 0x0800;
+/// Display the character with reduced intensity if the terminal supports it.
+pub const DIM: Attribute = 0x1000;
+/// Make the displayed character blink if the terminal supports it.
+pub const BLINK: Attribute = 0x2000;
+/// Draw a line through the displayed character if the terminal supports it.
+pub const STRIKETHROUGH: Attribute = 0x4000;
+
+
+//
+// Color
+//
+
+
+/// A color from the xterm 256 color palette. Attribute values for the same color differ between
+/// output modes, and some modes can't show every color; `attribute` takes care of both.
+///
+/// ~~~
+/// use termbox::{Color, OutputMode};
+///
+/// let orange = Color::rgb_cube(5, 2, 0);
+/// assert_eq!(orange.index(), 208);
+/// assert_eq!(orange.attribute(OutputMode::Color256), Some(208));
+/// assert_eq!(orange.attribute(OutputMode::Color216), Some(192));
+/// assert_eq!(orange.attribute(OutputMode::Grayscale), None);
+/// ~~~
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Color(u8);
+
+impl Color {
+  /// Returns the attribute value that displays this color in `mode`, or `None` if the mode can't
+  /// display it. In `OutputMode::Normal`, only the 16 system colors can be displayed; the bright
+  /// ones use `BOLD`. In `OutputMode::Color256`, palette index 0 collides with `DEFAULT`, so the
+  /// identical black from the color cube is used instead.
+  pub fn attribute (self, mode: ::OutputMode) -> Option<Attribute> {
+    let index = self.0 as Attribute;
+
+    match mode {
+      ::OutputMode::Normal => {
+        match index {
+          0..=7 => Some(index + 1),
+          8..=15 => Some((index - 7) | BOLD),
+          _ => None,
+        }
+      },
+      ::OutputMode::Color256 => Some(if index == 0 { 16 } else { index }),
+      ::OutputMode::Color216 => {
+        match index {
+          16..=231 => Some(index - 16),
+          _ => None,
+        }
+      },
+      ::OutputMode::Grayscale => {
+        match index {
+          232..=255 => Some(index - 232),
+          _ => None,
+        }
+      },
+    }
+  }
+
+  /// Returns the attribute value for the color closest to the 24-bit color `(r, g, b)` that `mode`
+  /// can display. In `OutputMode::Normal`, one of the 16 system colors is chosen. In other modes,
+  /// the system colors are avoided, since terminals often change them.
+  ///
+  /// ~~~
+  /// use termbox::{Color, OutputMode, BOLD, RED};
+  ///
+  /// assert_eq!(Color::from_rgb_approx(0xff, 0x10, 0x10, OutputMode::Normal), RED | BOLD);
+  /// assert_eq!(Color::from_rgb_approx(0xff, 0x87, 0x00, OutputMode::Color256), 208);
+  /// ~~~
+  pub fn from_rgb_approx (r: u8, g: u8, b: u8, mode: ::OutputMode) -> Attribute {
+    let (first, last) = match mode {
+      ::OutputMode::Normal => (0, 15),
+      ::OutputMode::Color256 => (16, 255),
+      ::OutputMode::Color216 => (16, 231),
+      ::OutputMode::Grayscale => (232, 255),
+    };
+
+    let mut best = Color(first);
+    let mut best_distance = u32::max_value();
+
+    for index in first..=last {
+      let (pr, pg, pb) = ::palette::rgb(index);
+      let (dr, dg, db) = (pr as i32 - r as i32, pg as i32 - g as i32, pb as i32 - b as i32);
+      // Weighted roughly by how sensitive the eye is to each channel.
+      let distance = (3 * dr * dr + 4 * dg * dg + 2 * db * db) as u32;
+      if distance < best_distance {
+        best = Color(index);
+        best_distance = distance;
+      }
+    }
+
+    return best.attribute(mode).unwrap();
+  }
+
+  /// Returns one of the 24 shades of gray, from `0` (darkest) to `23` (lightest). Higher levels
+  /// are clamped.
+  pub fn gray (level: u8) -> Color {
+    Color(232 + level.min(23))
+  }
+
+  /// Returns the palette index of the color.
+  pub fn index (self) -> u8 {
+    self.0
+  }
+
+  /// Returns the color at the given palette index.
+  pub fn indexed (index: u8) -> Color {
+    Color(index)
+  }
+
+  /// Returns a color from the 6x6x6 color cube. Each component ranges from `0` to `5`; higher
+  /// values are clamped.
+  pub fn rgb_cube (r: u8, g: u8, b: u8) -> Color {
+    Color(16 + 36 * r.min(5) + 6 * g.min(5) + b.min(5))
+  }
+
+  /// Returns the RGB value of the color in xterm's default palette. Terminals may use a different
+  /// palette, particularly for the 16 system colors.
+  pub fn to_rgb (self) -> (u8, u8, u8) {
+    ::palette::rgb(self.0)
+  }
+}
+
+// Describes why `attr` is not meaningful in `mode`, if it isn't. The termbox library silently masks
+// or wraps such values, which produces unexpected colors.
+pub(crate) fn problem (attr: Attribute, mode: ::OutputMode) -> Option<&'static str> {
+  let color = attr & 0xff;
+
+  if attr & 0x8000 != 0 {
+    return Some("unknown attribute flag 0x8000");
+  }
+
+  match mode {
+    ::OutputMode::Normal => {
+      if color > WHITE {
+        return Some("color is out of range for OutputMode::Normal; use one of the color constants");
+      }
+    },
+    ::OutputMode::Color256 => {
+      if attr & BOLD != 0 && color >= BLACK && color <= WHITE {
+        return Some("BOLD does not brighten colors in OutputMode::Color256; use indexes 8 to 15");
+      }
+    },
+    ::OutputMode::Color216 => {
+      if color > 215 {
+        return Some("color is out of range for OutputMode::Color216; use 0 to 215");
+      }
+    },
+    ::OutputMode::Grayscale => {
+      if color > 23 {
+        return Some("color is out of range for OutputMode::Grayscale; use 0 to 23");
+      }
+    },
+  }
+
+  return None;
+}
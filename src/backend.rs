@@ -0,0 +1,631 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::borrow::Cow;
+use std::char;
+use std::result;
+
+use headless::TestTermbox;
+use width;
+use {
+  Attribute,
+  Cell,
+  ClusterError,
+  ControlChars,
+  Coord,
+  Event,
+  InputMode,
+  OutOfBounds,
+  OutputMode,
+  Overflow,
+  Overlay,
+  PutOptions,
+  Rect,
+  Snapshot,
+  Termbox,
+  Time,
+  DEFAULT,
+};
+
+// Columns between tab stops unless changed with `set_tab_width`.
+pub(crate) const DEFAULT_TAB_WIDTH: Coord = 8;
+
+
+//
+// Backend
+//
+
+
+/// The drawing and event API shared by everything that can display a termbox user interface.
+/// `Termbox` implements this on top of the termbox C library, and `TestTermbox` implements it in
+/// memory. Code that only needs to draw and receive events can be written against this trait so
+/// that it works with either.
+///
+/// ~~~
+/// use termbox::{Backend, DEFAULT};
+/// use termbox::headless::TestTermbox;
+///
+/// fn draw_title<B: Backend> (tb: &mut B, title: &str) {
+///   let x = (tb.width() - title.len() as i32) / 2;
+///   tb.put_str(x, 0, title, DEFAULT, DEFAULT);
+/// }
+///
+/// let mut tb = TestTermbox::new(9, 1);
+/// draw_title(&mut tb, "abc");
+/// assert_eq!(tb.snapshot().to_plain_text(), "   abc\n");
+/// ~~~
+pub trait Backend {
+  /// Returns a slice representing the output buffer.
+  fn cell_buffer (&self) -> &[Cell];
+
+  /// Returns a mutable slice representing the output buffer.
+  fn cell_buffer_mut (&mut self) -> &mut [Cell];
+
+  /// Clears the output buffer using the attributes set with `set_clear_attributes`.
+  fn clear (&mut self);
+
+  /// Returns the cursor position, or `None` if the cursor is hidden.
+  fn cursor (&self) -> Option<(Coord, Coord)>;
+
+  /// Returns the height of the output buffer in character cells.
+  fn height (&self) -> Coord;
+
+  /// Hides the cursor.
+  fn hide_cursor (&mut self);
+
+  /// Returns the input mode.
+  fn input_mode (&self) -> InputMode;
+
+  /// Determines whether mouse events are enabled.
+  fn is_mouse_enabled (&self) -> bool;
+
+  /// Returns the output mode.
+  fn output_mode (&self) -> OutputMode;
+
+  /// Waits up to `timeout` milliseconds for an event.
+  fn peek_event (&mut self, timeout: Time) -> Option<Event>;
+
+  /// Waits for an event and returns it.
+  fn poll_event (&mut self) -> Event;
+
+  /// Makes changes to the output buffer visible to the user.
+  fn present (&mut self);
+
+  /// Changes a single character cell. Cells outside of the buffer are ignored.
+  fn put_cell (&mut self, x: Coord, y: Coord, cell: Cell);
+
+  /// Sets what attributes should be used when clearing the output buffer with `clear`.
+  fn set_clear_attributes (&mut self, fg: Attribute, bg: Attribute);
+
+  /// Sets the position of the cursor. If invalid coordinates are provided, the cursor is hidden.
+  /// Returns the previous position, or `None` if the cursor was hidden, so that it can be put back
+  /// later.
+  fn set_cursor (&mut self, x: Coord, y: Coord) -> Option<(Coord, Coord)>;
+
+  /// Sets the input mode.
+  fn set_input_mode (&mut self, mode: InputMode);
+
+  /// Enables or disables mouse events.
+  fn set_mouse_enabled (&mut self, enabled: bool);
+
+  /// Sets the output mode.
+  fn set_output_mode (&mut self, mode: OutputMode);
+
+  /// Returns the width of the output buffer in character cells.
+  fn width (&self) -> Coord;
+
+  /// Copies a rectangular region of cells from a slice to the output buffer.
+  fn blit (&mut self, x: Coord, y: Coord, w: Coord, h: Coord, cells: &[Cell]) {
+    assert!(w >= 0 && h >= 0);
+    assert!(cells.len() >= (w as usize).checked_mul(h as usize).unwrap());
+
+    let (width, height) = (self.width(), self.height());
+    for row in (-y).max(0)..h.min(height - y) {
+      for col in (-x).max(0)..w.min(width - x) {
+        self.put_cell(x + col, y + row, cells[(row * w + col) as usize]);
+      }
+    }
+  }
+
+  /// Copies the part of `cells` inside `src` to the output buffer, placing its top left corner at
+  /// `(x, y)`. `cells` is treated as rows of `stride` cells each. Source cells equal to
+  /// `transparent` are skipped.
+  fn blit_region (&mut self, x: Coord, y: Coord, cells: &[Cell], stride: Coord, src: Rect,
+                  transparent: Option<Cell>)
+  {
+    if stride <= 0 {
+      return;
+    }
+
+    let rows = (cells.len() / stride as usize) as Coord;
+    // Keep to the part of `src` that lands inside the output buffer.
+    let (left, top) = (src.x.max(0).max(src.x - x), src.y.max(0).max(src.y - y));
+    let right = (src.x + src.w).min(stride).min(src.x - x + self.width());
+    let bottom = (src.y + src.h).min(rows).min(src.y - y + self.height());
+
+    for row in top..bottom {
+      for col in left..right {
+        let cell = cells[(row * stride + col) as usize];
+        if let Some(key) = transparent {
+          if cell == key {
+            continue;
+          }
+        }
+        self.put_cell(x + col - src.x, y + row - src.y, cell);
+      }
+    }
+  }
+
+  /// Returns a copy of the cells inside `rect` in row-major order, for restoring later with
+  /// `paste_region`. Parts of the rectangle outside of the output buffer are filled with
+  /// default-colored spaces.
+  fn copy_region (&self, rect: Rect) -> Vec<Cell> {
+    let (width, height) = (self.width(), self.height());
+    let blank = Cell::new(' ', DEFAULT, DEFAULT);
+    let buffer = self.cell_buffer();
+    let mut cells = Vec::with_capacity(rect.w.max(0) as usize * rect.h.max(0) as usize);
+
+    for y in rect.y..rect.y + rect.h {
+      for x in rect.x..rect.x + rect.w {
+        if x >= 0 && y >= 0 && x < width && y < height {
+          cells.push(buffer[(y * width + x) as usize]);
+        } else {
+          cells.push(blank);
+        }
+      }
+    }
+
+    return cells;
+  }
+
+  /// Changes a single cell in the output buffer.
+  fn change_cell (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    self.put_cell(x, y, Cell::new(ch, fg, bg));
+  }
+
+  /// Changes the cell at the given position to display a single user-perceived character, which
+  /// may be two cells wide. A wide character also claims the cell to its right, which is set to a
+  /// space in the same colors. Returns the number of cells used. Variation selectors are dropped,
+  /// but any other combining mark is rejected with `ClusterError::CombiningCharacter`.
+  fn change_cell_str (&mut self, x: Coord, y: Coord, s: &str, fg: Attribute, bg: Attribute)
+                      -> result::Result<Coord, ClusterError>
+  {
+    let (ch, cells) = width::cluster(s)?;
+
+    if cells > 1 && x + 1 >= self.width() {
+      return Err(ClusterError::NoRoom);
+    }
+
+    self.change_cell(x, y, ch, fg, bg);
+    if cells > 1 {
+      // The terminal draws the wide character over this cell. It is cleared so that it doesn't
+      // show stale content if the wide character is later replaced by a narrow one.
+      self.change_cell(x + 1, y, ' ', fg, bg);
+    }
+    return Ok(cells as Coord);
+  }
+
+  /// Fills column `x` of the output buffer with `ch`, for vertical separators and sidebars. Columns
+  /// outside of the buffer are ignored.
+  fn fill_col (&mut self, x: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    if x < 0 || x >= self.width() {
+      return;
+    }
+    for y in 0..self.height() {
+      self.change_cell(x, y, ch, fg, bg);
+    }
+  }
+
+  /// Fills row `y` of the output buffer with `ch`, which should be one cell wide. Rows outside of
+  /// the buffer are ignored. The row is written through `cell_buffer_mut` as a whole, which is much
+  /// faster than changing each cell when redrawing the whole screen.
+  fn fill_row (&mut self, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    let (width, height) = (self.width(), self.height());
+    if y < 0 || y >= height {
+      return;
+    }
+    let start = (y * width) as usize;
+    self.cell_buffer_mut()[start..start + width as usize].fill(Cell::new(ch, fg, bg));
+  }
+
+  /// Returns the cell at the given position in the output buffer, or `None` if it is out of bounds.
+  fn get_cell (&self, x: Coord, y: Coord) -> Option<Cell> {
+    let (width, height) = (self.width(), self.height());
+    if x < 0 || y < 0 || x >= width || y >= height {
+      return None;
+    }
+    self.cell_buffer().get((y * width + x) as usize).cloned()
+  }
+
+  /// Copies `cells` to row `y` of the output buffer, starting at the left edge. Cells past the end
+  /// of the row are ignored, as are rows outside of the buffer. Like `fill_row`, the row is written
+  /// through `cell_buffer_mut` as a whole.
+  fn put_row (&mut self, y: Coord, cells: &[Cell]) {
+    let (width, height) = (self.width(), self.height());
+    if y < 0 || y >= height {
+      return;
+    }
+    let start = (y * width) as usize;
+    let len = cells.len().min(width as usize);
+    self.cell_buffer_mut()[start..start + len].copy_from_slice(&cells[..len]);
+  }
+
+  /// Writes a horizontal sequence of character cells without wrapping. Returns the number of
+  /// cells that were inside the output buffer.
+  fn put_str (&mut self, x: Coord, y: Coord, msg: &str, fg: Attribute, bg: Attribute) -> Coord {
+    let (width, height) = (self.width(), self.height());
+    let tab_width = self.tab_width();
+    let start = x;
+    let mut x = x;
+    let mut written = 0;
+
+    for ch in msg.chars() {
+      let (ch, count) = if ch == '\t' && tab_width > 0 {
+        (' ', tab_width - (x - start) % tab_width)
+      } else {
+        (ch, 1)
+      };
+      for _ in 0..count {
+        if x >= 0 && x < width && y >= 0 && y < height {
+          self.change_cell(x, y, ch, fg, bg);
+          written += 1;
+        }
+        x += 1;
+      }
+    }
+
+    return written;
+  }
+
+  /// Writes a string as controlled by `options`. Double-width characters take up two cells. Returns
+  /// the number of cells that were written.
+  fn put_str_with (&mut self, x: Coord, y: Coord, msg: &str, fg: Attribute, bg: Attribute,
+                   options: PutOptions)
+    -> Coord
+  {
+    let (width, height) = (self.width(), self.height());
+    let wrap = options.overflow == Overflow::Wrap;
+    let tab_width = if options.tab_width == 0 { self.tab_width() } else { options.tab_width };
+    let keep: &[char] = match (wrap, tab_width > 0) {
+      (true, true) => &['\n', '\t'],
+      (true, false) => &['\n'],
+      (false, true) => &['\t'],
+      (false, false) => &[],
+    };
+    let msg = replace_controls(msg, options.control_chars, keep);
+    let (mut col, mut row) = (x, y);
+    let mut written = 0;
+
+    for ch in msg.chars() {
+      if ch == '\n' && wrap {
+        col = x;
+        row += 1;
+        continue;
+      }
+
+      let (ch, count) = if ch == '\t' && tab_width > 0 {
+        (' ', tab_width - (col - x) % tab_width)
+      } else {
+        (ch, 1)
+      };
+      let ch_width = (width::char_width(ch) as Coord).max(1);
+
+      for _ in 0..count {
+        if col + ch_width > width {
+          // Give up if even a fresh line is too narrow for this character.
+          if !wrap || col == x {
+            return written;
+          }
+          col = x;
+          row += 1;
+        }
+        if row >= height {
+          return written;
+        }
+
+        if col >= 0 && row >= 0 {
+          let (fg, bg) = match (options.overlay, self.get_cell(col, row)) {
+            (Overlay::KeepBackground, Some(old)) => (fg, old.bg),
+            (Overlay::KeepForeground, Some(old)) => (old.fg, bg),
+            _ => (fg, bg),
+          };
+          self.change_cell(col, row, ch, fg, bg);
+          written += ch_width;
+        }
+        col += ch_width;
+      }
+    }
+
+    return written;
+  }
+
+  /// Writes a string downwards from `x`, `y`, one character per row, without wrapping. Returns the
+  /// number of cells that were inside the output buffer.
+  fn put_vstr (&mut self, x: Coord, y: Coord, msg: &str, fg: Attribute, bg: Attribute) -> Coord {
+    let (width, height) = (self.width(), self.height());
+    let mut y = y;
+    let mut written = 0;
+
+    for ch in msg.chars() {
+      if x >= 0 && x < width && y >= 0 && y < height {
+        self.change_cell(x, y, ch, fg, bg);
+        written += 1;
+      }
+      y += 1;
+    }
+
+    return written;
+  }
+
+  /// Restores cells saved with `copy_region` to the rectangle they were copied from. Cells outside
+  /// of the output buffer are ignored.
+  fn paste_region (&mut self, rect: Rect, cells: &[Cell]) {
+    let src = Rect::new(0, 0, rect.w, rect.h);
+    self.blit_region(rect.x, rect.y, cells, rect.w, src, None);
+  }
+
+  /// Returns an owned copy of the output buffer.
+  fn snapshot (&self) -> Snapshot {
+    Snapshot::new(self.width(), self.height(), self.output_mode(), self.cell_buffer().to_vec())
+  }
+
+  /// Returns the number of columns between the tab stops that `put_str` and `put_str_with` expand
+  /// tabs to. Tabs are written as-is if this is zero or less.
+  fn tab_width (&self) -> Coord {
+    DEFAULT_TAB_WIDTH
+  }
+
+  /// Changes a single cell like `change_cell`, but returns an error if it is outside of the output
+  /// buffer instead of ignoring it.
+  fn try_change_cell (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute)
+    -> result::Result<(), OutOfBounds>
+  {
+    self.try_put_cell(x, y, Cell::new(ch, fg, bg))
+  }
+
+  /// Changes a single cell like `put_cell`, but returns an error if it is outside of the output
+  /// buffer instead of ignoring it.
+  fn try_put_cell (&mut self, x: Coord, y: Coord, cell: Cell) -> result::Result<(), OutOfBounds> {
+    let (width, height) = (self.width(), self.height());
+    if x < 0 || y < 0 || x >= width || y >= height {
+      return Err(OutOfBounds { x: x, y: y, width: width, height: height });
+    }
+    self.put_cell(x, y, cell);
+    return Ok(());
+  }
+}
+
+impl Backend for Termbox {
+  fn cell_buffer (&self) -> &[Cell] {
+    Termbox::cell_buffer(self)
+  }
+
+  fn cell_buffer_mut (&mut self) -> &mut [Cell] {
+    Termbox::cell_buffer_mut(self)
+  }
+
+  fn clear (&mut self) {
+    Termbox::clear(self)
+  }
+
+  fn cursor (&self) -> Option<(Coord, Coord)> {
+    Termbox::cursor(self)
+  }
+
+  fn height (&self) -> Coord {
+    Termbox::height(self)
+  }
+
+  fn hide_cursor (&mut self) {
+    Termbox::hide_cursor(self)
+  }
+
+  fn input_mode (&self) -> InputMode {
+    Termbox::input_mode(self)
+  }
+
+  fn is_mouse_enabled (&self) -> bool {
+    Termbox::is_mouse_enabled(self)
+  }
+
+  fn output_mode (&self) -> OutputMode {
+    Termbox::output_mode(self)
+  }
+
+  fn peek_event (&mut self, timeout: Time) -> Option<Event> {
+    Termbox::peek_event(self, timeout)
+  }
+
+  fn poll_event (&mut self) -> Event {
+    Termbox::poll_event(self)
+  }
+
+  fn present (&mut self) {
+    Termbox::present(self)
+  }
+
+  fn put_cell (&mut self, x: Coord, y: Coord, cell: Cell) {
+    Termbox::put_cell(self, x, y, cell)
+  }
+
+  fn set_clear_attributes (&mut self, fg: Attribute, bg: Attribute) {
+    Termbox::set_clear_attributes(self, fg, bg)
+  }
+
+  fn set_cursor (&mut self, x: Coord, y: Coord) -> Option<(Coord, Coord)> {
+    Termbox::set_cursor(self, x, y)
+  }
+
+  fn set_input_mode (&mut self, mode: InputMode) {
+    Termbox::set_input_mode(self, mode)
+  }
+
+  fn set_mouse_enabled (&mut self, enabled: bool) {
+    Termbox::set_mouse_enabled(self, enabled)
+  }
+
+  fn set_output_mode (&mut self, mode: OutputMode) {
+    Termbox::set_output_mode(self, mode)
+  }
+
+  fn width (&self) -> Coord {
+    Termbox::width(self)
+  }
+
+  fn blit (&mut self, x: Coord, y: Coord, w: Coord, h: Coord, cells: &[Cell]) {
+    Termbox::blit(self, x, y, w, h, cells)
+  }
+
+  fn change_cell (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    Termbox::change_cell(self, x, y, ch, fg, bg)
+  }
+
+  fn fill_row (&mut self, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    Termbox::fill_row(self, y, ch, fg, bg)
+  }
+
+  fn put_row (&mut self, y: Coord, cells: &[Cell]) {
+    Termbox::put_row(self, y, cells)
+  }
+
+  fn tab_width (&self) -> Coord {
+    Termbox::tab_width(self)
+  }
+}
+
+impl Backend for TestTermbox {
+  fn cell_buffer (&self) -> &[Cell] {
+    TestTermbox::cell_buffer(self)
+  }
+
+  fn cell_buffer_mut (&mut self) -> &mut [Cell] {
+    TestTermbox::cell_buffer_mut(self)
+  }
+
+  fn clear (&mut self) {
+    TestTermbox::clear(self)
+  }
+
+  fn cursor (&self) -> Option<(Coord, Coord)> {
+    TestTermbox::cursor(self)
+  }
+
+  fn height (&self) -> Coord {
+    TestTermbox::height(self)
+  }
+
+  fn hide_cursor (&mut self) {
+    TestTermbox::hide_cursor(self)
+  }
+
+  fn input_mode (&self) -> InputMode {
+    TestTermbox::input_mode(self)
+  }
+
+  fn is_mouse_enabled (&self) -> bool {
+    TestTermbox::is_mouse_enabled(self)
+  }
+
+  fn output_mode (&self) -> OutputMode {
+    TestTermbox::output_mode(self)
+  }
+
+  fn peek_event (&mut self, timeout: Time) -> Option<Event> {
+    TestTermbox::peek_event(self, timeout)
+  }
+
+  fn poll_event (&mut self) -> Event {
+    TestTermbox::poll_event(self)
+  }
+
+  fn present (&mut self) {
+    TestTermbox::present(self)
+  }
+
+  fn put_cell (&mut self, x: Coord, y: Coord, cell: Cell) {
+    TestTermbox::put_cell(self, x, y, cell)
+  }
+
+  fn set_clear_attributes (&mut self, fg: Attribute, bg: Attribute) {
+    TestTermbox::set_clear_attributes(self, fg, bg)
+  }
+
+  fn set_cursor (&mut self, x: Coord, y: Coord) -> Option<(Coord, Coord)> {
+    TestTermbox::set_cursor(self, x, y)
+  }
+
+  fn set_input_mode (&mut self, mode: InputMode) {
+    TestTermbox::set_input_mode(self, mode)
+  }
+
+  fn set_mouse_enabled (&mut self, enabled: bool) {
+    TestTermbox::set_mouse_enabled(self, enabled)
+  }
+
+  fn set_output_mode (&mut self, mode: OutputMode) {
+    TestTermbox::set_output_mode(self, mode)
+  }
+
+  fn width (&self) -> Coord {
+    TestTermbox::width(self)
+  }
+
+  fn tab_width (&self) -> Coord {
+    TestTermbox::tab_width(self)
+  }
+}
+
+
+//
+// Functions
+//
+
+
+// Panics if a cell is outside of the output buffer, when the `debug-bounds` feature is enabled in
+// a debug build. Drawing functions that clip on purpose keep to the output buffer themselves, so
+// that only cells placed by the caller are checked.
+#[inline]
+pub(crate) fn debug_check_bounds (x: Coord, y: Coord, width: Coord, height: Coord) {
+  if cfg!(all(feature = "debug-bounds", debug_assertions))
+    && (x < 0 || y < 0 || x >= width || y >= height)
+  {
+    panic!("{}", OutOfBounds { x: x, y: y, width: width, height: height });
+  }
+}
+
+// Replaces the control characters in `msg` other than those in `keep` as chosen by `mode`.
+fn replace_controls<'a> (msg: &'a str, mode: ControlChars, keep: &[char]) -> Cow<'a, str> {
+  let replaced = |ch: char| ch.is_control() && !keep.contains(&ch);
+  if mode == ControlChars::Raw || !msg.chars().any(replaced) {
+    return Cow::Borrowed(msg);
+  }
+
+  let mut out = String::with_capacity(msg.len());
+  for ch in msg.chars() {
+    if !replaced(ch) {
+      out.push(ch);
+      continue;
+    }
+    let code = ch as u32;
+    match mode {
+      ControlChars::Raw => { out.push(ch); },
+      ControlChars::Pictures => {
+        out.push(match code {
+          0x00..=0x1f => char::from_u32(0x2400 + code).unwrap(),
+          0x7f => '\u{2421}',
+          _ => '\u{fffd}',
+        });
+      },
+      ControlChars::Caret => {
+        if code >= 0x80 {
+          out.push_str("M-");
+        }
+        out.push('^');
+        out.push(if code == 0x7f { '?' } else { char::from_u32((code & 0x7f) + 0x40).unwrap() });
+      },
+      ControlChars::Strip => {},
+    }
+  }
+  return Cow::Owned(out);
+}
@@ -0,0 +1,62 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+const ALPHABET: &'static [u8; 64] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn decode (input: &[u8]) -> Option<Vec<u8>> {
+  let mut output = Vec::with_capacity(input.len() / 4 * 3);
+  let mut bits: u32 = 0;
+  let mut num_bits = 0;
+
+  for &byte in input {
+    let value = match byte {
+      b'A'..=b'Z' => byte - b'A',
+      b'a'..=b'z' => byte - b'a' + 26,
+      b'0'..=b'9' => byte - b'0' + 52,
+      b'+' => 62,
+      b'/' => 63,
+      b'=' => { break; },
+      _ => { return None; },
+    };
+
+    bits = (bits << 6) | value as u32;
+    num_bits += 6;
+
+    if num_bits >= 8 {
+      num_bits -= 8;
+      output.push((bits >> num_bits) as u8);
+    }
+  }
+
+  return Some(output);
+}
+
+pub fn encode (input: &[u8]) -> String {
+  let mut output = String::with_capacity((input.len() + 2) / 3 * 4);
+
+  for chunk in input.chunks(3) {
+    let b0 = chunk[0] as u32;
+    let b1 = if chunk.len() > 1 { chunk[1] as u32 } else { 0 };
+    let b2 = if chunk.len() > 2 { chunk[2] as u32 } else { 0 };
+    let bits = (b0 << 16) | (b1 << 8) | b2;
+
+    output.push(ALPHABET[(bits >> 18) as usize & 63] as char);
+    output.push(ALPHABET[(bits >> 12) as usize & 63] as char);
+
+    if chunk.len() > 1 {
+      output.push(ALPHABET[(bits >> 6) as usize & 63] as char);
+    } else {
+      output.push('=');
+    }
+
+    if chunk.len() > 2 {
+      output.push(ALPHABET[bits as usize & 63] as char);
+    } else {
+      output.push('=');
+    }
+  }
+
+  return output;
+}
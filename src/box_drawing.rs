@@ -0,0 +1,315 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Draws lines and boxes that join up with box-drawing characters already in the output buffer.
+//! Each character is treated as a set of arms pointing up, right, down and left. When a line is
+//! drawn over an existing character, their arms are combined, so `─` drawn over `│` becomes `┼`
+//! and a border drawn along the edge of a table turns into `├`, `┬` and so on where they meet.
+//!
+//! Lines are drawn with `-`, `|` and `+` instead when the ASCII glyph profile is selected; see the
+//! `glyphs` module.
+//!
+//! ~~~
+//! use termbox::{Backend, DEFAULT};
+//! use termbox::box_drawing::{self, LineStyle};
+//! use termbox::headless::TestTermbox;
+//!
+//! let mut tb = TestTermbox::new(5, 3);
+//! box_drawing::draw_box(&mut tb, 0, 0, 5, 3, LineStyle::Light, DEFAULT, DEFAULT);
+//! box_drawing::vline(&mut tb, 2, 0, 3, LineStyle::Light, DEFAULT, DEFAULT);
+//! assert_eq!(tb.snapshot().to_plain_text(), "┌─┬─┐\n│ │ │\n└─┴─┘\n");
+//! ~~~
+
+use glyphs::{
+  self,
+  GlyphProfile,
+};
+use {
+  Attribute,
+  Backend,
+  Coord,
+};
+
+// Arm weights.
+const NONE: u8 = 0;
+const LIGHT: u8 = 1;
+const HEAVY: u8 = 2;
+const DOUBLE: u8 = 3;
+
+// Arm indices.
+const UP: usize = 0;
+const RIGHT: usize = 1;
+const DOWN: usize = 2;
+const LEFT: usize = 3;
+
+type Arms = [u8; 4];
+
+// The arms of each mergeable character, as weights for up, right, down and left.
+const GLYPHS: &'static [(char, Arms)] = &[
+  ('─', [0, 1, 0, 1]), ('━', [0, 2, 0, 2]), ('│', [1, 0, 1, 0]), ('┃', [2, 0, 2, 0]),
+  ('┌', [0, 1, 1, 0]), ('┍', [0, 2, 1, 0]), ('┎', [0, 1, 2, 0]), ('┏', [0, 2, 2, 0]),
+  ('┐', [0, 0, 1, 1]), ('┑', [0, 0, 1, 2]), ('┒', [0, 0, 2, 1]), ('┓', [0, 0, 2, 2]),
+  ('└', [1, 1, 0, 0]), ('┕', [1, 2, 0, 0]), ('┖', [2, 1, 0, 0]), ('┗', [2, 2, 0, 0]),
+  ('┘', [1, 0, 0, 1]), ('┙', [1, 0, 0, 2]), ('┚', [2, 0, 0, 1]), ('┛', [2, 0, 0, 2]),
+  ('├', [1, 1, 1, 0]), ('┝', [1, 2, 1, 0]), ('┞', [2, 1, 1, 0]), ('┟', [1, 1, 2, 0]),
+  ('┠', [2, 1, 2, 0]), ('┡', [2, 2, 1, 0]), ('┢', [1, 2, 2, 0]), ('┣', [2, 2, 2, 0]),
+  ('┤', [1, 0, 1, 1]), ('┥', [1, 0, 1, 2]), ('┦', [2, 0, 1, 1]), ('┧', [1, 0, 2, 1]),
+  ('┨', [2, 0, 2, 1]), ('┩', [2, 0, 1, 2]), ('┪', [1, 0, 2, 2]), ('┫', [2, 0, 2, 2]),
+  ('┬', [0, 1, 1, 1]), ('┭', [0, 1, 1, 2]), ('┮', [0, 2, 1, 1]), ('┯', [0, 2, 1, 2]),
+  ('┰', [0, 1, 2, 1]), ('┱', [0, 1, 2, 2]), ('┲', [0, 2, 2, 1]), ('┳', [0, 2, 2, 2]),
+  ('┴', [1, 1, 0, 1]), ('┵', [1, 1, 0, 2]), ('┶', [1, 2, 0, 1]), ('┷', [1, 2, 0, 2]),
+  ('┸', [2, 1, 0, 1]), ('┹', [2, 1, 0, 2]), ('┺', [2, 2, 0, 1]), ('┻', [2, 2, 0, 2]),
+  ('┼', [1, 1, 1, 1]), ('┽', [1, 1, 1, 2]), ('┾', [1, 2, 1, 1]), ('┿', [1, 2, 1, 2]),
+  ('╀', [2, 1, 1, 1]), ('╁', [1, 1, 2, 1]), ('╂', [2, 1, 2, 1]), ('╃', [2, 1, 1, 2]),
+  ('╄', [2, 2, 1, 1]), ('╅', [1, 1, 2, 2]), ('╆', [1, 2, 2, 1]), ('╇', [2, 2, 1, 2]),
+  ('╈', [1, 2, 2, 2]), ('╉', [2, 1, 2, 2]), ('╊', [2, 2, 2, 1]), ('╋', [2, 2, 2, 2]),
+  ('═', [0, 3, 0, 3]), ('║', [3, 0, 3, 0]),
+  ('╒', [0, 3, 1, 0]), ('╓', [0, 1, 3, 0]), ('╔', [0, 3, 3, 0]),
+  ('╕', [0, 0, 1, 3]), ('╖', [0, 0, 3, 1]), ('╗', [0, 0, 3, 3]),
+  ('╘', [1, 3, 0, 0]), ('╙', [3, 1, 0, 0]), ('╚', [3, 3, 0, 0]),
+  ('╛', [1, 0, 0, 3]), ('╜', [3, 0, 0, 1]), ('╝', [3, 0, 0, 3]),
+  ('╞', [1, 3, 1, 0]), ('╟', [3, 1, 3, 0]), ('╠', [3, 3, 3, 0]),
+  ('╡', [1, 0, 1, 3]), ('╢', [3, 0, 3, 1]), ('╣', [3, 0, 3, 3]),
+  ('╤', [0, 3, 1, 3]), ('╥', [0, 1, 3, 1]), ('╦', [0, 3, 3, 3]),
+  ('╧', [1, 3, 0, 3]), ('╨', [3, 1, 0, 1]), ('╩', [3, 3, 0, 3]),
+  ('╪', [1, 3, 1, 3]), ('╫', [3, 1, 3, 1]), ('╬', [3, 3, 3, 3]),
+  ('╭', [0, 1, 1, 0]), ('╮', [0, 0, 1, 1]), ('╯', [1, 0, 0, 1]), ('╰', [1, 1, 0, 0]),
+  ('╴', [0, 0, 0, 1]), ('╵', [1, 0, 0, 0]), ('╶', [0, 1, 0, 0]), ('╷', [0, 0, 1, 0]),
+  ('╸', [0, 0, 0, 2]), ('╹', [2, 0, 0, 0]), ('╺', [0, 2, 0, 0]), ('╻', [0, 0, 2, 0]),
+  ('╼', [0, 2, 0, 1]), ('╽', [1, 0, 2, 0]), ('╾', [0, 1, 0, 2]), ('╿', [2, 0, 1, 0]),
+];
+
+
+//
+// LineStyle
+//
+
+
+/// The stroke used to draw lines and boxes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LineStyle {
+  Light,
+  Heavy,
+  Double,
+  /// Light lines with rounded box corners.
+  Rounded,
+}
+
+impl LineStyle {
+  fn corners (self) -> [char; 4] {
+    if glyphs::profile() == GlyphProfile::Ascii {
+      let glyphs = glyphs::current();
+      return [glyphs.top_left, glyphs.top_right, glyphs.bottom_left, glyphs.bottom_right];
+    }
+
+    match self {
+      LineStyle::Light => ['┌', '┐', '└', '┘'],
+      LineStyle::Heavy => ['┏', '┓', '┗', '┛'],
+      LineStyle::Double => ['╔', '╗', '╚', '╝'],
+      LineStyle::Rounded => ['╭', '╮', '╰', '╯'],
+    }
+  }
+
+  fn weight (self) -> u8 {
+    match self {
+      LineStyle::Light | LineStyle::Rounded => LIGHT,
+      LineStyle::Heavy => HEAVY,
+      LineStyle::Double => DOUBLE,
+    }
+  }
+}
+
+
+//
+// Functions
+//
+
+
+/// Draws the outline of a rectangle, merging it with any lines it crosses or touches.
+pub fn draw_box<B: Backend> (tb: &mut B, x: Coord, y: Coord, w: Coord, h: Coord,
+                             style: LineStyle, fg: Attribute, bg: Attribute)
+{
+  if w < 2 || h < 2 {
+    return;
+  }
+
+  let (right, bottom) = (x + w - 1, y + h - 1);
+  let weight = style.weight();
+
+  for col in x + 1..right {
+    put_arms(tb, col, y, [NONE, weight, NONE, weight], fg, bg);
+    put_arms(tb, col, bottom, [NONE, weight, NONE, weight], fg, bg);
+  }
+  for row in y + 1..bottom {
+    put_arms(tb, x, row, [weight, NONE, weight, NONE], fg, bg);
+    put_arms(tb, right, row, [weight, NONE, weight, NONE], fg, bg);
+  }
+
+  let corners = style.corners();
+  put_merged(tb, x, y, corners[0], fg, bg);
+  put_merged(tb, right, y, corners[1], fg, bg);
+  put_merged(tb, x, bottom, corners[2], fg, bg);
+  put_merged(tb, right, bottom, corners[3], fg, bg);
+}
+
+/// Draws a horizontal line `len` cells long. Where an end of the line meets an existing line, it
+/// joins it rather than crossing it, so a line drawn between the sides of a box ends in `├` and
+/// `┤`.
+pub fn hline<B: Backend> (tb: &mut B, x: Coord, y: Coord, len: Coord, style: LineStyle,
+                          fg: Attribute, bg: Attribute)
+{
+  let weight = style.weight();
+
+  for col in x..x + len {
+    let mut arms = [NONE, weight, NONE, weight];
+    if col == x && arms_at(tb, col, y).is_some() {
+      arms[LEFT] = NONE;
+    }
+    if col == x + len - 1 && arms_at(tb, col, y).is_some() {
+      arms[RIGHT] = NONE;
+    }
+    put_arms(tb, col, y, arms, fg, bg);
+  }
+}
+
+/// Returns the character that results from drawing `new` over `existing`. If both are box-drawing
+/// characters, the arms of `new` are added to those of `existing`, replacing arms of a different
+/// weight. Otherwise `new` is returned.
+pub fn merge (existing: char, new: char) -> char {
+  match (char_arms(existing), char_arms(new)) {
+    (Some(old_arms), Some(new_arms)) => {
+      let arms = combine(old_arms, new_arms);
+      // Keep the new character as it is if merging adds nothing, which preserves rounded corners.
+      if arms == new_arms {
+        return new;
+      }
+      return render(arms, new_arms).unwrap_or(new);
+    },
+    _ => new,
+  }
+}
+
+/// Changes a single cell to `ch`, merged with the box-drawing character already there, if any.
+pub fn put_merged<B: Backend> (tb: &mut B, x: Coord, y: Coord, ch: char, fg: Attribute,
+                               bg: Attribute)
+{
+  let existing = match cell_char(tb, x, y) {
+    Some(existing) => existing,
+    None => { return; },
+  };
+  tb.change_cell(x, y, merge(existing, ch), fg, bg);
+}
+
+/// Draws a vertical line `len` cells long. Where an end of the line meets an existing line, it
+/// joins it rather than crossing it.
+pub fn vline<B: Backend> (tb: &mut B, x: Coord, y: Coord, len: Coord, style: LineStyle,
+                          fg: Attribute, bg: Attribute)
+{
+  let weight = style.weight();
+
+  for row in y..y + len {
+    let mut arms = [weight, NONE, weight, NONE];
+    if row == y && arms_at(tb, x, row).is_some() {
+      arms[UP] = NONE;
+    }
+    if row == y + len - 1 && arms_at(tb, x, row).is_some() {
+      arms[DOWN] = NONE;
+    }
+    put_arms(tb, x, row, arms, fg, bg);
+  }
+}
+
+// Returns the arms of the box-drawing character at the given position, if any.
+fn arms_at<B: Backend> (tb: &B, x: Coord, y: Coord) -> Option<Arms> {
+  cell_char(tb, x, y).and_then(char_arms)
+}
+
+// Returns the arms of a line character in the current glyph profile.
+fn char_arms (ch: char) -> Option<Arms> {
+  if glyphs::profile() == GlyphProfile::Ascii {
+    return match ch {
+      '-' => Some([NONE, LIGHT, NONE, LIGHT]),
+      '|' => Some([LIGHT, NONE, LIGHT, NONE]),
+      '+' => Some([LIGHT, LIGHT, LIGHT, LIGHT]),
+      _ => None,
+    };
+  }
+  return glyph_arms(ch);
+}
+
+fn cell_char<B: Backend> (tb: &B, x: Coord, y: Coord) -> Option<char> {
+  let (width, height) = (tb.width(), tb.height());
+  if x < 0 || y < 0 || x >= width || y >= height {
+    return None;
+  }
+  let cell = tb.cell_buffer()[(y * width + x) as usize];
+  return Some(cell.ch());
+}
+
+fn combine (old_arms: Arms, new_arms: Arms) -> Arms {
+  let mut arms = old_arms;
+  for i in 0..4 {
+    if new_arms[i] != NONE {
+      arms[i] = new_arms[i];
+    }
+  }
+  return arms;
+}
+
+// Finds the character with the given arms. Unicode lacks many mixtures of weights, such as double
+// and heavy lines meeting. If there is no exact match, every arm is redrawn in the heaviest weight
+// used by `new_arms`.
+fn compose (arms: Arms, new_arms: Arms) -> Option<char> {
+  if let Some(ch) = find_glyph(arms) {
+    return Some(ch);
+  }
+
+  let weight = new_arms.iter().cloned().max().unwrap_or(NONE);
+  let mut uniform = arms;
+  for arm in uniform.iter_mut() {
+    if *arm != NONE {
+      *arm = weight;
+    }
+  }
+  return find_glyph(uniform);
+}
+
+fn find_glyph (arms: Arms) -> Option<char> {
+  GLYPHS.iter().find(|&&(_, a)| a == arms).map(|&(ch, _)| ch)
+}
+
+fn glyph_arms (ch: char) -> Option<Arms> {
+  GLYPHS.iter().find(|&&(c, _)| c == ch).map(|&(_, arms)| arms)
+}
+
+// Finds the character to draw for the given arms in the current glyph profile.
+fn render (arms: Arms, new_arms: Arms) -> Option<char> {
+  if glyphs::profile() == GlyphProfile::Ascii {
+    let vertical = arms[UP] != NONE || arms[DOWN] != NONE;
+    let horizontal = arms[LEFT] != NONE || arms[RIGHT] != NONE;
+    return match (horizontal, vertical) {
+      (true, true) => Some('+'),
+      (true, false) => Some('-'),
+      (false, true) => Some('|'),
+      (false, false) => None,
+    };
+  }
+  return compose(arms, new_arms);
+}
+
+fn put_arms<B: Backend> (tb: &mut B, x: Coord, y: Coord, arms: Arms, fg: Attribute, bg: Attribute) {
+  let existing = match arms_at(tb, x, y) {
+    Some(existing) => combine(existing, arms),
+    None => {
+      if cell_char(tb, x, y).is_none() {
+        return;
+      }
+      arms
+    },
+  };
+
+  if let Some(ch) = render(existing, arms) {
+    tb.change_cell(x, y, ch, fg, bg);
+  }
+}
@@ -0,0 +1,278 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::f32::consts::PI;
+
+use {
+  Attribute,
+  Backend,
+  Coord,
+  DEFAULT,
+  DEFAULT_CELL_ASPECT_RATIO,
+};
+
+// Bits of the braille pattern for each dot of a cell, indexed by row and then column.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+// The braille pattern with no dots raised.
+const BRAILLE_BASE: u32 = 0x2800;
+
+
+//
+// CanvasMode
+//
+
+
+/// How a `Canvas` maps dots onto cells.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CanvasMode {
+  /// Two dots to a cell, one above the other, drawn with half blocks. Each dot has its own color.
+  HalfBlocks,
+  /// Eight dots to a cell, two wide and four high, drawn with braille patterns. All dots of a cell
+  /// share one color, that of the dot drawn last.
+  Braille,
+}
+
+impl CanvasMode {
+  /// Returns the number of dots across and down each cell.
+  pub fn dots_per_cell (self) -> (Coord, Coord) {
+    match self {
+      CanvasMode::HalfBlocks => (1, 2),
+      CanvasMode::Braille => (2, 4),
+    }
+  }
+}
+
+
+//
+// Canvas
+//
+
+
+/// A surface of dots finer than the cell grid, for plots and games. Dots are addressed from the
+/// top left, and anything drawn outside of the canvas is clipped. Nothing appears on the screen
+/// until the canvas is copied to a backend with `blit`.
+///
+/// ~~~
+/// use termbox::RED;
+/// use termbox::canvas::{Canvas, CanvasMode};
+/// use termbox::headless::TestTermbox;
+///
+/// let mut canvas = Canvas::new(CanvasMode::Braille, 2, 1);
+/// assert_eq!(canvas.dot_size(), (4, 4));
+/// canvas.line(0, 0, 3, 3, RED);
+///
+/// let mut tb = TestTermbox::new(2, 1);
+/// canvas.blit(&mut tb, 0, 0);
+/// assert_eq!(tb.snapshot().to_plain_text(), "\u{2811}\u{2884}\n");
+/// ~~~
+pub struct Canvas {
+  mode: CanvasMode,
+  width: Coord,
+  height: Coord,
+  // The color of each lit dot, in row-major order.
+  dots: Vec<Option<Attribute>>,
+  // The color of the dot drawn last in each cell, used for braille.
+  cell_colors: Vec<Attribute>,
+  background: Attribute,
+  aspect_ratio: f32,
+}
+
+impl Canvas {
+  /// Returns the aspect ratio of cells used by `circle`. See `set_aspect_ratio`.
+  pub fn aspect_ratio (&self) -> f32 {
+    self.aspect_ratio
+  }
+
+  pub fn background (&self) -> Attribute {
+    self.background
+  }
+
+  /// Copies the canvas to `tb` with its top left cell at `x`, `y`. Every cell of the canvas is
+  /// written, so cells without lit dots are cleared to the background.
+  pub fn blit<B: Backend> (&self, tb: &mut B, x: Coord, y: Coord) {
+    for row in 0..self.height {
+      for col in 0..self.width {
+        let (ch, fg, bg) = match self.mode {
+          CanvasMode::HalfBlocks => self.half_block(col, row),
+          CanvasMode::Braille => self.braille(col, row),
+        };
+        if tb.get_cell(x + col, y + row).is_some() {
+          tb.change_cell(x + col, y + row, ch, fg, bg);
+        }
+      }
+    }
+  }
+
+  /// Draws the outline of a circle centered on `cx`, `cy` with a horizontal radius of `r` dots.
+  /// The vertical radius is scaled by the aspect ratio so that the circle comes out round.
+  pub fn circle (&mut self, cx: Coord, cy: Coord, r: Coord, color: Attribute) {
+    if r <= 0 {
+      self.point(cx, cy, color);
+      return;
+    }
+
+    let (dots_x, dots_y) = self.mode.dots_per_cell();
+    let dot_aspect = self.aspect_ratio * dots_x as f32 / dots_y as f32;
+    let rx = r as f32;
+    let ry = rx / dot_aspect;
+    let steps = (8.0 * rx.max(ry)).ceil().max(8.0) as usize;
+
+    let point_at = |i: usize| -> (Coord, Coord) {
+      let angle = 2.0 * PI * i as f32 / steps as f32;
+      (cx + (rx * angle.cos()).round() as Coord, cy + (ry * angle.sin()).round() as Coord)
+    };
+    let mut last = point_at(0);
+    for i in 1..steps + 1 {
+      let next = point_at(i);
+      self.line(last.0, last.1, next.0, next.1, color);
+      last = next;
+    }
+  }
+
+  /// Turns off every dot.
+  pub fn clear (&mut self) {
+    for dot in &mut self.dots {
+      *dot = None;
+    }
+  }
+
+  /// Returns the size of the canvas in dots.
+  pub fn dot_size (&self) -> (Coord, Coord) {
+    let (dots_x, dots_y) = self.mode.dots_per_cell();
+    (self.width * dots_x, self.height * dots_y)
+  }
+
+  /// Returns the color of the dot at `x`, `y`, or `None` if it is off or outside of the canvas.
+  pub fn get (&self, x: Coord, y: Coord) -> Option<Attribute> {
+    match self.offset(x, y) {
+      Some(i) => self.dots[i],
+      None => None,
+    }
+  }
+
+  /// Returns the height of the canvas in cells.
+  pub fn height (&self) -> Coord {
+    self.height
+  }
+
+  /// Draws a straight line of dots from `x0`, `y0` to `x1`, `y1`, including both ends.
+  pub fn line (&mut self, x0: Coord, y0: Coord, x1: Coord, y1: Coord, color: Attribute) {
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let step_x = if x0 < x1 { 1 } else { -1 };
+    let step_y = if y0 < y1 { 1 } else { -1 };
+    let (mut x, mut y) = (x0, y0);
+    let mut error = dx + dy;
+
+    loop {
+      self.point(x, y, color);
+      if x == x1 && y == y1 {
+        break;
+      }
+      let doubled = 2 * error;
+      if doubled >= dy {
+        error += dy;
+        x += step_x;
+      }
+      if doubled <= dx {
+        error += dx;
+        y += step_y;
+      }
+    }
+  }
+
+  pub fn mode (&self) -> CanvasMode {
+    self.mode
+  }
+
+  /// Creates a blank canvas `width` cells wide and `height` cells high.
+  pub fn new (mode: CanvasMode, width: Coord, height: Coord) -> Canvas {
+    let width = width.max(0);
+    let height = height.max(0);
+    let (dots_x, dots_y) = mode.dots_per_cell();
+    let cells = width as usize * height as usize;
+
+    Canvas {
+      mode: mode,
+      width: width,
+      height: height,
+      dots: vec![None; cells * (dots_x * dots_y) as usize],
+      cell_colors: vec![DEFAULT; cells],
+      background: DEFAULT,
+      aspect_ratio: DEFAULT_CELL_ASPECT_RATIO,
+    }
+  }
+
+  /// Lights the dot at `x`, `y` in `color`. Dots outside of the canvas are ignored.
+  pub fn point (&mut self, x: Coord, y: Coord, color: Attribute) {
+    if let Some(i) = self.offset(x, y) {
+      let (dots_x, dots_y) = self.mode.dots_per_cell();
+      let cell = (y / dots_y * self.width + x / dots_x) as usize;
+      self.dots[i] = Some(color);
+      self.cell_colors[cell] = color;
+    }
+  }
+
+  /// Sets how many times taller than wide a cell is, which `circle` uses to keep circles round.
+  /// The default assumes cells twice as tall as they are wide. Pass
+  /// `Termbox::cell_aspect_ratio` to match the terminal.
+  pub fn set_aspect_ratio (&mut self, ratio: f32) {
+    if ratio > 0.0 {
+      self.aspect_ratio = ratio;
+    }
+  }
+
+  /// Sets the color behind dots and of cells without lit dots. The default is `DEFAULT`.
+  pub fn set_background (&mut self, color: Attribute) {
+    self.background = color;
+  }
+
+  /// Turns off the dot at `x`, `y`.
+  pub fn unset (&mut self, x: Coord, y: Coord) {
+    if let Some(i) = self.offset(x, y) {
+      self.dots[i] = None;
+    }
+  }
+
+  /// Returns the width of the canvas in cells.
+  pub fn width (&self) -> Coord {
+    self.width
+  }
+
+  // Returns the braille pattern for the cell at `col`, `row`.
+  fn braille (&self, col: Coord, row: Coord) -> (char, Attribute, Attribute) {
+    let mut bits = 0;
+    for (dy, dot_row) in BRAILLE_DOTS.iter().enumerate() {
+      for (dx, &bit) in dot_row.iter().enumerate() {
+        if self.get(col * 2 + dx as Coord, row * 4 + dy as Coord).is_some() {
+          bits |= bit;
+        }
+      }
+    }
+    if bits == 0 {
+      return (' ', DEFAULT, self.background);
+    }
+    let ch = ::std::char::from_u32(BRAILLE_BASE + bits).unwrap_or(' ');
+    return (ch, self.cell_colors[(row * self.width + col) as usize], self.background);
+  }
+
+  // Returns the half block for the cell at `col`, `row`.
+  fn half_block (&self, col: Coord, row: Coord) -> (char, Attribute, Attribute) {
+    match (self.get(col, row * 2), self.get(col, row * 2 + 1)) {
+      (Some(top), Some(bottom)) => ('\u{2580}', top, bottom),
+      (Some(top), None) => ('\u{2580}', top, self.background),
+      (None, Some(bottom)) => ('\u{2584}', bottom, self.background),
+      (None, None) => (' ', DEFAULT, self.background),
+    }
+  }
+
+  fn offset (&self, x: Coord, y: Coord) -> Option<usize> {
+    let (width, height) = self.dot_size();
+    if x < 0 || y < 0 || x >= width || y >= height {
+      return None;
+    }
+    return Some(y as usize * width as usize + x as usize);
+  }
+}
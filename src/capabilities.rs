@@ -0,0 +1,136 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::env;
+
+use detect;
+use images::ImageProtocol;
+use terminfo::{
+  self,
+  Terminfo,
+};
+use OutputMode;
+
+
+//
+// Capabilities
+//
+
+
+/// Features of the terminal, guessed from the environment and the terminfo database when termbox
+/// is opened. See `Termbox::capabilities`. Applications can use this to choose an output mode and
+/// fall back to simpler drawing where a feature is missing.
+///
+/// ~~~
+/// use termbox::OutputMode;
+/// use termbox::capabilities::{Capabilities, MouseProtocol};
+///
+/// let caps = Capabilities::for_term("linux");
+/// assert!(!caps.title);
+/// assert!(!caps.wide_chars);
+/// if caps.colors < 256 {
+///   assert_eq!(caps.output_mode(), OutputMode::Normal);
+/// }
+/// ~~~
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Capabilities {
+  /// The terminal's name, taken from `TERM`.
+  pub term: String,
+  /// How many colors the terminal has in its palette.
+  pub colors: u32,
+  /// Whether the terminal accepts 24-bit colors.
+  pub truecolor: bool,
+  /// How the terminal reports mouse events.
+  pub mouse: MouseProtocol,
+  /// Whether the locale uses UTF-8, so characters outside of ASCII can be output.
+  pub unicode: bool,
+  /// Whether the terminal draws wide characters, such as CJK ideographs and most emoji, across two
+  /// cells. Text layout in termbox assumes it does.
+  pub wide_chars: bool,
+  /// Whether the terminal's window title can be set.
+  pub title: bool,
+  /// Whether the terminal understands OSC 8 hyperlinks. See `Termbox::set_hyperlinks_enabled`.
+  pub hyperlinks: bool,
+  /// Whether the terminal supports synchronized output (mode 2026). See
+  /// `Termbox::set_synchronized_output`.
+  pub synchronized_output: bool,
+  /// How images can be drawn. See `Termbox::draw_image`.
+  pub images: ImageProtocol,
+}
+
+impl Capabilities {
+  /// Detects the capabilities of the terminal named by `TERM`.
+  pub fn detect () -> Capabilities {
+    let term = env::var("TERM").unwrap_or_default();
+    return Capabilities::for_term(&term);
+  }
+
+  /// Detects capabilities as if `TERM` were `term`. Other environment variables are still
+  /// consulted.
+  pub fn for_term (term: &str) -> Capabilities {
+    let info = Terminfo::load(term);
+    let console = term == "linux" || term.starts_with("vt") || term == "dumb" || term.is_empty();
+
+    let colors = match info.as_ref().and_then(|info| info.number(terminfo::MAX_COLORS)) {
+      Some(n) => n as u32,
+      None => {
+        if term.contains("256color") || term.ends_with("-direct") {
+          256
+        } else if console && term != "linux" {
+          0
+        } else {
+          8
+        }
+      },
+    };
+    let mouse = match info.as_ref().and_then(|info| info.string(terminfo::KEY_MOUSE)) {
+      Some(seq) if seq.starts_with(b"\x1b[<") => MouseProtocol::Sgr,
+      Some(seq) if seq.starts_with(b"\x1b[M") => MouseProtocol::X10,
+      Some(_) => MouseProtocol::None,
+      None => if console { MouseProtocol::None } else { MouseProtocol::Sgr },
+    };
+    let title = detect::title(term)
+      || info.as_ref().map(|info| info.flag(terminfo::HAS_STATUS_LINE)).unwrap_or(false);
+
+    return Capabilities {
+      term: term.to_string(),
+      colors: colors,
+      truecolor: detect::truecolor(term),
+      mouse: mouse,
+      unicode: detect::utf8_locale(),
+      wide_chars: !console,
+      title: title,
+      hyperlinks: detect::hyperlinks(),
+      synchronized_output: detect::synchronized_output(term),
+      images: detect::image_protocol(term),
+    };
+  }
+
+  /// Returns the output mode with the most colors that the terminal supports.
+  pub fn output_mode (&self) -> OutputMode {
+    if self.colors >= 256 || self.truecolor {
+      OutputMode::Color256
+    } else {
+      OutputMode::Normal
+    }
+  }
+}
+
+
+//
+// MouseProtocol
+//
+
+
+/// The encoding a terminal uses for mouse reports.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MouseProtocol {
+  /// The terminal doesn't report mouse events.
+  None,
+  /// The original xterm encoding, which can't report positions past column or row 223.
+  X10,
+  /// The SGR encoding (mode 1006), which has no position limit and reports which button was
+  /// released.
+  Sgr,
+}
@@ -0,0 +1,405 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! C bindings for the safe API, so C and C++ programs can use the pure Rust backend and the
+//! features this crate adds on top of termbox, such as bracketed paste, clipboard access and color
+//! approximation. The declarations are in `include/termbox_rs.h`. Build the crate as a static or
+//! dynamic library with the `capi` feature:
+//!
+//! ~~~text
+//! cargo rustc --release --no-default-features --features pure-rust,capi --crate-type staticlib
+//! ~~~
+//!
+//! Every function except `tbrs_open` takes the handle returned by `tbrs_open`, which must not be
+//! null or used after it is passed to `tbrs_close`. Strings are NUL-terminated UTF-8; invalid
+//! sequences are replaced with U+FFFD. As with the Rust API, only one handle can be open at a
+//! time.
+
+use std::ffi::CStr;
+use std::os::raw::{
+  c_char,
+  c_int,
+};
+use std::ptr;
+
+use ffi;
+use {
+  Attribute,
+  Color,
+  Coord,
+  Error,
+  Event,
+  InputMode,
+  MouseButton,
+  OutputMode,
+  Signal,
+  Termbox,
+};
+
+pub const TBRS_EVENT_NONE: c_int = 0;
+pub const TBRS_EVENT_KEY: c_int = 1;
+pub const TBRS_EVENT_RESIZE: c_int = 2;
+pub const TBRS_EVENT_MOUSE: c_int = 3;
+pub const TBRS_EVENT_PASTE_START: c_int = 4;
+pub const TBRS_EVENT_PASTE_END: c_int = 5;
+pub const TBRS_EVENT_SIGNAL: c_int = 6;
+pub const TBRS_EVENT_RESUMED: c_int = 7;
+pub const TBRS_EVENT_RESUME_FAILED: c_int = 8;
+pub const TBRS_EVENT_OTHER: c_int = 9;
+
+pub const TBRS_MOUSE_LEFT: c_int = 0;
+pub const TBRS_MOUSE_RIGHT: c_int = 1;
+pub const TBRS_MOUSE_MIDDLE: c_int = 2;
+pub const TBRS_MOUSE_RELEASE: c_int = 3;
+pub const TBRS_MOUSE_WHEEL_UP: c_int = 4;
+pub const TBRS_MOUSE_WHEEL_DOWN: c_int = 5;
+pub const TBRS_MOUSE_WHEEL_LEFT: c_int = 6;
+pub const TBRS_MOUSE_WHEEL_RIGHT: c_int = 7;
+
+pub const TBRS_INPUT_ESC: c_int = ffi::TB_INPUT_ESC;
+pub const TBRS_INPUT_ALT: c_int = ffi::TB_INPUT_ALT;
+
+pub const TBRS_OUTPUT_NORMAL: c_int = ffi::TB_OUTPUT_NORMAL;
+pub const TBRS_OUTPUT_256: c_int = ffi::TB_OUTPUT_256;
+pub const TBRS_OUTPUT_216: c_int = ffi::TB_OUTPUT_216;
+pub const TBRS_OUTPUT_GRAYSCALE: c_int = ffi::TB_OUTPUT_GRAYSCALE;
+
+pub const TBRS_SIGNAL_INTERRUPT: c_int = 0;
+pub const TBRS_SIGNAL_TERMINATE: c_int = 1;
+
+// Error codes stored by `tbrs_open`. The first three match termbox's `tb_init`.
+pub const TBRS_EUNSUPPORTED_TERMINAL: c_int = -1;
+pub const TBRS_EFAILED_TO_OPEN_TTY: c_int = -2;
+pub const TBRS_EPIPE_TRAP_ERROR: c_int = -3;
+pub const TBRS_ELOCKED: c_int = -4;
+
+
+//
+// TbrsEvent
+//
+
+
+/// An event as seen from C. Which fields are meaningful depends on `kind`, one of the
+/// `TBRS_EVENT_*` constants:
+///
+/// * Key events set `key`, `ch` and `alt`. `ch` is zero if the key has no character.
+/// * Resize events set `w` and `h`.
+/// * Mouse events set `x`, `y`, `button`, one of the `TBRS_MOUSE_*` constants, and the modifier
+///   flags `shift`, `alt` and `ctrl`.
+/// * Signal events set `signal`, one of the `TBRS_SIGNAL_*` constants.
+///
+/// Events that C can't see the contents of, such as custom events, timers and raw input, have the
+/// kind `TBRS_EVENT_OTHER` and no fields set. `TBRS_EVENT_NONE` only means that no event arrived.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TbrsEvent {
+  pub kind: c_int,
+  pub key: u16,
+  pub ch: u32,
+  pub alt: c_int,
+  pub w: c_int,
+  pub h: c_int,
+  pub x: c_int,
+  pub y: c_int,
+  pub button: c_int,
+  pub signal: c_int,
+  pub shift: c_int,
+  pub ctrl: c_int,
+}
+
+impl TbrsEvent {
+  fn from_event (event: Option<Event>) -> TbrsEvent {
+    let mut raw = TbrsEvent::default();
+
+    match event {
+      Some(Event::Key(key)) => {
+        raw.kind = TBRS_EVENT_KEY;
+        raw.key = key.key;
+        raw.ch = key.ch.map(|ch| ch as u32).unwrap_or(0);
+        raw.alt = key.alt as c_int;
+      },
+      Some(Event::Resize(resize)) => {
+        raw.kind = TBRS_EVENT_RESIZE;
+        raw.w = resize.w;
+        raw.h = resize.h;
+      },
+      Some(Event::Mouse(mouse)) => {
+        raw.kind = TBRS_EVENT_MOUSE;
+        raw.x = mouse.x;
+        raw.y = mouse.y;
+        raw.button = match mouse.button {
+          MouseButton::Left => TBRS_MOUSE_LEFT,
+          MouseButton::Right => TBRS_MOUSE_RIGHT,
+          MouseButton::Middle => TBRS_MOUSE_MIDDLE,
+          MouseButton::Release => TBRS_MOUSE_RELEASE,
+          MouseButton::WheelUp => TBRS_MOUSE_WHEEL_UP,
+          MouseButton::WheelDown => TBRS_MOUSE_WHEEL_DOWN,
+          MouseButton::WheelLeft => TBRS_MOUSE_WHEEL_LEFT,
+          MouseButton::WheelRight => TBRS_MOUSE_WHEEL_RIGHT,
+        };
+        raw.shift = mouse.shift as c_int;
+        raw.alt = mouse.alt as c_int;
+        raw.ctrl = mouse.ctrl as c_int;
+      },
+      Some(Event::PasteStart) => { raw.kind = TBRS_EVENT_PASTE_START; },
+      Some(Event::PasteEnd) => { raw.kind = TBRS_EVENT_PASTE_END; },
+      Some(Event::Signal(signal)) => {
+        raw.kind = TBRS_EVENT_SIGNAL;
+        raw.signal = match signal {
+          Signal::Interrupt => TBRS_SIGNAL_INTERRUPT,
+          Signal::Terminate => TBRS_SIGNAL_TERMINATE,
+        };
+      },
+      Some(Event::Resumed) => { raw.kind = TBRS_EVENT_RESUMED; },
+      Some(Event::ResumeFailed) => { raw.kind = TBRS_EVENT_RESUME_FAILED; },
+      Some(_) => { raw.kind = TBRS_EVENT_OTHER; },
+      None => {},
+    }
+
+    return raw;
+  }
+}
+
+
+//
+// Functions
+//
+
+
+/// Changes a single cell. `ch` is a Unicode code point; invalid code points are ignored.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_change_cell (tb: *mut Termbox, x: c_int, y: c_int, ch: u32,
+                                           fg: Attribute, bg: Attribute)
+{
+  if let Some(ch) = ::std::char::from_u32(ch) {
+    (*tb).change_cell(x, y, ch, fg, bg);
+  }
+}
+
+/// Clears the output buffer with the clear attributes.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_clear (tb: *mut Termbox) {
+  (*tb).clear();
+}
+
+/// Shuts termbox down, restores the terminal and frees the handle. Null is ignored.
+///
+/// # Safety
+///
+/// `tb` must be null or a handle returned by `tbrs_open`. Once closed, the handle must not be
+/// used or closed again.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_close (tb: *mut Termbox) {
+  if !tb.is_null() {
+    drop(Box::from_raw(tb));
+  }
+}
+
+/// Returns the height of the output buffer in cells.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_height (tb: *mut Termbox) -> c_int {
+  (*tb).height()
+}
+
+/// Opens `/dev/tty` and initializes termbox. Returns null on failure, storing one of the
+/// `TBRS_E*` codes in `error` if it isn't null.
+///
+/// # Safety
+///
+/// `error` must be null or point to a writable `int`.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_open (error: *mut c_int) -> *mut Termbox {
+  match Termbox::open() {
+    Ok(tb) => Box::into_raw(Box::new(tb)),
+    Err(e) => {
+      if !error.is_null() {
+        *error = match e {
+          Error::UnsupportedTerminal => TBRS_EUNSUPPORTED_TERMINAL,
+          Error::FailedToOpenTty => TBRS_EFAILED_TO_OPEN_TTY,
+          Error::PipeTrapError => TBRS_EPIPE_TRAP_ERROR,
+          Error::Locked => TBRS_ELOCKED,
+        };
+      }
+      ptr::null_mut()
+    },
+  }
+}
+
+/// Waits up to `timeout` milliseconds for an event and stores it in `event`. Returns the event's
+/// kind, or `TBRS_EVENT_NONE` if the timeout expired.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed. `event` must point to a
+/// writable `tbrs_event`.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_peek_event (tb: *mut Termbox, event: *mut TbrsEvent,
+                                          timeout: c_int) -> c_int
+{
+  let raw = TbrsEvent::from_event((*tb).peek_event(timeout));
+  *event = raw;
+  return raw.kind;
+}
+
+/// Waits for an event and stores it in `event`. Returns the event's kind.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed. `event` must point to a
+/// writable `tbrs_event`.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_poll_event (tb: *mut Termbox, event: *mut TbrsEvent) -> c_int {
+  let raw = TbrsEvent::from_event(Some((*tb).poll_event()));
+  *event = raw;
+  return raw.kind;
+}
+
+/// Synchronizes the terminal with the output buffer.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_present (tb: *mut Termbox) {
+  (*tb).present();
+}
+
+/// Writes a string without wrapping. Returns the number of cells that were inside the output
+/// buffer.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed. `msg` must point to a
+/// NUL-terminated string that stays valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_put_str (tb: *mut Termbox, x: c_int, y: c_int, msg: *const c_char,
+                                       fg: Attribute, bg: Attribute) -> c_int
+{
+  let msg = CStr::from_ptr(msg).to_string_lossy();
+  return (*tb).put_str(x, y, &msg, fg, bg);
+}
+
+/// Returns the closest color to the given 24-bit color in the current output mode. Termbox has no
+/// true color output, so this is how C programs should pick colors from RGB values.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_rgb (tb: *mut Termbox, r: u8, g: u8, b: u8) -> Attribute {
+  Color::from_rgb_approx(r, g, b, (*tb).output_mode())
+}
+
+/// Enables or disables bracketed paste, which reports pasted text between
+/// `TBRS_EVENT_PASTE_START` and `TBRS_EVENT_PASTE_END`. Only the pure Rust backend supports it;
+/// with termbox-sys this does nothing.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[cfg(feature = "pure-rust")]
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_set_bracketed_paste (tb: *mut Termbox, enabled: c_int) {
+  (*tb).set_bracketed_paste(enabled != 0);
+}
+
+/// Does nothing, since bracketed paste needs the pure Rust backend. Exported so that the header is
+/// the same for both backends.
+#[cfg(not(feature = "pure-rust"))]
+#[no_mangle]
+pub extern "C" fn tbrs_set_bracketed_paste (_tb: *mut Termbox, _enabled: c_int) {}
+
+/// Sets the attributes used by `tbrs_clear`.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_set_clear_attributes (tb: *mut Termbox, fg: Attribute,
+                                                    bg: Attribute)
+{
+  (*tb).set_clear_attributes(fg, bg);
+}
+
+/// Copies text to the system clipboard with OSC 52. Returns zero on success and -1 if the text
+/// couldn't be written to the terminal.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed. `text` must point to
+/// a NUL-terminated string that stays valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_set_clipboard (tb: *mut Termbox, text: *const c_char) -> c_int {
+  let text = CStr::from_ptr(text).to_string_lossy();
+  match (*tb).set_clipboard(&text) {
+    Ok(()) => 0,
+    Err(_) => -1,
+  }
+}
+
+/// Moves the cursor. Pass -1 for both coordinates to hide it.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_set_cursor (tb: *mut Termbox, x: Coord, y: Coord) {
+  (*tb).set_cursor(x, y);
+}
+
+/// Sets the input mode to `TBRS_INPUT_ESC` or `TBRS_INPUT_ALT`. Other values are ignored.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_set_input_mode (tb: *mut Termbox, mode: c_int) {
+  if let Some(mode) = InputMode::from_raw(mode) {
+    (*tb).set_input_mode(mode);
+  }
+}
+
+/// Enables or disables mouse events.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_set_mouse_enabled (tb: *mut Termbox, enabled: c_int) {
+  (*tb).set_mouse_enabled(enabled != 0);
+}
+
+/// Sets the output mode to one of the `TBRS_OUTPUT_*` constants. Other values are ignored.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_set_output_mode (tb: *mut Termbox, mode: c_int) {
+  if let Some(mode) = OutputMode::from_raw(mode) {
+    (*tb).set_output_mode(mode);
+  }
+}
+
+/// Returns the width of the output buffer in cells.
+///
+/// # Safety
+///
+/// `tb` must be a handle returned by `tbrs_open` that hasn't been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tbrs_width (tb: *mut Termbox) -> c_int {
+  (*tb).width()
+}
@@ -0,0 +1,105 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Writes presented frames as an asciicast v2 recording, which can be played with asciinema.
+
+use std::env;
+use std::fmt::Write as FmtWrite;
+use std::io::{
+  self,
+  Write,
+};
+use std::time::{
+  Instant,
+  SystemTime,
+  UNIX_EPOCH,
+};
+
+use ffi;
+use {
+  Coord,
+  Snapshot,
+};
+
+
+//
+// Caster
+//
+
+
+/// Writes a frame to the recording each time the screen is presented.
+pub(crate) struct Caster {
+  writer: Box<dyn Write>,
+  start: Instant,
+  size: (Coord, Coord),
+  last: String,
+}
+
+impl Caster {
+  /// Records a presented frame. Frames identical to the previous one are skipped.
+  pub fn frame (&mut self, snapshot: &Snapshot, cursor: (Coord, Coord)) -> io::Result<()> {
+    let elapsed = self.start.elapsed();
+    let time = elapsed.as_secs() as f64 + elapsed.subsec_micros() as f64 / 1e6;
+
+    let size = (snapshot.width(), snapshot.height());
+    if size != self.size {
+      self.size = size;
+      writeln!(self.writer, "[{:.6}, \"r\", \"{}x{}\"]", time, size.0, size.1)?;
+    }
+
+    let mut out = snapshot.to_ansi_screen();
+    if cursor.0 == ffi::TB_HIDE_CURSOR || cursor.1 == ffi::TB_HIDE_CURSOR {
+      out.push_str("\x1b[?25l");
+    } else {
+      let _ = write!(out, "\x1b[{};{}H\x1b[?25h", cursor.1 + 1, cursor.0 + 1);
+    }
+    if out == self.last {
+      return Ok(());
+    }
+
+    writeln!(self.writer, "[{:.6}, \"o\", {}]", time, json_string(&out))?;
+    self.last = out;
+    return self.writer.flush();
+  }
+
+  /// Writes the header of the recording for a terminal of the given size.
+  pub fn new (writer: Box<dyn Write>, width: Coord, height: Coord) -> io::Result<Caster> {
+    let mut caster = Caster {
+      writer: writer,
+      start: Instant::now(),
+      size: (width, height),
+      last: String::new(),
+    };
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let term = env::var("TERM").unwrap_or_default();
+    writeln!(caster.writer, "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \
+                             \"env\": {{\"TERM\": {}}}}}",
+             width, height, timestamp, json_string(&term))?;
+    caster.writer.flush()?;
+
+    return Ok(caster);
+  }
+}
+
+// Quotes a string for JSON.
+fn json_string (s: &str) -> String {
+  let mut out = String::with_capacity(s.len() + 2);
+  out.push('"');
+
+  for ch in s.chars() {
+    match ch {
+      '"' => { out.push_str("\\\""); },
+      '\\' => { out.push_str("\\\\"); },
+      '\n' => { out.push_str("\\n"); },
+      '\r' => { out.push_str("\\r"); },
+      '\t' => { out.push_str("\\t"); },
+      ch if (ch as u32) < 0x20 || ch == '\x7f' => { let _ = write!(out, "\\u{:04x}", ch as u32); },
+      ch => { out.push(ch); },
+    }
+  }
+
+  out.push('"');
+  return out;
+}
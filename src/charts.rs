@@ -0,0 +1,409 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Charts for plotting data series: `line_chart`, `bar_chart` and `histogram`. Each chart is drawn
+//! into a rectangle with a value axis on the left, range labels below, and colored series. Turning
+//! off `ChartOptions::axes` and `ChartOptions::legend` leaves just the data, which makes
+//! `line_chart` a finer-grained alternative to `widgets::sparkline`.
+//!
+//! ~~~
+//! use termbox::{Rect, BLUE, GREEN};
+//! use termbox::charts::{self, Bar, ChartOptions};
+//! use termbox::headless::TestTermbox;
+//!
+//! let bars = [
+//!   Bar { label: "cpu", value: 4.0, color: GREEN },
+//!   Bar { label: "io", value: 2.0, color: BLUE },
+//! ];
+//! let mut tb = TestTermbox::new(10, 4);
+//! charts::bar_chart(&mut tb, Rect::new(0, 0, 10, 4), &bars, &ChartOptions::default());
+//! assert_eq!(tb.snapshot().to_plain_text(), "4│███\n │███ ███\n0└────────\n  cpu io\n");
+//! ~~~
+
+use canvas::{
+  Canvas,
+  CanvasMode,
+};
+use glyphs;
+use theme::Style;
+use width;
+use {
+  Attribute,
+  Backend,
+  Coord,
+  Rect,
+  DEFAULT,
+};
+
+// How far outside of the plot area, in multiples of its size, line chart points are clamped to.
+// Points beyond the range are still joined to their neighbours at roughly the right angle, but
+// lines to wildly distant points don't take forever to draw.
+const MAX_OVERSHOOT: Coord = 16;
+
+
+//
+// Bar
+//
+
+
+/// A bar of a `bar_chart`.
+#[derive(Clone, Copy, Debug)]
+pub struct Bar<'a> {
+  /// Shown under the bar, clipped to its width.
+  pub label: &'a str,
+  pub value: f64,
+  pub color: Attribute,
+}
+
+
+//
+// ChartOptions
+//
+
+
+/// Settings shared by the chart functions.
+#[derive(Clone, Copy, Debug)]
+pub struct ChartOptions {
+  /// The range of x values shown by `line_chart` and binned by `histogram`. `None` fits the data.
+  pub x_range: Option<(f64, f64)>,
+  /// The range of the value axis. `None` fits the data, including zero for bars.
+  pub y_range: Option<(f64, f64)>,
+  /// Whether to draw the axes with the labels of their ranges.
+  pub axes: bool,
+  /// Whether `line_chart` shows the names of its series on the top row.
+  pub legend: bool,
+  /// How `line_chart` plots its series.
+  pub mode: CanvasMode,
+  pub axis_style: Style,
+  pub label_style: Style,
+  /// The color behind the chart.
+  pub background: Attribute,
+}
+
+impl Default for ChartOptions {
+  fn default () -> ChartOptions {
+    ChartOptions {
+      x_range: None,
+      y_range: None,
+      axes: true,
+      legend: true,
+      mode: CanvasMode::Braille,
+      axis_style: Style::new(DEFAULT, DEFAULT, 0),
+      label_style: Style::new(DEFAULT, DEFAULT, 0),
+      background: DEFAULT,
+    }
+  }
+}
+
+
+//
+// Series
+//
+
+
+/// A named series of points for a `line_chart`.
+#[derive(Clone, Copy, Debug)]
+pub struct Series<'a> {
+  /// Shown in the legend. Series without a name are left out of it.
+  pub name: &'a str,
+  /// Points as `(x, y)`, joined by lines in the order given. A point that isn't finite, such as
+  /// NaN, leaves a gap in the line.
+  pub points: &'a [(f64, f64)],
+  pub color: Attribute,
+}
+
+
+//
+// Functions
+//
+
+
+/// Draws a column for each bar in `rect`, from left to right, with its label underneath. Bars are
+/// as wide as fits, with a gap between them if there is room, and bars that don't fit are left
+/// out. Each bar rises from the bottom of the value range to its value, in eighths of a cell with
+/// the Unicode glyph profile.
+pub fn bar_chart<B: Backend> (tb: &mut B, rect: Rect, bars: &[Bar], options: &ChartOptions) {
+  if rect.w <= 0 || rect.h <= 0 {
+    return;
+  }
+  clear(tb, rect, options.background);
+
+  let y_range = fit_range(bars.iter().map(|bar| bar.value), options.y_range, true);
+  let (mut area, mut label_y) = draw_axes(tb, rect, y_range, options);
+  if label_y.is_none() && area.h > 1 && bars.iter().any(|bar| !bar.label.is_empty()) {
+    area.h -= 1;
+    label_y = Some(area.y + area.h);
+  }
+
+  let count = bars.len() as Coord;
+  let gap = if count * 2 - 1 <= area.w { 1 } else { 0 };
+  let bar_width = ((area.w - gap * (count - 1)) / count.max(1)).max(1);
+  for (i, bar) in bars.iter().enumerate() {
+    let x = area.x + i as Coord * (bar_width + gap);
+    if x + bar_width > area.x + area.w {
+      break;
+    }
+    let column = Rect::new(x, area.y, bar_width, area.h);
+    draw_column(tb, column, bar.value, y_range, bar.color, options.background);
+    if let Some(y) = label_y {
+      put_text(tb, x, y, x + bar_width, bar.label, options.label_style);
+    }
+  }
+}
+
+/// Draws a histogram of `values` in `rect`: the x range is divided into `bins` equal bins, and a
+/// column for each shows how many values fall in it. Values outside of the x range are left out.
+pub fn histogram<B: Backend> (tb: &mut B, rect: Rect, values: &[f64], bins: usize,
+                              color: Attribute, options: &ChartOptions)
+{
+  if rect.w <= 0 || rect.h <= 0 || bins == 0 {
+    return;
+  }
+  clear(tb, rect, options.background);
+
+  let x_range = fit_range(values.iter().cloned(), options.x_range, false);
+  let mut counts = vec![0usize; bins];
+  for &value in values {
+    if !value.is_finite() || value < x_range.0 || value > x_range.1 {
+      continue;
+    }
+    let bin = ((value - x_range.0) / (x_range.1 - x_range.0) * bins as f64) as usize;
+    counts[bin.min(bins - 1)] += 1;
+  }
+
+  let y_range = fit_range(counts.iter().map(|&count| count as f64), options.y_range, true);
+  let (area, label_y) = draw_axes(tb, rect, y_range, options);
+  let bin_width = (area.w / bins as Coord).max(1);
+  let mut right = area.x;
+  for (i, &count) in counts.iter().enumerate() {
+    let x = area.x + i as Coord * bin_width;
+    if x + bin_width > area.x + area.w {
+      break;
+    }
+    let column = Rect::new(x, area.y, bin_width, area.h);
+    draw_column(tb, column, count as f64, y_range, color, options.background);
+    right = x + bin_width;
+  }
+  if let Some(y) = label_y {
+    draw_range_labels(tb, area.x, right, y, x_range, options.label_style);
+  }
+}
+
+/// Draws each series as a line through its points in `rect`, with the names of the series above
+/// it. The points are plotted on a `Canvas`, so lines are finer than cells.
+///
+/// ~~~no_run
+/// use termbox::{Rect, Termbox, RED};
+/// use termbox::charts::{self, ChartOptions, Series};
+///
+/// let points: Vec<(f64, f64)> = (0..100).map(|i| (i as f64, (i as f64 / 10.0).sin())).collect();
+/// let series = [Series { name: "sin", points: &points, color: RED }];
+///
+/// let mut tb = Termbox::open().unwrap();
+/// charts::line_chart(&mut tb, Rect::new(0, 0, 40, 12), &series, &ChartOptions::default());
+/// tb.present();
+/// ~~~
+pub fn line_chart<B: Backend> (tb: &mut B, rect: Rect, series: &[Series],
+                               options: &ChartOptions)
+{
+  if rect.w <= 0 || rect.h <= 0 {
+    return;
+  }
+  clear(tb, rect, options.background);
+
+  let mut rect = rect;
+  if options.legend && rect.h > 1 && series.iter().any(|series| !series.name.is_empty()) {
+    draw_legend(tb, rect, series, options);
+    rect.y += 1;
+    rect.h -= 1;
+  }
+
+  let points = || series.iter().flat_map(|series| series.points.iter());
+  let x_range = fit_range(points().map(|point| point.0), options.x_range, false);
+  let y_range = fit_range(points().map(|point| point.1), options.y_range, false);
+  let (area, label_y) = draw_axes(tb, rect, y_range, options);
+  if let Some(y) = label_y {
+    draw_range_labels(tb, area.x, area.x + area.w, y, x_range, options.label_style);
+  }
+
+  let mut canvas = Canvas::new(options.mode, area.w, area.h);
+  canvas.set_background(options.background);
+  let (dots_x, dots_y) = canvas.dot_size();
+  for series in series {
+    let mut last = None;
+    for &(x, y) in series.points {
+      if !x.is_finite() || !y.is_finite() {
+        last = None;
+        continue;
+      }
+      let dot = (scale(x, x_range, dots_x), dots_y - 1 - scale(y, y_range, dots_y));
+      match last {
+        Some((last_x, last_y)) => { canvas.line(last_x, last_y, dot.0, dot.1, series.color); },
+        None => { canvas.point(dot.0, dot.1, series.color); },
+      }
+      last = Some(dot);
+    }
+  }
+  canvas.blit(tb, area.x, area.y);
+}
+
+fn clear<B: Backend> (tb: &mut B, rect: Rect, bg: Attribute) {
+  for y in rect.y..rect.y + rect.h {
+    for x in rect.x..rect.x + rect.w {
+      tb.change_cell(x, y, ' ', DEFAULT, bg);
+    }
+  }
+}
+
+// Draws the axes along the left and bottom of `rect`, with the value range labelled on the left.
+// Returns the plot area and the row under the x axis, or all of `rect` and `None` if axes are
+// turned off or don't fit.
+fn draw_axes<B: Backend> (tb: &mut B, rect: Rect, y_range: (f64, f64), options: &ChartOptions)
+  -> (Rect, Option<Coord>)
+{
+  let low = format_value(y_range.0);
+  let high = format_value(y_range.1);
+  let label_width = text_width(&low).max(text_width(&high)) as Coord;
+  if !options.axes || rect.w < label_width + 2 || rect.h < 3 {
+    return (rect, None);
+  }
+
+  let glyphs = glyphs::current();
+  let (fg, bg) = options.axis_style.attributes();
+  let axis_x = rect.x + label_width;
+  let axis_y = rect.y + rect.h - 2;
+  for y in rect.y..axis_y {
+    tb.change_cell(axis_x, y, glyphs.vertical, fg, bg);
+  }
+  tb.change_cell(axis_x, axis_y, glyphs.bottom_left, fg, bg);
+  for x in axis_x + 1..rect.x + rect.w {
+    tb.change_cell(x, axis_y, glyphs.horizontal, fg, bg);
+  }
+
+  let high_x = axis_x - text_width(&high) as Coord;
+  put_text(tb, high_x, rect.y, axis_x, &high, options.label_style);
+  let low_x = axis_x - text_width(&low) as Coord;
+  put_text(tb, low_x, axis_y, axis_x, &low, options.label_style);
+
+  let area = Rect::new(axis_x + 1, rect.y, rect.x + rect.w - axis_x - 1, axis_y - rect.y);
+  return (area, Some(axis_y + 1));
+}
+
+// Draws a column rising from the bottom of `rect` in proportion to where `value` lies in `range`.
+fn draw_column<B: Backend> (tb: &mut B, rect: Rect, value: f64, range: (f64, f64),
+                            color: Attribute, bg: Attribute)
+{
+  let levels = glyphs::current().sparkline;
+  let steps = levels.len() as Coord;
+  // A value that isn't a number stays NaN, which converts to a height of zero.
+  let fraction = ((value - range.0) / (range.1 - range.0)).clamp(0.0, 1.0);
+  let height = (fraction * (rect.h * steps) as f64).round() as Coord;
+
+  for row in 0..rect.h {
+    let ch = match height - row * steps {
+      n if n >= steps => levels[steps as usize - 1],
+      n if n > 0 => levels[n as usize - 1],
+      _ => { continue; },
+    };
+    for x in rect.x..rect.x + rect.w {
+      tb.change_cell(x, rect.y + rect.h - 1 - row, ch, color, bg);
+    }
+  }
+}
+
+// Draws the names of the series across the top row of `rect`, each after a short line in its
+// color.
+fn draw_legend<B: Backend> (tb: &mut B, rect: Rect, series: &[Series], options: &ChartOptions) {
+  let marker = glyphs::current().horizontal;
+  let right = rect.x + rect.w;
+  let mut x = rect.x;
+  for series in series.iter().filter(|series| !series.name.is_empty()) {
+    if x + 2 > right {
+      break;
+    }
+    tb.change_cell(x, rect.y, marker, series.color, options.background);
+    x = put_text(tb, x + 2, rect.y, right, series.name, options.label_style) + 2;
+  }
+}
+
+// Labels the ends of the x axis between `left` and `right` on row `y`, leaving out the upper
+// label if it would run into the lower one.
+fn draw_range_labels<B: Backend> (tb: &mut B, left: Coord, right: Coord, y: Coord,
+                                  range: (f64, f64), style: Style)
+{
+  let low = format_value(range.0);
+  let high = format_value(range.1);
+  let low_end = put_text(tb, left, y, right, &low, style);
+  let high_x = right - text_width(&high) as Coord;
+  if high_x > low_end {
+    put_text(tb, high_x, y, right, &high, style);
+  }
+}
+
+// Returns the range to plot: `range` if given, or else the range of the finite `values`. An empty
+// range is widened so that values can be placed in it.
+fn fit_range<I> (values: I, range: Option<(f64, f64)>, include_zero: bool) -> (f64, f64)
+  where I: Iterator<Item = f64>
+{
+  let (mut low, mut high) = match range {
+    Some(range) => range,
+    None => {
+      let init = if include_zero { (0.0, 0.0) } else { (f64::INFINITY, f64::NEG_INFINITY) };
+      values.filter(|value| value.is_finite())
+        .fold(init, |range, value| (range.0.min(value), range.1.max(value)))
+    },
+  };
+
+  if !low.is_finite() || !high.is_finite() {
+    return (0.0, 1.0);
+  }
+  if high < low {
+    ::std::mem::swap(&mut low, &mut high);
+  }
+  if high == low {
+    if include_zero && low == 0.0 {
+      return (0.0, 1.0);
+    }
+    return (low - 1.0, high + 1.0);
+  }
+  return (low, high);
+}
+
+// Formats an axis label, without a fraction for whole numbers and with at most two decimals
+// otherwise.
+fn format_value (value: f64) -> String {
+  if value == value.trunc() && value.abs() < 1e15 {
+    return format!("{}", value as i64);
+  }
+  let text = format!("{:.2}", value);
+  return text.trim_end_matches('0').trim_end_matches('.').to_string();
+}
+
+// Draws as much of `text` as fits between `x` and `right`. Returns the column after the text.
+fn put_text<B: Backend> (tb: &mut B, x: Coord, y: Coord, right: Coord, text: &str, style: Style)
+  -> Coord
+{
+  let (fg, bg) = style.attributes();
+  let mut x = x;
+  for ch in text.chars() {
+    let ch_width = (width::char_width(ch) as Coord).max(1);
+    if x + ch_width > right {
+      break;
+    }
+    tb.change_cell(x, y, ch, fg, bg);
+    x += ch_width;
+  }
+  return x;
+}
+
+// Maps `value` from `range` onto dots `0..dots`.
+fn scale (value: f64, range: (f64, f64), dots: Coord) -> Coord {
+  let dot = ((value - range.0) / (range.1 - range.0) * (dots - 1) as f64).round();
+  let limit = (dots.max(1) * MAX_OVERSHOOT) as f64;
+  return dot.max(-limit).min(limit) as Coord;
+}
+
+fn text_width (text: &str) -> usize {
+  text.chars().map(|ch| width::char_width(ch).max(1)).sum()
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use {
+  Coord,
+  MouseButton,
+  MouseEvent,
+};
+
+// Defaults for `Termbox::set_click_thresholds`.
+pub const DEFAULT_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+pub const DEFAULT_CLICK_DISTANCE: Coord = 1;
+
+struct Click {
+  button: MouseButton,
+  x: Coord,
+  y: Coord,
+  time: Instant,
+  count: u8,
+}
+
+
+//
+// ClickCounter
+//
+
+
+/// Works out how many times in a row a mouse button was clicked.
+pub struct ClickCounter {
+  interval: Duration,
+  distance: Coord,
+  last: Option<Click>,
+  // Whether a button is held down, in which case further presses are reports of dragging.
+  held: bool,
+}
+
+impl ClickCounter {
+  /// Sets `event.clicks`. A press counts as another click if it is of the same button as the last
+  /// one, close enough to it and soon enough after it. Releases take the count of the press they
+  /// end.
+  pub fn count (&mut self, event: &mut MouseEvent) {
+    match event.button {
+      MouseButton::WheelUp | MouseButton::WheelDown | MouseButton::WheelLeft
+        | MouseButton::WheelRight => { return; },
+      MouseButton::Release => {
+        self.held = false;
+        event.clicks = self.last.as_ref().map_or(1, |last| last.count);
+        return;
+      },
+      _ => {},
+    }
+
+    let now = Instant::now();
+    if let Some(ref last) = self.last {
+      if self.held && last.button == event.button {
+        event.clicks = last.count;
+        return;
+      }
+    }
+
+    let count = match self.last {
+      Some(ref last) if last.button == event.button
+        && now.duration_since(last.time) <= self.interval
+        && (event.x - last.x).abs() <= self.distance
+        && (event.y - last.y).abs() <= self.distance => last.count.saturating_add(1),
+      _ => 1,
+    };
+
+    self.held = true;
+    self.last = Some(Click {
+      button: event.button,
+      x: event.x,
+      y: event.y,
+      time: now,
+      count: count,
+    });
+    event.clicks = count;
+  }
+
+  pub fn new () -> ClickCounter {
+    ClickCounter {
+      interval: DEFAULT_CLICK_INTERVAL,
+      distance: DEFAULT_CLICK_DISTANCE,
+      last: None,
+      held: false,
+    }
+  }
+
+  pub fn set_thresholds (&mut self, interval: Duration, distance: Coord) {
+    self.interval = interval;
+    self.distance = distance.max(0);
+  }
+
+  pub fn thresholds (&self) -> (Duration, Coord) {
+    (self.interval, self.distance)
+  }
+}
@@ -0,0 +1,123 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Thin adapters that mimic the API of older releases of this crate. Code written against the old
+//! API can usually be ported by replacing `use termbox::Termbox` with
+//! `use termbox::compat::Termbox` (and likewise for `Event`). New code should use the types in the
+//! crate root instead.
+
+use std::ops::{
+  Deref,
+  DerefMut,
+};
+
+use {
+  Coord,
+  Key,
+  KeyEvent,
+  MouseEvent,
+  Result,
+  Time,
+};
+
+/// Old name for `Key`.
+pub type KeySym = Key;
+
+
+//
+// Event
+//
+
+
+/// Old form of `Event`, where resize events carry the new size directly.
+#[derive(Clone, Debug)]
+pub enum Event {
+  Key(KeyEvent),
+  /// Contains the new width and height of the terminal.
+  Resize(Coord, Coord),
+  Mouse(MouseEvent),
+  PasteStart,
+  PasteEnd,
+  Signal(::Signal),
+  TaskDone(::TaskId, ::TaskResult),
+  Timer(::TimerId),
+  Custom(::Payload),
+  Raw(Vec<u8>),
+  Unknown(Vec<u8>),
+  Resumed,
+  ResumeFailed,
+}
+
+impl From<::Event> for Event {
+  fn from (event: ::Event) -> Event {
+    match event {
+      ::Event::Key(event) => Event::Key(event),
+      ::Event::Resize(event) => Event::Resize(event.w, event.h),
+      ::Event::Mouse(event) => Event::Mouse(event),
+      ::Event::PasteStart => Event::PasteStart,
+      ::Event::PasteEnd => Event::PasteEnd,
+      ::Event::Signal(signal) => Event::Signal(signal),
+      ::Event::TaskDone(id, result) => Event::TaskDone(id, result),
+      ::Event::Timer(id) => Event::Timer(id),
+      ::Event::Custom(payload) => Event::Custom(payload),
+      ::Event::Raw(bytes) => Event::Raw(bytes),
+      ::Event::Unknown(sequence) => Event::Unknown(sequence),
+      ::Event::Resumed => Event::Resumed,
+      ::Event::ResumeFailed => Event::ResumeFailed,
+    }
+  }
+}
+
+
+//
+// Termbox
+//
+
+
+/// Wraps `termbox::Termbox` with the old method names. All other methods are available through
+/// `Deref`.
+pub struct Termbox {
+  inner: ::Termbox,
+}
+
+impl Termbox {
+  /// Old name for `Termbox::open`.
+  pub fn new () -> Result<Termbox> {
+    ::Termbox::open().map(|inner| Termbox { inner: inner })
+  }
+
+  /// Returns the width and height of the output buffer in character cells.
+  pub fn get_size (&self) -> (Coord, Coord) {
+    (self.inner.width(), self.inner.height())
+  }
+
+  /// Unwraps the current `termbox::Termbox` to ease migrating one call site at a time.
+  pub fn into_inner (self) -> ::Termbox {
+    self.inner
+  }
+
+  /// Like `termbox::Termbox::peek_event`, but returns an old style event.
+  pub fn peek_event (&mut self, timeout: Time) -> Option<Event> {
+    self.inner.peek_event(timeout).map(Event::from)
+  }
+
+  /// Like `termbox::Termbox::poll_event`, but returns an old style event.
+  pub fn poll_event (&mut self) -> Event {
+    Event::from(self.inner.poll_event())
+  }
+}
+
+impl Deref for Termbox {
+  type Target = ::Termbox;
+
+  fn deref (&self) -> &::Termbox {
+    &self.inner
+  }
+}
+
+impl DerefMut for Termbox {
+  fn deref_mut (&mut self) -> &mut ::Termbox {
+    &mut self.inner
+  }
+}
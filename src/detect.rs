@@ -0,0 +1,143 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Guesses terminal features that termbox does not know about from the environment.
+
+use std::env;
+
+use images::ImageProtocol;
+
+/// Determines whether the terminal is likely to understand OSC 8 hyperlinks. Terminals that don't
+/// usually ignore the sequence, but a few older ones print it as garbage, so this errs on the side
+/// of caution.
+pub fn hyperlinks () -> bool {
+  if env::var_os("KITTY_WINDOW_ID").is_some() || env::var_os("WT_SESSION").is_some() {
+    return true;
+  }
+
+  if let Ok(program) = env::var("TERM_PROGRAM") {
+    match program.as_str() {
+      "iTerm.app" | "WezTerm" | "vscode" | "Hyper" | "ghostty" => { return true; },
+      _ => {},
+    }
+  }
+
+  if let Ok(version) = env::var("VTE_VERSION") {
+    if version.parse::<u32>().map(|v| v >= 5000).unwrap_or(false) {
+      return true;
+    }
+  }
+
+  if let Ok(term) = env::var("TERM") {
+    if term.contains("kitty") || term.starts_with("foot") || term.starts_with("alacritty") {
+      return true;
+    }
+  }
+
+  return false;
+}
+
+/// Determines whether the terminal supports synchronized output (mode 2026), which holds back
+/// redrawing the screen until a whole frame has been received. Other terminals ignore the mode, so
+/// this only decides whether it is worth sending.
+pub fn synchronized_output (term: &str) -> bool {
+  const PREFIXES: &'static [&'static str] = &[
+    "xterm-kitty", "foot", "alacritty", "wezterm", "contour", "xterm-ghostty", "tmux",
+  ];
+
+  if env::var_os("KITTY_WINDOW_ID").is_some() || env::var_os("WT_SESSION").is_some() {
+    return true;
+  }
+
+  if let Ok(program) = env::var("TERM_PROGRAM") {
+    match program.as_str() {
+      "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "contour" | "tmux" => { return true; },
+      _ => {},
+    }
+  }
+
+  return PREFIXES.iter().any(|prefix| term.starts_with(prefix));
+}
+
+/// Guesses which protocol the terminal understands for drawing images. Terminals that understand
+/// both are given the one they implement best.
+pub fn image_protocol (term: &str) -> ImageProtocol {
+  if env::var_os("KITTY_WINDOW_ID").is_some() || term.contains("kitty")
+    || term.contains("ghostty")
+  {
+    return ImageProtocol::Kitty;
+  }
+
+  if let Ok(program) = env::var("TERM_PROGRAM") {
+    match program.as_str() {
+      "ghostty" => { return ImageProtocol::Kitty; },
+      "iTerm.app" | "WezTerm" | "mintty" => { return ImageProtocol::Iterm2; },
+      _ => {},
+    }
+  }
+
+  if let Ok(version) = env::var("KONSOLE_VERSION") {
+    // Konsole has understood the kitty graphics protocol since version 22.04.
+    if version.parse::<u32>().map(|v| v >= 220400).unwrap_or(false) {
+      return ImageProtocol::Kitty;
+    }
+  }
+
+  return ImageProtocol::HalfBlocks;
+}
+
+/// Determines whether the locale uses UTF-8, going by the variables that select the character
+/// encoding. The first of `LC_ALL`, `LC_CTYPE` and `LANG` that is set decides.
+pub fn utf8_locale () -> bool {
+  for name in &["LC_ALL", "LC_CTYPE", "LANG"] {
+    if let Ok(value) = env::var(name) {
+      if !value.is_empty() {
+        let value = value.to_lowercase();
+        return value.contains("utf-8") || value.contains("utf8");
+      }
+    }
+  }
+
+  return false;
+}
+
+/// Determines whether the terminal can set its window title with OSC 0 and OSC 2. Nearly every
+/// terminal emulator can; the Linux console and hardware terminals can't.
+pub fn title (term: &str) -> bool {
+  const PREFIXES: &'static [&'static str] = &[
+    "xterm", "rxvt", "urxvt", "screen", "tmux", "alacritty", "foot", "kitty", "konsole", "gnome",
+    "vte", "st-", "wezterm", "putty", "iterm", "ghostty", "contour",
+  ];
+
+  if env::var_os("TERM_PROGRAM").is_some() || env::var_os("WT_SESSION").is_some() {
+    return true;
+  }
+  return term == "st" || PREFIXES.iter().any(|prefix| term.starts_with(prefix));
+}
+
+/// Determines whether the terminal accepts 24-bit colors, going by `COLORTERM` and the terminal
+/// name.
+pub fn truecolor (term: &str) -> bool {
+  if let Ok(value) = env::var("COLORTERM") {
+    if value == "truecolor" || value == "24bit" {
+      return true;
+    }
+  }
+  return term.ends_with("-direct") || term.contains("truecolor") || term.contains("24bit");
+}
+
+/// Guesses whether the terminal has a dark background from `COLORFGBG`, which some terminals set
+/// to the palette indices of their default colors, such as `15;0`.
+pub fn dark_background () -> Option<bool> {
+  let value = match env::var("COLORFGBG") {
+    Ok(value) => value,
+    Err(_) => { return None; },
+  };
+
+  // The background is the last field. Some terminals put a third field in the middle.
+  match value.rsplit(';').next().and_then(|bg| bg.parse::<u32>().ok()) {
+    Some(bg) => Some(bg < 7 || bg == 8),
+    None => None,
+  }
+}
@@ -0,0 +1,25 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Everything from `termbox-sys`, plus declarations for the functions of the termbox C library
+//! that `termbox-sys` does not bind. With the `pure-rust` feature, the same API is provided by the
+//! `pure` module instead.
+
+#[cfg(not(feature = "pure-rust"))]
+use std::os::raw::{
+  c_char,
+  c_int,
+};
+
+#[cfg(not(feature = "pure-rust"))]
+pub use termbox_sys::*;
+
+#[cfg(feature = "pure-rust")]
+pub use pure::*;
+
+#[cfg(not(feature = "pure-rust"))]
+extern "C" {
+  pub fn tb_init_file (name: *const c_char) -> c_int;
+  pub fn tb_init_fd (inout: c_int) -> c_int;
+}
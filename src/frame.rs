@@ -0,0 +1,160 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use images::Image;
+use theme::{
+  Role,
+  Style,
+};
+use {
+  Attribute,
+  Backend,
+  Cell,
+  Coord,
+  Event,
+  InputMode,
+  OutputMode,
+  Rect,
+  RegionId,
+  Termbox,
+  Time,
+};
+
+
+//
+// Frame
+//
+
+
+/// Draws one frame of a `Termbox`, passed to the closure given to `Termbox::frame`. It can be used
+/// anywhere a `Backend` is expected, but `present` does nothing, since the frame is presented once
+/// the closure returns. Events should be read between frames rather than inside them, as waiting
+/// for an event may carry out a present that was deferred by `Termbox::present_at_most`.
+pub struct Frame<'a> {
+  tb: &'a mut Termbox,
+}
+
+impl<'a> Frame<'a> {
+  pub(crate) fn new (tb: &'a mut Termbox) -> Frame<'a> {
+    Frame {
+      tb: tb,
+    }
+  }
+
+  /// Draws an image, like `Termbox::draw_image`.
+  pub fn draw_image (&mut self, rect: Rect, image: &Image) {
+    self.tb.draw_image(rect, image)
+  }
+
+  /// Registers a region for hit-testing, like `Termbox::register_region`.
+  pub fn register_region (&mut self, id: RegionId, rect: Rect) {
+    self.tb.register_region(id, rect)
+  }
+
+  /// Returns the style of `role` in the current theme and output mode.
+  pub fn style (&self, role: Role) -> Style {
+    self.tb.style(role)
+  }
+
+  /// Returns the `Termbox` being drawn, for reading settings that a frame has no method for.
+  pub fn termbox (&self) -> &Termbox {
+    self.tb
+  }
+}
+
+impl<'a> Backend for Frame<'a> {
+  fn cell_buffer (&self) -> &[Cell] {
+    self.tb.cell_buffer()
+  }
+
+  fn cell_buffer_mut (&mut self) -> &mut [Cell] {
+    self.tb.cell_buffer_mut()
+  }
+
+  fn clear (&mut self) {
+    self.tb.clear()
+  }
+
+  fn cursor (&self) -> Option<(Coord, Coord)> {
+    self.tb.cursor()
+  }
+
+  fn height (&self) -> Coord {
+    self.tb.height()
+  }
+
+  fn hide_cursor (&mut self) {
+    self.tb.hide_cursor()
+  }
+
+  fn input_mode (&self) -> InputMode {
+    self.tb.input_mode()
+  }
+
+  fn is_mouse_enabled (&self) -> bool {
+    self.tb.is_mouse_enabled()
+  }
+
+  fn output_mode (&self) -> OutputMode {
+    self.tb.output_mode()
+  }
+
+  fn peek_event (&mut self, timeout: Time) -> Option<Event> {
+    self.tb.peek_event(timeout)
+  }
+
+  fn poll_event (&mut self) -> Event {
+    self.tb.poll_event()
+  }
+
+  fn present (&mut self) {}
+
+  fn put_cell (&mut self, x: Coord, y: Coord, cell: Cell) {
+    self.tb.put_cell(x, y, cell)
+  }
+
+  fn set_clear_attributes (&mut self, fg: Attribute, bg: Attribute) {
+    self.tb.set_clear_attributes(fg, bg)
+  }
+
+  fn set_cursor (&mut self, x: Coord, y: Coord) -> Option<(Coord, Coord)> {
+    self.tb.set_cursor(x, y)
+  }
+
+  fn set_input_mode (&mut self, mode: InputMode) {
+    self.tb.set_input_mode(mode)
+  }
+
+  fn set_mouse_enabled (&mut self, enabled: bool) {
+    self.tb.set_mouse_enabled(enabled)
+  }
+
+  fn set_output_mode (&mut self, mode: OutputMode) {
+    self.tb.set_output_mode(mode)
+  }
+
+  fn width (&self) -> Coord {
+    self.tb.width()
+  }
+
+  fn blit (&mut self, x: Coord, y: Coord, w: Coord, h: Coord, cells: &[Cell]) {
+    self.tb.blit(x, y, w, h, cells)
+  }
+
+  fn change_cell (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    self.tb.change_cell(x, y, ch, fg, bg)
+  }
+
+  fn fill_row (&mut self, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    self.tb.fill_row(y, ch, fg, bg)
+  }
+
+  fn put_row (&mut self, y: Coord, cells: &[Cell]) {
+    self.tb.put_row(y, cells)
+  }
+
+  fn tab_width (&self) -> Coord {
+    self.tb.tab_width()
+  }
+}
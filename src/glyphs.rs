@@ -0,0 +1,157 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Chooses between Unicode and plain ASCII characters for interface decorations. Borders,
+//! scrollbars, gauges, spinners and tree guides look up their characters here, so an application
+//! can fall back to ASCII on terminals or fonts without box-drawing and block characters by
+//! changing a single setting.
+//!
+//! ~~~
+//! use termbox::glyphs::{self, GlyphProfile};
+//!
+//! glyphs::set_profile(GlyphProfile::detect());
+//! let spinner = glyphs::current().spinner;
+//! assert!(!spinner.is_empty());
+//! ~~~
+
+use std::sync::atomic::{
+  AtomicBool,
+  Ordering,
+};
+
+use detect;
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+const UNICODE_GLYPHS: Glyphs = Glyphs {
+  horizontal: '─',
+  vertical: '│',
+  top_left: '┌',
+  top_right: '┐',
+  bottom_left: '└',
+  bottom_right: '┘',
+  junction: '┼',
+  scrollbar_track: '│',
+  scrollbar_thumb: '█',
+  gauge_full: '█',
+  gauge_empty: ' ',
+  gauge_partial: &['▏', '▎', '▍', '▌', '▋', '▊', '▉'],
+  spinner: &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'],
+  sparkline: &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'],
+  tree_branch: "├─ ",
+  tree_last: "└─ ",
+  tree_vertical: "│  ",
+  tree_space: "   ",
+};
+
+const ASCII_GLYPHS: Glyphs = Glyphs {
+  horizontal: '-',
+  vertical: '|',
+  top_left: '+',
+  top_right: '+',
+  bottom_left: '+',
+  bottom_right: '+',
+  junction: '+',
+  scrollbar_track: '|',
+  scrollbar_thumb: '#',
+  gauge_full: '#',
+  gauge_empty: '.',
+  gauge_partial: &[],
+  spinner: &['|', '/', '-', '\\'],
+  sparkline: &['.', '-', '=', '#'],
+  tree_branch: "|- ",
+  tree_last: "`- ",
+  tree_vertical: "|  ",
+  tree_space: "   ",
+};
+
+
+//
+// GlyphProfile
+//
+
+
+/// Selects the set of characters used for decorations.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GlyphProfile {
+  /// Box-drawing, block and braille characters.
+  Unicode,
+  /// Printable ASCII characters only.
+  Ascii,
+}
+
+impl GlyphProfile {
+  /// Guesses a profile from the locale environment variables. `Unicode` is chosen if the locale
+  /// uses UTF-8.
+  pub fn detect () -> GlyphProfile {
+    if detect::utf8_locale() { GlyphProfile::Unicode } else { GlyphProfile::Ascii }
+  }
+
+  /// Returns the characters of the profile.
+  pub fn glyphs (self) -> &'static Glyphs {
+    match self {
+      GlyphProfile::Unicode => &UNICODE_GLYPHS,
+      GlyphProfile::Ascii => &ASCII_GLYPHS,
+    }
+  }
+}
+
+
+//
+// Glyphs
+//
+
+
+/// The characters used to draw decorations in a glyph profile.
+#[derive(Debug)]
+pub struct Glyphs {
+  pub horizontal: char,
+  pub vertical: char,
+  pub top_left: char,
+  pub top_right: char,
+  pub bottom_left: char,
+  pub bottom_right: char,
+  /// Where horizontal and vertical lines cross.
+  pub junction: char,
+  pub scrollbar_track: char,
+  pub scrollbar_thumb: char,
+  pub gauge_full: char,
+  pub gauge_empty: char,
+  /// Partially filled gauge cells from one eighth to seven eighths full. Empty if the profile can
+  /// only show whole cells.
+  pub gauge_partial: &'static [char],
+  /// Animation frames for activity indicators.
+  pub spinner: &'static [char],
+  /// Column chart cells from lowest to completely full.
+  pub sparkline: &'static [char],
+  /// Tree guide for an item with more siblings below it.
+  pub tree_branch: &'static str,
+  /// Tree guide for the last item among its siblings.
+  pub tree_last: &'static str,
+  /// Tree guide continuing past the children of an item with more siblings.
+  pub tree_vertical: &'static str,
+  /// Tree guide indenting the children of a last item.
+  pub tree_space: &'static str,
+}
+
+
+//
+// Functions
+//
+
+
+/// Returns the characters of the current profile.
+pub fn current () -> &'static Glyphs {
+  profile().glyphs()
+}
+
+/// Returns the current profile. The default is `Unicode`.
+pub fn profile () -> GlyphProfile {
+  if ASCII.load(Ordering::Relaxed) { GlyphProfile::Ascii } else { GlyphProfile::Unicode }
+}
+
+/// Changes the profile used by everything drawn from now on.
+pub fn set_profile (profile: GlyphProfile) {
+  ASCII.store(profile == GlyphProfile::Ascii, Ordering::Relaxed);
+}
@@ -0,0 +1,123 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::ops::{
+  Index,
+  IndexMut,
+};
+use std::slice::{
+  Chunks,
+  ChunksMut,
+};
+
+use {Cell, Coord};
+
+
+//
+// CellGrid
+//
+
+
+/// A two-dimensional view of the output buffer, returned by `Termbox::cell_grid` and
+/// `Termbox::cell_grid_mut`. The view borrows the `Termbox`, so its dimensions always match the
+/// buffer it was taken from. Cells can be indexed by `(x, y)`.
+pub struct CellGrid<S> {
+  cells: S,
+  width: Coord,
+  height: Coord,
+}
+
+impl<S: AsRef<[Cell]>> CellGrid<S> {
+  /// Returns the cells in row-major order.
+  pub fn cells (&self) -> &[Cell] {
+    self.cells.as_ref()
+  }
+
+  /// Returns the cell at the given position, or `None` if it is out of bounds.
+  pub fn get (&self, x: Coord, y: Coord) -> Option<&Cell> {
+    match self.offset(x, y) {
+      Some(i) => self.cells.as_ref().get(i),
+      None => None,
+    }
+  }
+
+  /// Returns the height of the grid in character cells.
+  pub fn height (&self) -> Coord {
+    self.height
+  }
+
+  pub fn new (cells: S, width: Coord, height: Coord) -> CellGrid<S> {
+    assert!(width >= 0 && height >= 0);
+    assert!(cells.as_ref().len() >= width as usize * height as usize);
+
+    CellGrid {
+      cells: cells,
+      width: width,
+      height: height,
+    }
+  }
+
+  /// Returns an iterator over the rows of the grid, from top to bottom.
+  pub fn rows<'a> (&'a self) -> Chunks<'a, Cell> {
+    let len = self.width as usize * self.height as usize;
+    self.cells.as_ref()[..len].chunks(self.width.max(1) as usize)
+  }
+
+  /// Returns the width of the grid in character cells.
+  pub fn width (&self) -> Coord {
+    self.width
+  }
+
+  fn offset (&self, x: Coord, y: Coord) -> Option<usize> {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return None;
+    }
+    return Some(y as usize * self.width as usize + x as usize);
+  }
+}
+
+impl<S: AsRef<[Cell]> + AsMut<[Cell]>> CellGrid<S> {
+  /// Returns the cells in row-major order for modification.
+  pub fn cells_mut (&mut self) -> &mut [Cell] {
+    self.cells.as_mut()
+  }
+
+  /// Returns the cell at the given position for modification, or `None` if it is out of bounds.
+  pub fn get_mut (&mut self, x: Coord, y: Coord) -> Option<&mut Cell> {
+    match self.offset(x, y) {
+      Some(i) => self.cells.as_mut().get_mut(i),
+      None => None,
+    }
+  }
+
+  /// Returns an iterator over the rows of the grid for modification, from top to bottom.
+  pub fn rows_mut<'a> (&'a mut self) -> ChunksMut<'a, Cell> {
+    let len = self.width as usize * self.height as usize;
+    let width = self.width.max(1) as usize;
+    self.cells.as_mut()[..len].chunks_mut(width)
+  }
+}
+
+impl<S: AsRef<[Cell]>> Index<(Coord, Coord)> for CellGrid<S> {
+  type Output = Cell;
+
+  fn index (&self, (x, y): (Coord, Coord)) -> &Cell {
+    match self.get(x, y) {
+      Some(cell) => cell,
+      None => {
+        panic!("cell ({}, {}) is out of bounds for a {}x{} grid", x, y, self.width, self.height);
+      },
+    }
+  }
+}
+
+impl<S: AsRef<[Cell]> + AsMut<[Cell]>> IndexMut<(Coord, Coord)> for CellGrid<S> {
+  fn index_mut (&mut self, (x, y): (Coord, Coord)) -> &mut Cell {
+    let (width, height) = (self.width, self.height);
+    match self.get_mut(x, y) {
+      Some(cell) => cell,
+      None => { panic!("cell ({}, {}) is out of bounds for a {}x{} grid", x, y, width, height); },
+    }
+  }
+}
@@ -0,0 +1,595 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::collections::VecDeque;
+use std::io;
+use std::result;
+
+use backend;
+use theme::Style;
+use {
+  Attribute,
+  Backend,
+  Cell,
+  ClusterError,
+  Coord,
+  Event,
+  InputMode,
+  IntoCoord,
+  OutOfBounds,
+  OutputMode,
+  PutOptions,
+  Rect,
+  ResizeEvent,
+  Snapshot,
+  TermWriter,
+  Time,
+  DEFAULT,
+};
+
+
+//
+// TestTermbox
+//
+
+
+/// An in-memory stand-in for `Termbox` that does not need a terminal. It has the same drawing and
+/// event methods as `Termbox`, but events come from a queue filled by the test with `push_event`,
+/// presented frames can be inspected with `presented`, and hyperlinks with `links`.
+///
+/// ~~~
+/// use termbox::headless::TestTermbox;
+/// use termbox::{Event, DEFAULT};
+///
+/// let mut tb = TestTermbox::new(20, 2);
+/// tb.put_str(0, 0, "Hello", DEFAULT, DEFAULT);
+/// tb.present();
+/// assert_eq!(tb.presented().unwrap().to_plain_text(), "Hello\n\n");
+///
+/// tb.resize(10, 1);
+/// match tb.poll_event() {
+///   Event::Resize(event) => { assert_eq!((event.w, event.h), (10, 1)); },
+///   _ => { panic!(); },
+/// }
+/// assert_eq!(tb.width(), 10);
+/// ~~~
+pub struct TestTermbox {
+  width: Coord,
+  height: Coord,
+  cells: Vec<Cell>,
+  clear_fg: Attribute,
+  clear_bg: Attribute,
+  cursor: Option<(Coord, Coord)>,
+  input_mode: InputMode,
+  mouse_enabled: bool,
+  output_mode: OutputMode,
+  events: VecDeque<Event>,
+  presented: Option<Snapshot>,
+  present_count: usize,
+  clipboard: Option<String>,
+  tab_width: Coord,
+  links: Vec<(Rect, String)>,
+}
+
+impl TestTermbox {
+  /// Creates a blank output buffer of the given size with no queued events.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `width` or `height` is negative.
+  pub fn new (width: Coord, height: Coord) -> TestTermbox {
+    let mut tb = TestTermbox {
+      width: 0,
+      height: 0,
+      cells: Vec::new(),
+      clear_fg: DEFAULT,
+      clear_bg: DEFAULT,
+      cursor: None,
+      input_mode: InputMode::Esc,
+      mouse_enabled: false,
+      output_mode: OutputMode::Normal,
+      events: VecDeque::new(),
+      presented: None,
+      present_count: 0,
+      clipboard: None,
+      tab_width: backend::DEFAULT_TAB_WIDTH,
+      links: Vec::new(),
+    };
+    tb.resize_buffer(width, height);
+    return tb;
+  }
+
+  /// Copies a rectangular region of cells from a slice to the output buffer.
+  pub fn blit<X, Y, W, H> (&mut self, x: X, y: Y, w: W, h: H, cells: &[Cell])
+    where X: IntoCoord, Y: IntoCoord, W: IntoCoord, H: IntoCoord
+  {
+    let (x, y, w, h) = (x.into_coord(), y.into_coord(), w.into_coord(), h.into_coord());
+    Backend::blit(self, x, y, w, h, cells)
+  }
+
+  /// Copies part of a slice of cells to the output buffer. See `Termbox::blit_region`.
+  pub fn blit_region<X, Y, S> (&mut self, x: X, y: Y, cells: &[Cell], stride: S, src: Rect,
+                               transparent: Option<Cell>)
+    where X: IntoCoord, Y: IntoCoord, S: IntoCoord
+  {
+    let (x, y, stride) = (x.into_coord(), y.into_coord(), stride.into_coord());
+    Backend::blit_region(self, x, y, cells, stride, src, transparent)
+  }
+
+  /// Returns a slice representing the output buffer.
+  pub fn cell_buffer (&self) -> &[Cell] {
+    &self.cells
+  }
+
+  /// Returns a mutable slice representing the output buffer.
+  pub fn cell_buffer_mut (&mut self) -> &mut [Cell] {
+    &mut self.cells
+  }
+
+  /// Changes a single cell in the output buffer.
+  pub fn change_cell<X, Y> (&mut self, x: X, y: Y, ch: char, fg: Attribute, bg: Attribute)
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::change_cell(self, x, y, ch, fg, bg)
+  }
+
+  /// Changes the cell at the given position to display a single user-perceived character, which
+  /// may be two cells wide. Returns the number of cells used.
+  pub fn change_cell_str<X, Y> (&mut self, x: X, y: Y, s: &str, fg: Attribute, bg: Attribute)
+                              -> result::Result<Coord, ClusterError>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::change_cell_str(self, x, y, s, fg, bg)
+  }
+
+  /// Clears the output buffer using the attributes set with `set_clear_attributes`, and forgets
+  /// the links written with `put_link`.
+  pub fn clear (&mut self) {
+    let blank = Cell::new(' ', self.clear_fg, self.clear_bg);
+    for cell in self.cells.iter_mut() {
+      *cell = blank;
+    }
+    self.links.clear();
+  }
+
+  /// Returns the clipboard contents set with `set_clipboard`.
+  pub fn clipboard (&self) -> Option<&str> {
+    self.clipboard.as_ref().map(|s| s.as_str())
+  }
+
+  /// Returns a copy of the cells inside `rect`. See `Termbox::copy_region`.
+  pub fn copy_region (&self, rect: Rect) -> Vec<Cell> {
+    Backend::copy_region(self, rect)
+  }
+
+  /// Returns the cursor position, or `None` if the cursor is hidden.
+  pub fn cursor (&self) -> Option<(Coord, Coord)> {
+    self.cursor
+  }
+
+  /// Fills column `x` of the output buffer with `ch`. See `Termbox::fill_col`.
+  pub fn fill_col<X: IntoCoord> (&mut self, x: X, ch: char, fg: Attribute, bg: Attribute) {
+    Backend::fill_col(self, x.into_coord(), ch, fg, bg)
+  }
+
+  /// Fills row `y` of the output buffer with `ch`. See `Termbox::fill_row`.
+  pub fn fill_row<Y: IntoCoord> (&mut self, y: Y, ch: char, fg: Attribute, bg: Attribute) {
+    Backend::fill_row(self, y.into_coord(), ch, fg, bg)
+  }
+
+  /// Returns the cell at the given position. See `Termbox::get_cell`.
+  pub fn get_cell<X, Y> (&self, x: X, y: Y) -> Option<Cell>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::get_cell(self, x, y)
+  }
+
+  /// Returns the height of the output buffer in character cells.
+  pub fn height (&self) -> Coord {
+    self.height
+  }
+
+  /// Hides the cursor.
+  pub fn hide_cursor (&mut self) {
+    self.cursor = None;
+  }
+
+  /// Returns the input mode. See `set_input_mode`.
+  pub fn input_mode (&self) -> InputMode {
+    self.input_mode
+  }
+
+  /// Determines whether mouse events are enabled. See `set_mouse_enabled`.
+  pub fn is_mouse_enabled (&self) -> bool {
+    self.mouse_enabled
+  }
+
+  /// Returns the links written with `put_link` since the last call to `clear`, in the order they
+  /// were written. Each covers the cells its text was written to, clipped to the output buffer,
+  /// and keeps its URL as given.
+  pub fn links (&self) -> &[(Rect, String)] {
+    &self.links
+  }
+
+  /// Returns the current output mode. See `set_output_mode`.
+  pub fn output_mode (&self) -> OutputMode {
+    self.output_mode
+  }
+
+  /// Restores cells saved with `copy_region`. See `Termbox::paste_region`.
+  pub fn paste_region (&mut self, rect: Rect, cells: &[Cell]) {
+    Backend::paste_region(self, rect, cells)
+  }
+
+  /// Removes the next event from the queue. The timeout is ignored; if the queue is empty, `None`
+  /// is returned immediately.
+  pub fn peek_event (&mut self, _timeout: Time) -> Option<Event> {
+    let event = self.events.pop_front();
+    if let Some(Event::Resize(ref event)) = event {
+      self.resize_buffer(event.w, event.h);
+    }
+    return event;
+  }
+
+  /// Removes the next event from the queue.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the queue is empty, since a real terminal would wait forever.
+  pub fn poll_event (&mut self) -> Event {
+    match self.peek_event(0) {
+      Some(event) => event,
+      None => { panic!("poll_event called with no events queued"); },
+    }
+  }
+
+  /// Returns the number of times `present` has been called.
+  pub fn present_count (&self) -> usize {
+    self.present_count
+  }
+
+  /// Records the current output buffer as the frame that the user would see.
+  pub fn present (&mut self) {
+    self.presented = Some(self.snapshot());
+    self.present_count += 1;
+  }
+
+  /// Returns the frame recorded by the most recent call to `present`.
+  pub fn presented (&self) -> Option<&Snapshot> {
+    self.presented.as_ref()
+  }
+
+  /// Adds an event to the end of the queue read by `peek_event` and `poll_event`.
+  pub fn push_event (&mut self, event: Event) {
+    self.events.push_back(event);
+  }
+
+  /// Changes a single character cell. Cells outside of the buffer are ignored, unless the
+  /// `debug-bounds` feature is enabled in a debug build, in which case they panic.
+  pub fn put_cell<X, Y> (&mut self, x: X, y: Y, cell: Cell)
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    backend::debug_check_bounds(x, y, self.width, self.height);
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return;
+    }
+    self.cells[(y * self.width + x) as usize] = cell;
+  }
+
+  /// Writes `text` like `put_str` and records the link, so that it can be inspected with `links`.
+  /// As with `Termbox`, nothing is recorded if none of the text is inside the output buffer.
+  pub fn put_link<X, Y> (&mut self, x: X, y: Y, text: &str, url: &str, fg: Attribute,
+                         bg: Attribute)
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    let written = self.put_str(x, y, text, fg, bg);
+
+    if written > 0 {
+      self.links.push((Rect::new(x.max(0), y, written, 1), url.to_string()));
+    }
+  }
+
+  /// Copies `cells` to row `y` of the output buffer. See `Termbox::put_row`.
+  pub fn put_row<Y: IntoCoord> (&mut self, y: Y, cells: &[Cell]) {
+    Backend::put_row(self, y.into_coord(), cells)
+  }
+
+  /// Writes a horizontal sequence of character cells without wrapping. Returns the number of
+  /// cells that were inside the output buffer.
+  pub fn put_str<X, Y> (&mut self, x: X, y: Y, msg: &str, fg: Attribute, bg: Attribute)
+    -> Coord
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::put_str(self, x, y, msg, fg, bg)
+  }
+
+  /// Writes a string as controlled by `options`. See `Termbox::put_str_with`.
+  pub fn put_str_with<X, Y> (&mut self, x: X, y: Y, msg: &str, fg: Attribute, bg: Attribute,
+                             options: PutOptions)
+    -> Coord
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::put_str_with(self, x, y, msg, fg, bg, options)
+  }
+
+  /// Writes a string downwards, one character per row. See `Termbox::put_vstr`.
+  pub fn put_vstr<X, Y> (&mut self, x: X, y: Y, msg: &str, fg: Attribute, bg: Attribute) -> Coord
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::put_vstr(self, x, y, msg, fg, bg)
+  }
+
+  /// Returns the clipboard contents set with `set_clipboard`. The timeout is ignored.
+  pub fn request_clipboard (&mut self, _timeout: Time) -> Option<String> {
+    self.clipboard.clone()
+  }
+
+  /// Queues a resize event. As with a real terminal, the output buffer is resized when the event
+  /// is received, keeping the cells in the top left corner.
+  pub fn resize (&mut self, width: Coord, height: Coord) {
+    self.push_event(Event::Resize(ResizeEvent { w: width, h: height }));
+  }
+
+  /// Sets what attributes should be used when clearing the output buffer with `clear`.
+  pub fn set_clear_attributes (&mut self, fg: Attribute, bg: Attribute) {
+    self.clear_fg = fg;
+    self.clear_bg = bg;
+  }
+
+  /// Stores `text` so that it can be inspected with `clipboard`.
+  pub fn set_clipboard (&mut self, text: &str) -> io::Result<()> {
+    self.clipboard = Some(text.to_string());
+    return Ok(());
+  }
+
+  /// Sets the position of the cursor. If invalid coordinates are provided, the cursor is hidden.
+  /// Returns the previous position, or `None` if the cursor was hidden.
+  pub fn set_cursor<X, Y> (&mut self, x: X, y: Y) -> Option<(Coord, Coord)>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    let previous = self.cursor;
+    if x < 0 || y < 0 {
+      self.cursor = None;
+    } else {
+      self.cursor = Some((x, y));
+    }
+    return previous;
+  }
+
+  /// Sets the input mode.
+  pub fn set_input_mode (&mut self, mode: InputMode) {
+    self.input_mode = mode;
+  }
+
+  /// Enables or disables mouse events.
+  pub fn set_mouse_enabled (&mut self, enabled: bool) {
+    self.mouse_enabled = enabled;
+  }
+
+  /// Sets the output mode.
+  pub fn set_output_mode (&mut self, mode: OutputMode) {
+    self.output_mode = mode;
+  }
+
+  /// Sets the number of columns between tab stops. See `Termbox::set_tab_width`.
+  pub fn set_tab_width (&mut self, width: Coord) {
+    self.tab_width = width;
+  }
+
+  /// Returns an owned copy of the output buffer.
+  pub fn snapshot (&self) -> Snapshot {
+    Backend::snapshot(self)
+  }
+
+  /// Returns the number of columns between tab stops. See `Termbox::set_tab_width`.
+  pub fn tab_width (&self) -> Coord {
+    self.tab_width
+  }
+
+  /// Changes a single cell, returning an error if it is outside of the output buffer. See
+  /// `Termbox::try_change_cell`.
+  pub fn try_change_cell<X, Y> (&mut self, x: X, y: Y, ch: char, fg: Attribute, bg: Attribute)
+    -> result::Result<(), OutOfBounds>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::try_change_cell(self, x, y, ch, fg, bg)
+  }
+
+  /// Changes a single cell, returning an error if it is outside of the output buffer. See
+  /// `Termbox::try_put_cell`.
+  pub fn try_put_cell<X, Y> (&mut self, x: X, y: Y, cell: Cell)
+    -> result::Result<(), OutOfBounds>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::try_put_cell(self, x, y, cell)
+  }
+
+  /// Returns the width of the output buffer in character cells.
+  pub fn width (&self) -> Coord {
+    self.width
+  }
+
+  /// Returns a writer that draws formatted text starting at `(x, y)`. See `Termbox::writer_at`.
+  pub fn writer_at<'a, X, Y> (&'a mut self, x: X, y: Y, style: Style)
+    -> TermWriter<'a, TestTermbox>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    TermWriter::new(self, x, y, style)
+  }
+
+  fn resize_buffer (&mut self, width: Coord, height: Coord) {
+    assert!(width >= 0 && height >= 0);
+    let blank = Cell::new(' ', self.clear_fg, self.clear_bg);
+    let mut cells = vec![blank; (width * height) as usize];
+
+    for y in 0..height.min(self.height) {
+      for x in 0..width.min(self.width) {
+        cells[(y * width + x) as usize] = self.cells[(y * self.width + x) as usize];
+      }
+    }
+
+    self.width = width;
+    self.height = height;
+    self.cells = cells;
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use {
+    Overflow,
+    BLUE,
+    RED,
+  };
+
+  fn text (tb: &TestTermbox) -> String {
+    tb.snapshot().to_plain_text()
+  }
+
+  #[test]
+  #[should_panic]
+  fn new_negative_size () {
+    TestTermbox::new(-1, 1);
+  }
+
+  #[test]
+  fn put_str_with_wrap () {
+    let mut tb = TestTermbox::new(6, 3);
+    let options = PutOptions { overflow: Overflow::Wrap, ..PutOptions::default() };
+    assert_eq!(tb.put_str_with(1, 0, "abcdefg\nhi", DEFAULT, DEFAULT, options), 9);
+    assert_eq!(text(&tb), " abcde\n fg\n hi\n");
+
+    // Text past the last row is dropped.
+    tb.clear();
+    assert_eq!(tb.put_str_with(0, 1, "abcdefghijklmno", DEFAULT, DEFAULT, options), 12);
+    assert_eq!(text(&tb), "\nabcdef\nghijkl\n");
+
+    // Without wrapping, text stops at the edge and newlines are written as-is.
+    tb.clear();
+    assert_eq!(tb.put_str_with(3, 0, "abcdef", DEFAULT, DEFAULT, PutOptions::default()), 3);
+    assert_eq!(text(&tb), "   abc\n\n\n");
+
+    // A wide character that doesn't fit at the end of a line moves to the next one.
+    tb.clear();
+    assert_eq!(tb.put_str_with(0, 0, "abcde\u{4e00}", DEFAULT, DEFAULT, options), 7);
+    assert_eq!(tb.get_cell(0, 1).unwrap().ch(), '\u{4e00}');
+  }
+
+  #[test]
+  fn put_str_with_tabs () {
+    let mut tb = TestTermbox::new(12, 1);
+    let options = PutOptions { tab_width: 4, ..PutOptions::default() };
+    assert_eq!(tb.put_str_with(1, 0, "a\tbc\td", DEFAULT, DEFAULT, options), 9);
+    assert_eq!(text(&tb), " a   bc  d\n");
+
+    // Tab stops follow the tab width of the terminal by default.
+    tb.clear();
+    tb.set_tab_width(3);
+    tb.put_str_with(0, 0, "\tx", DEFAULT, DEFAULT, PutOptions::default());
+    assert_eq!(text(&tb), "   x\n");
+
+    // A negative tab width writes tabs as-is.
+    tb.clear();
+    let raw = PutOptions { tab_width: -1, ..PutOptions::default() };
+    assert_eq!(tb.put_str_with(0, 0, "\tx", DEFAULT, DEFAULT, raw), 2);
+    assert_eq!(tb.get_cell(0, 0).unwrap().ch(), '\t');
+  }
+
+  #[test]
+  fn change_cell_str () {
+    let mut tb = TestTermbox::new(4, 1);
+    tb.put_str(0, 0, "wxyz", DEFAULT, DEFAULT);
+    assert_eq!(tb.change_cell_str(0, 0, "\u{4e00}", RED, BLUE), Ok(2));
+    assert_eq!(tb.get_cell(0, 0), Some(Cell::new('\u{4e00}', RED, BLUE)));
+    assert_eq!(tb.get_cell(1, 0), Some(Cell::new(' ', RED, BLUE)));
+    assert_eq!(tb.change_cell_str(2, 0, "e", DEFAULT, DEFAULT), Ok(1));
+    assert_eq!(tb.change_cell_str(2, 0, "\u{2764}\u{fe0f}", DEFAULT, DEFAULT), Ok(1));
+    assert_eq!(tb.get_cell(2, 0).unwrap().ch(), '\u{2764}');
+
+    assert_eq!(tb.change_cell_str(3, 0, "\u{4e00}", DEFAULT, DEFAULT), Err(ClusterError::NoRoom));
+    assert_eq!(tb.change_cell_str(0, 0, "", DEFAULT, DEFAULT), Err(ClusterError::Empty));
+    assert_eq!(tb.change_cell_str(0, 0, "\u{301}", DEFAULT, DEFAULT),
+               Err(ClusterError::NoBaseCharacter));
+    assert_eq!(tb.change_cell_str(0, 0, "e\u{301}", DEFAULT, DEFAULT),
+               Err(ClusterError::CombiningCharacter));
+    assert_eq!(tb.change_cell_str(0, 0, "ab", DEFAULT, DEFAULT),
+               Err(ClusterError::MultipleCharacters));
+    assert_eq!(tb.get_cell(3, 0).unwrap().ch(), 'z');
+  }
+
+  #[test]
+  fn try_change_cell () {
+    let mut tb = TestTermbox::new(3, 2);
+    assert_eq!(tb.try_change_cell(2, 1, 'x', RED, DEFAULT), Ok(()));
+    assert_eq!(tb.get_cell(2, 1), Some(Cell::new('x', RED, DEFAULT)));
+
+    let error = OutOfBounds { x: 3, y: 0, width: 3, height: 2 };
+    assert_eq!(tb.try_change_cell(3, 0, 'x', DEFAULT, DEFAULT), Err(error));
+    assert!(tb.try_change_cell(0, -1, 'x', DEFAULT, DEFAULT).is_err());
+    assert!(tb.try_change_cell(0usize, 2u16, 'x', DEFAULT, DEFAULT).is_err());
+    assert_eq!(text(&tb), "\n  x\n");
+  }
+
+  #[test]
+  fn blit_region_clipping () {
+    let sprite: Vec<Cell> = "abcdefghi".chars().map(|ch| Cell::new(ch, DEFAULT, DEFAULT)).collect();
+    let mut tb = TestTermbox::new(4, 3);
+
+    // Clipped by the left and top edges of the output buffer.
+    tb.blit_region(-1, -1, &sprite, 3, Rect::new(0, 0, 3, 3), None);
+    assert_eq!(text(&tb), "ef\nhi\n\n");
+
+    // Clipped by the right and bottom edges.
+    tb.clear();
+    tb.blit_region(3, 2, &sprite, 3, Rect::new(0, 0, 3, 3), None);
+    assert_eq!(text(&tb), "\n\n   a\n");
+
+    // The source rectangle is clipped to the slice.
+    tb.clear();
+    tb.blit_region(0, 0, &sprite, 3, Rect::new(1, 1, 5, 5), None);
+    assert_eq!(text(&tb), "ef\nhi\n\n");
+
+    // Transparent cells leave the buffer unchanged.
+    tb.clear();
+    tb.fill_row(0, '.', DEFAULT, DEFAULT);
+    let key = Cell::new('b', DEFAULT, DEFAULT);
+    tb.blit_region(0, 0, &sprite, 3, Rect::new(0, 0, 3, 1), Some(key));
+    assert_eq!(text(&tb), "a.c.\n\n\n");
+
+    // Nothing is drawn for a stride that isn't positive.
+    tb.clear();
+    tb.blit_region(0, 0, &sprite, 0, Rect::new(0, 0, 3, 3), None);
+    assert_eq!(text(&tb), "\n\n\n");
+  }
+
+  #[test]
+  fn put_link () {
+    let mut tb = TestTermbox::new(8, 2);
+    tb.put_link(-2, 0, "docs", "https://example.com/a b", DEFAULT, DEFAULT);
+    tb.put_link(6, 1, "more", "https://example.com/", DEFAULT, DEFAULT);
+    tb.put_link(0, 5, "gone", "https://example.com/", DEFAULT, DEFAULT);
+    assert_eq!(text(&tb), "cs\n      mo\n");
+    assert_eq!(tb.links(), &[
+      (Rect::new(0, 0, 2, 1), String::from("https://example.com/a b")),
+      (Rect::new(6, 1, 2, 1), String::from("https://example.com/")),
+    ]);
+
+    tb.clear();
+    assert!(tb.links().is_empty());
+  }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::fs::File;
+use std::io::{
+  self,
+  BufWriter,
+  Write,
+};
+use std::path::Path;
+
+use theme::Style;
+use width;
+use {
+  Attribute,
+  Coord,
+};
+
+// Attribute bits holding the color, as opposed to style flags such as `BOLD`.
+const COLOR_MASK: Attribute = 0x00ff;
+
+
+//
+// DrawHook
+//
+
+
+/// Is told about text drawn to a `Termbox`, for mirroring the screen to a screen reader or a log.
+/// See `Termbox::set_draw_hook`. Cells drawn one after another along a row in the same style are
+/// reported together as one piece of text, so `put_str` usually results in a single call. Closures
+/// taking the same arguments as `text_drawn` can be used as hooks, with their argument types
+/// written out, as in `|x: Coord, y: Coord, text: &str, style: Style| ...`.
+pub trait DrawHook {
+  /// Called with text drawn starting at `x`, `y`, and the style it was drawn in.
+  fn text_drawn (&mut self, x: Coord, y: Coord, text: &str, style: Style);
+
+  /// Called when a frame is presented, after all of the text drawn for it.
+  fn presented (&mut self) {}
+}
+
+impl<F> DrawHook for F where F: FnMut(Coord, Coord, &str, Style) {
+  fn text_drawn (&mut self, x: Coord, y: Coord, text: &str, style: Style) {
+    self(x, y, text, style)
+  }
+}
+
+
+//
+// DrawTracker
+//
+
+
+// Collects the cells drawn into runs of text for a `DrawHook`.
+pub(crate) struct DrawTracker {
+  hook: Box<dyn DrawHook>,
+  run: Option<Run>,
+}
+
+struct Run {
+  x: Coord,
+  y: Coord,
+  // The column after the last character of the run.
+  end: Coord,
+  text: String,
+  fg: Attribute,
+  bg: Attribute,
+}
+
+impl DrawTracker {
+  pub fn new (hook: Box<dyn DrawHook>) -> DrawTracker {
+    DrawTracker {
+      hook: hook,
+      run: None,
+    }
+  }
+
+  // Records a cell drawn at `x`, `y`, adding it to the current run if it continues it.
+  pub fn cell (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    let ch_width = (width::char_width(ch) as Coord).max(1);
+    if let Some(ref mut run) = self.run {
+      if run.y == y && run.fg == fg && run.bg == bg {
+        if run.end == x {
+          run.text.push(ch);
+          run.end += ch_width;
+          return;
+        }
+        // The blank that `change_cell_str` puts behind a wide character is part of it.
+        let after_wide = run.text.chars().last().is_some_and(|last| width::char_width(last) > 1);
+        if run.end == x + 1 && ch == ' ' && after_wide {
+          return;
+        }
+      }
+    }
+
+    self.flush();
+    self.run = Some(Run {
+      x: x,
+      y: y,
+      end: x + ch_width,
+      text: ch.to_string(),
+      fg: fg,
+      bg: bg,
+    });
+  }
+
+  // Reports the current run, if any.
+  pub fn flush (&mut self) {
+    if let Some(run) = self.run.take() {
+      let style = Style::new(run.fg & COLOR_MASK, run.bg, run.fg & !COLOR_MASK);
+      self.hook.text_drawn(run.x, run.y, &run.text, style);
+    }
+  }
+
+  pub fn presented (&mut self) {
+    self.flush();
+    self.hook.presented();
+  }
+}
+
+
+//
+// FileDrawLog
+//
+
+
+/// A `DrawHook` that writes the text drawn to a file, one piece per line as `x y text`, with a
+/// line of `--` after each frame. The file is flushed after each frame, so it can be followed
+/// with `tail -f` from another terminal. Errors writing the file are ignored, as there is no one
+/// to report them to while drawing.
+pub struct FileDrawLog {
+  out: BufWriter<File>,
+}
+
+impl FileDrawLog {
+  /// Creates the file at `path`, replacing it if it exists.
+  pub fn create<P: AsRef<Path>> (path: P) -> io::Result<FileDrawLog> {
+    Ok(FileDrawLog {
+      out: BufWriter::new(File::create(path)?),
+    })
+  }
+}
+
+impl DrawHook for FileDrawLog {
+  fn presented (&mut self) {
+    let _ = self.out.write_all(b"--\n");
+    let _ = self.out.flush();
+  }
+
+  fn text_drawn (&mut self, x: Coord, y: Coord, text: &str, _style: Style) {
+    let _ = writeln!(self.out, "{} {} {}", x, y, text);
+  }
+}
@@ -0,0 +1,309 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::fmt::Write;
+use std::sync::atomic::{
+  AtomicUsize,
+  Ordering,
+};
+
+use base64;
+use sgr;
+use {
+  Attribute,
+  Backend,
+  Color,
+  OutputMode,
+  Rect,
+  DEFAULT,
+};
+
+// Used to give each image an id, by which the kitty graphics protocol refers to it after it has
+// been sent once.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+// The kitty graphics protocol limits each chunk of image data to this many bytes of base64.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+// Deletes all placements of images on the screen, keeping the image data.
+pub(crate) const KITTY_DELETE_PLACEMENTS: &'static str = "\x1b_Ga=d,d=a,q=2\x1b\\";
+
+// Pixels less opaque than this are drawn as the default background by `draw_half_blocks`.
+const OPAQUE_THRESHOLD: u8 = 128;
+
+
+//
+// Image
+//
+
+
+/// A bitmap image that can be drawn into a rectangle of cells with `Termbox::draw_image` or
+/// `draw_half_blocks`. Pixels are stored as 8-bit RGBA, row by row from the top left.
+///
+/// ~~~
+/// use termbox::images::Image;
+///
+/// // A 2x1 image with a red and a blue pixel.
+/// let image = Image::from_rgb(2, 1, &[255, 0, 0, 0, 0, 255]).unwrap();
+/// assert_eq!(image.pixel(1, 0), Some([0, 0, 255, 255]));
+/// ~~~
+#[derive(Clone, Debug)]
+pub struct Image {
+  id: u32,
+  width: u32,
+  height: u32,
+  pixels: Vec<u8>,
+}
+
+impl Image {
+  /// Creates an opaque image from 8-bit RGB pixels. Returns `None` if either dimension is zero or
+  /// `pixels` doesn't hold exactly `width * height * 3` bytes.
+  pub fn from_rgb (width: u32, height: u32, pixels: &[u8]) -> Option<Image> {
+    if pixels.len() as u64 != width as u64 * height as u64 * 3 {
+      return None;
+    }
+    let mut rgba = Vec::with_capacity(pixels.len() / 3 * 4);
+    for pixel in pixels.chunks(3) {
+      rgba.extend_from_slice(pixel);
+      rgba.push(255);
+    }
+    return Image::from_rgba(width, height, rgba);
+  }
+
+  /// Creates an image from 8-bit RGBA pixels. Returns `None` if either dimension is zero or
+  /// `pixels` doesn't hold exactly `width * height * 4` bytes.
+  pub fn from_rgba (width: u32, height: u32, pixels: Vec<u8>) -> Option<Image> {
+    if width == 0 || height == 0 || pixels.len() as u64 != width as u64 * height as u64 * 4 {
+      return None;
+    }
+    Some(Image {
+      id: NEXT_ID.fetch_add(1, Ordering::Relaxed) as u32,
+      width: width,
+      height: height,
+      pixels: pixels,
+    })
+  }
+
+  pub fn height (&self) -> u32 {
+    self.height
+  }
+
+  /// Returns the RGBA value of the pixel at `x`, `y`, or `None` if it is outside of the image.
+  pub fn pixel (&self, x: u32, y: u32) -> Option<[u8; 4]> {
+    if x >= self.width || y >= self.height {
+      return None;
+    }
+    let i = (y as usize * self.width as usize + x as usize) * 4;
+    return Some([self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]);
+  }
+
+  pub fn pixels (&self) -> &[u8] {
+    &self.pixels
+  }
+
+  pub fn width (&self) -> u32 {
+    self.width
+  }
+
+  pub(crate) fn id (&self) -> u32 {
+    self.id
+  }
+
+  // Encodes the image as a PNG file, which is the most widely understood format for inline
+  // images. The data is stored without compression, since the terminal only decodes it once.
+  fn to_png (&self) -> Vec<u8> {
+    let row_len = self.width as usize * 4;
+    let mut raw = Vec::with_capacity((row_len + 1) * self.height as usize);
+    for row in self.pixels.chunks(row_len) {
+      // Each row starts with the number of the filter it was encoded with, here none.
+      raw.push(0);
+      raw.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xffff).peekable();
+    while let Some(block) = blocks.next() {
+      zlib.push(if blocks.peek().is_none() { 1 } else { 0 });
+      let len = block.len() as u16;
+      zlib.extend_from_slice(&len.to_le_bytes());
+      zlib.extend_from_slice(&(!len).to_le_bytes());
+      zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&self.width.to_be_bytes());
+    header.extend_from_slice(&self.height.to_be_bytes());
+    // 8 bits per channel, RGBA, and the default compression, filter and interlace methods.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_png_chunk(&mut png, b"IHDR", &header);
+    write_png_chunk(&mut png, b"IDAT", &zlib);
+    write_png_chunk(&mut png, b"IEND", &[]);
+    return png;
+  }
+}
+
+
+//
+// ImageProtocol
+//
+
+
+/// The way `Termbox::draw_image` shows images.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ImageProtocol {
+  /// The kitty graphics protocol, also understood by Ghostty and Konsole. Images are sent once and
+  /// then placed by id.
+  Kitty,
+  /// iTerm2's inline images, also understood by WezTerm and mintty. Images are sent again each
+  /// time they are placed.
+  Iterm2,
+  /// Colored half blocks, two pixels to a cell, which work in any terminal with Unicode and
+  /// colors.
+  HalfBlocks,
+}
+
+
+//
+// ImagePlacement
+//
+
+
+// An image drawn with `Termbox::draw_image`, to be sent to the terminal by `present`.
+pub(crate) struct ImagePlacement {
+  pub id: u32,
+  pub rect: Rect,
+  // The escape sequences that send the image data, if the terminal still needs them.
+  pub data: Option<String>,
+}
+
+
+//
+// Functions
+//
+
+
+/// Draws an image into `rect` with half block characters, scaling it to fill the rectangle. Each
+/// cell shows two pixels, one above the other, in colors chosen to suit the backend's output mode.
+/// Transparent pixels show the default background.
+pub fn draw_half_blocks<B: Backend> (tb: &mut B, rect: Rect, image: &Image) {
+  if rect.w <= 0 || rect.h <= 0 {
+    return;
+  }
+
+  let mode = tb.output_mode();
+  let color = |x: u64, y: u64| -> Option<Attribute> {
+    // Sample the middle of the area of the image that falls in each half cell.
+    let px = ((2 * x + 1) * image.width as u64 / (2 * rect.w as u64)) as u32;
+    let py = ((2 * y + 1) * image.height as u64 / (4 * rect.h as u64)) as u32;
+    let [r, g, b, a] = image.pixel(px, py).unwrap_or([0, 0, 0, 0]);
+    if a < OPAQUE_THRESHOLD { None } else { Some(Color::from_rgb_approx(r, g, b, mode)) }
+  };
+
+  // The image is scaled to all of `rect`, but only the part on screen is drawn.
+  let (width, height) = (tb.width(), tb.height());
+  for row in (-rect.y).max(0)..rect.h.min(height - rect.y) {
+    for col in (-rect.x).max(0)..rect.w.min(width - rect.x) {
+      let (x, y) = (col as u64, row as u64 * 2);
+      let (ch, fg, bg) = match (color(x, y), color(x, y + 1)) {
+        (Some(top), Some(bottom)) => ('\u{2580}', top, bottom),
+        (Some(top), None) => ('\u{2580}', top, DEFAULT),
+        (None, Some(bottom)) => ('\u{2584}', bottom, DEFAULT),
+        (None, None) => (' ', DEFAULT, DEFAULT),
+      };
+      tb.change_cell(rect.x + col, rect.y + row, ch, fg, bg);
+    }
+  }
+}
+
+// Returns the sequences that clear the cells of `rect` with the given attributes, with the cursor
+// where it was before.
+pub(crate) fn erase (rect: Rect, fg: Attribute, bg: Attribute, mode: OutputMode) -> String {
+  let mut out = String::from("\x1b7");
+  sgr::write_attributes(&mut out, fg, bg, mode);
+  for y in rect.y..rect.y + rect.h {
+    let _ = write!(out, "\x1b[{};{}H\x1b[{}X", y + 1, rect.x + 1, rect.w);
+  }
+  out.push_str("\x1b8");
+  return out;
+}
+
+// Returns the sequence that draws `image` inline in `rect` in iTerm2, with the cursor where it
+// was before.
+pub(crate) fn iterm2_inline (image: &Image, rect: Rect) -> String {
+  let png = image.to_png();
+  let mut out = String::from("\x1b7");
+  let _ = write!(out, "\x1b[{};{}H", rect.y + 1, rect.x + 1);
+  let _ = write!(out, "\x1b]1337;File=inline=1;size={};width={};height={};", png.len(), rect.w,
+                 rect.h);
+  out.push_str("preserveAspectRatio=0;doNotMoveCursor=1:");
+  out.push_str(&base64::encode(&png));
+  out.push_str("\x07\x1b8");
+  return out;
+}
+
+// Returns the sequence that places an image already sent with `kitty_transmit` in `rect`, with
+// the cursor where it was before. Responses from the terminal are suppressed, since they would
+// arrive as input.
+pub(crate) fn kitty_place (id: u32, rect: Rect) -> String {
+  let mut out = String::from("\x1b7");
+  let _ = write!(out, "\x1b[{};{}H", rect.y + 1, rect.x + 1);
+  let _ = write!(out, "\x1b_Ga=p,i={},c={},r={},C=1,q=2\x1b\\", id, rect.w, rect.h);
+  out.push_str("\x1b8");
+  return out;
+}
+
+// Returns the sequences that send `image` to a terminal that supports the kitty graphics
+// protocol, without showing it.
+pub(crate) fn kitty_transmit (image: &Image) -> String {
+  let data = base64::encode(&image.pixels);
+  let mut out = String::new();
+  let mut chunks = data.as_bytes().chunks(KITTY_CHUNK_SIZE).peekable();
+  let mut first = true;
+  while let Some(chunk) = chunks.next() {
+    let more = chunks.peek().is_some() as u8;
+    if first {
+      let _ = write!(out, "\x1b_Ga=t,f=32,s={},v={},i={},q=2,m={};", image.width, image.height,
+                     image.id, more);
+      first = false;
+    } else {
+      let _ = write!(out, "\x1b_Gm={};", more);
+    }
+    // Base64 is ASCII, so the chunk is valid UTF-8.
+    out.push_str(::std::str::from_utf8(chunk).unwrap());
+    out.push_str("\x1b\\");
+  }
+  return out;
+}
+
+fn adler32 (data: &[u8]) -> u32 {
+  let (mut a, mut b) = (1u32, 0u32);
+  for &byte in data {
+    a = (a + byte as u32) % 65521;
+    b = (b + a) % 65521;
+  }
+  return (b << 16) | a;
+}
+
+fn crc32 (data: &[u8]) -> u32 {
+  let mut crc = !0u32;
+  for &byte in data {
+    crc ^= byte as u32;
+    for _ in 0..8 {
+      crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+    }
+  }
+  return !crc;
+}
+
+fn write_png_chunk (png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+  png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+  let start = png.len();
+  png.extend_from_slice(kind);
+  png.extend_from_slice(data);
+  let crc = crc32(&png[start..]);
+  png.extend_from_slice(&crc.to_be_bytes());
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::any::Any;
+use std::sync::mpsc::{
+  self,
+  Receiver,
+  Sender,
+};
+use std::sync::Arc;
+
+use payload::Payload;
+use wake;
+
+
+//
+// EventInjector
+//
+
+
+/// A handle that other threads can use to wake the event loop with a value of their own, such as
+/// data that arrived from the network. Each value is reported by `Termbox::peek_event` and
+/// `Termbox::poll_event` as `Event::Custom`, which carries it. Created by
+/// `Termbox::event_injector`.
+///
+/// While any injector exists, the event loop waits for injected values as well as for the terminal.
+///
+/// ~~~no_run
+/// use std::thread;
+/// use termbox::{Event, Termbox};
+///
+/// let mut tb = Termbox::open().unwrap();
+/// let injector = tb.event_injector();
+/// thread::spawn(move || {
+///   injector.send(String::from("hello"));
+/// });
+///
+/// if let Event::Custom(payload) = tb.poll_event() {
+///   let message: String = payload.take().unwrap();
+/// }
+/// ~~~
+#[derive(Clone)]
+pub struct EventInjector {
+  sender: Sender<Payload>,
+  // Only held so that the event loop can count the injectors.
+  _handle: Arc<()>,
+}
+
+impl EventInjector {
+  /// Queues `value` for the event loop. Returns false if the `Termbox` has been dropped.
+  pub fn send<T: Any + Send> (&self, value: T) -> bool {
+    if self.sender.send(Payload::new(value)).is_err() {
+      return false;
+    }
+    wake::wake();
+    return true;
+  }
+}
+
+
+//
+// Injected
+//
+
+
+/// Receives values from injectors for the event loop.
+pub struct Injected {
+  sender: Sender<Payload>,
+  receiver: Receiver<Payload>,
+  handles: Arc<()>,
+}
+
+impl Injected {
+  /// Returns a new injector for the event loop.
+  pub fn injector (&self) -> EventInjector {
+    EventInjector {
+      sender: self.sender.clone(),
+      _handle: self.handles.clone(),
+    }
+  }
+
+  /// Determines whether any injector exists, in which case the event loop has to wait for values as
+  /// well as for the terminal.
+  pub fn is_listening (&self) -> bool {
+    Arc::strong_count(&self.handles) > 1
+  }
+
+  pub fn new () -> Injected {
+    let (sender, receiver) = mpsc::channel();
+
+    Injected {
+      sender: sender,
+      receiver: receiver,
+      handles: Arc::new(()),
+    }
+  }
+
+  /// Returns a value that has arrived, if any.
+  pub fn poll (&mut self) -> Option<Payload> {
+    self.receiver.try_recv().ok()
+  }
+}
@@ -2,14 +2,124 @@
 // This software is available under the terms of the zlib license.
 // See COPYING.TXT for more information.
 
+use std::cell::Cell;
 use std::sync::atomic::{
   AtomicBool,
   Ordering,
-  ATOMIC_BOOL_INIT,
 };
+use std::sync::{
+  Condvar,
+  Mutex,
+};
+use std::thread::{
+  self,
+  ThreadId,
+};
+use std::time::Instant;
+
+use ffi;
+use restore;
+use signals;
+use ShutdownError;
 
 // Only allow Termbox to be used from one thread.
-static mut LOCK_FLAG: AtomicBool = ATOMIC_BOOL_INIT;
+static LOCK_FLAG: AtomicBool = AtomicBool::new(false);
+
+// Signalled when the lock is released, for threads waiting in `Lock::wait`. The lock flag is
+// cleared while holding the mutex so a release can't be missed between checking and waiting.
+static RELEASE_MUTEX: Mutex<()> = Mutex::new(());
+static RELEASED: Condvar = Condvar::new();
+
+// Set while termbox is initialized. Termbox may be shut down early by the panic hook, and the C
+// library aborts if it is shut down twice.
+static ACTIVE_FLAG: AtomicBool = AtomicBool::new(false);
+
+// The thread that initialized termbox. Only that thread may shut it down from the panic hook, since
+// other threads may panic while it is blocked inside termbox.
+static OWNER: Mutex<Option<ThreadId>> = Mutex::new(None);
+
+thread_local! {
+  // Set on threads that run tasks. Their panics are caught and reported as `Event::TaskDone`, so
+  // the panic hook leaves the terminal alone.
+  static IN_TASK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Determines whether the current thread is running a task started with `Termbox::spawn_task`.
+pub fn is_task_thread () -> bool {
+  IN_TASK.with(|flag| flag.get())
+}
+
+/// Marks the current thread as running tasks. See `is_task_thread`.
+#[cfg(feature = "async")]
+pub fn set_task_thread () {
+  IN_TASK.with(|flag| flag.set(true));
+}
+
+/// Records that termbox has been initialized by the current thread, so that `shutdown` will shut
+/// it down.
+pub fn set_active () {
+  let mut owner = match OWNER.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  };
+  *owner = Some(thread::current().id());
+  ACTIVE_FLAG.store(true, Ordering::SeqCst);
+}
+
+/// Determines whether termbox is initialized and was initialized by the current thread.
+pub fn is_owned_by_current_thread () -> bool {
+  if !ACTIVE_FLAG.load(Ordering::SeqCst) {
+    return false;
+  }
+
+  let owner = match OWNER.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  };
+  return *owner == Some(thread::current().id());
+}
+
+/// Shuts down termbox if it is initialized, but leaves the terminal restorer and signal handlers
+/// in place. Used to suspend the process.
+pub fn deactivate () {
+  if ACTIVE_FLAG.swap(false, Ordering::SeqCst) {
+    unsafe {
+      ffi::tb_shutdown();
+    }
+  }
+}
+
+/// Shuts down termbox if it is initialized, and removes the terminal restorer and signal handlers.
+/// Reports whether the terminal could be restored, which is only known with the `pure-rust`
+/// feature.
+pub fn close () -> Result<(), ShutdownError> {
+  let result = if ACTIVE_FLAG.swap(false, Ordering::SeqCst) {
+    unsafe { shutdown_checked() }
+  } else {
+    Ok(())
+  };
+
+  restore::disarm();
+  signals::disable();
+  signals::disable_stop();
+  return result;
+}
+
+/// Like `close`, but ignores failures.
+pub fn shutdown () {
+  let _ = close();
+}
+
+#[cfg(feature = "pure-rust")]
+unsafe fn shutdown_checked () -> Result<(), ShutdownError> {
+  ffi::tb_shutdown_checked()
+}
+
+#[cfg(not(feature = "pure-rust"))]
+unsafe fn shutdown_checked () -> Result<(), ShutdownError> {
+  ffi::tb_shutdown();
+  return Ok(());
+}
 
 
 //
@@ -23,20 +133,45 @@ pub struct Lock {
 
 impl Lock {
   pub fn acquire () -> Option<Lock> {
-    unsafe {
-      if LOCK_FLAG.swap(true, Ordering::Acquire) {
-        None
-      } else {
-        Some(Lock { _uninstantiable: () })
+    if LOCK_FLAG.swap(true, Ordering::Acquire) {
+      None
+    } else {
+      Some(Lock { _uninstantiable: () })
+    }
+  }
+
+  /// Determines whether the lock is held.
+  pub fn is_held () -> bool {
+    LOCK_FLAG.load(Ordering::Acquire)
+  }
+
+  /// Waits until the lock is released or `deadline` passes. Returns false if the lock is still
+  /// held. The lock may be taken by another thread before the caller gets to acquire it.
+  pub fn wait (deadline: Instant) -> bool {
+    let mut guard = match RELEASE_MUTEX.lock() {
+      Ok(guard) => guard,
+      Err(poisoned) => poisoned.into_inner(),
+    };
+
+    while Lock::is_held() {
+      let now = Instant::now();
+      if now >= deadline {
+        return false;
       }
+      guard = match RELEASED.wait_timeout(guard, deadline - now) {
+        Ok((guard, _)) => guard,
+        Err(poisoned) => poisoned.into_inner().0,
+      };
     }
+
+    return true;
   }
 }
 
 impl Drop for Lock {
   fn drop (&mut self) {
-    unsafe {
-      LOCK_FLAG.store(false, Ordering::Release);
-    }
+    let _guard = RELEASE_MUTEX.lock();
+    LOCK_FLAG.store(false, Ordering::Release);
+    RELEASED.notify_all();
   }
 }
@@ -0,0 +1,389 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::fmt::{
+  self,
+  Display,
+  Formatter,
+};
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use {
+  KeyEvent,
+  KeyState,
+};
+use keys::*;
+
+/// How long `KeyMap` waits for the next key of a sequence by default.
+pub const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Names of special keys, as written in key specifications. The first name of each key is the one
+// used for display.
+const KEY_NAMES: &'static [(&'static str, Key)] = &[
+  ("F1", KEY_F1), ("F2", KEY_F2), ("F3", KEY_F3), ("F4", KEY_F4), ("F5", KEY_F5), ("F6", KEY_F6),
+  ("F7", KEY_F7), ("F8", KEY_F8), ("F9", KEY_F9), ("F10", KEY_F10), ("F11", KEY_F11),
+  ("F12", KEY_F12),
+  ("Insert", KEY_INSERT), ("Ins", KEY_INSERT),
+  ("Delete", KEY_DELETE), ("Del", KEY_DELETE),
+  ("Home", KEY_HOME),
+  ("End", KEY_END),
+  ("PgUp", KEY_PGUP), ("PageUp", KEY_PGUP),
+  ("PgDn", KEY_PGDN), ("PageDown", KEY_PGDN),
+  ("Up", KEY_ARROW_UP),
+  ("Down", KEY_ARROW_DOWN),
+  ("Left", KEY_ARROW_LEFT),
+  ("Right", KEY_ARROW_RIGHT),
+  ("Esc", KEY_ESC), ("Escape", KEY_ESC),
+  ("Enter", KEY_ENTER), ("Return", KEY_ENTER),
+  ("Tab", KEY_TAB),
+  ("Backspace", KEY_BACKSPACE2),
+  ("Space", KEY_SPACE),
+];
+
+struct Binding<A> {
+  keys: Vec<KeyPress>,
+  action: A,
+  // The group and description shown by `HelpView`.
+  help: Option<(String, String)>,
+}
+
+// Control keys that are not Ctrl and a letter, with the characters that name them.
+const CTRL_KEYS: &'static [(char, Key)] = &[
+  (' ', KEY_CTRL_2), ('@', KEY_CTRL_2), ('2', KEY_CTRL_2),
+  ('[', KEY_CTRL_LSQ_BRACKET),
+  ('\\', KEY_CTRL_BACKSLASH),
+  (']', KEY_CTRL_RSQ_BRACKET),
+  ('^', KEY_CTRL_6), ('6', KEY_CTRL_6),
+  ('_', KEY_CTRL_UNDERSCORE), ('/', KEY_CTRL_SLASH),
+];
+
+
+//
+// KeyMap
+//
+
+
+/// Maps keys and sequences of keys to actions. Keys are fed to the map as they are typed, and it
+/// returns the bound action once a whole sequence has been typed.
+///
+/// Sequences are written as key names separated by spaces, such as `"g g"` or `"Ctrl-X Ctrl-S"`.
+/// See `KeyPress::parse` for the names of keys. If too much time passes between two keys of a
+/// sequence, the keys typed so far are dropped; see `set_timeout`. A sequence that is bound itself
+/// is dispatched as soon as it is typed, so longer sequences that start with it can't be reached.
+///
+/// ~~~
+/// use termbox::keymap::{KeyMap, KeyPress};
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Action { Save, Top, Quit }
+///
+/// let mut keymap = KeyMap::new();
+/// keymap.bind("Ctrl-X Ctrl-S", Action::Save);
+/// keymap.bind("g g", Action::Top);
+/// keymap.bind("q", Action::Quit);
+///
+/// let press = |name| KeyPress::parse(name).unwrap();
+/// assert_eq!(keymap.feed_press(press("g")), None);
+/// assert_eq!(keymap.pending(), &[press("g")]);
+/// assert_eq!(keymap.feed_press(press("g")), Some(Action::Top));
+///
+/// // A key that breaks off a sequence starts a new one.
+/// assert_eq!(keymap.feed_press(press("Ctrl-X")), None);
+/// assert_eq!(keymap.feed_press(press("q")), Some(Action::Quit));
+/// ~~~
+pub struct KeyMap<A> {
+  bindings: Vec<Binding<A>>,
+  pending: Vec<KeyPress>,
+  last_key: Option<Instant>,
+  timeout: Option<Duration>,
+}
+
+impl<A: Clone> KeyMap<A> {
+  /// Binds a sequence of keys to an action, replacing any action it was already bound to. Returns
+  /// `false` if `keys` could not be parsed, in which case nothing is bound.
+  pub fn bind (&mut self, keys: &str, action: A) -> bool {
+    match parse_sequence(keys) {
+      Some(keys) => {
+        self.bind_keys(&keys, action);
+        return true;
+      },
+      None => { return false; },
+    }
+  }
+
+  /// Binds a sequence of keys to an action, replacing any action it was already bound to. Empty
+  /// sequences are ignored.
+  pub fn bind_keys (&mut self, keys: &[KeyPress], action: A) {
+    if keys.is_empty() {
+      return;
+    }
+    match self.bindings.iter_mut().find(|binding| binding.keys == keys) {
+      Some(binding) => { binding.action = action; },
+      None => {
+        self.bindings.push(Binding {
+          keys: keys.to_vec(),
+          action: action,
+          help: None,
+        });
+      },
+    }
+  }
+
+  /// Returns all bindings, in the order they were first bound.
+  pub fn bindings (&self) -> Vec<(&[KeyPress], &A)> {
+    self.bindings.iter().map(|binding| (binding.keys.as_slice(), &binding.action)).collect()
+  }
+
+  /// Feeds a key event to the map. Returns the bound action if the event completes a sequence.
+  /// Key releases are ignored.
+  pub fn feed (&mut self, event: &KeyEvent) -> Option<A> {
+    if event.state == KeyState::Release {
+      return None;
+    }
+    self.feed_press(KeyPress::from_event(event))
+  }
+
+  /// Feeds a key to the map, like `feed`.
+  pub fn feed_press (&mut self, press: KeyPress) -> Option<A> {
+    let now = Instant::now();
+    if let (Some(last_key), Some(timeout)) = (self.last_key, self.timeout) {
+      if now.duration_since(last_key) > timeout {
+        self.pending.clear();
+      }
+    }
+    self.last_key = Some(now);
+
+    self.pending.push(press);
+    if let Some(action) = self.lookup() {
+      return action;
+    }
+
+    // The key doesn't continue the sequence typed so far, so it may start a new one instead.
+    if self.pending.len() > 1 {
+      self.pending.clear();
+      self.pending.push(press);
+      if let Some(action) = self.lookup() {
+        return action;
+      }
+    }
+    self.pending.clear();
+    return None;
+  }
+
+  /// Returns the group and description of a bound sequence, as set with `set_help`.
+  pub fn help (&self, keys: &[KeyPress]) -> Option<(&str, &str)> {
+    let binding = self.bindings.iter().find(|binding| binding.keys == keys)?;
+    return binding.help.as_ref().map(|help| (help.0.as_str(), help.1.as_str()));
+  }
+
+  pub fn new () -> KeyMap<A> {
+    KeyMap {
+      bindings: Vec::new(),
+      pending: Vec::new(),
+      last_key: None,
+      timeout: Some(DEFAULT_SEQUENCE_TIMEOUT),
+    }
+  }
+
+  /// Returns the keys of the sequence being typed, for showing them to the user.
+  pub fn pending (&self) -> &[KeyPress] {
+    &self.pending
+  }
+
+  /// Drops the keys of the sequence being typed.
+  pub fn reset (&mut self) {
+    self.pending.clear();
+  }
+
+  /// Sets the description of a bound sequence, and the group it is listed under by `HelpView`.
+  /// Sequences without a description are left out of the help. Returns `false` if `keys` is not
+  /// bound.
+  pub fn set_help (&mut self, keys: &str, group: &str, description: &str) -> bool {
+    let keys = match parse_sequence(keys) {
+      Some(keys) => keys,
+      None => { return false; },
+    };
+    match self.bindings.iter_mut().find(|binding| binding.keys == keys) {
+      Some(binding) => {
+        binding.help = Some((group.to_string(), description.to_string()));
+        return true;
+      },
+      None => { return false; },
+    }
+  }
+
+  /// Sets how long the map waits for the next key of a sequence, or `None` to wait forever. The
+  /// default is `DEFAULT_SEQUENCE_TIMEOUT`.
+  pub fn set_timeout (&mut self, timeout: Option<Duration>) {
+    self.timeout = timeout;
+  }
+
+  pub fn timeout (&self) -> Option<Duration> {
+    self.timeout
+  }
+
+  /// Removes the binding of a sequence of keys. Returns the action it was bound to, or `None` if
+  /// it was not bound or could not be parsed.
+  pub fn unbind (&mut self, keys: &str) -> Option<A> {
+    let keys = parse_sequence(keys)?;
+    let index = self.bindings.iter().position(|binding| binding.keys == keys)?;
+    return Some(self.bindings.remove(index).action);
+  }
+
+  // Checks the pending keys against the bindings. Returns `Some(Some(action))` and clears them if
+  // they form a bound sequence, `Some(None)` if they start one, and `None` otherwise.
+  fn lookup (&mut self) -> Option<Option<A>> {
+    let action = self.bindings.iter()
+      .find(|binding| binding.keys == self.pending)
+      .map(|binding| binding.action.clone());
+    if action.is_some() {
+      self.pending.clear();
+      return Some(action);
+    }
+
+    if self.bindings.iter().any(|binding| binding.keys.starts_with(&self.pending)) {
+      return Some(None);
+    }
+    return None;
+  }
+}
+
+impl<A: Clone> Default for KeyMap<A> {
+  fn default () -> KeyMap<A> {
+    KeyMap::new()
+  }
+}
+
+
+//
+// KeyPress
+//
+
+
+/// A key together with the Alt modifier, as bound in a `KeyMap`. Keys that produce a character
+/// have `key` set to zero, except for Space, which is always `KEY_SPACE` without a character.
+///
+/// Formatting a `KeyPress` gives a name that `KeyPress::parse` accepts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct KeyPress {
+  pub key: Key,
+  pub ch: Option<char>,
+  pub alt: bool,
+}
+
+impl KeyPress {
+  /// Returns the key of an event.
+  pub fn from_event (event: &KeyEvent) -> KeyPress {
+    let (key, ch) = match event.ch {
+      Some(' ') => (KEY_SPACE, None),
+      Some(ch) => (0, Some(ch)),
+      None => (event.key, None),
+    };
+    KeyPress {
+      key: key,
+      ch: ch,
+      alt: event.alt,
+    }
+  }
+
+  /// Parses the name of a key. Names are either a single character, such as `g` or `G`, or one of
+  /// `F1` to `F12`, `Insert`, `Delete`, `Home`, `End`, `PgUp`, `PgDn`, `Up`, `Down`, `Left`,
+  /// `Right`, `Esc`, `Enter`, `Tab`, `Backspace` and `Space`. They can be prefixed with `Ctrl-`
+  /// (or `C-`) and `Alt-` (or `M-`), and `+` may be used instead of `-`. Ctrl works with letters
+  /// and Space, and with the punctuation keys the terminal has control codes for. Apart from
+  /// single characters, names are not case sensitive.
+  pub fn parse (name: &str) -> Option<KeyPress> {
+    let mut rest = name;
+    let mut ctrl = false;
+    let mut alt = false;
+    loop {
+      if let Some(stripped) = strip_modifier(rest, &["ctrl", "c"]) {
+        ctrl = true;
+        rest = stripped;
+      } else if let Some(stripped) = strip_modifier(rest, &["alt", "m"]) {
+        alt = true;
+        rest = stripped;
+      } else {
+        break;
+      }
+    }
+
+    let mut chars = rest.chars();
+    let (key, ch) = match (chars.next(), chars.next()) {
+      (Some(' '), None) => (KEY_SPACE, None),
+      (Some(ch), None) => (0, Some(ch)),
+      _ => {
+        let &(_, key) = KEY_NAMES.iter().find(|&&(name, _)| name.eq_ignore_ascii_case(rest))?;
+        (key, None)
+      },
+    };
+
+    if !ctrl {
+      return Some(KeyPress { key: key, ch: ch, alt: alt });
+    }
+
+    let base = if key == KEY_SPACE { ' ' } else { ch?.to_ascii_lowercase() };
+    let key = match base {
+      'a'..='z' => KEY_CTRL_A + (base as Key - 'a' as Key),
+      _ => CTRL_KEYS.iter().find(|&&(name, _)| name == base)?.1,
+    };
+    return Some(KeyPress { key: key, ch: None, alt: alt });
+  }
+}
+
+impl Display for KeyPress {
+  fn fmt (&self, f: &mut Formatter) -> fmt::Result {
+    if self.alt {
+      f.write_str("Alt-")?;
+    }
+
+    if let Some(ch) = self.ch {
+      return write!(f, "{}", ch);
+    }
+    if let Some(&(name, _)) = KEY_NAMES.iter().find(|&&(_, key)| key == self.key) {
+      return f.write_str(name);
+    }
+    match self.key {
+      KEY_CTRL_A..=KEY_CTRL_Z => {
+        let letter = (b'A' + (self.key - KEY_CTRL_A) as u8) as char;
+        write!(f, "Ctrl-{}", letter)
+      },
+      KEY_CTRL_2 => f.write_str("Ctrl-Space"),
+      key => match CTRL_KEYS.iter().find(|&&(_, ctrl_key)| ctrl_key == key) {
+        Some(&(name, _)) => write!(f, "Ctrl-{}", name),
+        None => write!(f, "0x{:04x}", key),
+      },
+    }
+  }
+}
+
+
+//
+// Functions
+//
+
+
+// Parses a sequence of key names separated by whitespace.
+fn parse_sequence (keys: &str) -> Option<Vec<KeyPress>> {
+  let keys: Option<Vec<KeyPress>> = keys.split_whitespace().map(KeyPress::parse).collect();
+  return keys.filter(|keys| !keys.is_empty());
+}
+
+// Removes a modifier prefix such as `Ctrl-` from the name of a key. A lone `-` or `+` after the
+// prefix is the key itself, so `Alt--` is Alt and the minus key.
+fn strip_modifier<'a> (name: &'a str, modifiers: &[&str]) -> Option<&'a str> {
+  for modifier in modifiers {
+    let len = modifier.len();
+    if name.len() > len + 1 && name.is_char_boundary(len)
+      && name[..len].eq_ignore_ascii_case(modifier)
+      && (name[len..].starts_with('-') || name[len..].starts_with('+'))
+    {
+      return Some(&name[len + 1..]);
+    }
+  }
+  return None;
+}
@@ -73,3 +73,85 @@ pub const KEY_CTRL_UNDERSCORE: Key = ::ffi::TB_KEY_CTRL_UNDERSCORE;
 pub const KEY_SPACE: Key = ::ffi::TB_KEY_SPACE;
 pub const KEY_BACKSPACE2: Key = ::ffi::TB_KEY_BACKSPACE2;
 pub const KEY_CTRL_8: Key = ::ffi::TB_KEY_CTRL_8;
+
+/// The first of a range of key codes that termbox never reports by itself, for keys defined by
+/// the application, such as keys with modifiers given their own input sequence with
+/// `Termbox::register_key_sequence`.
+pub const KEY_USER_FIRST: Key = 0xe000;
+/// The last of the key codes set aside for the application. See `KEY_USER_FIRST`.
+pub const KEY_USER_LAST: Key = 0xefff;
+
+// Termbox reports mouse buttons as pseudo-keys in this range. They are never valid keyboard keys.
+const MOUSE_KEY_FIRST: Key = ::ffi::TB_KEY_MOUSE_WHEEL_DOWN;
+const MOUSE_KEY_LAST: Key = ::ffi::TB_KEY_MOUSE_LEFT;
+
+/// Determines whether a raw key code is one of termbox's mouse pseudo-keys.
+pub(crate) fn is_mouse_key (key: Key) -> bool {
+  key >= MOUSE_KEY_FIRST && key <= MOUSE_KEY_LAST
+}
+
+
+//
+// KeyCategory
+//
+
+
+/// A broad classification of keys, returned by `KeyExt::category`.
+///
+/// Several key constants share a value (for example, `KEY_TAB` and `KEY_CTRL_I`), because the
+/// terminal cannot tell them apart. Such keys are classified by their most common meaning.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyCategory {
+  /// `F1` through `F12`. Contains the number of the function key.
+  Function(u8),
+  /// The arrow keys.
+  Arrow,
+  /// `Home`, `End`, `Page Up` and `Page Down`.
+  Navigation,
+  /// `Insert`, `Delete`, `Backspace`, `Tab`, `Enter` and `Space`.
+  Editing,
+  /// `Esc`.
+  Escape,
+  /// Any other control key combination, such as `Ctrl+A`.
+  Control,
+  /// A value that does not correspond to any known key.
+  Unknown,
+}
+
+
+//
+// KeyExt
+//
+
+
+/// Adds classification methods to `Key`.
+pub trait KeyExt {
+  /// Returns the category of the key.
+  fn category (self) -> KeyCategory;
+}
+
+impl KeyExt for Key {
+  fn category (self) -> KeyCategory {
+    match self {
+      KEY_F1 => KeyCategory::Function(1),
+      KEY_F2 => KeyCategory::Function(2),
+      KEY_F3 => KeyCategory::Function(3),
+      KEY_F4 => KeyCategory::Function(4),
+      KEY_F5 => KeyCategory::Function(5),
+      KEY_F6 => KeyCategory::Function(6),
+      KEY_F7 => KeyCategory::Function(7),
+      KEY_F8 => KeyCategory::Function(8),
+      KEY_F9 => KeyCategory::Function(9),
+      KEY_F10 => KeyCategory::Function(10),
+      KEY_F11 => KeyCategory::Function(11),
+      KEY_F12 => KeyCategory::Function(12),
+      KEY_ARROW_UP | KEY_ARROW_DOWN | KEY_ARROW_LEFT | KEY_ARROW_RIGHT => KeyCategory::Arrow,
+      KEY_HOME | KEY_END | KEY_PGUP | KEY_PGDN => KeyCategory::Navigation,
+      KEY_INSERT | KEY_DELETE | KEY_BACKSPACE | KEY_BACKSPACE2 | KEY_TAB | KEY_ENTER
+        | KEY_SPACE => KeyCategory::Editing,
+      KEY_ESC => KeyCategory::Escape,
+      key if key < KEY_SPACE => KeyCategory::Control,
+      _ => KeyCategory::Unknown,
+    }
+  }
+}
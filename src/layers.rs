@@ -0,0 +1,327 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Off-screen cell buffers that are stacked and flattened into the output buffer. Popups, menus
+//! and other overlays can be drawn on their own layer and moved, hidden or removed without
+//! redrawing what is underneath.
+//!
+//! ~~~
+//! use termbox::{Backend, DEFAULT};
+//! use termbox::headless::TestTermbox;
+//! use termbox::layers::{Layer, Layers};
+//!
+//! let mut background = Layer::new(6, 1);
+//! background.put_str(0, 0, "......", DEFAULT, DEFAULT);
+//! let mut popup = Layer::new(2, 1);
+//! popup.put_str(0, 0, "ab", DEFAULT, DEFAULT);
+//! popup.set_position(2, 0);
+//! popup.set_z(1);
+//!
+//! let mut layers = Layers::new();
+//! layers.add(popup);
+//! layers.add(background);
+//!
+//! let mut tb = TestTermbox::new(6, 1);
+//! layers.compose(&mut tb);
+//! assert_eq!(tb.snapshot().to_plain_text(), "..ab..\n");
+//! ~~~
+
+use grid::CellGrid;
+use {
+  Attribute,
+  Backend,
+  Cell,
+  Coord,
+  Rect,
+  DEFAULT,
+};
+
+
+//
+// Layer
+//
+
+
+/// A cell buffer with a position and stacking order. Cells equal to the layer's transparent cell
+/// let the layers below show through. New layers are filled with a transparent cell whose
+/// character is `'\0'`.
+pub struct Layer {
+  x: Coord,
+  y: Coord,
+  z: i32,
+  width: Coord,
+  height: Coord,
+  cells: Vec<Cell>,
+  transparent: Option<Cell>,
+  visible: bool,
+}
+
+impl Layer {
+  /// Returns the cells of the layer in row-major order.
+  pub fn cell_buffer (&self) -> &[Cell] {
+    &self.cells
+  }
+
+  /// Returns the cells of the layer in row-major order for modification.
+  pub fn cell_buffer_mut (&mut self) -> &mut [Cell] {
+    &mut self.cells
+  }
+
+  /// Returns the layer as a grid indexed by `(x, y)`.
+  pub fn cell_grid<'a> (&'a self) -> CellGrid<&'a [Cell]> {
+    CellGrid::new(&self.cells[..], self.width, self.height)
+  }
+
+  /// Returns the layer as a mutable grid indexed by `(x, y)`.
+  pub fn cell_grid_mut<'a> (&'a mut self) -> CellGrid<&'a mut [Cell]> {
+    CellGrid::new(&mut self.cells[..], self.width, self.height)
+  }
+
+  /// Changes a single cell. Cells outside of the layer are ignored.
+  pub fn change_cell (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    self.put_cell(x, y, Cell::new(ch, fg, bg));
+  }
+
+  /// Makes every cell transparent. If the layer has no transparent cell, it is filled with
+  /// default-colored spaces instead.
+  pub fn clear (&mut self) {
+    let blank = self.blank();
+    for cell in self.cells.iter_mut() {
+      *cell = blank;
+    }
+  }
+
+  /// Fills a rectangle with a single character.
+  pub fn fill (&mut self, rect: Rect, ch: char, fg: Attribute, bg: Attribute) {
+    for y in rect.y..rect.y + rect.h {
+      for x in rect.x..rect.x + rect.w {
+        self.change_cell(x, y, ch, fg, bg);
+      }
+    }
+  }
+
+  /// Returns the cell at the given position within the layer, or `None` if it is out of bounds.
+  pub fn get (&self, x: Coord, y: Coord) -> Option<&Cell> {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return None;
+    }
+    self.cells.get((y * self.width + x) as usize)
+  }
+
+  /// Returns the height of the layer in character cells.
+  pub fn height (&self) -> Coord {
+    self.height
+  }
+
+  /// Determines whether the layer is drawn by `Layers::compose`.
+  pub fn is_visible (&self) -> bool {
+    self.visible
+  }
+
+  /// Creates a visible, fully transparent layer at the origin with a z-order of zero.
+  pub fn new (width: Coord, height: Coord) -> Layer {
+    let mut layer = Layer {
+      x: 0,
+      y: 0,
+      z: 0,
+      width: 0,
+      height: 0,
+      cells: Vec::new(),
+      transparent: Some(Cell::new('\0', DEFAULT, DEFAULT)),
+      visible: true,
+    };
+    layer.resize(width, height);
+    return layer;
+  }
+
+  /// Returns the position of the top left corner of the layer in the output buffer.
+  pub fn position (&self) -> (Coord, Coord) {
+    (self.x, self.y)
+  }
+
+  /// Changes a single cell. Cells outside of the layer are ignored.
+  pub fn put_cell (&mut self, x: Coord, y: Coord, cell: Cell) {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return;
+    }
+    self.cells[(y * self.width + x) as usize] = cell;
+  }
+
+  /// Writes a horizontal sequence of character cells without wrapping. Returns the number of
+  /// cells that were inside the layer.
+  pub fn put_str (&mut self, x: Coord, y: Coord, msg: &str, fg: Attribute, bg: Attribute) -> Coord {
+    let mut x = x;
+    let mut written = 0;
+
+    for ch in msg.chars() {
+      if self.get(x, y).is_some() {
+        written += 1;
+      }
+      self.change_cell(x, y, ch, fg, bg);
+      x += 1;
+    }
+
+    return written;
+  }
+
+  /// Returns the area covered by the layer in the output buffer.
+  pub fn rect (&self) -> Rect {
+    Rect::new(self.x, self.y, self.width, self.height)
+  }
+
+  /// Changes the size of the layer. Cells inside both the old and new sizes are kept, and new
+  /// cells are cleared.
+  pub fn resize (&mut self, width: Coord, height: Coord) {
+    let (width, height) = (width.max(0), height.max(0));
+    let mut cells = vec![self.blank(); width as usize * height as usize];
+
+    for y in 0..height.min(self.height) {
+      for x in 0..width.min(self.width) {
+        cells[(y * width + x) as usize] = self.cells[(y * self.width + x) as usize];
+      }
+    }
+
+    self.width = width;
+    self.height = height;
+    self.cells = cells;
+  }
+
+  /// Moves the top left corner of the layer to the given position in the output buffer.
+  pub fn set_position (&mut self, x: Coord, y: Coord) {
+    self.x = x;
+    self.y = y;
+  }
+
+  /// Sets which cell value lets the layers below show through, or makes the layer opaque if
+  /// `None`.
+  pub fn set_transparent (&mut self, transparent: Option<Cell>) {
+    self.transparent = transparent;
+  }
+
+  /// Shows or hides the layer.
+  pub fn set_visible (&mut self, visible: bool) {
+    self.visible = visible;
+  }
+
+  /// Sets the stacking order. Layers with a higher z-order are drawn over those with a lower one.
+  pub fn set_z (&mut self, z: i32) {
+    self.z = z;
+  }
+
+  /// Returns the cell value that lets the layers below show through, if any.
+  pub fn transparent (&self) -> Option<Cell> {
+    self.transparent
+  }
+
+  /// Returns the width of the layer in character cells.
+  pub fn width (&self) -> Coord {
+    self.width
+  }
+
+  /// Returns the stacking order of the layer.
+  pub fn z (&self) -> i32 {
+    self.z
+  }
+
+  fn blank (&self) -> Cell {
+    match self.transparent {
+      Some(cell) => cell,
+      None => Cell::new(' ', DEFAULT, DEFAULT),
+    }
+  }
+}
+
+
+//
+// LayerId
+//
+
+
+/// Identifies a layer added to `Layers`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LayerId(u64);
+
+
+//
+// Layers
+//
+
+
+/// A stack of layers. See `Termbox::layers_mut` and `Termbox::compose`.
+pub struct Layers {
+  next_id: u64,
+  layers: Vec<(LayerId, Layer)>,
+}
+
+impl Layers {
+  /// Adds a layer to the stack and returns its id.
+  pub fn add (&mut self, layer: Layer) -> LayerId {
+    let id = LayerId(self.next_id);
+    self.next_id += 1;
+    self.layers.push((id, layer));
+    return id;
+  }
+
+  /// Removes every layer.
+  pub fn clear (&mut self) {
+    self.layers.clear();
+  }
+
+  /// Draws the visible layers into the output buffer, from the lowest z-order to the highest.
+  /// Layers with the same z-order are drawn in the order they were added. Cells not covered by
+  /// any opaque layer cell are left unchanged.
+  pub fn compose<B: Backend> (&self, tb: &mut B) {
+    let mut order: Vec<&Layer> = self.layers.iter()
+      .map(|&(_, ref layer)| layer)
+      .filter(|layer| layer.visible)
+      .collect();
+    order.sort_by_key(|layer| layer.z);
+
+    for layer in order {
+      let src = Rect::new(0, 0, layer.width, layer.height);
+      tb.blit_region(layer.x, layer.y, &layer.cells, layer.width, src, layer.transparent);
+    }
+  }
+
+  /// Returns the layer with the given id, if it hasn't been removed.
+  pub fn get (&self, id: LayerId) -> Option<&Layer> {
+    self.layers.iter().find(|&&(i, _)| i == id).map(|&(_, ref layer)| layer)
+  }
+
+  /// Returns the layer with the given id for modification, if it hasn't been removed.
+  pub fn get_mut (&mut self, id: LayerId) -> Option<&mut Layer> {
+    self.layers.iter_mut().find(|&&mut (i, _)| i == id).map(|&mut (_, ref mut layer)| layer)
+  }
+
+  /// Determines whether the stack has no layers.
+  pub fn is_empty (&self) -> bool {
+    self.layers.is_empty()
+  }
+
+  /// Returns the number of layers in the stack.
+  pub fn len (&self) -> usize {
+    self.layers.len()
+  }
+
+  pub fn new () -> Layers {
+    Layers {
+      next_id: 0,
+      layers: Vec::new(),
+    }
+  }
+
+  /// Removes a layer from the stack and returns it.
+  pub fn remove (&mut self, id: LayerId) -> Option<Layer> {
+    match self.layers.iter().position(|&(i, _)| i == id) {
+      Some(index) => Some(self.layers.remove(index).1),
+      None => None,
+    }
+  }
+}
+
+impl Default for Layers {
+  fn default () -> Layers {
+    Layers::new()
+  }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use {
+  Coord,
+  Rect,
+};
+
+
+//
+// Constraint
+//
+
+
+/// How `Layout` sizes one part of a split.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Constraint {
+  /// Exactly this many cells.
+  Fixed(Coord),
+  /// This percentage of the space being split, after margins and spacing.
+  Percent(u16),
+  /// At least this many cells, growing like `Flex(1)` when there is space left over.
+  Min(Coord),
+  /// A share of the space left over by the other parts, in proportion to the weights of all
+  /// flexible parts.
+  Flex(u32),
+}
+
+
+//
+// Direction
+//
+
+
+/// The direction in which `Layout` places its parts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Direction {
+  /// Side by side, from left to right.
+  Horizontal,
+  /// Stacked, from top to bottom.
+  Vertical,
+}
+
+
+//
+// Layout
+//
+
+
+/// Splits a rectangle into rows or columns. Parts with fixed sizes and percentages are sized
+/// first, and whatever is left is shared out between flexible parts. If the fixed parts don't fit,
+/// the last ones are shrunk, so the parts never reach outside of the rectangle. Layouts are cheap
+/// to split again, so the usual approach is to recompute all rectangles on each resize.
+///
+/// ~~~
+/// use termbox::Rect;
+/// use termbox::layout::{Constraint, Layout};
+///
+/// let rows = Layout::vertical(&[Constraint::Fixed(1), Constraint::Flex(1), Constraint::Fixed(1)])
+///   .split(Rect::new(0, 0, 80, 24));
+/// assert_eq!(rows, vec![Rect::new(0, 0, 80, 1),
+///                       Rect::new(0, 1, 80, 22),
+///                       Rect::new(0, 23, 80, 1)]);
+///
+/// let columns = Layout::horizontal(&[Constraint::Percent(25), Constraint::Flex(1)])
+///   .spacing(1)
+///   .split(rows[1]);
+/// assert_eq!(columns, vec![Rect::new(0, 1, 19, 22), Rect::new(20, 1, 60, 22)]);
+/// ~~~
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Layout {
+  direction: Direction,
+  constraints: Vec<Constraint>,
+  margin: Coord,
+  spacing: Coord,
+}
+
+impl Layout {
+  /// Creates a layout that places its parts side by side.
+  pub fn horizontal (constraints: &[Constraint]) -> Layout {
+    Layout::new(Direction::Horizontal, constraints)
+  }
+
+  /// Sets the number of cells left empty around all four sides of the rectangle before it is
+  /// split.
+  pub fn margin (mut self, margin: Coord) -> Layout {
+    self.margin = margin.max(0);
+    return self;
+  }
+
+  pub fn new (direction: Direction, constraints: &[Constraint]) -> Layout {
+    Layout {
+      direction: direction,
+      constraints: constraints.to_vec(),
+      margin: 0,
+      spacing: 0,
+    }
+  }
+
+  /// Sets the number of empty cells between neighbouring parts.
+  pub fn spacing (mut self, spacing: Coord) -> Layout {
+    self.spacing = spacing.max(0);
+    return self;
+  }
+
+  /// Returns one rectangle for each constraint, in order.
+  pub fn split (&self, rect: Rect) -> Vec<Rect> {
+    let area = Rect::new(rect.x + self.margin, rect.y + self.margin,
+                         (rect.w - self.margin * 2).max(0), (rect.h - self.margin * 2).max(0));
+    let (start, length) = match self.direction {
+      Direction::Horizontal => (area.x, area.w),
+      Direction::Vertical => (area.y, area.h),
+    };
+
+    let count = self.constraints.len() as Coord;
+    let available = (length - self.spacing * (count - 1).max(0)).max(0);
+    let sizes = sizes(&self.constraints, available);
+
+    let mut pos = start;
+    let mut rects = Vec::with_capacity(sizes.len());
+    for size in sizes {
+      rects.push(match self.direction {
+        Direction::Horizontal => Rect::new(pos, area.y, size, area.h),
+        Direction::Vertical => Rect::new(area.x, pos, area.w, size),
+      });
+      pos = (pos + size + self.spacing).min(start + length);
+    }
+
+    return rects;
+  }
+
+  /// Creates a layout that stacks its parts from top to bottom.
+  pub fn vertical (constraints: &[Constraint]) -> Layout {
+    Layout::new(Direction::Vertical, constraints)
+  }
+}
+
+
+//
+// Functions
+//
+
+
+// Works out the size of each part, given the space left after spacing.
+fn sizes (constraints: &[Constraint], available: Coord) -> Vec<Coord> {
+  let mut sizes: Vec<Coord> = constraints.iter().map(|&constraint| {
+    match constraint {
+      Constraint::Fixed(size) | Constraint::Min(size) => size.max(0),
+      Constraint::Percent(percent) => (available as i64 * percent as i64 / 100) as Coord,
+      Constraint::Flex(_) => 0,
+    }
+  }).collect();
+
+  // Shrink the last parts first if the fixed sizes don't fit.
+  let mut excess = sizes.iter().sum::<Coord>() - available;
+  for size in sizes.iter_mut().rev() {
+    if excess <= 0 {
+      break;
+    }
+    let cut = excess.min(*size);
+    *size -= cut;
+    excess -= cut;
+  }
+
+  let weight = |constraint: &Constraint| match *constraint {
+    Constraint::Flex(weight) => weight,
+    Constraint::Min(_) => 1,
+    _ => 0,
+  };
+  let total_weight: u32 = constraints.iter().map(&weight).sum();
+  let mut remaining = available - sizes.iter().sum::<Coord>();
+
+  if remaining > 0 && total_weight > 0 {
+    let share = remaining;
+    let mut last = None;
+    for (i, constraint) in constraints.iter().enumerate() {
+      let weight = weight(constraint);
+      if weight > 0 {
+        let extra = (share as u64 * weight as u64 / total_weight as u64) as Coord;
+        sizes[i] += extra;
+        remaining -= extra;
+        last = Some(i);
+      }
+    }
+    // Rounding leftovers go to the last flexible part, so the parts fill the rectangle.
+    if let Some(i) = last {
+      sizes[i] += remaining;
+    }
+  }
+
+  return sizes;
+}
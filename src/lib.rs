@@ -46,47 +46,194 @@
 //! }
 //! ~~~
 
-extern crate termbox_sys as ffi;
+extern crate libc;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(not(feature = "pure-rust"))]
+extern crate termbox_sys;
 extern crate try_from;
 
-/// Contains the `Attribute` type and attribute constants.
+#[cfg(not(any(feature = "pure-rust", feature = "termbox-sys")))]
+compile_error!("either the `termbox-sys` (default) or the `pure-rust` feature must be enabled");
+
+/// Contains functions for importing text colored with ANSI escape sequences.
+pub mod ansi;
+/// Contains `App`, a ready-made shell with a status bar, a command palette and a help overlay.
+pub mod app;
+/// Contains `TtyArbiter`, which lends the terminal to other programs.
+pub mod arbiter;
+/// Contains the `Attribute` type, attribute constants and `Color`.
 pub mod attributes;
+/// Contains the `Backend` trait.
+pub mod backend;
+/// Contains functions for drawing lines and boxes that join up with each other.
+pub mod box_drawing;
+/// Contains `Canvas`, which draws points, lines and circles with half blocks or braille dots.
+pub mod canvas;
+/// Contains functions for drawing line charts, bar charts and histograms.
+pub mod charts;
+/// Contains `Capabilities`, which describes the features of the terminal.
+pub mod capabilities;
+/// Contains `extern "C"` functions for using this crate from C.
+#[cfg(feature = "capi")]
+pub mod capi;
+/// Contains adapters for code written against older versions of this crate.
+pub mod compat;
+/// Contains the glyph profile setting, which switches decorations between Unicode and ASCII.
+pub mod glyphs;
+/// Contains the `CellGrid` type.
+pub mod grid;
+/// Contains `DrawHook`, which is told about text drawn to the screen, and `FileDrawLog`.
+pub mod hooks;
+/// Contains `TestTermbox`, which can be used to test user interfaces without a terminal.
+pub mod headless;
+/// Contains `Image`, which draws bitmaps with terminal graphics protocols or half blocks.
+pub mod images;
 /// Contains the `Key` type and key constants.
 pub mod keys;
-
+/// Contains `KeyMap`, which maps keys and sequences of keys to actions.
+pub mod keymap;
+/// Contains `Layer` and `Layers`, which stack off-screen buffers over the output buffer.
+pub mod layers;
+/// Contains `Layout`, which splits rectangles into rows and columns.
+pub mod layout;
+/// Contains `PaneTree`, which splits the screen into panes with dividers that can be dragged.
+pub mod panes;
+/// Contains the raw bindings to the termbox C library.
+#[cfg(feature = "unsafe-ffi")]
+pub mod raw;
+/// Contains functions for recording input events and playing them back.
+pub mod record;
+/// Contains `RemoteTermbox`, which is displayed and controlled by a viewer over a socket.
+pub mod remote;
+/// Contains `VirtualScreen`, which keeps a page of the user interface while another is shown.
+pub mod screens;
+/// Contains the `Snapshot` type.
+pub mod snapshot;
+/// Contains `Theme`, which maps semantic roles such as errors and selections to colors.
+pub mod theme;
+/// Contains `ThrottledReceiver`, which feeds channel messages to the event loop at a bounded rate.
+pub mod throttle;
+/// Contains the `Transliterator` trait, which replaces characters the terminal can't show.
+pub mod transliterate;
+/// Contains reusable interface components such as `Prompt`.
+pub mod widgets;
+
+mod base64;
+mod cast;
+mod clicks;
+mod detect;
+mod ffi;
+mod frame;
+mod inject;
 mod internal;
+mod link;
+mod palette;
+mod payload;
+#[cfg(feature = "pure-rust")]
+mod pure;
+mod regions;
+mod restore;
+#[cfg(feature = "serde")]
+mod serialization;
+mod sgr;
+mod signals;
+mod task;
+mod terminfo;
+mod timer;
+mod tty;
+mod wake;
+mod width;
+mod writer;
 
 pub use self::attributes::*;
+pub use self::backend::Backend;
+pub use self::frame::Frame;
+pub use self::grid::CellGrid;
+pub use self::inject::EventInjector;
 pub use self::keys::*;
+pub use self::payload::Payload;
+pub use self::regions::RegionId;
+pub use self::snapshot::Snapshot;
+pub use self::task::{
+  TaskId,
+  TaskResult,
+};
+pub use self::timer::TimerId;
+pub use self::writer::TermWriter;
 
+#[cfg(feature = "async")]
+use std::any::Any;
 use std::char;
+use std::collections::HashSet;
+#[cfg(not(feature = "pure-rust"))]
+use std::collections::VecDeque;
 use std::error;
+use std::ffi::CString;
 use std::fmt::{
   self,
   Display,
   Formatter,
 };
+use std::fs::File;
+use std::io;
 use std::mem;
 use std::os::raw::c_int;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::IntoRawFd;
+use std::panic;
+use std::path::Path;
 use std::result;
 use std::slice;
+use std::sync::Once;
+use std::time::{
+  Duration,
+  Instant,
+};
 
 use try_from::TryFrom;
 
+use box_drawing::LineStyle;
+use capabilities::Capabilities;
+use cast::Caster;
+use clicks::ClickCounter;
+use glyphs::GlyphProfile;
+use hooks::{
+  DrawHook,
+  DrawTracker,
+};
+use images::{
+  Image,
+  ImagePlacement,
+  ImageProtocol,
+};
+use inject::Injected;
 use internal::Lock;
-
-/// Represents a single character cell in the terminal output.
-///
-/// ~~~
-/// pub struct Cell {
-///   // The character displayed by the cell. This is assumed to be a unicode character,
-///   // but as termbox was written in C, this cannot be enforced at the language level.
-///   pub ch: u32,
-///   pub fg: Attribute,
-///   pub bg: Attribute,
-/// }
-/// ~~~
-pub type Cell = ffi::RawCell;
+use layers::Layers;
+use link::Link;
+use record::{
+  Player,
+  Recorder,
+};
+use regions::Regions;
+use screens::{
+  ScreenId,
+  VirtualScreen,
+};
+#[cfg(feature = "async")]
+use task::Tasks;
+use theme::{
+  Role,
+  Style,
+  Theme,
+};
+use timer::Timers;
+use transliterate::{
+  AsciiTransliterator,
+  Transliterator,
+};
+use tty::Tty;
 
 /// Integral type used to represent coordinates in cell space.
 pub type Coord = c_int;
@@ -98,6 +245,277 @@ pub type Result<T> = result::Result<T, Error>;
 pub type Time = c_int;
 
 
+//
+// Anchor
+//
+
+
+/// A corner, edge or the center of the screen, which content stays attached to when the terminal
+/// is resized. See `ResizePolicy::PreserveAnchored`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Anchor {
+  TopLeft,
+  Top,
+  TopRight,
+  Left,
+  Center,
+  Right,
+  BottomLeft,
+  Bottom,
+  BottomRight,
+}
+
+impl Anchor {
+  // Returns how far content has to move to stay attached to the anchor when the screen changes
+  // from `old` to `new` size.
+  fn offset (self, old: (Coord, Coord), new: (Coord, Coord)) -> (Coord, Coord) {
+    let (dw, dh) = (new.0 - old.0, new.1 - old.1);
+    let x = match self {
+      Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => 0,
+      Anchor::Top | Anchor::Center | Anchor::Bottom => dw / 2,
+      Anchor::TopRight | Anchor::Right | Anchor::BottomRight => dw,
+    };
+    let y = match self {
+      Anchor::TopLeft | Anchor::Top | Anchor::TopRight => 0,
+      Anchor::Left | Anchor::Center | Anchor::Right => dh / 2,
+      Anchor::BottomLeft | Anchor::Bottom | Anchor::BottomRight => dh,
+    };
+    return (x, y);
+  }
+}
+
+
+//
+// Background
+//
+
+
+/// The terminal's background color, as reported by `Termbox::request_background`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Background {
+  pub r: u8,
+  pub g: u8,
+  pub b: u8,
+}
+
+impl Background {
+  /// Determines whether the color is closer to black than to white, going by its luminance.
+  pub fn is_dark (&self) -> bool {
+    let luminance = 0.2126 * self.r as f32 + 0.7152 * self.g as f32 + 0.0722 * self.b as f32;
+    return luminance < 128.0;
+  }
+
+  /// Returns `Theme::dark` or `Theme::light` to suit the color.
+  pub fn theme (&self) -> Theme {
+    if self.is_dark() { Theme::dark() } else { Theme::light() }
+  }
+
+  // Parses the body of an OSC 11 reply, `rgb:RRRR/GGGG/BBBB`, where each component has one to four
+  // hex digits.
+  fn parse (body: &str) -> Option<Background> {
+    if !body.starts_with("rgb:") {
+      return None;
+    }
+
+    let components: Option<Vec<u8>> = body[4..]
+      .split('/')
+      .map(|component| {
+        let max = match component.len() {
+          1 => 0xf,
+          2 => 0xff,
+          3 => 0xfff,
+          4 => 0xffff,
+          _ => { return None; },
+        };
+        u32::from_str_radix(component, 16).ok().map(|value| (value * 255 / max) as u8)
+      })
+      .collect();
+
+    match components.as_ref().map(|components| &components[..]) {
+      Some(&[r, g, b]) => Some(Background { r: r, g: g, b: b }),
+      _ => None,
+    }
+  }
+}
+
+
+//
+// Cell
+//
+
+
+/// Represents a single character cell in the terminal output. It has the same layout as termbox's
+/// own cells, so the output buffer can be shared with the C library without copying. The default
+/// cell is a space in the default colors.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cell {
+  // Not a `char`, since the buffer is shared with termbox and may hold values that aren't valid
+  // characters.
+  ch: u32,
+  fg: Attribute,
+  bg: Attribute,
+}
+
+impl Cell {
+  /// Returns the background attributes of the cell.
+  pub fn bg (&self) -> Attribute {
+    self.bg
+  }
+
+  /// Returns the character displayed by the cell, or `'\u{fffd}'` if the cell holds an invalid
+  /// character. The cell covered by the right half of a wide character holds the space that
+  /// `change_cell_str` writes there, in the same colors as the wide character.
+  pub fn ch (&self) -> char {
+    char::from_u32(self.ch).unwrap_or('\u{fffd}')
+  }
+
+  /// Returns the foreground attributes of the cell.
+  pub fn fg (&self) -> Attribute {
+    self.fg
+  }
+
+  /// Creates a cell displaying `ch` with the given foreground and background attributes.
+  pub fn new (ch: char, fg: Attribute, bg: Attribute) -> Cell {
+    Cell {
+      ch: ch as u32,
+      fg: fg,
+      bg: bg,
+    }
+  }
+
+  /// Changes the background attributes of the cell.
+  pub fn set_bg (&mut self, bg: Attribute) {
+    self.bg = bg;
+  }
+
+  /// Changes the character displayed by the cell.
+  pub fn set_ch (&mut self, ch: char) {
+    self.ch = ch as u32;
+  }
+
+  /// Changes the foreground attributes of the cell.
+  pub fn set_fg (&mut self, fg: Attribute) {
+    self.fg = fg;
+  }
+
+  // Converts the cell to termbox's representation.
+  fn to_raw (self) -> ffi::RawCell {
+    ffi::RawCell { ch: self.ch, fg: self.fg, bg: self.bg }
+  }
+}
+
+impl Default for Cell {
+  fn default () -> Cell {
+    Cell::new(' ', DEFAULT, DEFAULT)
+  }
+}
+
+
+//
+// ClusterError
+//
+
+
+/// Returned by `change_cell_str` when a string cannot be displayed in a single cell.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ClusterError {
+  /// The string is empty.
+  Empty,
+  /// The string begins with a control or combining character.
+  NoBaseCharacter,
+  /// The string contains combining characters. A cell can only hold one character, so these
+  /// cannot be displayed. This is returned even if a precomposed character exists, such as `é`
+  /// for `e` followed by U+0301, since clusters are not normalized. Normalize text to NFC first
+  /// to avoid it.
+  CombiningCharacter,
+  /// The string contains more than one character.
+  MultipleCharacters,
+  /// The character is two cells wide, but there is only room for one.
+  NoRoom,
+}
+
+impl ClusterError {
+  pub fn as_str (self) -> &'static str {
+    match self {
+      ClusterError::Empty => "empty cluster",
+      ClusterError::NoBaseCharacter => "cluster has no base character",
+      ClusterError::CombiningCharacter => "combining characters are not supported",
+      ClusterError::MultipleCharacters => "cluster contains multiple characters",
+      ClusterError::NoRoom => "not enough room for a wide character",
+    }
+  }
+}
+
+impl Display for ClusterError {
+  fn fmt (&self, f: &mut Formatter) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl error::Error for ClusterError {
+  fn description (&self) -> &str {
+    self.as_str()
+  }
+}
+
+
+//
+// ControlChars
+//
+
+
+/// Determines how `Termbox::put_str_with` writes control characters, which some terminals act on
+/// in odd ways when they are written into cells. See `PutOptions::control_chars`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ControlChars {
+  /// Control characters are written as-is.
+  Raw,
+  /// C0 control characters and DEL are shown as the matching symbols from the Control Pictures
+  /// block, such as `␀` and `␛`. C1 control characters are shown as `�`.
+  Pictures,
+  /// Control characters are shown in caret notation, such as `^C`, the way `cat -v` does. C1
+  /// control characters are prefixed with `M-`.
+  Caret,
+  /// Control characters are left out.
+  Strip,
+}
+
+
+//
+// Diagnostic
+//
+
+
+/// A problem found in strict mode. See `Termbox::set_strict_mode`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Diagnostic {
+  /// An attribute passed to a drawing call is not meaningful in the current output mode, and is
+  /// likely to be displayed in an unexpected color.
+  InvalidAttribute {
+    x: Coord,
+    y: Coord,
+    attribute: Attribute,
+    mode: OutputMode,
+    reason: &'static str,
+  },
+}
+
+impl Display for Diagnostic {
+  fn fmt (&self, f: &mut Formatter) -> fmt::Result {
+    match *self {
+      Diagnostic::InvalidAttribute { x, y, attribute, mode, reason } => {
+        write!(f, "attribute {:#06x} at ({}, {}) in {:?} mode: {}", attribute, x, y, mode, reason)
+      },
+    }
+  }
+}
+
+// Strict mode keeps at most this many diagnostics until they are taken.
+const MAX_DIAGNOSTICS: usize = 256;
+
+
 //
 // Error
 //
@@ -152,8 +570,11 @@ impl error::Error for Error {
 
 
 /// Represents an event that describes a user input action.
-/// Events can be received with `Termbox::peek_event` or `Termbox::poll_event`.
-#[derive(Clone, Copy, Debug)]
+/// Events can be received with `Termbox::peek_event` or `Termbox::poll_event`. With the `serde`
+/// feature, events can be serialized, except for `TaskDone`, `Timer` and `Custom`, which only mean
+/// something to the `Termbox` that reported them.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Event {
   /// Received when the user presses a key on the keyboard.
   Key(KeyEvent),
@@ -162,14 +583,57 @@ pub enum Event {
   /// Received when the user presses a mouse button or uses the mouse wheel on the terminal.
   /// Mouse events are disabled by default, and must be enabled with `Termbox::set_mouse_enabled`.
   Mouse(MouseEvent),
+  /// Received before the key events for text pasted into the terminal. Only reported when
+  /// bracketed paste is enabled with `Termbox::set_bracketed_paste`.
+  PasteStart,
+  /// Received after the key events for pasted text. See `PasteStart`.
+  PasteEnd,
+  /// Received when the process is sent SIGINT or SIGTERM, if enabled with
+  /// `Termbox::set_signal_events`.
+  Signal(Signal),
+  /// Received when a task started with `Termbox::spawn_task` finishes. Contains the id returned by
+  /// `spawn_task` and the value returned by the task, or `Err` with the value it panicked with.
+  /// Tasks need the `async` feature, but the variant is always there, so that enabling the feature
+  /// doesn't break matches on `Event`.
+  #[cfg_attr(feature = "serde", serde(skip))]
+  TaskDone(TaskId, TaskResult),
+  /// Received when a timer set with `Termbox::set_timer` expires.
+  #[cfg_attr(feature = "serde", serde(skip))]
+  Timer(TimerId),
+  /// Received when a value is sent with an `EventInjector`. Contains the value.
+  #[cfg_attr(feature = "serde", serde(skip))]
+  Custom(Payload),
+  /// Received when bytes are read from the terminal, if enabled with `Termbox::set_raw_input`.
+  /// Contains the bytes read since the last `Raw` event.
+  Raw(Vec<u8>),
+  /// Received when the terminal sends an escape sequence that termbox can't decode, such as a
+  /// device report or a key with an exotic encoding. Contains the whole sequence, including the
+  /// leading escape. Only reported with the `pure-rust` feature.
+  Unknown(Vec<u8>),
+  /// Received after the process is continued following `Termbox::suspend`. The screen has been
+  /// restored from the output buffer, but programs that draw anything outside of it (such as
+  /// hyperlinks) should redraw.
+  Resumed,
+  /// Received in place of `Resumed` if termbox couldn't be reinitialized after the process was
+  /// suspended by job control (see `Termbox::set_job_control`). The terminal is left as it was
+  /// before termbox was initialized, and the `Termbox` should be dropped.
+  ResumeFailed,
 }
 
 impl Event {
   fn from_raw (raw: ffi::RawEvent) -> Option<Event> {
     match raw.etype {
-      ffi::TB_EVENT_KEY => Some(Event::Key(KeyEvent::from_raw(raw).unwrap())),
+      ffi::TB_EVENT_KEY => KeyEvent::from_raw(raw).map(Event::Key),
       ffi::TB_EVENT_RESIZE => Some(Event::Resize(ResizeEvent::from_raw(raw).unwrap())),
       ffi::TB_EVENT_MOUSE => Some(Event::Mouse(MouseEvent::from_raw(raw).unwrap())),
+      #[cfg(feature = "pure-rust")]
+      ffi::TB_EVENT_PASTE_START => Some(Event::PasteStart),
+      #[cfg(feature = "pure-rust")]
+      ffi::TB_EVENT_PASTE_END => Some(Event::PasteEnd),
+      #[cfg(feature = "pure-rust")]
+      ffi::TB_EVENT_RAW => Some(Event::Raw(unsafe { ffi::tb_take_raw_input() })),
+      #[cfg(feature = "pure-rust")]
+      ffi::TB_EVENT_UNKNOWN => Some(Event::Unknown(unsafe { ffi::tb_take_unknown_sequence() })),
       _ => None,
     }
   }
@@ -212,27 +676,85 @@ impl InputMode {
 }
 
 
+//
+// IntoCoord
+//
+
+
+/// Converts any primitive integer to a `Coord`, so that the drawing functions of `Termbox` and
+/// `TestTermbox` can be given `usize` indices or `u16` sizes without casts. Values too large or
+/// small for a `Coord` are clamped, which keeps them outside of the output buffer.
+pub trait IntoCoord {
+  fn into_coord (self) -> Coord;
+}
+
+macro_rules! impl_into_coord_signed {
+  ($($t:ty),*) => {
+    $(impl IntoCoord for $t {
+      fn into_coord (self) -> Coord {
+        (self as i64).clamp(Coord::MIN as i64, Coord::MAX as i64) as Coord
+      }
+    })*
+  };
+}
+
+macro_rules! impl_into_coord_unsigned {
+  ($($t:ty),*) => {
+    $(impl IntoCoord for $t {
+      fn into_coord (self) -> Coord {
+        (self as u64).min(Coord::MAX as u64) as Coord
+      }
+    })*
+  };
+}
+
+impl_into_coord_signed!(i8, i16, i32, i64, isize);
+impl_into_coord_unsigned!(u8, u16, u32, u64, usize);
+
+
 //
 // KeyEvent
 //
 
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeyEvent {
-  /// Code for the key that was pressed by the user. See the `keys` module.
+  /// Code for the key that was pressed by the user. See the `keys` module. This is never one of
+  /// termbox's mouse pseudo-keys.
   pub key: Key,
   /// If the pressed key can be translated into a Unicode character, this contains the code point.
+  /// This is `None` for special keys such as arrows and function keys, which were reported as
+  /// `Some('\0')` before.
   pub ch: Option<char>,
   pub alt: bool,
+  /// Whether the key was pressed, repeated or released. Always `Press` unless the kitty keyboard
+  /// protocol is enabled with `Termbox::set_kitty_keyboard`.
+  pub state: KeyState,
+}
+
+impl KeyEvent {
+  /// Returns the category of the pressed key. Events that carry a character, rather than a special
+  /// key, are classified as `None`.
+  pub fn category (&self) -> Option<KeyCategory> {
+    if self.ch.is_some() {
+      None
+    } else {
+      Some(self.key.category())
+    }
+  }
 }
 
 impl KeyEvent {
+  // Mouse pseudo-keys are never reported as key events.
   fn from_raw (raw: ffi::RawEvent) -> Option<KeyEvent> {
-    if raw.etype == ffi::TB_EVENT_KEY {
+    if raw.etype == ffi::TB_EVENT_KEY && !keys::is_mouse_key(raw.key) {
       Some(KeyEvent {
         key: raw.key,
-        ch: char::from_u32(raw.ch),
+        // Termbox sets `ch` to zero when a special key was pressed.
+        ch: if raw.ch == 0 { None } else { char::from_u32(raw.ch) },
         alt: (raw.emod & ffi::TB_MOD_ALT) != 0,
+        state: KeyState::from_raw(raw.emod),
       })
     } else {
       None
@@ -241,12 +763,47 @@ impl KeyEvent {
 }
 
 
+//
+// KeyState
+//
+
+
+/// Distinguishes the key events reported by the kitty keyboard protocol.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyState {
+  Press,
+  /// The key is being held down and the terminal is repeating it.
+  Repeat,
+  Release,
+}
+
+impl KeyState {
+  #[cfg(feature = "pure-rust")]
+  fn from_raw (emod: u8) -> KeyState {
+    if emod & ffi::TB_MOD_RELEASE != 0 {
+      KeyState::Release
+    } else if emod & ffi::TB_MOD_REPEAT != 0 {
+      KeyState::Repeat
+    } else {
+      KeyState::Press
+    }
+  }
+
+  #[cfg(not(feature = "pure-rust"))]
+  fn from_raw (_emod: u8) -> KeyState {
+    KeyState::Press
+  }
+}
+
+
 //
 // MouseButton
 //
 
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MouseButton {
   Left,
   Right,
@@ -254,6 +811,10 @@ pub enum MouseButton {
   Release,
   WheelUp,
   WheelDown,
+  /// Horizontal scrolling, sent by some terminals for tilting wheels and touchpads. Only reported
+  /// with the `pure-rust` feature.
+  WheelLeft,
+  WheelRight,
 }
 
 impl MouseButton {
@@ -265,6 +826,10 @@ impl MouseButton {
       ffi::TB_KEY_MOUSE_RELEASE => Some(MouseButton::Release),
       ffi::TB_KEY_MOUSE_WHEEL_UP => Some(MouseButton::WheelUp),
       ffi::TB_KEY_MOUSE_WHEEL_DOWN => Some(MouseButton::WheelDown),
+      #[cfg(feature = "pure-rust")]
+      ffi::TB_KEY_MOUSE_WHEEL_LEFT => Some(MouseButton::WheelLeft),
+      #[cfg(feature = "pure-rust")]
+      ffi::TB_KEY_MOUSE_WHEEL_RIGHT => Some(MouseButton::WheelRight),
       _ => None,
     }
   }
@@ -277,25 +842,79 @@ impl MouseButton {
 
 
 /// Mouse events are disabled by default. Use `Termbox::set_mouse_enabled` to enable them.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MouseEvent {
   pub button: MouseButton,
   pub x: Coord,
   pub y: Coord,
+  /// How many times in a row the button has been clicked at about the same place, counting this
+  /// press: 2 for a double click, 3 for a triple click, and so on. Releases carry the count of the
+  /// press they end, and wheel events are always 1. See `Termbox::set_click_thresholds`.
+  pub clicks: u8,
+  /// Whether Shift, Alt or Ctrl was held. Terminals often keep some combinations for themselves,
+  /// such as Shift with a button for selecting text. Only reported with the `pure-rust` feature.
+  pub shift: bool,
+  pub alt: bool,
+  pub ctrl: bool,
 }
 
 impl MouseEvent {
   fn from_raw (raw: ffi::RawEvent) -> Option<MouseEvent> {
     if raw.etype == ffi::TB_EVENT_MOUSE {
+      let (shift, alt, ctrl) = MouseEvent::modifiers_from_raw(raw.emod);
       Some(MouseEvent {
         button: MouseButton::from_raw(raw.key).unwrap(),
         x: Coord::try_from(raw.x).unwrap(),
         y: Coord::try_from(raw.y).unwrap(),
+        clicks: 1,
+        shift: shift,
+        alt: alt,
+        ctrl: ctrl,
       })
     } else {
       None
     }
   }
+
+  #[cfg(feature = "pure-rust")]
+  fn modifiers_from_raw (emod: u8) -> (bool, bool, bool) {
+    (emod & ffi::TB_MOD_SHIFT != 0, emod & ffi::TB_MOD_ALT != 0, emod & ffi::TB_MOD_CTRL != 0)
+  }
+
+  #[cfg(not(feature = "pure-rust"))]
+  fn modifiers_from_raw (_emod: u8) -> (bool, bool, bool) {
+    (false, false, false)
+  }
+}
+
+
+//
+// OutOfBounds
+//
+
+
+/// Returned by `try_change_cell` and `try_put_cell` when a cell is outside of the output buffer.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct OutOfBounds {
+  pub x: Coord,
+  pub y: Coord,
+  /// The size of the output buffer at the time.
+  pub width: Coord,
+  pub height: Coord,
+}
+
+impl Display for OutOfBounds {
+  fn fmt (&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "cell ({}, {}) is outside of the {}x{} output buffer", self.x, self.y, self.width,
+           self.height)
+  }
+}
+
+impl error::Error for OutOfBounds {
+  fn description (&self) -> &str {
+    "cell out of bounds"
+  }
 }
 
 
@@ -305,6 +924,7 @@ impl MouseEvent {
 
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OutputMode {
   /// Valid attributes are defined by constants found in the `attributes` module.
   Normal,
@@ -314,7 +934,7 @@ pub enum OutputMode {
   /// * `0x10 - 0xe7`: 216 additional colors.
   /// * `0xe8 - 0xff`: 24 shades of gray.
   /// Due to the addition of the `DEFAULT` attribute, the 8 standard colors should have their value
-  /// subtracted by one.
+  /// subtracted by one. `Color` computes the right values for each mode.
   Color256,
   /// Supports only the 216 colors from `0x10 - 0xe7` described above.
   Color216,
@@ -345,265 +965,2869 @@ impl OutputMode {
 
 
 //
-// ResizeEvent
+// Overflow
 //
 
 
-#[derive(Clone, Copy, Debug)]
-pub struct ResizeEvent {
-  pub w: Coord,
-  pub h: Coord,
+/// Determines what `Termbox::put_str_with` does with text that reaches the right edge of the
+/// output buffer.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Overflow {
+  /// The rest of the text is discarded.
+  Clip,
+  /// The text continues on the next line, starting again from the original column. Newlines in the
+  /// text also start a new line.
+  Wrap,
 }
 
-impl ResizeEvent {
-  fn from_raw (raw: ffi::RawEvent) -> Option<ResizeEvent> {
-    if raw.etype == ffi::TB_EVENT_RESIZE {
-      Some(ResizeEvent {
-        w: Coord::try_from(raw.w).unwrap(),
-        h: Coord::try_from(raw.h).unwrap(),
-      })
-    } else {
-      None
-    }
-  }
+
+//
+// Overlay
+//
+
+
+/// Determines which attributes `Termbox::put_str_with` takes from the cells it writes over. See
+/// `PutOptions::overlay`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Overlay {
+  /// Both attributes are replaced.
+  Replace,
+  /// The background of each cell is kept, so text can be drawn over a gradient or a highlight.
+  KeepBackground,
+  /// The foreground of each cell is kept, including style flags such as `BOLD`, so the background
+  /// can be changed to mark a selection.
+  KeepForeground,
 }
 
 
 //
-// Termbox
+// PixelSize
 //
 
 
-/// The main entry point for all termbox functions.
-/// This ensures that the terminal can only be accessed from one thread.
-/// Sadly, writing to `stdout` can potentially interfere with termbox output.
-pub struct Termbox {
-  #[allow(dead_code)]
-  lock: Lock,
+/// The size of the terminal in pixels. See `Termbox::pixel_size`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PixelSize {
+  /// Width of the text area in pixels.
+  pub width: u32,
+  /// Height of the text area in pixels.
+  pub height: u32,
+  /// Width of one cell in pixels.
+  pub cell_width: u32,
+  /// Height of one cell in pixels.
+  pub cell_height: u32,
 }
 
-impl Termbox {
-  /// Copies a rectangular region of cells from a slice to the output buffer.
-  pub fn blit (&mut self, x: Coord, y: Coord, w: Coord, h: Coord, cells: &[Cell]) {
-    unsafe {
-      let uwidth = usize::try_from(w).unwrap();
-      let uheight = usize::try_from(h).unwrap();
-      let min_len = uwidth.checked_mul(uheight).unwrap();
-      assert!(cells.len() >= min_len);
-      ffi::tb_blit(x, y, w, h, &cells[0]);
+impl PixelSize {
+  /// Returns how many times taller than wide a cell is. Drawing code can scale vertical distances
+  /// by the inverse of this so that circles come out round.
+  pub fn cell_aspect_ratio (&self) -> f32 {
+    if self.cell_width == 0 {
+      return DEFAULT_CELL_ASPECT_RATIO;
     }
+    return self.cell_height as f32 / self.cell_width as f32;
   }
+}
 
-  /// Returns a slice representing the output buffer.
-  pub fn cell_buffer<'a> (&'a self) -> &'a [Cell] {
-    unsafe {
-      let w = usize::try_from(ffi::tb_width()).unwrap();
-      let h = usize::try_from(ffi::tb_height()).unwrap();
-      let len = w.checked_mul(h).unwrap();
-      let ptr = ffi::tb_cell_buffer() as *const Cell;
-      return slice::from_raw_parts(ptr, len);
-    }
-  }
 
-  /// Returns a mutable slice representing the output buffer.
-  pub fn cell_buffer_mut<'a> (&'a mut self) -> &'a mut [Cell] {
-    unsafe {
-      let w = usize::try_from(ffi::tb_width()).unwrap();
-      let h = usize::try_from(ffi::tb_height()).unwrap();
-      let len = w.checked_mul(h).unwrap();
-      let ptr = ffi::tb_cell_buffer();
-      return slice::from_raw_parts_mut(ptr, len);
-    }
-  }
+//
+// PresentStats
+//
 
-  /// Changes a single cell in the output buffer.
-  pub fn change_cell (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
-    unsafe {
-      ffi::tb_change_cell(x, y, ch as u32, fg, bg);
+
+/// Measurements of one call that wrote the output buffer to the terminal. See
+/// `Termbox::last_present_stats`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PresentStats {
+  /// Number of cells that differed from the previously presented frame. After a resize, every cell
+  /// counts as changed.
+  pub cells_changed: usize,
+  /// Number of bytes sent to the terminal, including hyperlinks. Only known with the `pure-rust`
+  /// feature, since the C library writes to the terminal itself.
+  pub bytes_written: Option<usize>,
+  /// Time spent comparing, encoding and writing the frame.
+  pub duration: Duration,
+}
+
+
+//
+// PutOptions
+//
+
+
+/// Options for `Termbox::put_str_with`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PutOptions {
+  pub overflow: Overflow,
+  /// If positive, tabs are expanded with spaces to the next multiple of this many columns from the
+  /// starting column. If zero, the width set with `Termbox::set_tab_width` is used, which is 8 by
+  /// default. If negative, tabs are written as-is.
+  pub tab_width: Coord,
+  /// How control characters are written. Tabs that are expanded, and newlines when wrapping, are
+  /// not affected. The default is `ControlChars::Raw`.
+  pub control_chars: ControlChars,
+  /// Which attributes are kept from the cells that are written over. The default is
+  /// `Overlay::Replace`.
+  pub overlay: Overlay,
+}
+
+impl Default for PutOptions {
+  fn default () -> PutOptions {
+    PutOptions {
+      overflow: Overflow::Clip,
+      tab_width: 0,
+      control_chars: ControlChars::Raw,
+      overlay: Overlay::Replace,
     }
   }
+}
 
-  /// Clears the output buffer and sets all cell attributes to those specified with
+
+//
+// RawInput
+//
+
+
+/// Selects whether the bytes read from the terminal are reported by `Event::Raw`. See
+/// `Termbox::set_raw_input`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RawInput {
+  /// Only decoded events are reported.
+  Disabled,
+  /// Raw bytes are reported before the events decoded from them.
+  Alongside,
+  /// Raw bytes are reported instead of key, mouse and paste events.
+  Exclusive,
+}
+
+#[cfg(feature = "pure-rust")]
+impl RawInput {
+  fn from_raw (raw: c_int) -> RawInput {
+    if raw & ffi::TB_INPUT_RAW_ONLY != 0 {
+      RawInput::Exclusive
+    } else if raw & ffi::TB_INPUT_RAW != 0 {
+      RawInput::Alongside
+    } else {
+      RawInput::Disabled
+    }
+  }
+
+  fn to_raw (self) -> c_int {
+    match self {
+      RawInput::Disabled => 0,
+      RawInput::Alongside => ffi::TB_INPUT_RAW,
+      RawInput::Exclusive => ffi::TB_INPUT_RAW | ffi::TB_INPUT_RAW_ONLY,
+    }
+  }
+}
+
+
+//
+// Rect
+//
+
+
+/// A rectangle of character cells.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Rect {
+  pub x: Coord,
+  pub y: Coord,
+  pub w: Coord,
+  pub h: Coord,
+}
+
+impl Rect {
+  /// Determines whether the rectangle contains the given position.
+  pub fn contains<X: IntoCoord, Y: IntoCoord> (&self, x: X, y: Y) -> bool {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    x >= self.x && y >= self.y && x < self.x + self.w && y < self.y + self.h
+  }
+
+  pub fn new<X, Y, W, H> (x: X, y: Y, w: W, h: H) -> Rect
+    where X: IntoCoord, Y: IntoCoord, W: IntoCoord, H: IntoCoord
+  {
+    Rect {
+      x: x.into_coord(),
+      y: y.into_coord(),
+      w: w.into_coord(),
+      h: h.into_coord(),
+    }
+  }
+}
+
+
+//
+// ResizeEvent
+//
+
+
+/// Reports a change in the terminal's size. A resize that happens while the program is busy is
+/// reported as soon as it next waits for an event, so the same size may be reported twice.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ResizeEvent {
+  pub w: Coord,
+  pub h: Coord,
+}
+
+impl ResizeEvent {
+  fn from_raw (raw: ffi::RawEvent) -> Option<ResizeEvent> {
+    if raw.etype == ffi::TB_EVENT_RESIZE {
+      Some(ResizeEvent {
+        w: Coord::try_from(raw.w).unwrap(),
+        h: Coord::try_from(raw.h).unwrap(),
+      })
+    } else {
+      None
+    }
+  }
+}
+
+
+//
+// ResizePolicy
+//
+
+
+/// What happens to the screen contents when the terminal is resized and no handler is set with
+/// `Termbox::on_resize`. See `Termbox::set_resize_policy`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ResizePolicy {
+  /// Leave the output buffer as termbox resized it, until the application draws again.
+  Clear,
+  /// Put the last presented frame back, clipped to the new size.
+  PreserveTopLeft,
+  /// Put the last presented frame back, moved to stay attached to a corner, edge or the center of
+  /// the screen.
+  PreserveAnchored(Anchor),
+}
+
+impl ResizePolicy {
+  // Returns the anchor that the last frame is drawn at after a resize.
+  fn anchor (self) -> Anchor {
+    match self {
+      ResizePolicy::Clear | ResizePolicy::PreserveTopLeft => Anchor::TopLeft,
+      ResizePolicy::PreserveAnchored(anchor) => anchor,
+    }
+  }
+}
+
+
+//
+// ShutdownError
+//
+
+
+/// An error reported by `Termbox::close`. Termbox is shut down regardless, but the terminal may be
+/// left in a state the user has to repair, for example with `reset`.
+#[derive(Debug)]
+pub enum ShutdownError {
+  /// The escape sequences that restore the terminal's modes and leave the alternate screen could
+  /// not be written.
+  Write(io::Error),
+  /// The terminal's original settings could not be restored.
+  Settings(io::Error),
+}
+
+impl Display for ShutdownError {
+  fn fmt (&self, f: &mut Formatter) -> fmt::Result {
+    match *self {
+      ShutdownError::Write(ref err) => write!(f, "failed to reset terminal modes: {}", err),
+      ShutdownError::Settings(ref err) => write!(f, "failed to restore terminal settings: {}", err),
+    }
+  }
+}
+
+impl error::Error for ShutdownError {
+  fn source (&self) -> Option<&(dyn error::Error + 'static)> {
+    match *self {
+      ShutdownError::Write(ref err) => Some(err),
+      ShutdownError::Settings(ref err) => Some(err),
+    }
+  }
+}
+
+
+//
+// Signal
+//
+
+
+/// A signal reported by `Event::Signal`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Signal {
+  /// SIGINT. Note that pressing Ctrl-C is reported as a key event instead, since termbox puts the
+  /// terminal in raw mode.
+  Interrupt,
+  /// SIGTERM.
+  Terminate,
+}
+
+
+//
+// Termbox
+//
+
+
+// Sent around each frame while synchronized output is enabled.
+const BEGIN_SYNCHRONIZED_UPDATE: &'static [u8] = b"\x1b[?2026h";
+const END_SYNCHRONIZED_UPDATE: &'static [u8] = b"\x1b[?2026l";
+
+// Assumed when the pixel size of cells is unknown. Typical terminal fonts are about twice as tall
+// as they are wide.
+const DEFAULT_CELL_ASPECT_RATIO: f32 = 2.0;
+
+// How long to wait for the terminal to answer a pixel size query, in milliseconds.
+const PIXEL_SIZE_TIMEOUT: Time = 100;
+
+/// The main entry point for all termbox functions.
+/// This ensures that the terminal can only be accessed from one thread.
+/// Sadly, writing to `stdout` can potentially interfere with termbox output.
+pub struct Termbox {
+  tty: Tty,
+  capabilities: Capabilities,
+  hyperlinks: bool,
+  synchronized_output: bool,
+  tab_width: Coord,
+  utf8: bool,
+  links: Vec<Link>,
+  cursor: (Coord, Coord),
+  cursor_dirty: bool,
+  frame_interval: Option<Duration>,
+  last_present: Option<Instant>,
+  present_pending: bool,
+  size: (Coord, Coord),
+  resize_handler: Option<Box<dyn FnMut(&mut Termbox, Coord, Coord)>>,
+  draw_hook: Option<DrawTracker>,
+  #[cfg(feature = "async")]
+  tasks: Tasks,
+  init: Box<dyn Fn() -> c_int>,
+  clear_attributes: (Attribute, Attribute),
+  job_control: bool,
+  resumed: bool,
+  resume_failed: bool,
+  pixel_size: Option<Option<PixelSize>>,
+  layers: Layers,
+  transliterator: Box<dyn Transliterator>,
+  strict: bool,
+  diagnostics: Vec<Diagnostic>,
+  resize_policy: ResizePolicy,
+  last_frame: Option<Snapshot>,
+  resize_debounce: Option<Duration>,
+  pending_resize: Option<(Event, Instant)>,
+  // An event read ahead by `poll_event_coalesced` that couldn't be merged, returned next.
+  held_event: Option<Event>,
+  // Set while `poll_event_coalesced` merges events, which keeps the last resize for afterwards.
+  defer_resize_handler: bool,
+  deferred_resize: Option<Event>,
+  // Input read while waiting for a reply from the terminal, reported before any new input.
+  #[cfg(not(feature = "pure-rust"))]
+  queued_input: VecDeque<Event>,
+  theme: Theme,
+  timers: Timers,
+  injected: Injected,
+  recorder: Option<Recorder>,
+  player: Option<Player>,
+  caster: Option<Caster>,
+  clicks: ClickCounter,
+  regions: Regions,
+  image_protocol: ImageProtocol,
+  images: Vec<ImagePlacement>,
+  placed_images: Vec<(u32, Rect)>,
+  sent_images: HashSet<u32>,
+  // The output buffer as of the last present, without secondary cursors. Only kept while present
+  // stats are enabled or `frame_if_changed` is in use, since copying it costs time on every present.
+  presented: Option<Vec<Cell>>,
+  present_stats_enabled: bool,
+  compare_frames: bool,
+  secondary_cursors: Vec<(Coord, Coord)>,
+  secondary_cursor_style: Option<Style>,
+  present_stats: Option<PresentStats>,
+  // Screens other than the active one, whose cells are in the output buffer.
+  screens: Vec<(ScreenId, VirtualScreen)>,
+  active_screen: ScreenId,
+  next_screen_id: u64,
+  // Declared last so that it is released only after everything else has been dropped, and a new
+  // instance can't be opened while parts of this one are still being torn down.
+  #[allow(dead_code)]
+  lock: Lock,
+}
+
+impl Termbox {
+  /// Returns the screen being shown. Every `Termbox` starts with one screen. See `add_screen`.
+  pub fn active_screen (&self) -> ScreenId {
+    self.active_screen
+  }
+
+  /// Adds a blank screen the size of the output buffer, which can be drawn on with `screen_mut`
+  /// and shown with `switch_screen`. Screens are independent full-screen pages, like tabs, each
+  /// with its own cells and cursor.
+  pub fn add_screen (&mut self) -> ScreenId {
+    let id = ScreenId(self.next_screen_id);
+    self.next_screen_id += 1;
+    let screen = VirtualScreen::new(self.width(), self.height());
+    self.screens.push((id, screen));
+    return id;
+  }
+
+  /// Copies a rectangular region of cells from a slice to the output buffer.
+  pub fn blit<X, Y, W, H> (&mut self, x: X, y: Y, w: W, h: H, cells: &[Cell])
+    where X: IntoCoord, Y: IntoCoord, W: IntoCoord, H: IntoCoord
+  {
+    let (x, y, w, h) = (x.into_coord(), y.into_coord(), w.into_coord(), h.into_coord());
+    unsafe {
+      let uwidth = usize::try_from(w).unwrap();
+      let uheight = usize::try_from(h).unwrap();
+      let min_len = uwidth.checked_mul(uheight).unwrap();
+      assert!(cells.len() >= min_len);
+      if self.is_strict() {
+        for (i, cell) in cells[..min_len].iter().enumerate() {
+          let (col, row) = ((i % uwidth) as Coord, (i / uwidth) as Coord);
+          self.check_attributes(x + col, y + row, cell.fg, cell.bg);
+        }
+      }
+      ffi::tb_blit(x, y, w, h, &cells[0] as *const Cell as *const ffi::RawCell);
+    }
+    if self.draw_hook.is_some() {
+      for row in 0..h {
+        for col in 0..w {
+          let cell = cells[(row * w + col) as usize];
+          self.report_drawn(x + col, y + row, cell.ch(), cell.fg, cell.bg);
+        }
+      }
+    }
+  }
+
+  /// Copies the part of `cells` inside `src` to the output buffer, placing its top left corner at
+  /// `(x, y)`. `cells` is treated as rows of `stride` cells each, and `src` is clipped to it. If
+  /// `transparent` is given, source cells equal to it are skipped, leaving the output buffer
+  /// unchanged underneath, so sprites can be drawn over a background.
+  pub fn blit_region<X, Y, S> (&mut self, x: X, y: Y, cells: &[Cell], stride: S, src: Rect,
+                               transparent: Option<Cell>)
+    where X: IntoCoord, Y: IntoCoord, S: IntoCoord
+  {
+    let (x, y, stride) = (x.into_coord(), y.into_coord(), stride.into_coord());
+    Backend::blit_region(self, x, y, cells, stride, src, transparent)
+  }
+
+  /// Stops a timer set with `set_timer`. Returns false if there was no timer with the given id.
+  pub fn cancel_timer (&mut self, id: TimerId) -> bool {
+    self.timers.cancel(id)
+  }
+
+  /// Returns the features of the terminal detected when termbox was opened.
+  pub fn capabilities (&self) -> &Capabilities {
+    &self.capabilities
+  }
+
+  /// Returns how many times taller than wide a cell is, using `pixel_size`. If the pixel size is
+  /// unknown, cells are assumed to be twice as tall as they are wide. Drawing code that maps
+  /// several dots onto each cell, such as braille or half-block plots, can use this to keep
+  /// shapes from being stretched vertically.
+  pub fn cell_aspect_ratio (&mut self) -> f32 {
+    match self.pixel_size() {
+      Some(pixel_size) => pixel_size.cell_aspect_ratio(),
+      None => DEFAULT_CELL_ASPECT_RATIO,
+    }
+  }
+
+  /// Returns a slice representing the output buffer.
+  pub fn cell_buffer<'a> (&'a self) -> &'a [Cell] {
+    unsafe {
+      let w = usize::try_from(ffi::tb_width()).unwrap();
+      let h = usize::try_from(ffi::tb_height()).unwrap();
+      let len = w.checked_mul(h).unwrap();
+      let ptr = ffi::tb_cell_buffer() as *const Cell;
+      return slice::from_raw_parts(ptr, len);
+    }
+  }
+
+  /// Returns a mutable slice representing the output buffer.
+  pub fn cell_buffer_mut<'a> (&'a mut self) -> &'a mut [Cell] {
+    unsafe {
+      let w = usize::try_from(ffi::tb_width()).unwrap();
+      let h = usize::try_from(ffi::tb_height()).unwrap();
+      let len = w.checked_mul(h).unwrap();
+      let ptr = ffi::tb_cell_buffer() as *mut Cell;
+      return slice::from_raw_parts_mut(ptr, len);
+    }
+  }
+
+  /// Returns the output buffer as a grid indexed by `(x, y)`.
+  pub fn cell_grid<'a> (&'a self) -> CellGrid<&'a [Cell]> {
+    let (w, h) = unsafe { (ffi::tb_width(), ffi::tb_height()) };
+    return CellGrid::new(self.cell_buffer(), w, h);
+  }
+
+  /// Returns the output buffer as a mutable grid indexed by `(x, y)`.
+  pub fn cell_grid_mut<'a> (&'a mut self) -> CellGrid<&'a mut [Cell]> {
+    let (w, h) = unsafe { (ffi::tb_width(), ffi::tb_height()) };
+    return CellGrid::new(self.cell_buffer_mut(), w, h);
+  }
+
+  /// Changes a single cell in the output buffer. Cells outside of the output buffer are ignored,
+  /// unless the `debug-bounds` feature is enabled in a debug build, in which case they panic. See
+  /// also `try_change_cell`.
+  pub fn change_cell<X, Y> (&mut self, x: X, y: Y, ch: char, fg: Attribute, bg: Attribute)
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    backend::debug_check_bounds(x, y, self.width(), self.height());
+    if self.is_strict() {
+      self.check_attributes(x, y, fg, bg);
+    }
+    unsafe {
+      ffi::tb_change_cell(x, y, ch as u32, fg, bg);
+    }
+    self.report_drawn(x, y, ch, fg, bg);
+  }
+
+  /// Changes a single cell in the output buffer, using the colors of `role` in the current theme.
+  pub fn change_cell_as<X, Y> (&mut self, x: X, y: Y, ch: char, role: Role)
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    let (fg, bg) = self.style(role).attributes();
+    self.change_cell(x, y, ch, fg, bg);
+  }
+
+  /// Changes the cell at the given position to display a single user-perceived character, which
+  /// may be two cells wide (for example, CJK characters or emoji). A wide character also claims the
+  /// cell to its right, which is set to a space in the same colors. Returns the number of cells
+  /// used. Variation selectors are dropped, but any other combining mark is rejected with
+  /// `ClusterError::CombiningCharacter`, so decomposed text should be normalized to NFC first.
+  pub fn change_cell_str<X, Y> (&mut self, x: X, y: Y, s: &str, fg: Attribute, bg: Attribute)
+                              -> result::Result<Coord, ClusterError>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::change_cell_str(self, x, y, s, fg, bg)
+  }
+
+  /// Clears the output buffer and sets all cell attributes to those specified with
   /// `set_clear_attributes`.
   pub fn clear (&mut self) {
     unsafe {
       ffi::tb_clear();
     }
+    self.links.clear();
+    self.images.clear();
+  }
+
+  /// Removes all regions registered with `register_region`.
+  pub fn clear_regions (&mut self) {
+    self.regions.clear();
+  }
+
+  /// Returns the longest time between clicks and the farthest distance in cells between them for
+  /// them to count as a double or triple click. See `set_click_thresholds`.
+  pub fn click_thresholds (&self) -> (Duration, Coord) {
+    self.clicks.thresholds()
+  }
+
+  /// Shuts down termbox and restores the terminal, like dropping the `Termbox`, but reports whether
+  /// the terminal could be restored. When this returns, another instance can be opened right
+  /// away. Failures can only be detected with the `pure-rust` feature.
+  pub fn close (self) -> result::Result<(), ShutdownError> {
+    let result = internal::close();
+    drop(self);
+    return result;
+  }
+
+  /// Draws the visible layers from `layers_mut` into the output buffer. Cells not covered by an
+  /// opaque layer cell are left as they are, so the screen can be cleared or drawn as usual first.
+  pub fn compose (&mut self) {
+    let layers = mem::replace(&mut self.layers, Layers::new());
+    layers.compose(self);
+    self.layers = layers;
+  }
+
+  /// Returns a copy of the cells inside `rect` in row-major order. Together with `paste_region`,
+  /// this lets a dialog save the cells it covers and put them back when it is dismissed. Parts of
+  /// the rectangle outside of the output buffer are filled with default-colored spaces.
+  pub fn copy_region (&self, rect: Rect) -> Vec<Cell> {
+    Backend::copy_region(self, rect)
+  }
+
+  /// Returns the cursor position set with `set_cursor`, or `None` if the cursor is hidden. This
+  /// is where the cursor will be once presented, even if it hasn't been moved there yet.
+  pub fn cursor (&self) -> Option<(Coord, Coord)> {
+    match self.cursor {
+      (x, y) if x < 0 || y < 0 => None,
+      position => Some(position),
+    }
+  }
+
+  /// Draws an image scaled to fill `rect`, using the protocol chosen with `set_image_protocol`.
+  /// With a graphics protocol, the cells of `rect` are cleared and the image is sent to the
+  /// terminal by `present`, where it stays until `clear` is called or the terminal is resized.
+  /// Images are only sent again if they are drawn in different places than on the last present.
+  /// Text drawn over an image without clearing may be hidden by it. Images that don't fit on the
+  /// screen are drawn with half blocks.
+  ///
+  /// ~~~no_run
+  /// use termbox::{Rect, Termbox};
+  /// use termbox::images::Image;
+  ///
+  /// let checkers: Vec<u8> = (0..64).flat_map(|i| {
+  ///   if (i % 8 + i / 8) % 2 == 0 { vec![255, 255, 255] } else { vec![0, 0, 0] }
+  /// }).collect();
+  /// let image = Image::from_rgb(8, 8, &checkers).unwrap();
+  ///
+  /// let mut tb = Termbox::open().unwrap();
+  /// tb.draw_image(Rect::new(2, 1, 16, 8), &image);
+  /// tb.present();
+  /// ~~~
+  pub fn draw_image (&mut self, rect: Rect, image: &Image) {
+    if rect.w <= 0 || rect.h <= 0 {
+      return;
+    }
+    let fits = rect.x >= 0 && rect.y >= 0 && rect.x + rect.w <= self.width()
+      && rect.y + rect.h <= self.height();
+    if self.image_protocol == ImageProtocol::HalfBlocks || !fits {
+      images::draw_half_blocks(self, rect, image);
+      return;
+    }
+
+    let (fg, bg) = self.clear_attributes;
+    for y in rect.y..rect.y + rect.h {
+      for x in rect.x..rect.x + rect.w {
+        self.change_cell(x, y, ' ', fg, bg);
+      }
+    }
+
+    let id = image.id();
+    if self.images.iter().any(|placement| placement.id == id && placement.rect == rect) {
+      return;
+    }
+    let data = match self.image_protocol {
+      ImageProtocol::Kitty => {
+        let queued = self.images.iter().any(|placement| placement.id == id);
+        if self.sent_images.contains(&id) || queued {
+          None
+        } else {
+          Some(images::kitty_transmit(image))
+        }
+      },
+      ImageProtocol::Iterm2 => {
+        if self.placed_images.contains(&(id, rect)) {
+          None
+        } else {
+          Some(images::iterm2_inline(image, rect))
+        }
+      },
+      ImageProtocol::HalfBlocks => None,
+    };
+    self.images.push(ImagePlacement {
+      id: id,
+      rect: rect,
+      data: data,
+    });
+  }
+
+  /// Returns how long to wait for the rest of an escape sequence before reporting a lone Esc. See
+  /// `set_escape_delay`. Only available with the `pure-rust` feature.
+  #[cfg(feature = "pure-rust")]
+  pub fn escape_delay (&self) -> Duration {
+    unsafe {
+      return Duration::from_millis(ffi::tb_select_escape_delay(-1) as u64);
+    }
+  }
+
+  /// Returns a handle that other threads can use to send values to the event loop. See
+  /// `EventInjector`.
+  pub fn event_injector (&self) -> EventInjector {
+    self.injected.injector()
+  }
+
+  /// Fills column `x` of the output buffer with `ch`, for vertical separators and sidebars. Columns
+  /// outside of the buffer are ignored.
+  pub fn fill_col<X: IntoCoord> (&mut self, x: X, ch: char, fg: Attribute, bg: Attribute) {
+    Backend::fill_col(self, x.into_coord(), ch, fg, bg)
+  }
+
+  /// Fills row `y` of the output buffer with `ch`, which should be one cell wide. Rows outside of
+  /// the buffer are ignored. The row is written to the buffer as a whole, which is much faster than
+  /// changing each cell when redrawing the whole screen.
+  pub fn fill_row<Y: IntoCoord> (&mut self, y: Y, ch: char, fg: Attribute, bg: Attribute) {
+    let y = y.into_coord();
+    let (width, height) = (self.width(), self.height());
+    if y < 0 || y >= height {
+      return;
+    }
+    if self.is_strict() {
+      self.check_attributes(0, y, fg, bg);
+    }
+    let start = (y * width) as usize;
+    self.cell_buffer_mut()[start..start + width as usize].fill(Cell::new(ch, fg, bg));
+    self.report_row(y, width);
+  }
+
+  /// Applies cursor changes and writes any output that termbox has collected but not yet sent to
+  /// the terminal. Presenting already does this, so it is only needed when the cursor is moved
+  /// without presenting. Only available with the `pure-rust` feature.
+  #[cfg(feature = "pure-rust")]
+  pub fn flush (&mut self) -> io::Result<()> {
+    self.flush_cursor();
+    unsafe {
+      return ffi::tb_flush();
+    }
+  }
+
+  /// Immediately applies cursor changes made with `set_cursor` or `hide_cursor`, instead of
+  /// waiting for the next call to `present`. They are still only visible after the terminal's
+  /// output is flushed, which termbox does when presenting.
+  pub fn flush_cursor (&mut self) {
+    if self.cursor_dirty {
+      unsafe {
+        ffi::tb_set_cursor(self.cursor.0, self.cursor.1);
+      }
+      self.cursor_dirty = false;
+    }
+  }
+
+  /// Draws a frame with `draw` and presents it once `draw` returns, so that a frame can't be
+  /// presented half-drawn or left unpresented. Returns the result of `draw`.
+  ///
+  /// ~~~no_run
+  /// use termbox::{Backend, Termbox, DEFAULT};
+  ///
+  /// let mut tb = Termbox::open().unwrap();
+  /// tb.frame(|f| {
+  ///   f.clear();
+  ///   f.put_str(0, 0, "Hello, world!", DEFAULT, DEFAULT);
+  /// });
+  /// ~~~
+  pub fn frame<F, T> (&mut self, draw: F) -> T
+    where F: FnOnce(&mut Frame) -> T
+  {
+    let result = draw(&mut Frame::new(self));
+    self.present();
+    return result;
+  }
+
+  /// Like `frame`, but only presents if the frame differs from the last one presented, or the
+  /// cursor was moved. This suits loops that redraw everything on each event whether or not
+  /// anything changed.
+  pub fn frame_if_changed<F, T> (&mut self, draw: F) -> T
+    where F: FnOnce(&mut Frame) -> T
+  {
+    let result = draw(&mut Frame::new(self));
+    let changed = match self.presented {
+      Some(ref presented) => self.cell_buffer() != &presented[..],
+      None => true,
+    };
+    if !self.compare_frames {
+      self.compare_frames = true;
+      self.presented = Some(Vec::new());
+    }
+    if changed || self.cursor_dirty || !self.links.is_empty() {
+      self.present();
+    }
+    return result;
+  }
+
+  /// Returns the cell at the given position in the output buffer, or `None` if it is out of bounds.
+  /// Widgets can use this to inspect what they are drawn over, for example to darken the cells
+  /// under a shadow.
+  pub fn get_cell<X, Y> (&self, x: X, y: Y) -> Option<Cell>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::get_cell(self, x, y)
+  }
+
+  /// Returns the height of the output buffer in character cells.
+  pub fn height (&self) -> Coord {
+    unsafe {
+      ffi::tb_height()
+    }
+  }
+
+  /// Determines whether `put_link` emits hyperlinks. See `set_hyperlinks_enabled`.
+  pub fn hyperlinks_enabled (&self) -> bool {
+    self.hyperlinks
+  }
+
+  /// Sets the cursor to an invalid position, making it invisible to the user. Like `set_cursor`,
+  /// this takes effect on the next call to `present`.
+  pub fn hide_cursor (&mut self) {
+    self.set_cursor(ffi::TB_HIDE_CURSOR, ffi::TB_HIDE_CURSOR);
+  }
+
+  /// Returns the id of the region registered with `register_region` that contains the cell at `x`,
+  /// `y`. Where regions overlap, the one registered last wins.
+  pub fn hit_test<X: IntoCoord, Y: IntoCoord> (&self, x: X, y: Y) -> Option<RegionId> {
+    self.regions.hit_test(x.into_coord(), y.into_coord())
+  }
+
+  /// Returns how `draw_image` shows images. See `set_image_protocol`.
+  pub fn image_protocol (&self) -> ImageProtocol {
+    self.image_protocol
+  }
+
+  /// Returns the input mode. See `set_input_mode`.
+  pub fn input_mode (&self) -> InputMode {
+    unsafe {
+      let raw_mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
+      return InputMode::from_raw(raw_mode).unwrap();
+    }
+  }
+
+  /// Installs a panic hook that shuts down termbox before the panic message is printed, so that
+  /// the message is readable and the terminal is usable again. The previously installed hook is
+  /// still run afterwards. Installing the hook more than once has no further effect.
+  ///
+  /// Termbox is only shut down if the panic happens on the thread that opened the `Termbox`.
+  /// Panics on other threads are reported by the previous hook with the terminal left as it is.
+  /// Panics in tasks started with `spawn_task` skip the hook entirely, since they are caught and
+  /// reported with `Event::TaskDone`.
+  pub fn install_panic_hook () {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+      let prev_hook = panic::take_hook();
+      panic::set_hook(Box::new(move |info| {
+        if internal::is_task_thread() {
+          return;
+        }
+        if internal::is_owned_by_current_thread() {
+          internal::shutdown();
+        }
+        prev_hook(info);
+      }));
+    });
+  }
+
+  /// Determines whether bracketed paste is enabled. See `set_bracketed_paste`.
+  #[cfg(feature = "pure-rust")]
+  pub fn is_bracketed_paste_enabled (&self) -> bool {
+    unsafe {
+      let mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
+      return (mode & ffi::TB_INPUT_PASTE) != 0;
+    }
+  }
+
+  /// Guesses whether the terminal has a dark background. The terminal is asked with
+  /// `request_background` first, and if it doesn't answer within `timeout` milliseconds, the
+  /// `COLORFGBG` environment variable is consulted. Returns `None` if neither gives an answer.
+  pub fn is_dark_background (&mut self, timeout: Time) -> Option<bool> {
+    match self.request_background(timeout) {
+      Some(background) => Some(background.is_dark()),
+      None => detect::dark_background(),
+    }
+  }
+
+  /// Determines whether the kitty keyboard protocol is enabled. See `set_kitty_keyboard`.
+  #[cfg(feature = "pure-rust")]
+  pub fn is_kitty_keyboard_enabled (&self) -> bool {
+    unsafe {
+      let mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
+      return (mode & ffi::TB_INPUT_KITTY) != 0;
+    }
+  }
+
+  /// Determines whether mouse events are enabled. See `set_mouse_enabled`.
+  pub fn is_mouse_enabled (&self) -> bool {
+    unsafe {
+      let mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
+      return (mode & ffi::TB_INPUT_MOUSE) != 0;
+    }
+  }
+
+  /// Determines whether an instance of `Termbox` exists in this process, in which case `open`
+  /// fails with `Error::Locked`.
+  pub fn is_open () -> bool {
+    Lock::is_held()
+  }
+
+  /// Determines whether a recording started with `replay` still has events to play.
+  pub fn is_replaying (&self) -> bool {
+    self.player.is_some()
+  }
+
+  /// Returns measurements of the last time the output buffer was written to the terminal, or
+  /// `None` if nothing has been presented since they were enabled with `set_present_stats_enabled`.
+  /// Useful for finding out why an interface is slow or flickers, especially over a slow
+  /// connection.
+  pub fn last_present_stats (&self) -> Option<PresentStats> {
+    self.present_stats
+  }
+
+  /// Returns the layer stack drawn by `compose`.
+  pub fn layers (&self) -> &Layers {
+    &self.layers
+  }
+
+  /// Returns the layer stack drawn by `compose` for modification.
+  pub fn layers_mut (&mut self) -> &mut Layers {
+    &mut self.layers
+  }
+
+  /// Shows a dialog in `rect` and runs an event loop until it is closed. The cells covered by the
+  /// dialog are saved, a border is drawn around its edge in the `Border` style of the theme, and
+  /// the inside is cleared with the `Normal` style. `handler` is called with the rectangle inside
+  /// the border, first with `None` to draw the contents, then with each event. The screen is
+  /// presented after each call. When `handler` returns `Some`, the covered cells are restored,
+  /// presented, and the value is returned. If the terminal is resized, the dialog is drawn again
+  /// over whatever the resize handler drew before `handler` receives the resize event.
+  ///
+  /// ~~~no_run
+  /// use termbox::{Event, Rect, Termbox};
+  /// use termbox::keys::KEY_ENTER;
+  ///
+  /// let mut tb = Termbox::open().unwrap();
+  /// let confirmed = tb.modal(Rect::new(10, 5, 30, 5), |tb, inner, event| {
+  ///   match event {
+  ///     None => { tb.put_str(inner.x, inner.y, "Quit? (y/n)", 0, 0); None },
+  ///     Some(&Event::Key(key)) if key.ch == Some('y') || key.key == KEY_ENTER => Some(true),
+  ///     Some(&Event::Key(key)) if key.ch == Some('n') => Some(false),
+  ///     _ => None,
+  ///   }
+  /// });
+  /// ~~~
+  pub fn modal<F, T> (&mut self, rect: Rect, mut handler: F) -> T
+    where F: FnMut(&mut Termbox, Rect, Option<&Event>) -> Option<T>
+  {
+    let inner = Rect::new(rect.x + 1, rect.y + 1, (rect.w - 2).max(0), (rect.h - 2).max(0));
+    let mut saved = self.copy_region(rect);
+    self.draw_modal_frame(rect);
+
+    let mut result = handler(self, inner, None);
+    while result.is_none() {
+      self.present();
+      let event = self.poll_event();
+      if let Event::Resize(_) = event {
+        saved = self.copy_region(rect);
+        self.draw_modal_frame(rect);
+      }
+      result = handler(self, inner, Some(&event));
+    }
+
+    self.paste_region(rect, &saved);
+    self.present();
+    return result.unwrap();
+  }
+
+  /// Sets a function to redraw the screen after the terminal is resized. When `peek_event` or
+  /// `poll_event` receives a resize event, the output buffer is cleared with the clear attributes,
+  /// the handler is called with the new width and height, and the result is presented. The resize
+  /// event is still returned afterwards. Replaces any previous handler. See also
+  /// `set_resize_policy`.
+  pub fn on_resize<F> (&mut self, handler: F)
+    where F: FnMut(&mut Termbox, Coord, Coord) + 'static
+  {
+    self.resize_handler = Some(Box::new(handler));
+  }
+
+  /// Locks the terminal to an instance of `Termbox`. Only one instance may exist in a process.
+  pub fn open () -> Result<Termbox> {
+    Termbox::open_with(|| unsafe { ffi::tb_init() }, Tty::open)
+  }
+
+  /// Like `open`, but uses an already open terminal device instead of `/dev/tty`. The file must be
+  /// open for both reading and writing. Termbox closes it when the `Termbox` is dropped.
+  pub fn open_file (file: File) -> Result<Termbox> {
+    let tty = file.try_clone().and_then(Tty::from_file);
+    // Termbox takes ownership of the descriptor it is given, so it gets a duplicate each time, in
+    // case it is reinitialized by `suspend`.
+    Termbox::open_with(move || unsafe {
+      match file.try_clone() {
+        Ok(file) => ffi::tb_init_fd(file.into_raw_fd()),
+        Err(_) => ffi::TB_EFAILED_TO_OPEN_TTY,
+      }
+    }, move || tty)
+  }
+
+  /// Like `open_file`, but reads input from one file and writes output to another, for example
+  /// when driving a pseudo-terminal. Only available with the `pure-rust` feature, since the
+  /// termbox C library uses a single file descriptor for both.
+  #[cfg(feature = "pure-rust")]
+  pub fn open_fds (input: File, output: File) -> Result<Termbox> {
+    let tty = input.try_clone()
+      .and_then(|tty_input| output.try_clone().map(|tty_output| (tty_input, tty_output)))
+      .map(|(tty_input, tty_output)| Tty::from_files(tty_input, tty_output));
+    Termbox::open_with(move || unsafe {
+      match (input.try_clone(), output.try_clone()) {
+        (Ok(input), Ok(output)) => ffi::tb_init_fds(input.into_raw_fd(), output.into_raw_fd()),
+        _ => ffi::TB_EFAILED_TO_OPEN_TTY,
+      }
+    }, move || tty)
+  }
+
+  /// Like `open`, but opens the terminal device at `path` instead of `/dev/tty`. This allows a
+  /// program whose standard streams are redirected to still display a user interface.
+  pub fn open_on (path: &Path) -> Result<Termbox> {
+    let name = match CString::new(path.as_os_str().as_bytes()) {
+      Ok(name) => name,
+      Err(_) => { return Err(Error::FailedToOpenTty); },
+    };
+
+    Termbox::open_with(move || unsafe { ffi::tb_init_file(name.as_ptr()) }, || Tty::open_path(path))
+  }
+
+  /// Like `open`, but if another instance of `Termbox` exists, waits up to `timeout` for it to be
+  /// dropped instead of failing immediately. Returns `Error::Locked` if it still exists when the
+  /// timeout expires. This lets a supervisor restart a user interface component without
+  /// coordinating with the thread that owned the previous instance.
+  pub fn open_timeout (timeout: Duration) -> Result<Termbox> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+      match Termbox::open() {
+        Err(Error::Locked) => {
+          if !Lock::wait(deadline) {
+            return Err(Error::Locked);
+          }
+        },
+        result => { return result; },
+      }
+    }
+  }
+
+  /// Returns the number of bytes of output collected while presenting before they are written to
+  /// the terminal, or zero if each frame is written at once. See `set_output_buffer_capacity`.
+  /// Only available with the `pure-rust` feature.
+  #[cfg(feature = "pure-rust")]
+  pub fn output_buffer_capacity (&self) -> usize {
+    unsafe {
+      return ffi::tb_select_output_buffer_capacity(-1) as usize;
+    }
+  }
+
+  /// Returns the current output mode. See `set_output_mode`.
+  pub fn output_mode (&self) -> OutputMode {
+    unsafe {
+      let raw_mode = ffi::tb_select_output_mode(ffi::TB_OUTPUT_CURRENT);
+      return OutputMode::from_raw(raw_mode).unwrap();
+    }
+  }
+
+  /// Restores cells saved with `copy_region` to the rectangle they were copied from. Cells outside
+  /// of the output buffer are ignored.
+  pub fn paste_region (&mut self, rect: Rect, cells: &[Cell]) {
+    Backend::paste_region(self, rect, cells)
+  }
+
+  /// Waits up to `timeout` milliseconds for an event. If an event is received, that event is
+  /// returned. Otherwise, `None` is returned. A `timeout` of zero can be specified to poll for
+  /// events that have already been received without waiting. See also `peek_event_for`.
+  pub fn peek_event (&mut self, timeout: Time) -> Option<Event> {
+    self.peek_event_for(Duration::from_millis(timeout.max(0) as u64))
+  }
+
+  /// Like `peek_event`, but takes the timeout as a `Duration`.
+  pub fn peek_event_for (&mut self, timeout: Duration) -> Option<Event> {
+    self.poll_event_deadline(Instant::now() + timeout)
+  }
+
+  /// Returns the size of the terminal's text area and of one cell in pixels, for placing images
+  /// and correcting for the aspect ratio of cells. The size reported by the kernel is used if
+  /// available. Otherwise the terminal is asked with an XTWINOPS query, waiting briefly for the
+  /// reply. Returns `None` if the size can't be determined. The result is remembered until the
+  /// terminal is resized.
+  pub fn pixel_size (&mut self) -> Option<PixelSize> {
+    if let Some(pixel_size) = self.pixel_size {
+      return pixel_size;
+    }
+
+    let pixel_size = self.query_pixel_size();
+    self.pixel_size = Some(pixel_size);
+    return pixel_size;
+  }
+
+  /// Waits for an input event and returns it.
+  pub fn poll_event (&mut self) -> Event {
+    if let Some(event) = self.held_event.take() {
+      return event;
+    }
+    loop {
+      if let Some(event) = self.pending_event() {
+        return self.recorded(event);
+      }
+
+      let limit = self.wait_limit();
+      let event = match self.wait_raw_event(limit) {
+        Some(event) => event,
+        None => { continue; },
+      };
+
+      if let Some(event) = self.process_event(event) {
+        return self.recorded(event);
+      }
+    }
+  }
+
+  /// Waits for an input event like `poll_event`, then merges it with any events of the same kind
+  /// that are already waiting, returning only the latest. A burst of resize events, as sent while
+  /// the user drags the corner of the window, is returned as its last resize, and mouse events
+  /// reporting a drag with the same button are returned as the last position. This prevents
+  /// redrawing for every step of a burst that would be out of date before it is presented. Every
+  /// merged event is still recorded, so the output buffer always has the size of the last resize,
+  /// but the handler set with `on_resize` only runs once for the whole burst.
+  pub fn poll_event_coalesced (&mut self) -> Event {
+    self.defer_resize_handler = true;
+    let mut event = self.poll_event();
+    while let Some(next) = self.peek_event(0) {
+      if !supersedes(&next, &event) {
+        self.held_event = Some(next);
+        break;
+      }
+      event = next;
+    }
+
+    self.defer_resize_handler = false;
+    if let Some(resize) = self.deferred_resize.take() {
+      self.handle_resize(&resize);
+    }
+    return event;
+  }
+
+  /// Waits for an event until `deadline`. Returns `None` if the deadline passes first. The wait is
+  /// resumed if it is cut short, for example by a signal, so `None` is never returned early. This
+  /// makes it easy to wait for events for a fixed time in total, regardless of how many arrive.
+  pub fn poll_event_deadline (&mut self, deadline: Instant) -> Option<Event> {
+    if let Some(event) = self.held_event.take() {
+      return Some(event);
+    }
+    loop {
+      if let Some(event) = self.pending_event() {
+        return Some(self.recorded(event));
+      }
+
+      let remaining = millis_until(deadline);
+      let wait = match self.wait_limit() {
+        Some(limit) => limit.min(remaining),
+        None => remaining,
+      };
+
+      match self.wait_raw_event(Some(wait)) {
+        Some(event) => {
+          if let Some(event) = self.process_event(event) {
+            return Some(self.recorded(event));
+          }
+        },
+        None => {
+          if Instant::now() >= deadline {
+            return None;
+          }
+        },
+      }
+    }
+  }
+
+  /// Writes any changes to the output buffer into the terminal. This must be called in order for
+  /// the user to see any changes. If a frame rate limit was set with `present_at_most` and the
+  /// previous frame was presented too recently, this is deferred like `request_present`.
+  pub fn present (&mut self) {
+    if let (Some(interval), Some(last)) = (self.frame_interval, self.last_present) {
+      if last.elapsed() < interval {
+        self.present_pending = true;
+        return;
+      }
+    }
+    self.present_now();
+  }
+
+  /// Limits how often `present` actually writes to the terminal. Presents that happen within
+  /// `1 / fps` seconds of the previous one are merged and carried out later by `peek_event` or
+  /// `poll_event`. A rate of zero removes the limit.
+  pub fn present_at_most (&mut self, fps: u32) {
+    if fps == 0 {
+      self.frame_interval = None;
+    } else {
+      self.frame_interval = Some(Duration::from_secs(1) / fps);
+    }
+  }
+
+  /// Changes a single character cell. Cells outside of the output buffer are handled as by
+  /// `change_cell`.
+  pub fn put_cell<X, Y> (&mut self, x: X, y: Y, cell: Cell)
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    backend::debug_check_bounds(x, y, self.width(), self.height());
+    if self.is_strict() {
+      self.check_attributes(x, y, cell.fg, cell.bg);
+    }
+    unsafe {
+      ffi::tb_put_cell(x, y, &cell.to_raw());
+    }
+    self.report_drawn(x, y, cell.ch(), cell.fg, cell.bg);
+  }
+
+  /// Writes `text` like `put_str` and attaches `url` to it using an OSC 8 hyperlink, so the user
+  /// can click it in terminals that support this. The link is sent to the terminal by the next call
+  /// to `present`, and covers whatever the written cells hold by then. Characters in `url` outside
+  /// of printable ASCII are percent-encoded. If hyperlinks are disabled, only the text is written.
+  pub fn put_link<X, Y> (&mut self, x: X, y: Y, text: &str, url: &str, fg: Attribute,
+                         bg: Attribute)
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    let written = self.put_str(x, y, text, fg, bg);
+
+    if self.hyperlinks && written > 0 {
+      let start = x.max(0);
+      self.links.push(Link::new(start, y, start + written, url));
+    }
+  }
+
+  /// Copies `cells` to row `y` of the output buffer, starting at the left edge. Cells past the end
+  /// of the row are ignored, as are rows outside of the buffer. Like `fill_row`, the row is written
+  /// to the buffer as a whole.
+  pub fn put_row<Y: IntoCoord> (&mut self, y: Y, cells: &[Cell]) {
+    let y = y.into_coord();
+    let (width, height) = (self.width(), self.height());
+    if y < 0 || y >= height {
+      return;
+    }
+    let len = cells.len().min(width as usize);
+    if self.is_strict() {
+      for (x, cell) in cells[..len].iter().enumerate() {
+        self.check_attributes(x as Coord, y, cell.fg, cell.bg);
+      }
+    }
+    let start = (y * width) as usize;
+    self.cell_buffer_mut()[start..start + len].copy_from_slice(&cells[..len]);
+    self.report_row(y, len as Coord);
+  }
+
+  /// Writes a horizontal sequence of character cells without wrapping. This is just a quick and
+  /// dirty way to write strings without providing many options; see `put_str_with` for more
+  /// control. Tabs are expanded to the tab stops set with `set_tab_width`. Returns the number of
+  /// cells that were inside the output buffer, so a result shorter than the string means it was
+  /// cut off.
+  pub fn put_str<X, Y> (&mut self, x: X, y: Y, msg: &str, fg: Attribute, bg: Attribute) -> Coord
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::put_str(self, x, y, msg, fg, bg)
+  }
+
+  /// Writes a string like `put_str`, using the colors of `role` in the current theme.
+  pub fn put_str_as<X, Y> (&mut self, x: X, y: Y, msg: &str, role: Role) -> Coord
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    let (fg, bg) = self.style(role).attributes();
+    return self.put_str(x, y, msg, fg, bg);
+  }
+
+  /// Writes a string, clipping it at the right edge or wrapping it onto following lines as
+  /// controlled by `options`, and optionally expanding tabs. Double-width characters take up two
+  /// cells, and are never split across lines. Returns the number of cells that were written.
+  pub fn put_str_with<X, Y> (&mut self, x: X, y: Y, msg: &str, fg: Attribute, bg: Attribute,
+                             options: PutOptions)
+    -> Coord
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::put_str_with(self, x, y, msg, fg, bg, options)
+  }
+
+  /// Writes a string downwards from `x`, `y`, one character per row, without wrapping. Returns the
+  /// number of cells that were inside the output buffer.
+  pub fn put_vstr<X, Y> (&mut self, x: X, y: Y, msg: &str, fg: Attribute, bg: Attribute) -> Coord
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::put_vstr(self, x, y, msg, fg, bg)
+  }
+
+  /// Returns whether the bytes read from the terminal are reported. See `set_raw_input`. Only
+  /// available with the `pure-rust` feature.
+  #[cfg(feature = "pure-rust")]
+  pub fn raw_input (&self) -> RawInput {
+    unsafe {
+      return RawInput::from_raw(ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT));
+    }
+  }
+
+  /// Makes the input sequence `seq` decode as `key`, for terminals that send special keys in ways
+  /// termbox doesn't recognize, or to give keys with modifiers their own codes. Registered
+  /// sequences are matched before the built-in ones, the longest first. Registering a sequence
+  /// again maps it to the new key. Keys termbox has no constant for can use codes from
+  /// `KEY_USER_FIRST` to `KEY_USER_LAST`. Only available with the `pure-rust` feature.
+  ///
+  /// ~~~no_run
+  /// use termbox::{Event, Termbox, KEY_USER_FIRST};
+  ///
+  /// const KEY_CTRL_ARROW_LEFT: termbox::Key = KEY_USER_FIRST;
+  ///
+  /// let mut tb = Termbox::open().unwrap();
+  /// tb.register_key_sequence(b"\x1b[1;5D", KEY_CTRL_ARROW_LEFT);
+  ///
+  /// if let Event::Key(key) = tb.poll_event() {
+  ///   if key.key == KEY_CTRL_ARROW_LEFT {
+  ///     // Move back a word.
+  ///   }
+  /// }
+  /// ~~~
+  #[cfg(feature = "pure-rust")]
+  pub fn register_key_sequence (&mut self, seq: &[u8], key: Key) {
+    unsafe {
+      ffi::tb_register_key_sequence(seq, key);
+    }
+  }
+
+  /// Registers a rectangle of the screen under an id, so that mouse events can be mapped to the
+  /// widget drawn there with `hit_test`. Registering an id again moves its region and puts it on
+  /// top. All regions are removed when the terminal is resized, as the layout they came from no
+  /// longer applies, so they are usually registered again while drawing each frame.
+  ///
+  /// ~~~no_run
+  /// use termbox::{Event, MouseButton, Rect, Termbox};
+  ///
+  /// const OK_BUTTON: u64 = 1;
+  ///
+  /// let mut tb = Termbox::open().unwrap();
+  /// tb.set_mouse_enabled(true);
+  /// tb.put_str(0, 0, "[ OK ]", termbox::DEFAULT, termbox::DEFAULT);
+  /// tb.register_region(OK_BUTTON, Rect::new(0, 0, 6, 1));
+  /// tb.present();
+  ///
+  /// if let Event::Mouse(mouse) = tb.poll_event() {
+  ///   if mouse.button == MouseButton::Left && tb.hit_test(mouse.x, mouse.y) == Some(OK_BUTTON) {
+  ///     // ...
+  ///   }
+  /// }
+  /// ~~~
+  pub fn register_region (&mut self, id: RegionId, rect: Rect) {
+    self.regions.register(id, rect);
+  }
+
+  /// Removes the hook set with `set_draw_hook`.
+  pub fn remove_draw_hook (&mut self) {
+    self.draw_hook = None;
+  }
+
+  /// Removes a screen and returns it. The active screen can't be removed, so `None` is returned
+  /// for it as well as for unknown screens.
+  pub fn remove_screen (&mut self, id: ScreenId) -> Option<VirtualScreen> {
+    match self.screens.iter().position(|&(i, _)| i == id) {
+      Some(index) => Some(self.screens.remove(index).1),
+      None => None,
+    }
+  }
+
+  /// Schedules a present without performing it now. Any number of requests made before the next
+  /// frame are merged into one, which is carried out the next time `peek_event` or `poll_event` is
+  /// called (respecting the limit set with `present_at_most`). This allows independent parts of a
+  /// program to ask for a redraw without each presenting on its own.
+  pub fn request_present (&mut self) {
+    self.present_pending = true;
+  }
+
+  /// Plays back a recording made with `start_recording`. Its events are reported by `peek_event`
+  /// and `poll_event` at the same times after this call as they were after recording started,
+  /// interleaved with live input. Replaces any playback in progress. Returns an error if the
+  /// recording can't be read or contains an invalid line, in which case nothing is played.
+  pub fn replay<R: io::Read> (&mut self, reader: R) -> io::Result<()> {
+    self.player = Some(Player::open(reader)?);
+    return Ok(());
+  }
+
+  /// Asks the terminal for its background color with OSC 11, waiting up to `timeout` milliseconds
+  /// for the reply. Returns `None` if the terminal doesn't answer. Applications can use this to
+  /// choose between light and dark colors; see `Background::theme`.
+  pub fn request_background (&mut self, timeout: Time) -> Option<Background> {
+    if self.tty.write(b"\x1b]11;?\x07").is_err() {
+      return None;
+    }
+
+    // The reply has the form `ESC ] 11 ; rgb:RRRR/GGGG/BBBB ST`.
+    let reply = match self.read_reply(timeout, |reply| reply.starts_with(b"\x1b]11;")) {
+      Some(reply) => reply,
+      None => { return None; },
+    };
+    let reply = String::from_utf8_lossy(&reply);
+    let body = match reply.find("rgb:") {
+      Some(start) => reply[start..].trim_end_matches('\x07').trim_end_matches("\x1b\\"),
+      None => { return None; },
+    };
+    return Background::parse(body);
+  }
+
+  /// Asks the terminal for the contents of the system clipboard using an OSC 52 escape sequence,
+  /// waiting up to `timeout` milliseconds for a reply. Returns `None` if the terminal does not
+  /// support clipboard queries (many only allow setting the clipboard) or the reply is not valid
+  /// UTF-8. Input received while waiting for the reply is still reported as events.
+  pub fn request_clipboard (&mut self, timeout: Time) -> Option<String> {
+    if self.tty.write(b"\x1b]52;c;?\x07").is_err() {
+      return None;
+    }
+
+    let reply = match self.read_reply(timeout, |reply| reply.starts_with(b"\x1b]52;")) {
+      Some(reply) => reply,
+      None => { return None; },
+    };
+
+    // The reply has the form `ESC ] 52 ; c ; <base64> ST`.
+    let body = match reply.iter().rposition(|&b| b == b';') {
+      Some(pos) => &reply[pos + 1..],
+      None => { return None; },
+    };
+    let body = match body.iter().position(|&b| b == 0x07 || b == 0x1b) {
+      Some(end) => &body[..end],
+      None => body,
+    };
+
+    match base64::decode(body) {
+      Some(bytes) => String::from_utf8(bytes).ok(),
+      None => None,
+    }
+  }
+
+  /// Returns what happens to the screen contents after the terminal is resized. See
+  /// `set_resize_policy`.
+  pub fn resize_policy (&self) -> ResizePolicy {
+    self.resize_policy
+  }
+
+  /// Returns a screen that isn't being shown. The active screen is the output buffer itself, so
+  /// `None` is returned for it as well as for unknown screens.
+  pub fn screen (&self, id: ScreenId) -> Option<&VirtualScreen> {
+    self.screens.iter().find(|&&(i, _)| i == id).map(|&(_, ref screen)| screen)
+  }
+
+  /// Returns a screen that isn't being shown for drawing. See `screen`.
+  pub fn screen_mut (&mut self, id: ScreenId) -> Option<&mut VirtualScreen> {
+    self.screens.iter_mut().find(|&&mut (i, _)| i == id).map(|&mut (_, ref mut screen)| screen)
+  }
+
+  /// Enables or disables bracketed paste. While enabled, text pasted into the terminal is reported
+  /// between `Event::PasteStart` and `Event::PasteEnd`, so that it can be told apart from typed
+  /// keys. Disabled by default. Only available with the `pure-rust` feature.
+  #[cfg(feature = "pure-rust")]
+  pub fn set_bracketed_paste (&mut self, enabled: bool) {
+    unsafe {
+      let prev_mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
+      let new_mode;
+
+      if enabled {
+        new_mode = prev_mode | ffi::TB_INPUT_PASTE;
+      } else {
+        new_mode = prev_mode & !ffi::TB_INPUT_PASTE;
+      }
+
+      if new_mode != prev_mode {
+        ffi::tb_select_input_mode(new_mode);
+      }
+    }
+  }
+
+  /// Sets what attributes should be used when clearing the output buffer with `clear`.
+  pub fn set_clear_attributes (&mut self, fg: Attribute, bg: Attribute) {
+    unsafe {
+      ffi::tb_set_clear_attributes(fg, bg);
+    }
+    self.clear_attributes = (fg, bg);
+  }
+
+  /// Sets how soon and how close to the previous click a press of the same mouse button has to be
+  /// to count towards `MouseEvent::clicks`. The distance is measured in cells, horizontally and
+  /// vertically. Defaults to 500 milliseconds and 1 cell.
+  pub fn set_click_thresholds (&mut self, interval: Duration, distance: Coord) {
+    self.clicks.set_thresholds(interval, distance);
+  }
+
+  /// Copies `text` to the system clipboard using an OSC 52 escape sequence. This is written to the
+  /// terminal immediately rather than waiting for `present`. Terminals that do not support OSC 52
+  /// silently ignore it.
+  pub fn set_clipboard (&mut self, text: &str) -> io::Result<()> {
+    let mut seq = String::from("\x1b]52;c;");
+    seq.push_str(&base64::encode(text.as_bytes()));
+    seq.push('\x07');
+    return self.tty.write(seq.as_bytes());
+  }
+
+  /// Sets the position of the cursor. If invalid coordinates are provided, the cursor is hidden.
+  /// The cursor is moved on the next call to `present`, so that it doesn't jump around while a
+  /// frame is being drawn. Use `flush_cursor` to move it sooner. Returns the previous position,
+  /// or `None` if the cursor was hidden, so that a widget taking the focus for a while can put the
+  /// cursor back afterwards.
+  pub fn set_cursor<X, Y> (&mut self, x: X, y: Y) -> Option<(Coord, Coord)>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    let previous = self.cursor();
+    if self.cursor != (x, y) {
+      self.cursor = (x, y);
+      self.cursor_dirty = true;
+    }
+    return previous;
+  }
+
+  /// Sets a hook that is told about text drawn to the output buffer with `change_cell`,
+  /// `put_cell`, `blit` and everything built on them, for an accessibility layer that forwards
+  /// content to a screen reader, or for a log. Text outside of the output buffer and clearing are
+  /// not reported. Replaces any previous hook.
+  ///
+  /// ~~~no_run
+  /// use termbox::{Termbox, DEFAULT};
+  /// use termbox::hooks::FileDrawLog;
+  ///
+  /// let mut tb = Termbox::open().unwrap();
+  /// tb.set_draw_hook(FileDrawLog::create("screen.log").unwrap());
+  /// tb.put_str(0, 0, "Hello", DEFAULT, DEFAULT);
+  /// tb.present();
+  /// ~~~
+  pub fn set_draw_hook<H> (&mut self, hook: H)
+    where H: DrawHook + 'static
+  {
+    self.draw_hook = Some(DrawTracker::new(Box::new(hook)));
+  }
+
+  /// Sets how long to wait for the rest of an escape sequence after Esc is received before
+  /// reporting it as a lone Esc key. Terminals send special keys as sequences starting with Esc,
+  /// so a shorter delay makes Esc more responsive, while a longer one helps over slow connections
+  /// where sequences may arrive in pieces. Defaults to 25 milliseconds. Only available with the
+  /// `pure-rust` feature.
+  #[cfg(feature = "pure-rust")]
+  pub fn set_escape_delay (&mut self, delay: Duration) {
+    let ms = delay.as_secs().saturating_mul(1000).saturating_add(delay.subsec_millis() as u64);
+    unsafe {
+      ffi::tb_select_escape_delay(ms.min(c_int::max_value() as u64) as c_int);
+    }
+  }
+
+  /// Enables or disables hyperlinks written by `put_link`. By default, hyperlinks are enabled if
+  /// the terminal appears to support them.
+  pub fn set_hyperlinks_enabled (&mut self, enabled: bool) {
+    self.hyperlinks = enabled;
+  }
+
+  /// Sets how `draw_image` shows images. The default is the protocol in `Capabilities::images`.
+  /// Images already drawn are removed on the next present, so they have to be drawn again.
+  pub fn set_image_protocol (&mut self, protocol: ImageProtocol) {
+    if protocol != self.image_protocol {
+      self.image_protocol = protocol;
+      self.images.clear();
+    }
+  }
+
+  /// Sets the method termbox should use to handle ESC sequences in the input buffer.
+  pub fn set_input_mode (&mut self, mode: InputMode) {
+    unsafe {
+      let prev_mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
+      let flags = prev_mode & !INPUT_MODE_MASK;
+      ffi::tb_select_input_mode(mode.to_raw() | flags);
+    }
+  }
+
+  /// Enables or disables job control. While enabled, pressing Ctrl-Z or sending the process
+  /// SIGTSTP suspends it with `suspend`, and `Event::Resumed` is reported once it is continued, or
+  /// `Event::ResumeFailed` if termbox couldn't be reinitialized. Ctrl-Z is not reported as a key
+  /// event. Disabled by default.
+  pub fn set_job_control (&mut self, enabled: bool) {
+    self.job_control = enabled;
+    if enabled {
+      signals::enable_stop();
+    } else {
+      signals::disable_stop();
+    }
+  }
+
+  /// Enables or disables the kitty keyboard protocol on terminals that support it. While enabled,
+  /// key events report whether a key was pressed, repeated or released (see `KeyEvent::state`),
+  /// and Esc is never confused with the start of an Alt key combination. Programs that don't
+  /// expect release events should ignore them. Terminals without support ignore the request.
+  /// Disabled by default. Only available with the `pure-rust` feature.
+  #[cfg(feature = "pure-rust")]
+  pub fn set_kitty_keyboard (&mut self, enabled: bool) {
+    unsafe {
+      let prev_mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
+      let new_mode;
+
+      if enabled {
+        new_mode = prev_mode | ffi::TB_INPUT_KITTY;
+      } else {
+        new_mode = prev_mode & !ffi::TB_INPUT_KITTY;
+      }
+
+      if new_mode != prev_mode {
+        ffi::tb_select_input_mode(new_mode);
+      }
+    }
+  }
+
+  /// Enables or disables mouse events. Mouse events are disabled by default.
+  pub fn set_mouse_enabled (&mut self, enabled: bool) {
+    unsafe {
+      let prev_mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
+      let new_mode;
+
+      if enabled {
+        new_mode = prev_mode | ffi::TB_INPUT_MOUSE;
+      } else {
+        new_mode = prev_mode & !ffi::TB_INPUT_MOUSE;
+      }
+
+      if new_mode != prev_mode {
+        ffi::tb_select_input_mode(new_mode);
+      }
+    }
+  }
+
+  /// Sets how many bytes of output to collect while presenting before writing them to the terminal.
+  /// By default, each frame is written in one go once it has been encoded, which gives the best
+  /// throughput. Over slow links, a smaller buffer lets the top of a large frame start arriving
+  /// sooner. Zero restores the default. Only available with the `pure-rust` feature.
+  #[cfg(feature = "pure-rust")]
+  pub fn set_output_buffer_capacity (&mut self, bytes: usize) {
+    unsafe {
+      ffi::tb_select_output_buffer_capacity(bytes.min(c_int::max_value() as usize) as c_int);
+    }
+  }
+
+  /// Sets the method termbox should use to interpret output attributes.
+  pub fn set_output_mode (&mut self, mode: OutputMode) {
+    unsafe {
+      ffi::tb_select_output_mode(mode.to_raw());
+    }
+  }
+
+  /// Enables or disables measuring each present for `last_present_stats`. Counting the changed
+  /// cells needs a copy of the output buffer to be kept and updated on every present, so this is
+  /// disabled by default.
+  pub fn set_present_stats_enabled (&mut self, enabled: bool) {
+    self.present_stats_enabled = enabled;
+    if enabled {
+      if self.presented.is_none() {
+        self.presented = Some(Vec::new());
+      }
+    } else {
+      self.present_stats = None;
+      if !self.compare_frames {
+        self.presented = None;
+      }
+    }
+  }
+
+  /// Selects whether the bytes read from the terminal are reported by `Event::Raw`, so that
+  /// applications can handle input termbox doesn't decode, such as replies to their own queries or
+  /// keys with unusual escape sequences. With `RawInput::Alongside`, the raw bytes are reported
+  /// before the events decoded from them. With `RawInput::Exclusive`, they are not decoded at all.
+  /// Disabled by default. Only available with the `pure-rust` feature.
+  ///
+  /// ~~~no_run
+  /// use termbox::{Event, RawInput, Termbox};
+  ///
+  /// let mut tb = Termbox::open().unwrap();
+  /// tb.set_raw_input(RawInput::Alongside);
+  ///
+  /// if let Event::Raw(bytes) = tb.poll_event() {
+  ///   println!("read {:?}", bytes);
+  /// }
+  /// ~~~
+  #[cfg(feature = "pure-rust")]
+  pub fn set_raw_input (&mut self, raw_input: RawInput) {
+    unsafe {
+      let prev_mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
+      let mask = ffi::TB_INPUT_RAW | ffi::TB_INPUT_RAW_ONLY;
+      let new_mode = (prev_mode & !mask) | raw_input.to_raw();
+
+      if new_mode != prev_mode {
+        ffi::tb_select_input_mode(new_mode);
+      }
+    }
+  }
+
+  /// Sets what happens to the screen contents after the terminal is resized. Unless the policy is
+  /// `ResizePolicy::Clear` and no handler is set with `on_resize`, a resize event received by
+  /// `peek_event` or `poll_event` clears the output buffer, copies the last presented frame back
+  /// into it at the position chosen by the policy, and presents it before the event is returned.
+  /// This keeps simple applications looking sensible without a full redraw, and hides the garbage
+  /// some terminals show until the application draws again. The default is `ResizePolicy::Clear`,
+  /// since preserving the frame copies the output buffer on every present.
+  pub fn set_resize_policy (&mut self, policy: ResizePolicy) {
+    self.resize_policy = policy;
+    if policy == ResizePolicy::Clear {
+      self.last_frame = None;
+    }
+  }
+
+  /// Sets how long a burst of resize events must be quiet before it is reported, or disables this
+  /// with `None`. While a burst is in progress, resize events are withheld and a
+  /// cheap placeholder is presented for each one instead: the last presented frame clipped to the
+  /// new size, with a border around the edge of the terminal. When no further resize arrives
+  /// within `interval`, a single resize event with the final size is reported, and the handler
+  /// set with `on_resize` runs. This keeps the window content tracking the resize without running
+  /// a full layout for each intermediate size. Disabled by default.
+  pub fn set_resize_debounce (&mut self, interval: Option<Duration>) {
+    self.resize_debounce = interval;
+  }
+
+  /// Enables or disables redrawing the last presented frame after the terminal is resized. This is
+  /// the same as setting the resize policy to `ResizePolicy::PreserveTopLeft` or
+  /// `ResizePolicy::Clear`. See `set_resize_policy`.
+  pub fn set_redraw_on_resize (&mut self, enabled: bool) {
+    let policy = if enabled { ResizePolicy::PreserveTopLeft } else { ResizePolicy::Clear };
+    self.set_resize_policy(policy);
+  }
+
+  /// Installs a last-resort handler that resets the terminal if the process exits or is killed by a
+  /// signal such as SIGTERM or SIGSEGV without dropping the `Termbox`. This leaves the alternate
+  /// screen, shows the cursor and restores the terminal settings so the user's shell is usable
+  /// again. SIGKILL can't be caught, so it is not covered. The handler is removed when the
+  /// `Termbox` is dropped or this is called with `false`.
+  pub fn set_restore_on_exit (&mut self, enabled: bool) {
+    if enabled {
+      restore::arm(self.tty.input_fd(), self.tty.output_fd());
+    } else {
+      restore::disarm();
+    }
+  }
+
+  /// Sets the style of the cells under secondary cursors, or `None` to show them by toggling
+  /// reverse video, which is the default. See `set_secondary_cursors`.
+  pub fn set_secondary_cursor_style (&mut self, style: Option<Style>) {
+    self.secondary_cursor_style = style;
+  }
+
+  /// Shows additional cursors at the given positions in the next presented frame, for editors
+  /// with multiple cursors. Terminals only have one real cursor, so these are drawn by changing
+  /// the style of the cells under them while presenting. The output buffer is left untouched, and
+  /// the cursors are removed once presented, so they have to be set again for each frame.
+  /// Positions outside of the output buffer are ignored.
+  pub fn set_secondary_cursors (&mut self, positions: &[(Coord, Coord)]) {
+    self.secondary_cursors.clear();
+    self.secondary_cursors.extend_from_slice(positions);
+  }
+
+  /// Enables or disables reporting SIGINT and SIGTERM as `Event::Signal` instead of letting them
+  /// terminate the process. This gives the program a chance to save its state before exiting.
+  /// Disabled by default. The previous signal handlers are put back when disabled or when the
+  /// `Termbox` is dropped.
+  pub fn set_signal_events (&mut self, enabled: bool) {
+    if enabled {
+      signals::enable();
+    } else {
+      signals::disable();
+    }
+  }
+
+  /// Enables or disables strict mode. In debug builds, strict mode checks the attributes passed to
+  /// `change_cell`, `put_cell`, `blit` and the drawing calls built on them, and records a
+  /// `Diagnostic` for each attribute that isn't meaningful in the current output mode, such as an
+  /// out of range color. Diagnostics are retrieved with `take_diagnostics`. Release builds never
+  /// check attributes. Disabled by default.
+  pub fn set_strict_mode (&mut self, enabled: bool) {
+    self.strict = enabled;
+  }
+
+  /// Enables or disables synchronized output. While enabled, each present is wrapped in the
+  /// sequences that begin and end a synchronized update (mode 2026), so that the terminal shows
+  /// the whole frame at once instead of tearing part way through a large redraw. Terminals without
+  /// the mode ignore the sequences. By default, it is enabled if the terminal is known to support
+  /// it.
+  pub fn set_synchronized_output (&mut self, enabled: bool) {
+    self.synchronized_output = enabled;
+  }
+
+  /// Sets the number of columns between the tab stops that `put_str` expands tabs to, measured from
+  /// the column the string starts at. `put_str_with` uses it too, unless `PutOptions::tab_width`
+  /// says otherwise. Zero or less writes tabs as-is, which most terminals display as garbage. The
+  /// default is 8.
+  pub fn set_tab_width (&mut self, width: Coord) {
+    self.tab_width = width;
+  }
+
+  /// Sets the theme used by `style` and the drawing calls that take a `Role`. The default is
+  /// `Theme::dark`.
+  pub fn set_theme (&mut self, theme: Theme) {
+    self.theme = theme;
+  }
+
+  /// Starts a timer that delivers `Event::Timer(id)` through `peek_event` and `poll_event` after
+  /// `delay`, and then every `delay` if `repeating` is true. Setting a timer with the id of one that
+  /// is already running restarts it. Timers are checked between input events, so they can fire
+  /// late while the program is busy, but a repeating timer never fires more than once per wait.
+  pub fn set_timer (&mut self, id: TimerId, delay: Duration, repeating: bool) {
+    self.timers.set(id, delay, repeating);
+  }
+
+  /// Sets how characters outside of ASCII are replaced while UTF-8 output is disabled or the
+  /// ASCII glyph profile is selected. The default is `AsciiTransliterator`.
+  pub fn set_transliterator<T> (&mut self, transliterator: T)
+    where T: Transliterator + 'static
+  {
+    self.transliterator = Box::new(transliterator);
+  }
+
+  /// Enables or disables sending characters outside of ASCII to the terminal. Termbox always
+  /// writes UTF-8, which terminals using a legacy character set such as Latin-1 show as garbage.
+  /// While disabled, such characters are replaced as they are presented, by default with ASCII
+  /// approximations (`é` becomes `e`, `─` becomes `-`) or `?`. See `set_transliterator`. The
+  /// output buffer itself is left unchanged. By default this is enabled if the locale uses UTF-8.
+  pub fn set_utf8_enabled (&mut self, enabled: bool) {
+    self.utf8 = enabled;
+  }
+
+  /// Returns an owned copy of the output buffer. See `Snapshot`.
+  pub fn snapshot (&self) -> Snapshot {
+    Snapshot::new(self.width(), self.height(), self.output_mode(), self.cell_buffer().to_vec())
+  }
+
+  /// Runs `f` on a background thread. When it finishes, `peek_event` and `poll_event` report
+  /// `Event::TaskDone` with the returned id and the value returned by `f`. This keeps all changes to
+  /// the interface on the thread that owns the `Termbox`. A task that panics is still reported,
+  /// with the value it panicked with in place of a result.
+  #[cfg(feature = "async")]
+  pub fn spawn_task<F, T> (&mut self, f: F) -> TaskId
+    where F: FnOnce() -> T + Send + 'static, T: Any + Send
+  {
+    self.tasks.spawn(f)
+  }
+
+  /// Starts recording every event reported by `peek_event` and `poll_event` to `writer`, in the
+  /// format described in the `record` module. Replaces any recording in progress. Each event is
+  /// flushed as it is written, so the recording survives a crash. Recording stops if writing
+  /// fails.
+  pub fn start_recording<W: io::Write + 'static> (&mut self, writer: W) {
+    self.recorder = Some(Recorder::new(Box::new(writer)));
+  }
+
+  /// Starts recording every presented frame to `writer` as an asciicast v2 screencast, which can
+  /// be played back with `asciinema play` or embedded in a web page. The header is written
+  /// immediately. Replaces any screencast in progress. Recording stops if writing fails.
+  ///
+  /// ~~~no_run
+  /// use std::fs::File;
+  /// use termbox::Termbox;
+  ///
+  /// let mut tb = Termbox::open().unwrap();
+  /// tb.start_screencast(File::create("demo.cast").unwrap()).unwrap();
+  /// ~~~
+  pub fn start_screencast<W: io::Write + 'static> (&mut self, writer: W) -> io::Result<()> {
+    self.caster = None;
+    self.caster = Some(Caster::new(Box::new(writer), self.width(), self.height())?);
+    return Ok(());
+  }
+
+  /// Stops the recording started with `start_recording`, if any.
+  pub fn stop_recording (&mut self) {
+    self.recorder = None;
+  }
+
+  /// Stops the screencast started with `start_screencast`, if any.
+  pub fn stop_screencast (&mut self) {
+    self.caster = None;
+  }
+
+  /// Returns the style of `role` in the current theme and output mode.
+  pub fn style (&self, role: Role) -> Style {
+    self.theme.get(role, self.output_mode())
+  }
+
+  /// Restores the terminal to the state it was in before termbox was initialized and stops the
+  /// process, as the shell expects when the user presses Ctrl-Z. When the process is continued,
+  /// termbox is reinitialized, the contents of the output buffer are presented again, and
+  /// `Event::Resumed` is reported by the next call to `peek_event` or `poll_event`. Returns an
+  /// error if termbox can't be reinitialized, in which case the `Termbox` should be dropped.
+  pub fn suspend (&mut self) -> Result<()> {
+    let released = self.release();
+    signals::stop();
+    self.reacquire(released)?;
+    self.resumed = true;
+    return Ok(());
+  }
+
+  /// Shows another screen. The cells and cursor of the active screen are put aside until it is
+  /// switched back to, and those of `id` are copied to the output buffer, to be shown by the next
+  /// present. A screen that is smaller or larger than the output buffer, because the terminal was
+  /// resized while it wasn't shown, is resized keeping its top left corner. Layers and settings
+  /// are shared by all screens. Returns false if `id` isn't a screen.
+  pub fn switch_screen (&mut self, id: ScreenId) -> bool {
+    if id == self.active_screen {
+      return true;
+    }
+    let mut screen = match self.remove_screen(id) {
+      Some(screen) => screen,
+      None => { return false; },
+    };
+
+    let (width, height) = (self.width(), self.height());
+    let cursor = self.cursor();
+    let previous = VirtualScreen::from_cells(width, height, self.cell_buffer(), cursor);
+    self.screens.push((self.active_screen, previous));
+    self.active_screen = id;
+
+    screen.resize(width, height);
+    if !screen.cell_buffer().is_empty() {
+      self.blit(0, 0, width, height, screen.cell_buffer());
+    }
+    match screen.cursor() {
+      Some((x, y)) => { self.set_cursor(x, y); },
+      None => { self.hide_cursor(); },
+    }
+    return true;
   }
 
-  /// Returns the height of the output buffer in character cells.
-  pub fn height (&self) -> Coord {
+  /// Determines whether presents are wrapped in synchronized updates. See
+  /// `set_synchronized_output`.
+  pub fn synchronized_output (&self) -> bool {
+    self.synchronized_output
+  }
+
+  /// Returns the number of columns between tab stops. See `set_tab_width`.
+  pub fn tab_width (&self) -> Coord {
+    self.tab_width
+  }
+
+  /// Removes and returns the diagnostics recorded in strict mode, oldest first. See
+  /// `set_strict_mode`.
+  pub fn take_diagnostics (&mut self) -> Vec<Diagnostic> {
+    mem::replace(&mut self.diagnostics, Vec::new())
+  }
+
+  /// Returns the current theme. See `set_theme`.
+  pub fn theme (&self) -> &Theme {
+    &self.theme
+  }
+
+  /// Changes a single cell like `change_cell`, but returns an error if it is outside of the output
+  /// buffer instead of ignoring it.
+  pub fn try_change_cell<X, Y> (&mut self, x: X, y: Y, ch: char, fg: Attribute, bg: Attribute)
+    -> result::Result<(), OutOfBounds>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::try_change_cell(self, x, y, ch, fg, bg)
+  }
+
+  /// Changes a single cell like `put_cell`, but returns an error if it is outside of the output
+  /// buffer instead of ignoring it.
+  pub fn try_put_cell<X, Y> (&mut self, x: X, y: Y, cell: Cell)
+    -> result::Result<(), OutOfBounds>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    Backend::try_put_cell(self, x, y, cell)
+  }
+
+  /// Removes a sequence registered with `register_key_sequence`, so that it is decoded by the
+  /// built-in parser again. Returns false if it was not registered. Only available with the
+  /// `pure-rust` feature.
+  #[cfg(feature = "pure-rust")]
+  pub fn unregister_key_sequence (&mut self, seq: &[u8]) -> bool {
     unsafe {
-      ffi::tb_height()
+      return ffi::tb_unregister_key_sequence(seq) != 0;
     }
   }
 
-  /// Sets the cursor to an invalid position, making it invisible to the user.
-  pub fn hide_cursor (&mut self) {
+  /// Removes a region registered with `register_region`. Returns false if there was no region with
+  /// the given id.
+  pub fn unregister_region (&mut self, id: RegionId) -> bool {
+    self.regions.unregister(id)
+  }
+
+  /// Determines whether characters outside of ASCII are sent to the terminal as they are. See
+  /// `set_utf8_enabled`.
+  pub fn utf8_enabled (&self) -> bool {
+    self.utf8
+  }
+
+  /// Returns the width of the output buffer in character cells.
+  pub fn width (&self) -> Coord {
     unsafe {
-      ffi::tb_set_cursor(ffi::TB_HIDE_CURSOR, ffi::TB_HIDE_CURSOR);
+      ffi::tb_width()
     }
   }
 
-  /// Returns the input mode. See `set_input_mode`.
-  pub fn input_mode (&self) -> InputMode {
-    unsafe {
-      let raw_mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
-      return InputMode::from_raw(raw_mode).unwrap();
+  /// Returns a writer that draws formatted text into the output buffer starting at `(x, y)`. See
+  /// `TermWriter`.
+  ///
+  /// ~~~no_run
+  /// use std::fmt::Write;
+  /// use termbox::Termbox;
+  /// use termbox::theme::Role;
+  ///
+  /// let mut tb = Termbox::open().unwrap();
+  /// let style = tb.style(Role::Normal);
+  /// write!(tb.writer_at(0, 3, style), "{} items", 12).unwrap();
+  /// ~~~
+  pub fn writer_at<'a, X, Y> (&'a mut self, x: X, y: Y, style: Style)
+    -> TermWriter<'a, Termbox>
+    where X: IntoCoord, Y: IntoCoord
+  {
+    let (x, y) = (x.into_coord(), y.into_coord());
+    TermWriter::new(self, x, y, style)
+  }
+
+  // Records a diagnostic for each attribute that is not meaningful in the current output mode.
+  fn check_attributes (&mut self, x: Coord, y: Coord, fg: Attribute, bg: Attribute) {
+    let mode = self.output_mode();
+
+    for &attribute in &[fg, bg] {
+      if let Some(reason) = attributes::problem(attribute, mode) {
+        if self.diagnostics.len() < MAX_DIAGNOSTICS {
+          self.diagnostics.push(Diagnostic::InvalidAttribute {
+            x: x,
+            y: y,
+            attribute: attribute,
+            mode: mode,
+            reason: reason,
+          });
+        }
+      }
     }
   }
 
-  /// Determines whether mouse events are enabled. See `set_mouse_enabled`.
-  pub fn is_mouse_enabled (&self) -> bool {
-    unsafe {
-      let mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
-      return (mode & ffi::TB_INPUT_MOUSE) != 0;
+  // Copies a frame presented before a resize into the output buffer, positioned according to the
+  // resize policy. Parts that don't fit are clipped.
+  fn blit_last_frame (&mut self, frame: &Snapshot) {
+    let anchor = self.resize_policy.anchor();
+    let (x, y) = anchor.offset((frame.width(), frame.height()), (self.width(), self.height()));
+    let src = Rect::new(0, 0, frame.width(), frame.height());
+    self.blit_region(x, y, frame.cells(), frame.width(), src, None);
+  }
+
+  // Compares the terminal's current size against the last size reported to the caller. A resize
+  // that happens while the caller is busy is otherwise only noticed by termbox once it waits for
+  // input again, and may be reported after other queued input.
+  fn check_resize (&mut self) -> Option<Event> {
+    match self.tty.size() {
+      Some(size) if size != self.size => {
+        return Some(Event::Resize(ResizeEvent { w: size.0, h: size.1 }));
+      },
+      _ => { return None; },
     }
   }
 
-  /// Locks the terminal to an instance of `Termbox`. Only one instance may exist in a process.
-  pub fn open () -> Result<Termbox> {
-    unsafe {
-      let lock;
-      match Lock::acquire() {
-        Some(l) => { lock = l; },
-        None => { return Err(Error::Locked); },
+  // Counts the cells of the output buffer that differ from the last presented frame if present
+  // stats are enabled, and remembers the buffer for next time if anything needs it.
+  fn count_changed_cells (&mut self) -> usize {
+    let mut presented = match self.presented.take() {
+      Some(presented) => presented,
+      None => { return 0; },
+    };
+
+    let changed = {
+      let buffer = self.cell_buffer();
+      if buffer.len() != presented.len() {
+        presented = buffer.to_vec();
+        buffer.len()
+      } else {
+        let changed = if self.present_stats_enabled {
+          buffer.iter().zip(&presented).filter(|&(a, b)| a != b).count()
+        } else {
+          0
+        };
+        presented.copy_from_slice(buffer);
+        changed
+      }
+    };
+
+    self.presented = Some(presented);
+    return changed;
+  }
+
+  // Changes the style of the cells under the secondary cursors and forgets the cursors. Returns the
+  // original cells along with their positions in the output buffer.
+  fn draw_secondary_cursors (&mut self) -> Vec<(usize, Cell)> {
+    let positions = mem::replace(&mut self.secondary_cursors, Vec::new());
+    let style = self.secondary_cursor_style.map(|style| style.attributes());
+    let (width, height) = (self.width(), self.height());
+    let mut covered: Vec<(usize, Cell)> = Vec::with_capacity(positions.len());
+
+    for (x, y) in positions {
+      if x < 0 || y < 0 || x >= width || y >= height {
+        continue;
+      }
+      let index = (y * width + x) as usize;
+      if covered.iter().any(|&(i, _)| i == index) {
+        continue;
       }
 
-      match ffi::tb_init() {
-        0 => { return Ok(Termbox { lock: lock }); },
-        n => { return Err(Error::from_raw(n).unwrap()); },
+      let buffer = self.cell_buffer_mut();
+      covered.push((index, buffer[index]));
+      match style {
+        Some((fg, bg)) => {
+          buffer[index].fg = fg;
+          buffer[index].bg = bg;
+        },
+        None => { buffer[index].fg ^= REVERSE; },
       }
     }
+
+    return covered;
   }
 
-  /// Returns the current output mode. See `set_output_mode`.
-  pub fn output_mode (&self) -> OutputMode {
-    unsafe {
-      let raw_mode = ffi::tb_select_output_mode(ffi::TB_OUTPUT_CURRENT);
-      return OutputMode::from_raw(raw_mode).unwrap();
+  // Withholds a resize event while resize debouncing is enabled, presenting a placeholder frame
+  // instead. The event is reported later by `pending_event`. Other events are returned as-is.
+  fn debounce_resize (&mut self, event: Event) -> Option<Event> {
+    let interval = match (self.resize_debounce, &event) {
+      (Some(interval), &Event::Resize(_)) => interval,
+      _ => { return Some(event); },
+    };
+
+    if let Event::Resize(ref resize) = event {
+      self.size = (resize.w, resize.h);
+      self.pixel_size = None;
+      self.regions.clear();
+      self.images.clear();
     }
+    self.pending_resize = Some((event, Instant::now() + interval));
+    self.present_resize_placeholder();
+    return None;
   }
 
-  /// Waits up to `timeout` milliseconds for an event. If an event is received, that event is
-  /// returned. Otherwise, `None` is returned. A `timeout` of zero can be specified to poll for
-  /// events that have already been received without waiting.
-  pub fn peek_event (&self, timeout: Time) -> Option<Event> {
+  // Clears the area of a dialog shown by `modal` and draws its border. The area may extend past
+  // the screen, in which case only the part on screen is cleared.
+  fn draw_modal_frame (&mut self, rect: Rect) {
+    let (fg, bg) = self.style(Role::Normal).attributes();
+    for y in rect.y.max(0)..(rect.y + rect.h).min(self.height()) {
+      for x in rect.x.max(0)..(rect.x + rect.w).min(self.width()) {
+        self.change_cell(x, y, ' ', fg, bg);
+      }
+    }
+
+    let (fg, bg) = self.style(Role::Border).attributes();
+    box_drawing::draw_box(self, rect.x, rect.y, rect.w, rect.h, LineStyle::Light, fg, bg);
+  }
+
+  // Debounces resizes, handles them and counts clicks, for events read from termbox or replayed.
+  // Returns `None` if the event was consumed.
+  fn handle_event (&mut self, event: Event) -> Option<Event> {
+    let mut event = self.debounce_resize(event)?;
+    self.handle_resize(&event);
+    if let Event::Mouse(ref mut mouse) = event {
+      self.clicks.count(mouse);
+    }
+    return Some(event);
+  }
+
+  // Records the size reported by a resize event and runs the handler set with `on_resize`. Resize
+  // events from termbox are handled even if `check_resize` already reported the same size, since
+  // termbox only resizes its buffers after reading its own resize notification. While a burst is
+  // being merged by `poll_event_coalesced`, the handler is put off until the burst is over.
+  fn handle_resize (&mut self, event: &Event) {
+    if let Event::Resize(ref resize) = *event {
+      self.size = (resize.w, resize.h);
+      self.pixel_size = None;
+      self.regions.clear();
+      self.images.clear();
+
+      if self.defer_resize_handler {
+        self.deferred_resize = Some(event.clone());
+      } else if let Some(mut handler) = self.resize_handler.take() {
+        self.clear();
+        handler(self, resize.w, resize.h);
+        self.present();
+        if self.resize_handler.is_none() {
+          self.resize_handler = Some(handler);
+        }
+      } else if let Some(frame) = self.last_frame.clone() {
+        self.clear();
+        self.blit_last_frame(&frame);
+        self.present();
+      }
+    }
+  }
+
+  // Returns the sequences that update the images on the screen, as drawn with `draw_image` since
+  // the last present. The first is written before the cells, to erase images that are no longer
+  // drawn, and the second after them, so that new images are drawn over the cleared cells.
+  fn image_output (&mut self) -> (String, String) {
+    let current: Vec<(u32, Rect)> =
+      self.images.iter().map(|placement| (placement.id, placement.rect)).collect();
+    if current == self.placed_images {
+      return (String::new(), String::new());
+    }
+
+    let mut before = String::new();
+    let mut after = String::new();
+    match self.image_protocol {
+      ImageProtocol::Kitty => {
+        after.push_str(images::KITTY_DELETE_PLACEMENTS);
+        for placement in &mut self.images {
+          if let Some(data) = placement.data.take() {
+            if self.sent_images.insert(placement.id) {
+              after.push_str(&data);
+            }
+          }
+          after.push_str(&images::kitty_place(placement.id, placement.rect));
+        }
+      },
+      ImageProtocol::Iterm2 => {
+        let (fg, bg) = self.clear_attributes;
+        let mode = self.output_mode();
+        for placed in &self.placed_images {
+          if !current.contains(placed) {
+            before.push_str(&images::erase(placed.1, fg, bg, mode));
+          }
+        }
+        for placement in &mut self.images {
+          if let Some(data) = placement.data.take() {
+            after.push_str(&data);
+          }
+        }
+      },
+      ImageProtocol::HalfBlocks => {},
+    }
+
+    self.placed_images = current;
+    return (before, after);
+  }
+
+  // Strict mode only has an effect in debug builds.
+  fn is_strict (&self) -> bool {
+    cfg!(debug_assertions) && self.strict
+  }
+
+  // Acquires the lock, initializes termbox with `init` and opens a second handle to the same
+  // terminal with `open_tty`. `init` is kept so that termbox can be reinitialized after `suspend`.
+  fn open_with<F, G> (init: F, open_tty: G) -> Result<Termbox>
+    where F: Fn() -> c_int + 'static, G: FnOnce() -> io::Result<Tty>
+  {
+    let lock;
+    match Lock::acquire() {
+      Some(l) => { lock = l; },
+      None => { return Err(Error::Locked); },
+    }
+
+    // The tty is opened first so the terminal's settings can be saved before termbox changes them.
+    let tty = match open_tty() {
+      Ok(tty) => tty,
+      Err(_) => { return Err(Error::FailedToOpenTty); },
+    };
+    restore::save_termios(tty.input_fd());
+    wake::open();
+
+    match init() {
+      0 => { internal::set_active(); },
+      n => { return Err(Error::from_raw(n).unwrap()); },
+    }
+
+    let capabilities = Capabilities::detect();
+    let mut tb = Termbox {
+      tty: tty,
+      capabilities: capabilities.clone(),
+      hyperlinks: capabilities.hyperlinks,
+      synchronized_output: capabilities.synchronized_output,
+      tab_width: backend::DEFAULT_TAB_WIDTH,
+      utf8: capabilities.unicode,
+      links: Vec::new(),
+      cursor: (ffi::TB_HIDE_CURSOR, ffi::TB_HIDE_CURSOR),
+      cursor_dirty: false,
+      frame_interval: None,
+      last_present: None,
+      present_pending: false,
+      size: (0, 0),
+      resize_handler: None,
+      draw_hook: None,
+      #[cfg(feature = "async")]
+      tasks: Tasks::new(),
+      init: Box::new(init),
+      clear_attributes: (DEFAULT, DEFAULT),
+      job_control: false,
+      resumed: false,
+      resume_failed: false,
+      pixel_size: None,
+      layers: Layers::new(),
+      transliterator: Box::new(AsciiTransliterator),
+      strict: false,
+      diagnostics: Vec::new(),
+      resize_policy: ResizePolicy::Clear,
+      last_frame: None,
+      resize_debounce: None,
+      pending_resize: None,
+      held_event: None,
+      defer_resize_handler: false,
+      deferred_resize: None,
+      #[cfg(not(feature = "pure-rust"))]
+      queued_input: VecDeque::new(),
+      theme: Theme::default(),
+      timers: Timers::new(),
+      injected: Injected::new(),
+      recorder: None,
+      player: None,
+      caster: None,
+      clicks: ClickCounter::new(),
+      regions: Regions::new(),
+      image_protocol: capabilities.images,
+      images: Vec::new(),
+      placed_images: Vec::new(),
+      sent_images: HashSet::new(),
+      presented: None,
+      present_stats_enabled: false,
+      compare_frames: false,
+      secondary_cursors: Vec::new(),
+      secondary_cursor_style: None,
+      present_stats: None,
+      screens: Vec::new(),
+      active_screen: ScreenId(0),
+      next_screen_id: 1,
+      lock: lock,
+    };
+    tb.size = (tb.width(), tb.height());
+    return Ok(tb);
+  }
+
+  // Returns an event that is ready without waiting for input, if any.
+  fn pending_event (&mut self) -> Option<Event> {
+    if signals::take_stop() {
+      self.suspend_for_job_control();
+    }
+    if self.resumed {
+      self.resumed = false;
+      return Some(Event::Resumed);
+    }
+    if self.resume_failed {
+      self.resume_failed = false;
+      return Some(Event::ResumeFailed);
+    }
+
+    if let Some(event) = self.check_resize() {
+      if let Some(event) = self.debounce_resize(event) {
+        self.handle_resize(&event);
+        return Some(event);
+      }
+    }
+    if let Some((event, deadline)) = self.pending_resize.take() {
+      if deadline <= Instant::now() {
+        self.handle_resize(&event);
+        return Some(event);
+      }
+      self.pending_resize = Some((event, deadline));
+    }
+
+    if let Some(signal) = signals::take() {
+      return Some(Event::Signal(signal));
+    }
+    if let Some(id) = self.timers.poll() {
+      return Some(Event::Timer(id));
+    }
+    if let Some(payload) = self.injected.poll() {
+      return Some(Event::Custom(payload));
+    }
+    if let Some(event) = self.player.as_mut().and_then(|player| player.poll()) {
+      // Replayed events are handled like those read from termbox, except that a replayed Ctrl-Z
+      // doesn't suspend the process.
+      if let Some(event) = self.handle_event(event) {
+        return Some(event);
+      }
+    }
+    if self.player.as_ref().map_or(false, |player| player.next_deadline().is_none()) {
+      self.player = None;
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
+    {
+      if let Some(event) = self.queued_input.pop_front() {
+        if let Some(event) = self.process_event(event) {
+          return Some(event);
+        }
+      }
+    }
+
+    #[cfg(feature = "async")]
+    {
+      if let Some((id, result)) = self.tasks.poll() {
+        return Some(Event::TaskDone(id, result));
+      }
+    }
+
+    return None;
+  }
+
+  // Returns how many milliseconds remain until a requested present may be carried out, or `None`
+  // if no present is pending.
+  fn pending_present_delay (&self) -> Option<Time> {
+    if !self.present_pending {
+      return None;
+    }
+
+    match (self.frame_interval, self.last_present) {
+      (Some(interval), Some(last)) => Some(millis_until(last + interval)),
+      _ => Some(0),
+    }
+  }
+
+  fn peek_raw_event (&self, timeout: Time) -> Option<Event> {
     unsafe {
-      let mut raw: ffi::RawEvent = mem::uninitialized();
+      let mut raw: ffi::RawEvent = mem::zeroed();
       let result = ffi::tb_peek_event(&mut raw, timeout);
 
-      if result < 0 {
+      // An interrupted wait is treated like a timeout. Callers check their own deadline.
+      if result < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+        return None;
+      } else if result < 0 {
         panic!("tb_peek_event returned {}", result);
       } else if result == 0 {
         return None;
       } else {
-        return Some(Event::from_raw(raw).unwrap());
+        return Event::from_raw(raw);
       }
     }
   }
 
-  /// Waits for an input event and returns it.
-  pub fn poll_event (&self) -> Event {
-    unsafe {
-      let mut raw: ffi::RawEvent = mem::uninitialized();
-      let result = ffi::tb_poll_event(&mut raw);
+  fn poll_raw_event (&self) -> Event {
+    loop {
+      unsafe {
+        let mut raw: ffi::RawEvent = mem::zeroed();
+        let result = ffi::tb_poll_event(&mut raw);
+
+        if result < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+          continue;
+        } else if result <= 0 {
+          panic!("tb_poll_event returned {}", result);
+        } else if let Some(event) = Event::from_raw(raw) {
+          return event;
+        }
+      }
+    }
+  }
 
-      if result <= 0 {
-        panic!("tb_poll_event returned {}", result);
-      } else {
-        return Event::from_raw(raw).unwrap();
+  fn present_now (&mut self) {
+    let started = Instant::now();
+    if let Some(ref mut hook) = self.draw_hook {
+      hook.presented();
+    }
+    self.flush_cursor();
+    // The frame is remembered without secondary cursors, which only last for this present.
+    let cells_changed = self.count_changed_cells();
+    if self.resize_policy != ResizePolicy::Clear || self.resize_debounce.is_some() {
+      self.last_frame = Some(self.snapshot());
+    }
+    let covered = self.draw_secondary_cursors();
+
+    let mut extra_bytes = 0;
+    if self.synchronized_output {
+      let _ = self.tty.write(BEGIN_SYNCHRONIZED_UPDATE);
+      extra_bytes += BEGIN_SYNCHRONIZED_UPDATE.len();
+    }
+
+    let (before_cells, after_cells) = self.image_output();
+    if !before_cells.is_empty() {
+      let _ = self.tty.write(before_cells.as_bytes());
+      extra_bytes += before_cells.len();
+    }
+
+    if self.utf8 && glyphs::profile() == GlyphProfile::Unicode {
+      unsafe {
+        ffi::tb_present();
+      }
+    } else {
+      // Termbox only sends cells that differ from what it last sent, so the replacements are
+      // undone after presenting and applied again each time.
+      let original = self.cell_buffer().to_vec();
+      let transliterator = mem::replace(&mut self.transliterator, Box::new(AsciiTransliterator));
+      for cell in self.cell_buffer_mut().iter_mut() {
+        if !cell.ch().is_ascii() {
+          cell.set_ch(transliterator.transliterate(cell.ch()));
+        }
+      }
+      self.transliterator = transliterator;
+      unsafe {
+        ffi::tb_present();
+      }
+      self.cell_buffer_mut().copy_from_slice(&original);
+    }
+
+    let buffer = self.cell_buffer_mut();
+    for (index, cell) in covered {
+      buffer[index] = cell;
+    }
+
+    if !self.links.is_empty() {
+      let (width, height, mode) = (self.width(), self.height(), self.output_mode());
+      let transliterate = !self.utf8 || glyphs::profile() != GlyphProfile::Unicode;
+      let links = mem::replace(&mut self.links, Vec::new());
+      let mut out = String::new();
+      for link in links {
+        link.write(&mut out, self.cell_buffer(), width, height, mode, |ch| {
+          if transliterate && !ch.is_ascii() { self.transliterator.transliterate(ch) } else { ch }
+        });
+      }
+      let _ = self.tty.write(out.as_bytes());
+      extra_bytes += out.len();
+    }
+
+    if !after_cells.is_empty() {
+      let _ = self.tty.write(after_cells.as_bytes());
+      extra_bytes += after_cells.len();
+    }
+
+    if self.synchronized_output {
+      let _ = self.tty.write(END_SYNCHRONIZED_UPDATE);
+      extra_bytes += END_SYNCHRONIZED_UPDATE.len();
+    }
+
+    if self.present_stats_enabled {
+      self.present_stats = Some(PresentStats {
+        cells_changed: cells_changed,
+        bytes_written: present_bytes().map(|bytes| bytes + extra_bytes),
+        duration: started.elapsed(),
+      });
+    }
+
+    if self.caster.is_some() {
+      let (snapshot, cursor) = (self.snapshot(), self.cursor);
+      let failed = self.caster.as_mut().unwrap().frame(&snapshot, cursor).is_err();
+      if failed {
+        self.caster = None;
       }
     }
+
+    self.last_present = Some(Instant::now());
+    self.present_pending = false;
   }
 
-  /// Writes any changes to the output buffer into the terminal. This must be called in order for
-  /// the user to see any changes.
-  pub fn present (&mut self) {
-    unsafe {
-      ffi::tb_present();
+  // Presents the last presented frame, placed according to the resize policy and clipped to the
+  // current size, with a border around it. The last frame itself is kept for the next placeholder
+  // and for the resize policy.
+  fn present_resize_placeholder (&mut self) {
+    let frame = match self.last_frame.clone() {
+      Some(frame) => frame,
+      None => { return; },
+    };
+    let (width, height) = (self.width(), self.height());
+    let (fg, bg) = self.clear_attributes;
+
+    self.clear();
+    self.blit_last_frame(&frame);
+    box_drawing::draw_box(self, 0, 0, width, height, LineStyle::Light, fg, bg);
+    self.present_now();
+    self.last_frame = Some(frame);
+  }
+
+  // Applies built-in handling to an event read from termbox. Returns `None` if the event was
+  // consumed.
+  fn process_event (&mut self, event: Event) -> Option<Event> {
+    if let Event::Key(key) = event {
+      if self.job_control && key.key == KEY_CTRL_Z && key.ch.is_none() && !key.alt {
+        self.suspend_for_job_control();
+        return None;
+      }
     }
+
+    return self.handle_event(event);
   }
 
-  /// Changes a single character cell.
-  pub fn put_cell (&mut self, x: Coord, y: Coord, cell: Cell) {
-    unsafe {
-      ffi::tb_put_cell(x, y, &cell);
+  fn query_pixel_size (&mut self) -> Option<PixelSize> {
+    let (cols, rows) = (self.width().max(1) as u32, self.height().max(1) as u32);
+
+    if let Some((width, height, ws_cols, ws_rows)) = self.tty.pixel_size() {
+      let (width, height) = (width as u32, height as u32);
+      return Some(PixelSize {
+        width: width,
+        height: height,
+        cell_width: width / ws_cols as u32,
+        cell_height: height / ws_rows as u32,
+      });
+    }
+
+    // XTWINOPS 16 reports the cell size as `ESC [ 6 ; height ; width t`.
+    if self.tty.write(b"\x1b[16t").is_err() {
+      return None;
+    }
+    let is_reply = |reply: &[u8]| reply.starts_with(b"\x1b[6;") && reply.ends_with(b"t");
+    let reply = match self.read_reply(PIXEL_SIZE_TIMEOUT, is_reply) {
+      Some(reply) => reply,
+      None => { return None; },
+    };
+    let fields: Vec<u32> = String::from_utf8_lossy(&reply)
+      .trim_start_matches("\x1b[")
+      .trim_end_matches('t')
+      .split(';')
+      .filter_map(|field| field.parse().ok())
+      .collect();
+
+    match fields[..] {
+      [6, cell_height, cell_width] if cell_width > 0 && cell_height > 0 => {
+        return Some(PixelSize {
+          width: cell_width * cols,
+          height: cell_height * rows,
+          cell_width: cell_width,
+          cell_height: cell_height,
+        });
+      },
+      _ => { return None; },
     }
   }
 
-  /// Writes a horizontal sequence of character cells without wrapping. This is just a quick and
-  /// dirty way to write strings without providing many options.
-  pub fn put_str (&mut self, x: Coord, y: Coord, msg: &str, fg: Attribute, bg: Attribute) {
+  // Reinitializes termbox after `release`, restoring its modes and presenting the saved output
+  // buffer again.
+  pub(crate) fn reacquire (&mut self, released: Released) -> Result<()> {
+    match (self.init)() {
+      0 => { internal::set_active(); },
+      n => { return Err(Error::from_raw(n).unwrap()); },
+    }
+
     unsafe {
-      let mut x = x;
-      for ch in msg.chars() {
-        ffi::tb_change_cell(x, y, ch as u32, fg, bg);
-        x += 1;
+      ffi::tb_select_input_mode(released.input_mode);
+      #[cfg(feature = "pure-rust")]
+      ffi::tb_select_escape_delay(released.escape_delay);
+      #[cfg(feature = "pure-rust")]
+      ffi::tb_select_output_buffer_capacity(released.output_buffer_capacity);
+      #[cfg(feature = "pure-rust")]
+      for entry in &released.key_sequences {
+        ffi::tb_register_key_sequence(&entry.0, entry.1);
       }
+      ffi::tb_set_clear_attributes(self.clear_attributes.0, self.clear_attributes.1);
     }
+    self.set_output_mode(released.output_mode);
+    self.clear();
+
+    // The terminal may have been resized while it was released.
+    let snapshot = released.snapshot;
+    let w = snapshot.width().min(self.width());
+    let h = snapshot.height().min(self.height());
+    for y in 0..h {
+      for x in 0..w {
+        if let Some(cell) = snapshot.get(x, y) {
+          self.put_cell(x, y, *cell);
+        }
+      }
+    }
+
+    // The screen was redrawn from scratch, so images have to be sent again.
+    self.placed_images.clear();
+    self.sent_images.clear();
+
+    self.cursor_dirty = true;
+    self.size = (self.width(), self.height());
+    self.present_now();
+    return Ok(());
   }
 
-  /// Sets what attributes should be used when clearing the output buffer with `clear`.
-  pub fn set_clear_attributes (&mut self, fg: Attribute, bg: Attribute) {
-    unsafe {
-      ffi::tb_set_clear_attributes(fg, bg);
-    }
+  // Reads input until the terminal sends an escape sequence for which `is_reply` returns true, or
+  // `timeout` milliseconds pass. Other input is left for `peek_event` and `poll_event`.
+  #[cfg(feature = "pure-rust")]
+  fn read_reply<F> (&mut self, timeout: Time, is_reply: F) -> Option<Vec<u8>>
+    where F: Fn(&[u8]) -> bool
+  {
+    unsafe { ffi::tb_read_reply(timeout, &is_reply) }
   }
 
-  /// Sets the position of the cursor. If invalid coordinates are provided, the cursor is hidden.
-  pub fn set_cursor (&mut self, x: Coord, y: Coord) {
-    unsafe {
-      ffi::tb_set_cursor(x, y);
+  // Reads input until the terminal sends an escape sequence for which `is_reply` returns true, or
+  // `timeout` milliseconds pass, and queues the other input for `peek_event` and `poll_event`.
+  // The C library can't be given input back, so the reply is read from the terminal directly and
+  // the other input is decoded here. Only characters and control keys are recognized in it; any
+  // other escape sequences are dropped.
+  #[cfg(not(feature = "pure-rust"))]
+  fn read_reply<F> (&mut self, timeout: Time, is_reply: F) -> Option<Vec<u8>>
+    where F: Fn(&[u8]) -> bool
+  {
+    let (reply, mut input) = self.tty.read_reply(timeout, is_reply);
+
+    while !input.is_empty() {
+      let (key, ch, alt, len) = match input[0] {
+        0x1b => {
+          match tty::sequence_len(&input) {
+            None if input.len() == 1 => (KEY_ESC, None, false, 1),
+            None => { break; },
+            Some(2) if input[1] >= 0x20 && input[1] < 0x7f => (0, Some(input[1] as char), true, 2),
+            Some(len) => {
+              input.drain(..len);
+              continue;
+            },
+          }
+        },
+        byte @ 0x00..=0x20 | byte @ 0x7f => (byte as Key, None, false, 1),
+        byte => {
+          let len = match byte {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+          };
+          let len = len.min(input.len());
+          match std::str::from_utf8(&input[..len]).ok().and_then(|s| s.chars().next()) {
+            Some(ch) => (0, Some(ch), false, len),
+            None => {
+              input.drain(..len);
+              continue;
+            },
+          }
+        },
+      };
+
+      input.drain(..len);
+      self.queued_input.push_back(Event::Key(KeyEvent {
+        key: key,
+        ch: ch,
+        alt: alt,
+        state: KeyState::Press,
+      }));
     }
+
+    return reply;
   }
 
-  /// Sets the method termbox should use to handle ESC sequences in the input buffer.
-  pub fn set_input_mode (&mut self, mode: InputMode) {
-    unsafe {
-      let prev_mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
-      let flags = prev_mode & !INPUT_MODE_MASK;
-      ffi::tb_select_input_mode(mode.to_raw() | flags);
+  // Appends an event to the recording started with `start_recording`, if any. Recording stops if
+  // the event can't be written.
+  fn recorded (&mut self, event: Event) -> Event {
+    let failed = match self.recorder {
+      Some(ref mut recorder) => recorder.record(&event).is_err(),
+      None => false,
+    };
+    if failed {
+      self.recorder = None;
     }
+    return event;
   }
 
-  /// Enables or disables mouse events. Mouse events are disabled by default.
-  pub fn set_mouse_enabled (&mut self, enabled: bool) {
-    unsafe {
-      let prev_mode = ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT);
-      let new_mode;
+  // Saves the state of termbox and shuts it down, restoring the terminal to the state it was in
+  // before termbox was initialized.
+  pub(crate) fn release (&mut self) -> Released {
+    let released = Released {
+      snapshot: self.snapshot(),
+      input_mode: unsafe { ffi::tb_select_input_mode(ffi::TB_INPUT_CURRENT) },
+      #[cfg(feature = "pure-rust")]
+      escape_delay: unsafe { ffi::tb_select_escape_delay(-1) },
+      #[cfg(feature = "pure-rust")]
+      output_buffer_capacity: unsafe { ffi::tb_select_output_buffer_capacity(-1) },
+      #[cfg(feature = "pure-rust")]
+      key_sequences: unsafe { ffi::tb_key_sequences() },
+      output_mode: self.output_mode(),
+    };
+    internal::deactivate();
+    return released;
+  }
 
-      if enabled {
-        new_mode = prev_mode | ffi::TB_INPUT_MOUSE;
-      } else {
-        new_mode = prev_mode & !ffi::TB_INPUT_MOUSE;
+  // Passes a cell drawn to the output buffer to the draw hook, if there is one.
+  fn report_drawn (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    if self.draw_hook.is_none() {
+      return;
+    }
+    let (width, height) = (self.width(), self.height());
+    if let Some(ref mut hook) = self.draw_hook {
+      if x >= 0 && y >= 0 && x < width && y < height {
+        hook.cell(x, y, ch, fg, bg);
       }
+    }
+  }
 
-      if new_mode != prev_mode {
-        ffi::tb_select_input_mode(new_mode);
-      }
+  // Reports the first `len` cells of row `y` to the draw hook, if there is one.
+  fn report_row (&mut self, y: Coord, len: Coord) {
+    if self.draw_hook.is_none() {
+      return;
+    }
+    let start = (y * self.width()) as usize;
+    for x in 0..len {
+      let cell = self.cell_buffer()[start + x as usize];
+      self.report_drawn(x, y, cell.ch(), cell.fg, cell.bg);
     }
   }
 
-  /// Sets the method termbox should use to interpret output attributes.
-  pub fn set_output_mode (&mut self, mode: OutputMode) {
-    unsafe {
-      ffi::tb_select_output_mode(mode.to_raw());
+  // Suspends the process on behalf of `set_job_control`. There is no caller to report failure to,
+  // so it is reported with `Event::ResumeFailed` instead.
+  fn suspend_for_job_control (&mut self) {
+    if self.suspend().is_err() {
+      self.resume_failed = true;
     }
   }
 
-  /// Returns the width of the output buffer in character cells.
-  pub fn width (&self) -> Coord {
-    unsafe {
-      ffi::tb_width()
+  // Carries out a requested present if it is due, and returns how long the next wait for input may
+  // last before the event loop needs to run again, or `None` if it may wait indefinitely.
+  fn wait_limit (&mut self) -> Option<Time> {
+    if self.pending_present_delay() == Some(0) {
+      self.present_now();
+    }
+
+    let mut limit = self.pending_present_delay();
+    if let Some((_, deadline)) = self.pending_resize {
+      let resize_limit = millis_until(deadline);
+      limit = Some(limit.map_or(resize_limit, |limit| limit.min(resize_limit)));
+    }
+    let deadlines = [
+      self.timers.next_deadline(),
+      self.player.as_ref().and_then(|player| player.next_deadline()),
+    ];
+    for deadline in deadlines.iter().filter_map(|&deadline| deadline) {
+      let deadline_limit = millis_until(deadline);
+      limit = Some(limit.map_or(deadline_limit, |limit| limit.min(deadline_limit)));
+    }
+    return limit;
+  }
+
+  // Waits up to `limit` milliseconds, or indefinitely, for an event from termbox. While something
+  // other than the terminal can wake the event loop, such as an injector, a task or a caught signal,
+  // the wait also ends when it does, returning `None`.
+  fn wait_raw_event (&self, limit: Option<Time>) -> Option<Event> {
+    #[allow(unused_mut)]
+    let mut wakeable = signals::is_enabled() || signals::is_stop_enabled()
+      || self.injected.is_listening();
+    #[cfg(feature = "async")]
+    {
+      wakeable = wakeable || self.tasks.is_busy();
+    }
+
+    if !wakeable {
+      return match limit {
+        Some(limit) => self.peek_raw_event(limit),
+        None => Some(self.poll_raw_event()),
+      };
+    }
+
+    // Termbox can only wait for the terminal, so the wait happens here instead, and termbox is only
+    // asked for events it can return right away. Input it has already read is returned first.
+    if let Some(event) = self.peek_raw_event(0) {
+      return Some(event);
     }
+    wake::wait(self.tty.input_fd(), limit);
+    return self.peek_raw_event(0);
+  }
+}
+
+// The state of termbox saved by `Termbox::release`.
+pub(crate) struct Released {
+  snapshot: Snapshot,
+  input_mode: c_int,
+  #[cfg(feature = "pure-rust")]
+  escape_delay: c_int,
+  #[cfg(feature = "pure-rust")]
+  output_buffer_capacity: c_int,
+  #[cfg(feature = "pure-rust")]
+  key_sequences: Vec<(Vec<u8>, Key)>,
+  output_mode: OutputMode,
+}
+
+// Returns the number of milliseconds from now until `instant`, or zero if it has passed.
+fn millis_until (instant: Instant) -> Time {
+  let now = Instant::now();
+  if instant <= now {
+    0
+  } else {
+    let millis = (instant - now).as_millis();
+    if millis > Time::max_value() as u128 { Time::max_value() } else { millis as Time }
+  }
+}
+
+// Returns the number of bytes written to the terminal by the last call to `tb_present`, if known.
+#[cfg(feature = "pure-rust")]
+fn present_bytes () -> Option<usize> {
+  unsafe {
+    return Some(ffi::tb_present_bytes());
+  }
+}
+
+#[cfg(not(feature = "pure-rust"))]
+fn present_bytes () -> Option<usize> {
+  None
+}
+
+// Determines whether `next` makes `event` out of date, so that only `next` needs to be handled. A
+// press of the button that was already pressed, with no release in between, reports a drag.
+fn supersedes (next: &Event, event: &Event) -> bool {
+  match (next, event) {
+    (Event::Resize(_), Event::Resize(_)) => true,
+    (Event::Mouse(next), Event::Mouse(event)) => {
+      let buttons = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+      buttons.contains(&event.button) && next.button == event.button && next.shift == event.shift
+        && next.alt == event.alt && next.ctrl == event.ctrl
+    },
+    _ => false,
   }
 }
 
 impl Drop for Termbox {
   fn drop (&mut self) {
-    unsafe {
-      ffi::tb_shutdown();
-    }
+    internal::shutdown();
   }
 }
@@ -0,0 +1,92 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::fmt::Write;
+
+use sgr;
+use width;
+use {
+  Cell,
+  Coord,
+  OutputMode,
+};
+
+
+//
+// Link
+//
+
+
+/// A run of cells that should be made clickable the next time the output buffer is presented.
+pub struct Link {
+  pub x: Coord,
+  pub y: Coord,
+  pub end: Coord,
+  pub url: String,
+}
+
+impl Link {
+  /// Creates a link covering the cells from `x` up to `end` on row `y`. Characters in `url` that
+  /// are not printable ASCII, including control characters that would end the escape sequence
+  /// early, are percent-encoded.
+  pub fn new (x: Coord, y: Coord, end: Coord, url: &str) -> Link {
+    let mut encoded = String::with_capacity(url.len());
+    for &byte in url.as_bytes() {
+      if byte > b' ' && byte < 0x7f {
+        encoded.push(byte as char);
+      } else {
+        let _ = write!(encoded, "%{:02X}", byte);
+      }
+    }
+
+    Link {
+      x: x,
+      y: y,
+      end: end,
+      url: encoded,
+    }
+  }
+
+  /// Appends escape sequences that rewrite the link's cells from `buffer` wrapped in an OSC 8
+  /// hyperlink, passing each character through `map` as termbox's output was. The cursor position
+  /// and attributes are saved and restored around it so termbox's idea of the terminal state
+  /// remains correct.
+  pub fn write<F> (&self, out: &mut String, buffer: &[Cell], width: Coord, height: Coord,
+                   mode: OutputMode, map: F)
+    where F: Fn(char) -> char
+  {
+    if self.y < 0 || self.y >= height {
+      return;
+    }
+
+    // Cells are clipped to the buffer, as termbox would have done when they were written.
+    let x = self.x.max(0);
+    let end = self.end.min(width);
+    if x >= end {
+      return;
+    }
+
+    out.push_str("\x1b7");
+    let _ = write!(out, "\x1b[{};{}H", self.y + 1, x + 1);
+    let _ = write!(out, "\x1b]8;;{}\x1b\\", self.url);
+
+    let row = (self.y * width) as usize;
+    let mut attributes = None;
+    let mut col = x;
+    while col < end {
+      let cell = buffer[row + col as usize];
+      if attributes != Some((cell.fg(), cell.bg())) {
+        sgr::write_attributes(out, cell.fg(), cell.bg(), mode);
+        attributes = Some((cell.fg(), cell.bg()));
+      }
+      let ch = map(cell.ch());
+      out.push(ch);
+      // A wide character covers the following cell, as in termbox's own output.
+      col += (width::char_width(ch) as Coord).max(1);
+    }
+
+    out.push_str("\x1b]8;;\x1b\\");
+    out.push_str("\x1b8");
+  }
+}
@@ -0,0 +1,31 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! The default xterm 256 color palette, used wherever colors must be converted to RGB.
+
+// The 16 system colors as displayed by xterm.
+const SYSTEM: [(u8, u8, u8); 16] = [
+  (0x00, 0x00, 0x00), (0xcd, 0x00, 0x00), (0x00, 0xcd, 0x00), (0xcd, 0xcd, 0x00),
+  (0x00, 0x00, 0xee), (0xcd, 0x00, 0xcd), (0x00, 0xcd, 0xcd), (0xe5, 0xe5, 0xe5),
+  (0x7f, 0x7f, 0x7f), (0xff, 0x00, 0x00), (0x00, 0xff, 0x00), (0xff, 0xff, 0x00),
+  (0x5c, 0x5c, 0xff), (0xff, 0x00, 0xff), (0x00, 0xff, 0xff), (0xff, 0xff, 0xff),
+];
+
+// Intensities of the six steps of each channel in the 6x6x6 color cube.
+pub const CUBE_STEPS: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+
+/// Returns the RGB value of a palette index.
+pub fn rgb (index: u8) -> (u8, u8, u8) {
+  match index {
+    0..=15 => SYSTEM[index as usize],
+    16..=231 => {
+      let n = index - 16;
+      (CUBE_STEPS[(n / 36) as usize], CUBE_STEPS[(n / 6 % 6) as usize], CUBE_STEPS[(n % 6) as usize])
+    },
+    _ => {
+      let level = 8 + (index - 232) * 10;
+      (level, level, level)
+    },
+  }
+}
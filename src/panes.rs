@@ -0,0 +1,699 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Splits the screen into panes, as terminal multiplexers do. A `PaneTree` divides an area with
+//! horizontal and vertical splits, each with a one-cell divider that can be dragged with the
+//! mouse, and keeps track of which pane has the focus. Panes are drawn through a `PanePainter`,
+//! which keeps whatever is drawn inside the pane.
+//!
+//! ~~~
+//! use termbox::{Rect, DEFAULT};
+//! use termbox::headless::TestTermbox;
+//! use termbox::layout::Direction;
+//! use termbox::panes::PaneTree;
+//!
+//! let mut panes = PaneTree::new();
+//! let left = panes.focused();
+//! let right = panes.split(left, Direction::Horizontal).unwrap();
+//! assert_eq!(panes.panes(), vec![left, right]);
+//!
+//! let mut tb = TestTermbox::new(9, 2);
+//! panes.paint(&mut tb, Rect::new(0, 0, 9, 2), |pane, painter| {
+//!   let name = if pane == left { "left" } else { "right" };
+//!   painter.put_str(0, 0, name, DEFAULT, DEFAULT);
+//! });
+//! assert_eq!(tb.snapshot().to_plain_text(), "left│righ\n    │\n");
+//! ~~~
+
+use std::mem;
+
+use box_drawing::{
+  self,
+  LineStyle,
+};
+use layout::Direction;
+use theme::Style;
+use width;
+use {
+  Attribute,
+  Backend,
+  Cell,
+  Coord,
+  MouseButton,
+  MouseEvent,
+  Rect,
+  DEFAULT,
+};
+
+// The share of a split given to the pane being split.
+const EVEN_RATIO: f32 = 0.5;
+
+
+//
+// PaneId
+//
+
+
+/// Identifies a pane of a `PaneTree`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PaneId(u64);
+
+
+//
+// PaneLayout
+//
+
+
+/// The splits of a `PaneTree`, as saved in a `PaneTreeState`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PaneLayout {
+  Pane(PaneId),
+  /// A split of the area into two sides, the first being on the left or at the top.
+  Split {
+    direction: Direction,
+    /// The share of the space, not counting the divider, given to `first`, from 0.0 to 1.0.
+    ratio: f32,
+    first: Box<PaneLayout>,
+    second: Box<PaneLayout>,
+  },
+}
+
+impl PaneLayout {
+  fn collect_panes (&self, panes: &mut Vec<PaneId>) {
+    match *self {
+      PaneLayout::Pane(pane) => { panes.push(pane); },
+      PaneLayout::Split { ref first, ref second, .. } => {
+        first.collect_panes(panes);
+        second.collect_panes(panes);
+      },
+    }
+  }
+}
+
+
+//
+// PanePainter
+//
+
+
+/// Draws inside one pane, passed to the closure given to `PaneTree::paint`. Coordinates are
+/// relative to the top left corner of the pane, and anything outside of the pane is left out.
+pub struct PanePainter<'a, B: 'a + Backend> {
+  tb: &'a mut B,
+  rect: Rect,
+  focused: bool,
+}
+
+impl<'a, B: Backend> PanePainter<'a, B> {
+  /// Returns the backend being drawn, for drawing widgets that take a rectangle, which should be
+  /// given `rect`. Nothing keeps what is drawn this way inside the pane.
+  pub fn backend (&mut self) -> &mut B {
+    self.tb
+  }
+
+  /// Changes a single cell. Cells outside of the pane are ignored.
+  pub fn change_cell (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    self.put_cell(x, y, Cell::new(ch, fg, bg));
+  }
+
+  /// Fills the pane with spaces in the given colors.
+  pub fn clear (&mut self, fg: Attribute, bg: Attribute) {
+    for y in 0..self.rect.h {
+      for x in 0..self.rect.w {
+        self.change_cell(x, y, ' ', fg, bg);
+      }
+    }
+  }
+
+  /// Returns the height of the pane in character cells.
+  pub fn height (&self) -> Coord {
+    self.rect.h
+  }
+
+  /// Determines whether the pane has the focus.
+  pub fn is_focused (&self) -> bool {
+    self.focused
+  }
+
+  /// Changes a single cell. Cells outside of the pane are ignored.
+  pub fn put_cell (&mut self, x: Coord, y: Coord, cell: Cell) {
+    if x < 0 || y < 0 || x >= self.rect.w || y >= self.rect.h {
+      return;
+    }
+    self.tb.put_cell(self.rect.x + x, self.rect.y + y, cell);
+  }
+
+  /// Writes a horizontal sequence of character cells without wrapping. Wide characters that
+  /// would reach past the right edge of the pane are left out. Returns the number of cells that
+  /// were inside the pane.
+  pub fn put_str (&mut self, x: Coord, y: Coord, msg: &str, fg: Attribute, bg: Attribute)
+    -> Coord
+  {
+    if y < 0 || y >= self.rect.h {
+      return 0;
+    }
+
+    let mut x = x;
+    let mut written = 0;
+    for ch in msg.chars() {
+      let ch_width = (width::char_width(ch) as Coord).max(1);
+      if x >= self.rect.w {
+        break;
+      }
+      if x >= 0 && x + ch_width <= self.rect.w {
+        self.change_cell(x, y, ch, fg, bg);
+        written += ch_width;
+      }
+      x += ch_width;
+    }
+    return written;
+  }
+
+  /// Returns the area of the pane in the output buffer.
+  pub fn rect (&self) -> Rect {
+    self.rect
+  }
+
+  /// Sets the position of the cursor. The cursor is hidden if the position is outside of the
+  /// pane.
+  pub fn set_cursor (&mut self, x: Coord, y: Coord) {
+    if x < 0 || y < 0 || x >= self.rect.w || y >= self.rect.h {
+      self.tb.hide_cursor();
+    } else {
+      self.tb.set_cursor(self.rect.x + x, self.rect.y + y);
+    }
+  }
+
+  /// Returns the width of the pane in character cells.
+  pub fn width (&self) -> Coord {
+    self.rect.w
+  }
+}
+
+
+//
+// PaneTree
+//
+
+
+/// A tree of splits dividing an area into panes. The tree doesn't hold on to the area, which is
+/// passed to each method that needs it, so it can follow the size of the terminal without being
+/// told about resizes. Every tree has at least one pane.
+pub struct PaneTree {
+  root: Node,
+  focused: PaneId,
+  next_id: u64,
+  // The split whose divider is being dragged.
+  dragging: Option<u64>,
+  line_style: LineStyle,
+  divider_style: Style,
+}
+
+enum Node {
+  Pane(PaneId),
+  Split(Box<Split>),
+}
+
+struct Split {
+  id: u64,
+  direction: Direction,
+  // The share of the space, not counting the divider, given to `first`.
+  ratio: f32,
+  first: Node,
+  second: Node,
+}
+
+// A divider placed by `PaneTree::place`.
+struct Divider {
+  split: u64,
+  direction: Direction,
+  // The area of the whole split, and of the divider within it.
+  area: Rect,
+  rect: Rect,
+}
+
+impl PaneTree {
+  /// Removes a pane, giving its space to the pane or panes it was split from. If the pane had the
+  /// focus, it moves to the first pane in that space. Returns false if there is no such pane or
+  /// it is the last one.
+  pub fn close (&mut self, pane: PaneId) -> bool {
+    match remove_pane(&mut self.root, pane) {
+      Some(neighbor) => {
+        if self.focused == pane {
+          self.focused = neighbor;
+        }
+        true
+      },
+      None => false,
+    }
+  }
+
+  /// Returns the style dividers are drawn in. See `set_divider_style`.
+  pub fn divider_style (&self) -> Style {
+    self.divider_style
+  }
+
+  /// Moves the focus to the pane after the focused one in the order of `panes`, wrapping around
+  /// to the first.
+  pub fn focus_next (&mut self) {
+    let panes = self.panes();
+    if let Some(index) = panes.iter().position(|&pane| pane == self.focused) {
+      self.focused = panes[(index + 1) % panes.len()];
+    }
+  }
+
+  /// Moves the focus to the pane before the focused one in the order of `panes`, wrapping around
+  /// to the last.
+  pub fn focus_prev (&mut self) {
+    let panes = self.panes();
+    if let Some(index) = panes.iter().position(|&pane| pane == self.focused) {
+      self.focused = panes[(index + panes.len() - 1) % panes.len()];
+    }
+  }
+
+  /// Returns the pane with the focus.
+  pub fn focused (&self) -> PaneId {
+    self.focused
+  }
+
+  /// Handles a mouse event in a tree laid out in `area`. Pressing the left button on a divider
+  /// and dragging moves it, and pressing it in a pane gives that pane the focus. Mouse events
+  /// must be enabled for this to be of use. Returns true if the event was used to move a divider,
+  /// in which case the program shouldn't handle it further; clicks in panes return false even
+  /// though they move the focus, so that the pane can handle them too.
+  pub fn handle_mouse (&mut self, area: Rect, event: &MouseEvent) -> bool {
+    match event.button {
+      MouseButton::Left => {},
+      MouseButton::Release => { return self.dragging.take().is_some(); },
+      _ => { return false; },
+    }
+
+    let (panes, dividers) = self.place(area);
+    if let Some(split) = self.dragging {
+      if let Some(divider) = dividers.iter().find(|divider| divider.split == split) {
+        let (pos, start, len) = match divider.direction {
+          Direction::Horizontal => (event.x, divider.area.x, divider.area.w),
+          Direction::Vertical => (event.y, divider.area.y, divider.area.h),
+        };
+        let available = (len - 1).max(1);
+        let ratio = (pos - start) as f32 / available as f32;
+        if let Some(split) = find_split(&mut self.root, split) {
+          split.ratio = ratio.clamp(0.0, 1.0);
+        }
+      }
+      return true;
+    }
+
+    let (x, y) = (event.x, event.y);
+    if let Some(divider) = dividers.iter().find(|divider| divider.rect.contains(x, y)) {
+      self.dragging = Some(divider.split);
+      return true;
+    }
+    if let Some(&(pane, _)) = panes.iter().find(|&&(_, rect)| rect.contains(x, y)) {
+      self.focused = pane;
+    }
+    return false;
+  }
+
+  /// Determines whether a divider is being dragged with the mouse.
+  pub fn is_dragging (&self) -> bool {
+    self.dragging.is_some()
+  }
+
+  /// Returns the area of each pane when the tree is laid out in `area`, in the order of `panes`.
+  pub fn layout (&self, area: Rect) -> Vec<(PaneId, Rect)> {
+    self.place(area).0
+  }
+
+  /// Returns the stroke dividers are drawn with. See `set_line_style`.
+  pub fn line_style (&self) -> LineStyle {
+    self.line_style
+  }
+
+  /// Creates a tree with a single pane, which has the focus.
+  pub fn new () -> PaneTree {
+    PaneTree {
+      root: Node::Pane(PaneId(0)),
+      focused: PaneId(0),
+      next_id: 1,
+      dragging: None,
+      line_style: LineStyle::Light,
+      divider_style: Style::new(DEFAULT, DEFAULT, 0),
+    }
+  }
+
+  /// Draws the dividers of the tree laid out in `area`, joined where they meet, and calls `paint`
+  /// with a painter for each pane in the order of `panes`. Panes aren't cleared first, and
+  /// dividers merge with lines already in the output buffer, so the output buffer should be
+  /// cleared before painting, as for any other frame.
+  pub fn paint<B, F> (&self, tb: &mut B, area: Rect, mut paint: F)
+    where B: Backend, F: FnMut(PaneId, &mut PanePainter<B>)
+  {
+    let (panes, dividers) = self.place(area);
+    let (fg, bg) = self.divider_style.attributes();
+
+    // Dividers come outermost first, so each one can be extended into the dividers at its ends,
+    // which joins them.
+    for divider in &dividers {
+      let rect = divider.rect;
+      match divider.direction {
+        Direction::Horizontal => {
+          let top = if rect.y > area.y { rect.y - 1 } else { rect.y };
+          let end = rect.y + rect.h;
+          let bottom = if end < area.y + area.h { end + 1 } else { end };
+          box_drawing::vline(tb, rect.x, top, bottom - top, self.line_style, fg, bg);
+        },
+        Direction::Vertical => {
+          let left = if rect.x > area.x { rect.x - 1 } else { rect.x };
+          let end = rect.x + rect.w;
+          let right = if end < area.x + area.w { end + 1 } else { end };
+          box_drawing::hline(tb, left, rect.y, right - left, self.line_style, fg, bg);
+        },
+      }
+    }
+
+    for (pane, rect) in panes {
+      let mut painter = PanePainter {
+        tb: &mut *tb,
+        rect: rect,
+        focused: pane == self.focused,
+      };
+      paint(pane, &mut painter);
+    }
+  }
+
+  /// Returns the pane at the given position when the tree is laid out in `area`, if any.
+  pub fn pane_at (&self, area: Rect, x: Coord, y: Coord) -> Option<PaneId> {
+    self.layout(area).into_iter().find(|&(_, rect)| rect.contains(x, y)).map(|(pane, _)| pane)
+  }
+
+  /// Returns every pane, from left to right and top to bottom.
+  pub fn panes (&self) -> Vec<PaneId> {
+    self.layout(Rect::new(0, 0, 0, 0)).into_iter().map(|(pane, _)| pane).collect()
+  }
+
+  /// Returns the area of a pane when the tree is laid out in `area`, or `None` if there is no
+  /// such pane.
+  pub fn rect (&self, area: Rect, pane: PaneId) -> Option<Rect> {
+    self.layout(area).into_iter().find(|&(id, _)| id == pane).map(|(_, rect)| rect)
+  }
+
+  /// Sets the style dividers are drawn in. The default is `DEFAULT` on `DEFAULT`.
+  pub fn set_divider_style (&mut self, style: Style) {
+    self.divider_style = style;
+  }
+
+  /// Gives a pane the focus. Returns false if there is no such pane.
+  pub fn set_focus (&mut self, pane: PaneId) -> bool {
+    if !self.panes().contains(&pane) {
+      return false;
+    }
+    self.focused = pane;
+    return true;
+  }
+
+  /// Sets the stroke dividers are drawn with. The default is `LineStyle::Light`.
+  pub fn set_line_style (&mut self, style: LineStyle) {
+    self.line_style = style;
+  }
+
+  /// Resizes a pane by setting its share, from 0.0 to 1.0, of the split it is directly part of,
+  /// not counting the divider. Returns false if there is no such pane or it is the only one.
+  pub fn set_ratio (&mut self, pane: PaneId, ratio: f32) -> bool {
+    let ratio = ratio.clamp(0.0, 1.0);
+    match find_parent(&mut self.root, pane) {
+      Some(split) => {
+        split.ratio = if split.first.is_pane(pane) { ratio } else { 1.0 - ratio };
+        true
+      },
+      None => false,
+    }
+  }
+
+  /// Replaces the splits and focus with those saved with `state`. Panes keep their ids, so a
+  /// program can match them up with what it shows in each. Returns false, leaving the tree as it
+  /// was, if a pane appears more than once or the focused pane is missing.
+  pub fn set_state (&mut self, state: &PaneTreeState) -> bool {
+    let mut panes = Vec::new();
+    state.layout.collect_panes(&mut panes);
+    let count = panes.len();
+    panes.sort();
+    panes.dedup();
+    if panes.len() != count || !panes.contains(&state.focused) {
+      return false;
+    }
+
+    // Splits are numbered after the panes, since both are given ids from the same counter.
+    self.next_id = panes.last().map_or(0, |pane| pane.0 + 1);
+    self.root = self.build_node(&state.layout);
+    self.focused = state.focused;
+    self.dragging = None;
+    return true;
+  }
+
+  /// Splits a pane in two, giving the new pane the right half for `Direction::Horizontal` or the
+  /// bottom half for `Direction::Vertical`. The focus stays where it is. Returns the new pane, or
+  /// `None` if there is no such pane.
+  pub fn split (&mut self, pane: PaneId, direction: Direction) -> Option<PaneId> {
+    let split_id = self.next_id;
+    let new_pane = PaneId(self.next_id + 1);
+    let node = find_pane(&mut self.root, pane)?;
+    self.next_id += 2;
+
+    *node = Node::Split(Box::new(Split {
+      id: split_id,
+      direction: direction,
+      ratio: EVEN_RATIO,
+      first: Node::Pane(pane),
+      second: Node::Pane(new_pane),
+    }));
+    return Some(new_pane);
+  }
+
+  /// Returns the splits and focus, for restoring with `set_state`. What is drawn in the panes and
+  /// the styles of the dividers are not included.
+  pub fn state (&self) -> PaneTreeState {
+    PaneTreeState {
+      layout: self.root.to_layout(),
+      focused: self.focused,
+    }
+  }
+
+  // Builds the node for `layout`, giving each split a new id.
+  fn build_node (&mut self, layout: &PaneLayout) -> Node {
+    match *layout {
+      PaneLayout::Pane(pane) => Node::Pane(pane),
+      PaneLayout::Split { direction, ratio, ref first, ref second } => {
+        let id = self.next_id;
+        self.next_id += 1;
+        Node::Split(Box::new(Split {
+          id: id,
+          direction: direction,
+          ratio: if ratio.is_nan() { EVEN_RATIO } else { ratio.clamp(0.0, 1.0) },
+          first: self.build_node(first),
+          second: self.build_node(second),
+        }))
+      },
+    }
+  }
+
+  // Returns the area of each pane and divider when the tree is laid out in `area`.
+  fn place (&self, area: Rect) -> (Vec<(PaneId, Rect)>, Vec<Divider>) {
+    let mut panes = Vec::new();
+    let mut dividers = Vec::new();
+    place_node(&self.root, area, &mut panes, &mut dividers);
+    return (panes, dividers);
+  }
+}
+
+impl Default for PaneTree {
+  fn default () -> PaneTree {
+    PaneTree::new()
+  }
+}
+
+impl Node {
+  fn is_pane (&self, pane: PaneId) -> bool {
+    match *self {
+      Node::Pane(id) => id == pane,
+      Node::Split(_) => false,
+    }
+  }
+
+  fn first_pane (&self) -> PaneId {
+    match *self {
+      Node::Pane(id) => id,
+      Node::Split(ref split) => split.first.first_pane(),
+    }
+  }
+
+  fn to_layout (&self) -> PaneLayout {
+    match *self {
+      Node::Pane(id) => PaneLayout::Pane(id),
+      Node::Split(ref split) => PaneLayout::Split {
+        direction: split.direction,
+        ratio: split.ratio,
+        first: Box::new(split.first.to_layout()),
+        second: Box::new(split.second.to_layout()),
+      },
+    }
+  }
+}
+
+impl Split {
+  // Divides `area` into the areas of the first side, the divider and the second side.
+  fn divide (&self, area: Rect) -> (Rect, Rect, Rect) {
+    let len = match self.direction {
+      Direction::Horizontal => area.w,
+      Direction::Vertical => area.h,
+    };
+    let available = (len - 1).max(0);
+    let first = ((available as f32 * self.ratio).round() as Coord).clamp(0, available);
+    let divider = len.min(1);
+    let second = available - first;
+
+    match self.direction {
+      Direction::Horizontal => (
+        Rect::new(area.x, area.y, first, area.h),
+        Rect::new(area.x + first, area.y, divider, area.h),
+        Rect::new(area.x + first + divider, area.y, second, area.h),
+      ),
+      Direction::Vertical => (
+        Rect::new(area.x, area.y, area.w, first),
+        Rect::new(area.x, area.y + first, area.w, divider),
+        Rect::new(area.x, area.y + first + divider, area.w, second),
+      ),
+    }
+  }
+}
+
+
+//
+// PaneTreeState
+//
+
+
+/// The splits and focus of a `PaneTree`, so that a program can save its layout when it exits and
+/// restore it when it starts again. With the `serde` feature, it can be serialized.
+///
+/// ~~~
+/// use termbox::layout::Direction;
+/// use termbox::panes::PaneTree;
+///
+/// let mut panes = PaneTree::new();
+/// let left = panes.focused();
+/// let right = panes.split(left, Direction::Horizontal).unwrap();
+/// panes.set_ratio(left, 0.25);
+/// panes.set_focus(right);
+///
+/// let mut restored = PaneTree::new();
+/// assert!(restored.set_state(&panes.state()));
+/// assert_eq!(restored.panes(), vec![left, right]);
+/// assert_eq!(restored.focused(), right);
+/// assert_eq!(restored.state(), panes.state());
+/// ~~~
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PaneTreeState {
+  pub layout: PaneLayout,
+  pub focused: PaneId,
+}
+
+
+//
+// Functions
+//
+
+
+// Returns the node holding `pane`.
+fn find_pane (node: &mut Node, pane: PaneId) -> Option<&mut Node> {
+  if node.is_pane(pane) {
+    return Some(node);
+  }
+  match *node {
+    Node::Pane(_) => None,
+    Node::Split(ref mut split) => {
+      match find_pane(&mut split.first, pane) {
+        Some(node) => Some(node),
+        None => find_pane(&mut split.second, pane),
+      }
+    },
+  }
+}
+
+// Returns the split that has `pane` as one of its sides.
+fn find_parent (node: &mut Node, pane: PaneId) -> Option<&mut Split> {
+  match *node {
+    Node::Pane(_) => None,
+    Node::Split(ref mut split) => {
+      if split.first.is_pane(pane) || split.second.is_pane(pane) {
+        return Some(&mut **split);
+      }
+      match find_parent(&mut split.first, pane) {
+        Some(parent) => Some(parent),
+        None => find_parent(&mut split.second, pane),
+      }
+    },
+  }
+}
+
+fn find_split (node: &mut Node, id: u64) -> Option<&mut Split> {
+  match *node {
+    Node::Pane(_) => None,
+    Node::Split(ref mut split) => {
+      if split.id == id {
+        return Some(&mut **split);
+      }
+      match find_split(&mut split.first, id) {
+        Some(split) => Some(split),
+        None => find_split(&mut split.second, id),
+      }
+    },
+  }
+}
+
+fn place_node (node: &Node, area: Rect, panes: &mut Vec<(PaneId, Rect)>,
+               dividers: &mut Vec<Divider>)
+{
+  match *node {
+    Node::Pane(id) => { panes.push((id, area)); },
+    Node::Split(ref split) => {
+      let (first, divider, second) = split.divide(area);
+      dividers.push(Divider {
+        split: split.id,
+        direction: split.direction,
+        area: area,
+        rect: divider,
+      });
+      place_node(&split.first, first, panes, dividers);
+      place_node(&split.second, second, panes, dividers);
+    },
+  }
+}
+
+// Replaces the split that has `pane` as one of its sides with its other side. Returns the first
+// pane of the other side.
+fn remove_pane (node: &mut Node, pane: PaneId) -> Option<PaneId> {
+  let other = match *node {
+    Node::Pane(_) => { return None; },
+    Node::Split(ref mut split) => {
+      let placeholder = Node::Pane(pane);
+      if split.first.is_pane(pane) {
+        mem::replace(&mut split.second, placeholder)
+      } else if split.second.is_pane(pane) {
+        mem::replace(&mut split.first, placeholder)
+      } else {
+        return match remove_pane(&mut split.first, pane) {
+          Some(neighbor) => Some(neighbor),
+          None => remove_pane(&mut split.second, pane),
+        };
+      }
+    },
+  };
+  let neighbor = other.first_pane();
+  *node = other;
+  return Some(neighbor);
+}
@@ -0,0 +1,93 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::any::Any;
+use std::fmt;
+use std::hash::{
+  Hash,
+  Hasher,
+};
+use std::sync::{
+  Arc,
+  Mutex,
+};
+
+
+//
+// Payload
+//
+
+
+/// A value of any type carried by an event, such as `Event::Custom`. Events can be cloned, so the
+/// value is shared by all clones of an event, and whichever takes it first gets it. Payloads are
+/// only equal to clones of themselves.
+///
+/// ~~~
+/// use termbox::Payload;
+///
+/// let payload = Payload::new(String::from("hello"));
+/// assert!(payload.is::<String>());
+/// assert_eq!(payload.take::<u32>(), None);
+/// assert_eq!(payload.take::<String>(), Some(String::from("hello")));
+/// assert_eq!(payload.take::<String>(), None);
+/// ~~~
+#[derive(Clone)]
+pub struct Payload {
+  value: Arc<Mutex<Option<Box<dyn Any + Send>>>>,
+}
+
+impl Payload {
+  /// Determines whether the value is a `T` and hasn't been taken yet.
+  pub fn is<T: Any> (&self) -> bool {
+    match *self.value.lock().unwrap() {
+      Some(ref value) => value.is::<T>(),
+      None => false,
+    }
+  }
+
+  pub fn new<T: Any + Send> (value: T) -> Payload {
+    Payload::from_box(Box::new(value))
+  }
+
+  /// Removes and returns the value. Returns `None` if it was already taken, or if it is not a `T`,
+  /// in which case it is kept.
+  pub fn take<T: Any> (&self) -> Option<T> {
+    let mut value = self.value.lock().unwrap();
+    match value.take().map(|value| value.downcast::<T>()) {
+      Some(Ok(taken)) => Some(*taken),
+      Some(Err(kept)) => {
+        *value = Some(kept);
+        None
+      },
+      None => None,
+    }
+  }
+
+  // Wraps a value that is already boxed, such as the value a thread panicked with.
+  pub(crate) fn from_box (value: Box<dyn Any + Send>) -> Payload {
+    Payload {
+      value: Arc::new(Mutex::new(Some(value))),
+    }
+  }
+}
+
+impl fmt::Debug for Payload {
+  fn fmt (&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("Payload { .. }")
+  }
+}
+
+impl Eq for Payload {}
+
+impl Hash for Payload {
+  fn hash<H: Hasher> (&self, state: &mut H) {
+    (&*self.value as *const Mutex<_>).hash(state);
+  }
+}
+
+impl PartialEq for Payload {
+  fn eq (&self, other: &Payload) -> bool {
+    Arc::ptr_eq(&self.value, &other.value)
+  }
+}
@@ -0,0 +1,498 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Decodes bytes read from the terminal into termbox events.
+
+use std::os::raw::c_int;
+use std::str;
+
+use try_from::TryFrom;
+
+use super::*;
+
+// Sent by the terminal around pasted text when bracketed paste is enabled.
+const PASTE_START: &'static [u8] = b"\x1b[200~";
+const PASTE_END: &'static [u8] = b"\x1b[201~";
+
+// Modifier bits and keypad key codes of the kitty keyboard protocol.
+const KITTY_MOD_SHIFT: u32 = 1;
+const KITTY_MOD_ALT: u32 = 2;
+const KITTY_MOD_CTRL: u32 = 4;
+const KITTY_KP_0: u32 = 57399;
+const KITTY_KP_9: u32 = 57408;
+const KITTY_KP_DECIMAL: u32 = 57409;
+const KITTY_KP_ADD: u32 = 57413;
+const KITTY_KP_ENTER: u32 = 57414;
+
+// Modifier bits of xterm mouse reports.
+const MOUSE_MOD_SHIFT: u32 = 4;
+const MOUSE_MOD_META: u32 = 8;
+const MOUSE_MOD_CTRL: u32 = 16;
+
+// Escape sequences sent by common terminals for special keys. Both the normal and the application
+// cursor key forms are recognized, as well as the forms used by the Linux console and rxvt.
+const KEY_SEQUENCES: &'static [(&'static [u8], u16)] = &[
+  (b"\x1bOP", TB_KEY_F1), (b"\x1b[11~", TB_KEY_F1), (b"\x1b[[A", TB_KEY_F1),
+  (b"\x1bOQ", TB_KEY_F2), (b"\x1b[12~", TB_KEY_F2), (b"\x1b[[B", TB_KEY_F2),
+  (b"\x1bOR", TB_KEY_F3), (b"\x1b[13~", TB_KEY_F3), (b"\x1b[[C", TB_KEY_F3),
+  (b"\x1bOS", TB_KEY_F4), (b"\x1b[14~", TB_KEY_F4), (b"\x1b[[D", TB_KEY_F4),
+  (b"\x1b[15~", TB_KEY_F5), (b"\x1b[[E", TB_KEY_F5),
+  (b"\x1b[17~", TB_KEY_F6),
+  (b"\x1b[18~", TB_KEY_F7),
+  (b"\x1b[19~", TB_KEY_F8),
+  (b"\x1b[20~", TB_KEY_F9),
+  (b"\x1b[21~", TB_KEY_F10),
+  (b"\x1b[23~", TB_KEY_F11),
+  (b"\x1b[24~", TB_KEY_F12),
+  (b"\x1b[2~", TB_KEY_INSERT),
+  (b"\x1b[3~", TB_KEY_DELETE),
+  (b"\x1b[H", TB_KEY_HOME), (b"\x1bOH", TB_KEY_HOME), (b"\x1b[1~", TB_KEY_HOME),
+  (b"\x1b[7~", TB_KEY_HOME),
+  (b"\x1b[F", TB_KEY_END), (b"\x1bOF", TB_KEY_END), (b"\x1b[4~", TB_KEY_END),
+  (b"\x1b[8~", TB_KEY_END),
+  (b"\x1b[5~", TB_KEY_PGUP),
+  (b"\x1b[6~", TB_KEY_PGDN),
+  (b"\x1b[A", TB_KEY_ARROW_UP), (b"\x1bOA", TB_KEY_ARROW_UP),
+  (b"\x1b[B", TB_KEY_ARROW_DOWN), (b"\x1bOB", TB_KEY_ARROW_DOWN),
+  (b"\x1b[C", TB_KEY_ARROW_RIGHT), (b"\x1bOC", TB_KEY_ARROW_RIGHT),
+  (b"\x1b[D", TB_KEY_ARROW_LEFT), (b"\x1bOD", TB_KEY_ARROW_LEFT),
+];
+
+
+//
+// Parse
+//
+
+
+pub enum Parse {
+  /// An event was decoded from the given number of bytes.
+  Event(RawEvent, usize),
+  /// The buffer holds the beginning of a sequence. More bytes are needed to decode it.
+  Incomplete,
+  /// The given number of bytes form an escape sequence that termbox has no event for.
+  Unknown(usize),
+  /// The buffer is empty.
+  Empty,
+}
+
+fn key_event (key: u16, ch: u32, emod: u8) -> RawEvent {
+  RawEvent { etype: TB_EVENT_KEY, emod: emod, key: key, ch: ch, w: 0, h: 0, x: 0, y: 0 }
+}
+
+fn mouse_event (button: u32, release: bool, x: i32, y: i32) -> RawEvent {
+  let mut emod = 0;
+  if button & MOUSE_MOD_SHIFT != 0 {
+    emod |= TB_MOD_SHIFT;
+  }
+  if button & MOUSE_MOD_META != 0 {
+    emod |= TB_MOD_ALT;
+  }
+  if button & MOUSE_MOD_CTRL != 0 {
+    emod |= TB_MOD_CTRL;
+  }
+  let key = mouse_key(button, release);
+  RawEvent { etype: TB_EVENT_MOUSE, emod: emod, key: key, ch: 0, w: 0, h: 0, x: x, y: y }
+}
+
+fn mouse_key (button: u32, release: bool) -> u16 {
+  if button & 64 != 0 {
+    match button & 3 {
+      0 => TB_KEY_MOUSE_WHEEL_UP,
+      1 => TB_KEY_MOUSE_WHEEL_DOWN,
+      2 => TB_KEY_MOUSE_WHEEL_LEFT,
+      _ => TB_KEY_MOUSE_WHEEL_RIGHT,
+    }
+  } else if release {
+    TB_KEY_MOUSE_RELEASE
+  } else {
+    match button & 3 {
+      0 => TB_KEY_MOUSE_LEFT,
+      1 => TB_KEY_MOUSE_MIDDLE,
+      2 => TB_KEY_MOUSE_RIGHT,
+      _ => TB_KEY_MOUSE_RELEASE,
+    }
+  }
+}
+
+/// Decodes the first event in `buf`. Sequences in `custom` are decoded as the keys they are paired
+/// with before any other parsing. If `force` is set, sequences that may be incomplete are decoded
+/// as well as possible instead of waiting for more input.
+pub fn parse (buf: &[u8], mode: c_int, custom: &[(Vec<u8>, u16)], force: bool) -> Parse {
+  if buf.is_empty() {
+    return Parse::Empty;
+  }
+
+  if let Some(parse) = parse_custom(buf, custom, force) {
+    return parse;
+  }
+
+  if buf[0] != 0x1b {
+    return parse_plain(buf, 0, force);
+  }
+
+  if buf.len() == 1 {
+    return if force { Parse::Event(key_event(TB_KEY_ESC, 0, 0), 1) } else { Parse::Incomplete };
+  }
+
+  // SGR mouse reports: ESC [ < b ; x ; y (M | m)
+  if buf.starts_with(b"\x1b[<") {
+    if let Some(end) = buf.iter().position(|&b| b == b'M' || b == b'm') {
+      let fields: Vec<Option<u32>> = str::from_utf8(&buf[3..end]).unwrap_or("")
+        .split(';')
+        .map(|s| s.parse().ok())
+        .collect();
+      // Coordinates start at 1. Reports that don't fit the event are passed on as unknown.
+      let coord = |field: Option<u32>| field.and_then(|n| i32::try_from(n).ok()).map(|n| n - 1);
+      if fields.len() == 3 {
+        return match (fields[0], coord(fields[1]), coord(fields[2])) {
+          (Some(button), Some(x), Some(y)) => {
+            Parse::Event(mouse_event(button, buf[end] == b'm', x, y), end + 1)
+          },
+          _ => Parse::Unknown(end + 1),
+        };
+      }
+    } else if !force {
+      return Parse::Incomplete;
+    }
+  }
+
+  // X10 mouse reports: ESC [ M b x y
+  if buf.starts_with(b"\x1b[M") {
+    if buf.len() >= 6 {
+      let button = (buf[3] as u32).wrapping_sub(32);
+      let x = buf[4] as i32 - 33;
+      let y = buf[5] as i32 - 33;
+      return Parse::Event(mouse_event(button, false, x, y), 6);
+    } else if !force {
+      return Parse::Incomplete;
+    }
+  }
+
+  for &(seq, etype) in &[(PASTE_START, TB_EVENT_PASTE_START), (PASTE_END, TB_EVENT_PASTE_END)] {
+    if buf.starts_with(seq) {
+      let event = RawEvent { etype: etype, emod: 0, key: 0, ch: 0, w: 0, h: 0, x: 0, y: 0 };
+      return Parse::Event(event, seq.len());
+    } else if !force && seq.starts_with(buf) {
+      return Parse::Incomplete;
+    }
+  }
+
+  if mode & TB_INPUT_KITTY != 0 {
+    if let Some(parse) = parse_kitty(buf, force) {
+      return parse;
+    }
+  }
+
+  for &(seq, key) in KEY_SEQUENCES {
+    if buf.starts_with(seq) {
+      return Parse::Event(key_event(key, 0, 0), seq.len());
+    }
+  }
+
+  if !force && KEY_SEQUENCES.iter().any(|&(seq, _)| seq.starts_with(buf)) {
+    return Parse::Incomplete;
+  }
+
+  if let Some(parse) = parse_unknown(buf, force) {
+    return parse;
+  }
+
+  // Not a known sequence. Either the user pressed Esc, or Alt and another key.
+  if mode & TB_INPUT_ALT != 0 {
+    return match parse_plain(&buf[1..], TB_MOD_ALT, force) {
+      Parse::Event(event, len) => Parse::Event(event, len + 1),
+      other => other,
+    };
+  }
+
+  return Parse::Event(key_event(TB_KEY_ESC, 0, 0), 1);
+}
+
+// Decodes the longest of the sequences registered by the application that `buf` starts with.
+// Waits for more input if `buf` could be the beginning of a longer one. Returns `None` if no
+// registered sequence applies.
+fn parse_custom (buf: &[u8], custom: &[(Vec<u8>, u16)], force: bool) -> Option<Parse> {
+  if !force && custom.iter().any(|entry| entry.0.len() > buf.len() && entry.0.starts_with(buf)) {
+    return Some(Parse::Incomplete);
+  }
+  custom.iter()
+    .filter(|entry| !entry.0.is_empty() && buf.starts_with(&entry.0))
+    .max_by_key(|entry| entry.0.len())
+    .map(|entry| Parse::Event(key_event(entry.1, 0, 0), entry.0.len()))
+}
+
+// Decodes kitty keyboard protocol reports, `ESC [ code[:shifted] ; mods[:type] u`, and the legacy
+// sequences for special keys with modifiers and event types added, such as
+// `ESC [ 1 ; mods[:type] A`. Returns `None` for other sequences.
+fn parse_kitty (buf: &[u8], force: bool) -> Option<Parse> {
+  if !buf.starts_with(b"\x1b[") {
+    return None;
+  }
+  let end = match buf[2..].iter().position(|&b| !(b.is_ascii_digit() || b == b';' || b == b':')) {
+    Some(pos) => pos + 2,
+    None => { return if force { None } else { Some(Parse::Incomplete) }; },
+  };
+
+  let fields: Vec<Vec<u32>> = str::from_utf8(&buf[2..end]).unwrap_or("")
+    .split(';')
+    .map(|field| field.split(':').map(|n| n.parse().unwrap_or(0)).collect())
+    .collect();
+  let field = |i: usize, j: usize| fields.get(i).and_then(|field| field.get(j)).cloned();
+  let mods = field(1, 0).unwrap_or(1).saturating_sub(1);
+  let mut emod = if mods & KITTY_MOD_ALT != 0 { TB_MOD_ALT } else { 0 };
+  match field(1, 1) {
+    Some(2) => { emod |= TB_MOD_REPEAT; },
+    Some(3) => { emod |= TB_MOD_RELEASE; },
+    _ => {},
+  }
+
+  let legacy = match buf[end] {
+    b'u' => {
+      return Some(match kitty_key(field(0, 0).unwrap_or(0), field(0, 1), mods) {
+        Some((key, ch)) => Parse::Event(key_event(key, ch, emod), end + 1),
+        None => Parse::Unknown(end + 1),
+      });
+    },
+    b'~' => format!("\x1b[{}~", field(0, 0).unwrap_or(0)),
+    b'A' | b'B' | b'C' | b'D' | b'H' | b'F' => format!("\x1b[{}", buf[end] as char),
+    b'P' | b'Q' | b'S' => format!("\x1bO{}", buf[end] as char),
+    _ => { return None; },
+  };
+  match KEY_SEQUENCES.iter().find(|&&(seq, _)| seq == legacy.as_bytes()) {
+    Some(&(_, key)) => Some(Parse::Event(key_event(key, 0, emod), end + 1)),
+    None => None,
+  }
+}
+
+// Finds the end of a well-formed control sequence (`ESC [`), string (`ESC ]`, `ESC P`, `ESC _`,
+// `ESC ^` or `ESC X`, terminated by ST or BEL) or SS3 sequence (`ESC O`) that no other parser
+// recognized. Returns `None` if the buffer doesn't start with one, so it can be decoded as Esc or
+// an Alt key combination instead.
+fn parse_unknown (buf: &[u8], force: bool) -> Option<Parse> {
+  let incomplete = if force { None } else { Some(Parse::Incomplete) };
+
+  match buf[1] {
+    b'[' => {
+      // Parameter bytes, then intermediate bytes, then a final byte.
+      let mut i = 2;
+      while i < buf.len() && (0x30..=0x3f).contains(&buf[i]) {
+        i += 1;
+      }
+      while i < buf.len() && (0x20..=0x2f).contains(&buf[i]) {
+        i += 1;
+      }
+      match buf.get(i) {
+        Some(&b) if (0x40..=0x7e).contains(&b) => Some(Parse::Unknown(i + 1)),
+        Some(_) => None,
+        None => incomplete,
+      }
+    },
+    b']' | b'P' | b'_' | b'^' | b'X' => {
+      for i in 2..buf.len() {
+        if buf[i] == 0x07 {
+          return Some(Parse::Unknown(i + 1));
+        } else if buf[i] == 0x1b && buf.get(i + 1) == Some(&b'\\') {
+          return Some(Parse::Unknown(i + 2));
+        }
+      }
+      incomplete
+    },
+    b'O' => {
+      match buf.get(2) {
+        Some(&b) if (0x40..=0x7e).contains(&b) => Some(Parse::Unknown(3)),
+        Some(_) => None,
+        None => incomplete,
+      }
+    },
+    _ => None,
+  }
+}
+
+// Translates the key code of a kitty keyboard report to a termbox key and character. Returns
+// `None` for keys termbox has no code for, such as media keys.
+fn kitty_key (code: u32, shifted: Option<u32>, mods: u32) -> Option<(u16, u32)> {
+  let ctrl = mods & KITTY_MOD_CTRL != 0;
+
+  match code {
+    8 => Some((TB_KEY_BACKSPACE, 0)),
+    9 => Some((TB_KEY_TAB, 0)),
+    13 | KITTY_KP_ENTER => Some((TB_KEY_ENTER, 0)),
+    27 => Some((TB_KEY_ESC, 0)),
+    127 => Some((TB_KEY_BACKSPACE2, 0)),
+    32 if ctrl => Some((TB_KEY_CTRL_2, 0)),
+    32 => Some((TB_KEY_SPACE, 0)),
+    0x61..=0x7a if ctrl => Some(((code & 0x1f) as u16, 0)),
+    KITTY_KP_0..=KITTY_KP_9 => Some((0, '0' as u32 + code - KITTY_KP_0)),
+    KITTY_KP_DECIMAL..=KITTY_KP_ADD => {
+      Some((0, b"./*-+"[(code - KITTY_KP_DECIMAL) as usize] as u32))
+    },
+    // Other keys in the private use area have no character.
+    0xe000..=0xf8ff => None,
+    _ => {
+      if mods & KITTY_MOD_SHIFT != 0 {
+        Some((0, shifted.unwrap_or(code)))
+      } else {
+        Some((0, code))
+      }
+    },
+  }
+}
+
+// Decodes a control key or a UTF-8 character.
+fn parse_plain (buf: &[u8], emod: u8, force: bool) -> Parse {
+  if buf.is_empty() {
+    return Parse::Empty;
+  }
+
+  let first = buf[0];
+  if first <= TB_KEY_SPACE as u8 || first == TB_KEY_BACKSPACE2 as u8 {
+    return Parse::Event(key_event(first as u16, 0, emod), 1);
+  }
+
+  let len = match first {
+    0x00..=0x7f => 1,
+    0xc0..=0xdf => 2,
+    0xe0..=0xef => 3,
+    0xf0..=0xf7 => 4,
+    _ => { return Parse::Event(key_event(0, 0xfffd, emod), 1); },
+  };
+
+  if buf.len() < len {
+    return if force {
+      Parse::Event(key_event(0, 0xfffd, emod), buf.len())
+    } else {
+      Parse::Incomplete
+    };
+  }
+
+  match str::from_utf8(&buf[..len]).ok().and_then(|s| s.chars().next()) {
+    Some(ch) => Parse::Event(key_event(0, ch as u32, emod), len),
+    None => Parse::Event(key_event(0, 0xfffd, emod), 1),
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const KITTY: c_int = TB_INPUT_ESC | TB_INPUT_KITTY;
+
+  fn event (buf: &[u8], mode: c_int, force: bool) -> (RawEvent, usize) {
+    match parse(buf, mode, &[], force) {
+      Parse::Event(event, len) => (event, len),
+      _ => panic!("no event for {:?}", buf),
+    }
+  }
+
+  fn is_incomplete (buf: &[u8], mode: c_int) -> bool {
+    return matches!(parse(buf, mode, &[], false), Parse::Incomplete);
+  }
+
+  fn unknown_len (buf: &[u8], mode: c_int) -> Option<usize> {
+    match parse(buf, mode, &[], false) {
+      Parse::Unknown(len) => Some(len),
+      _ => None,
+    }
+  }
+
+  fn key (buf: &[u8], mode: c_int) -> (u16, u32, u8, usize) {
+    let (event, len) = event(buf, mode, false);
+    assert_eq!(event.etype, TB_EVENT_KEY);
+    return (event.key, event.ch, event.emod, len);
+  }
+
+  fn mouse (buf: &[u8]) -> (u16, u8, i32, i32, usize) {
+    let (event, len) = event(buf, TB_INPUT_ESC, false);
+    assert_eq!(event.etype, TB_EVENT_MOUSE);
+    return (event.key, event.emod, event.x, event.y, len);
+  }
+
+  #[test]
+  fn esc_and_alt () {
+    assert!(is_incomplete(b"\x1b", TB_INPUT_ESC));
+    let (event, len) = event(b"\x1b", TB_INPUT_ESC, true);
+    assert_eq!((event.key, len), (TB_KEY_ESC, 1));
+
+    assert_eq!(key(b"\x1ba", TB_INPUT_ESC), (TB_KEY_ESC, 0, 0, 1));
+    assert_eq!(key(b"\x1ba", TB_INPUT_ALT), (0, 'a' as u32, TB_MOD_ALT, 2));
+    assert_eq!(key(b"\x1b\x01", TB_INPUT_ALT), (TB_KEY_CTRL_A, 0, TB_MOD_ALT, 2));
+    assert_eq!(key(b"\x1b[A", TB_INPUT_ALT), (TB_KEY_ARROW_UP, 0, 0, 3));
+  }
+
+  #[test]
+  fn utf8 () {
+    assert_eq!(key("é".as_bytes(), TB_INPUT_ESC), (0, 'é' as u32, 0, 2));
+    assert!(is_incomplete(&"é".as_bytes()[..1], TB_INPUT_ESC));
+    assert!(is_incomplete(&"😀".as_bytes()[..3], TB_INPUT_ESC));
+
+    let (event, len) = event(&"😀".as_bytes()[..3], TB_INPUT_ESC, true);
+    assert_eq!((event.ch, len), (0xfffd, 3));
+    assert_eq!(key(b"\xff", TB_INPUT_ESC), (0, 0xfffd, 0, 1));
+    assert_eq!(key(b"\xc3(", TB_INPUT_ESC), (0, 0xfffd, 0, 1));
+  }
+
+  #[test]
+  fn sgr_mouse () {
+    assert_eq!(mouse(b"\x1b[<0;10;5M"), (TB_KEY_MOUSE_LEFT, 0, 9, 4, 10));
+    assert_eq!(mouse(b"\x1b[<2;1;1m"), (TB_KEY_MOUSE_RELEASE, 0, 0, 0, 9));
+    assert_eq!(mouse(b"\x1b[<65;3;4M"), (TB_KEY_MOUSE_WHEEL_DOWN, 0, 2, 3, 10));
+    assert_eq!(mouse(b"\x1b[<16;1;1M"), (TB_KEY_MOUSE_LEFT, TB_MOD_CTRL, 0, 0, 10));
+    assert!(is_incomplete(b"\x1b[<0;10", TB_INPUT_ESC));
+
+    // Coordinates that don't fit in an i32, or don't parse at all, make the report unknown.
+    assert_eq!(unknown_len(b"\x1b[<0;4294967295;1M", TB_INPUT_ESC), Some(18));
+    assert_eq!(unknown_len(b"\x1b[<0;2147483648;1M", TB_INPUT_ESC), Some(18));
+    assert_eq!(unknown_len(b"\x1b[<0;99999999999;1M", TB_INPUT_ESC), Some(19));
+  }
+
+  #[test]
+  fn x10_mouse () {
+    assert_eq!(mouse(b"\x1b[M *%"), (TB_KEY_MOUSE_LEFT, 0, 9, 4, 6));
+    assert_eq!(mouse(b"\x1b[M!!!"), (TB_KEY_MOUSE_MIDDLE, 0, 0, 0, 6));
+    assert_eq!(mouse(b"\x1b[M`!!"), (TB_KEY_MOUSE_WHEEL_UP, 0, 0, 0, 6));
+    assert!(is_incomplete(b"\x1b[M ", TB_INPUT_ESC));
+  }
+
+  #[test]
+  fn paste () {
+    let (start, len) = event(b"\x1b[200~abc", TB_INPUT_ESC, false);
+    assert_eq!((start.etype, len), (TB_EVENT_PASTE_START, 6));
+    let (end, len) = event(b"\x1b[201~", TB_INPUT_ESC, false);
+    assert_eq!((end.etype, len), (TB_EVENT_PASTE_END, 6));
+    assert!(is_incomplete(b"\x1b[20", TB_INPUT_ESC));
+  }
+
+  #[test]
+  fn kitty () {
+    assert_eq!(key(b"\x1b[97u", KITTY), (0, 'a' as u32, 0, 5));
+    assert_eq!(key(b"\x1b[97:65;2u", KITTY), (0, 'A' as u32, 0, 10));
+    assert_eq!(key(b"\x1b[97;5u", KITTY), (TB_KEY_CTRL_A, 0, 0, 7));
+    assert_eq!(key(b"\x1b[97;3:2u", KITTY), (0, 'a' as u32, TB_MOD_ALT | TB_MOD_REPEAT, 9));
+    assert_eq!(key(b"\x1b[13;1:3u", KITTY), (TB_KEY_ENTER, 0, TB_MOD_RELEASE, 9));
+    assert_eq!(key(b"\x1b[57400u", KITTY), (0, '1' as u32, 0, 8));
+    assert_eq!(key(b"\x1b[1;3A", KITTY), (TB_KEY_ARROW_UP, 0, TB_MOD_ALT, 6));
+    assert_eq!(key(b"\x1b[3;1:3~", KITTY), (TB_KEY_DELETE, 0, TB_MOD_RELEASE, 8));
+    assert_eq!(unknown_len(b"\x1b[57428u", KITTY), Some(8));
+    assert!(is_incomplete(b"\x1b[97;5", KITTY));
+
+    // Without the kitty protocol, reports are not decoded as keys.
+    assert_eq!(unknown_len(b"\x1b[97;5u", TB_INPUT_ESC), Some(7));
+  }
+
+  #[test]
+  fn unknown () {
+    assert_eq!(unknown_len(b"\x1b[?1;2cx", TB_INPUT_ESC), Some(7));
+    assert_eq!(unknown_len(b"\x1b[99;5X", TB_INPUT_ESC), Some(7));
+    assert_eq!(unknown_len(b"\x1b]11;rgb:0/0/0\x07", TB_INPUT_ESC), Some(15));
+    assert_eq!(unknown_len(b"\x1b]11;rgb:0/0/0\x1b\\", TB_INPUT_ESC), Some(16));
+    assert_eq!(unknown_len(b"\x1bOz", TB_INPUT_ESC), Some(3));
+    assert!(is_incomplete(b"\x1b]11;rgb:0", TB_INPUT_ESC));
+    assert!(is_incomplete(b"\x1b[?1;2", TB_INPUT_ESC));
+
+    // A sequence cut short is decoded as Esc once the escape delay has passed.
+    let (event, len) = event(b"\x1b]11;rgb:0", TB_INPUT_ESC, true);
+    assert_eq!((event.key, len), (TB_KEY_ESC, 1));
+  }
+}
@@ -0,0 +1,1184 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! A pure Rust replacement for the termbox C library, used when the `pure-rust` feature is enabled.
+//! It provides the same functions and constants as `termbox-sys`, but writes ANSI escape sequences
+//! directly instead of consulting terminfo. Nearly every terminal in use today understands them.
+
+#![allow(dead_code)]
+
+use std::char;
+use std::ffi::CStr;
+use std::fs::{
+  File,
+  OpenOptions,
+};
+use std::io::{
+  self,
+  Write,
+};
+use std::mem;
+use std::os::raw::{
+  c_char,
+  c_int,
+};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{
+  AsRawFd,
+  FromRawFd,
+  RawFd,
+};
+use std::sync::atomic::{
+  AtomicBool,
+  Ordering,
+};
+use std::sync::{
+  Mutex,
+  MutexGuard,
+};
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use libc;
+
+use sgr;
+use tty;
+use width;
+use OutputMode;
+use ShutdownError;
+
+mod input;
+
+pub const TB_KEY_F1: u16 = 0xffff - 0;
+pub const TB_KEY_F2: u16 = 0xffff - 1;
+pub const TB_KEY_F3: u16 = 0xffff - 2;
+pub const TB_KEY_F4: u16 = 0xffff - 3;
+pub const TB_KEY_F5: u16 = 0xffff - 4;
+pub const TB_KEY_F6: u16 = 0xffff - 5;
+pub const TB_KEY_F7: u16 = 0xffff - 6;
+pub const TB_KEY_F8: u16 = 0xffff - 7;
+pub const TB_KEY_F9: u16 = 0xffff - 8;
+pub const TB_KEY_F10: u16 = 0xffff - 9;
+pub const TB_KEY_F11: u16 = 0xffff - 10;
+pub const TB_KEY_F12: u16 = 0xffff - 11;
+pub const TB_KEY_INSERT: u16 = 0xffff - 12;
+pub const TB_KEY_DELETE: u16 = 0xffff - 13;
+pub const TB_KEY_HOME: u16 = 0xffff - 14;
+pub const TB_KEY_END: u16 = 0xffff - 15;
+pub const TB_KEY_PGUP: u16 = 0xffff - 16;
+pub const TB_KEY_PGDN: u16 = 0xffff - 17;
+pub const TB_KEY_ARROW_UP: u16 = 0xffff - 18;
+pub const TB_KEY_ARROW_DOWN: u16 = 0xffff - 19;
+pub const TB_KEY_ARROW_LEFT: u16 = 0xffff - 20;
+pub const TB_KEY_ARROW_RIGHT: u16 = 0xffff - 21;
+pub const TB_KEY_MOUSE_LEFT: u16 = 0xffff - 22;
+pub const TB_KEY_MOUSE_RIGHT: u16 = 0xffff - 23;
+pub const TB_KEY_MOUSE_MIDDLE: u16 = 0xffff - 24;
+pub const TB_KEY_MOUSE_RELEASE: u16 = 0xffff - 25;
+pub const TB_KEY_MOUSE_WHEEL_UP: u16 = 0xffff - 26;
+pub const TB_KEY_MOUSE_WHEEL_DOWN: u16 = 0xffff - 27;
+// Not part of the C library. Reported by terminals that support horizontal scrolling.
+pub const TB_KEY_MOUSE_WHEEL_LEFT: u16 = 0xffff - 28;
+pub const TB_KEY_MOUSE_WHEEL_RIGHT: u16 = 0xffff - 29;
+
+pub const TB_KEY_CTRL_TILDE: u16 = 0x00;
+pub const TB_KEY_CTRL_2: u16 = 0x00;
+pub const TB_KEY_CTRL_A: u16 = 0x01;
+pub const TB_KEY_CTRL_B: u16 = 0x02;
+pub const TB_KEY_CTRL_C: u16 = 0x03;
+pub const TB_KEY_CTRL_D: u16 = 0x04;
+pub const TB_KEY_CTRL_E: u16 = 0x05;
+pub const TB_KEY_CTRL_F: u16 = 0x06;
+pub const TB_KEY_CTRL_G: u16 = 0x07;
+pub const TB_KEY_BACKSPACE: u16 = 0x08;
+pub const TB_KEY_CTRL_H: u16 = 0x08;
+pub const TB_KEY_TAB: u16 = 0x09;
+pub const TB_KEY_CTRL_I: u16 = 0x09;
+pub const TB_KEY_CTRL_J: u16 = 0x0a;
+pub const TB_KEY_CTRL_K: u16 = 0x0b;
+pub const TB_KEY_CTRL_L: u16 = 0x0c;
+pub const TB_KEY_ENTER: u16 = 0x0d;
+pub const TB_KEY_CTRL_M: u16 = 0x0d;
+pub const TB_KEY_CTRL_N: u16 = 0x0e;
+pub const TB_KEY_CTRL_O: u16 = 0x0f;
+pub const TB_KEY_CTRL_P: u16 = 0x10;
+pub const TB_KEY_CTRL_Q: u16 = 0x11;
+pub const TB_KEY_CTRL_R: u16 = 0x12;
+pub const TB_KEY_CTRL_S: u16 = 0x13;
+pub const TB_KEY_CTRL_T: u16 = 0x14;
+pub const TB_KEY_CTRL_U: u16 = 0x15;
+pub const TB_KEY_CTRL_V: u16 = 0x16;
+pub const TB_KEY_CTRL_W: u16 = 0x17;
+pub const TB_KEY_CTRL_X: u16 = 0x18;
+pub const TB_KEY_CTRL_Y: u16 = 0x19;
+pub const TB_KEY_CTRL_Z: u16 = 0x1a;
+pub const TB_KEY_ESC: u16 = 0x1b;
+pub const TB_KEY_CTRL_LSQ_BRACKET: u16 = 0x1b;
+pub const TB_KEY_CTRL_3: u16 = 0x1b;
+pub const TB_KEY_CTRL_4: u16 = 0x1c;
+pub const TB_KEY_CTRL_BACKSLASH: u16 = 0x1c;
+pub const TB_KEY_CTRL_5: u16 = 0x1d;
+pub const TB_KEY_CTRL_RSQ_BRACKET: u16 = 0x1d;
+pub const TB_KEY_CTRL_6: u16 = 0x1e;
+pub const TB_KEY_CTRL_7: u16 = 0x1f;
+pub const TB_KEY_CTRL_SLASH: u16 = 0x1f;
+pub const TB_KEY_CTRL_UNDERSCORE: u16 = 0x1f;
+pub const TB_KEY_SPACE: u16 = 0x20;
+pub const TB_KEY_BACKSPACE2: u16 = 0x7f;
+pub const TB_KEY_CTRL_8: u16 = 0x7f;
+
+pub const TB_MOD_ALT: u8 = 0x01;
+// Not part of the C library. Reported with TB_INPUT_KITTY for keys that are held down or released.
+pub const TB_MOD_REPEAT: u8 = 0x10;
+pub const TB_MOD_RELEASE: u8 = 0x20;
+// Not part of the C library. Reported for mouse events when Shift or Ctrl was held.
+pub const TB_MOD_SHIFT: u8 = 0x40;
+pub const TB_MOD_CTRL: u8 = 0x80;
+
+pub const TB_DEFAULT: u16 = 0x00;
+pub const TB_BLACK: u16 = 0x01;
+pub const TB_RED: u16 = 0x02;
+pub const TB_GREEN: u16 = 0x03;
+pub const TB_YELLOW: u16 = 0x04;
+pub const TB_BLUE: u16 = 0x05;
+pub const TB_MAGENTA: u16 = 0x06;
+pub const TB_CYAN: u16 = 0x07;
+pub const TB_WHITE: u16 = 0x08;
+pub const TB_BOLD: u16 = 0x0100;
+pub const TB_UNDERLINE: u16 = 0x0200;
+pub const TB_REVERSE: u16 = 0x0400;
+
+pub const TB_EVENT_KEY: u8 = 1;
+pub const TB_EVENT_RESIZE: u8 = 2;
+pub const TB_EVENT_MOUSE: u8 = 3;
+// Not part of the C library. Reported around pasted text when TB_INPUT_PASTE is set.
+pub const TB_EVENT_PASTE_START: u8 = 4;
+pub const TB_EVENT_PASTE_END: u8 = 5;
+// Not part of the C library. Reported when bytes are read from the terminal and TB_INPUT_RAW is
+// set. The bytes are retrieved with tb_take_raw_input.
+pub const TB_EVENT_RAW: u8 = 6;
+// Not part of the C library. Reported for escape sequences that can't be decoded. The sequence is
+// retrieved with tb_take_unknown_sequence.
+pub const TB_EVENT_UNKNOWN: u8 = 7;
+
+pub const TB_EUNSUPPORTED_TERMINAL: c_int = -1;
+pub const TB_EFAILED_TO_OPEN_TTY: c_int = -2;
+pub const TB_EPIPE_TRAP_ERROR: c_int = -3;
+
+pub const TB_HIDE_CURSOR: c_int = -1;
+
+pub const TB_INPUT_CURRENT: c_int = 0;
+pub const TB_INPUT_ESC: c_int = 1;
+pub const TB_INPUT_ALT: c_int = 2;
+pub const TB_INPUT_MOUSE: c_int = 4;
+// Not part of the C library. Enables bracketed paste.
+pub const TB_INPUT_PASTE: c_int = 8;
+// Not part of the C library. Enables the kitty keyboard protocol.
+pub const TB_INPUT_KITTY: c_int = 16;
+// Not part of the C library. Reports the bytes read from the terminal with TB_EVENT_RAW, before the
+// events decoded from them. With TB_INPUT_RAW_ONLY, the bytes are not decoded at all.
+pub const TB_INPUT_RAW: c_int = 32;
+pub const TB_INPUT_RAW_ONLY: c_int = 64;
+
+pub const TB_OUTPUT_CURRENT: c_int = 0;
+pub const TB_OUTPUT_NORMAL: c_int = 1;
+pub const TB_OUTPUT_256: c_int = 2;
+pub const TB_OUTPUT_216: c_int = 3;
+pub const TB_OUTPUT_GRAYSCALE: c_int = 4;
+
+pub const TB_EOF: c_int = -1;
+
+// How long to wait by default for the rest of an escape sequence before deciding that the user
+// pressed Esc.
+const DEFAULT_ESCAPE_DELAY_MS: c_int = 25;
+
+const ENTER_SEQUENCE: &'static [u8] = b"\x1b[?1049h\x1b[?1h\x1b=\x1b[?25l";
+const EXIT_SEQUENCE: &'static [u8] = b"\x1b[?25h\x1b[0m\x1b[H\x1b[2J\x1b[?1l\x1b>\x1b[?1049l";
+const MOUSE_ON_SEQUENCE: &'static [u8] = b"\x1b[?1000h\x1b[?1002h\x1b[?1006h";
+const MOUSE_OFF_SEQUENCE: &'static [u8] = b"\x1b[?1006l\x1b[?1002l\x1b[?1000l";
+const PASTE_ON_SEQUENCE: &'static [u8] = b"\x1b[?2004h";
+const PASTE_OFF_SEQUENCE: &'static [u8] = b"\x1b[?2004l";
+// Pushes the kitty keyboard flags for disambiguating escape codes, reporting event types and
+// reporting alternate keys, and pops them again.
+const KITTY_ON_SEQUENCE: &'static [u8] = b"\x1b[>7u";
+const KITTY_OFF_SEQUENCE: &'static [u8] = b"\x1b[<u";
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct RawEvent {
+  pub etype: u8,
+  pub emod: u8,
+  pub key: u16,
+  pub ch: u32,
+  pub w: i32,
+  pub h: i32,
+  pub x: i32,
+  pub y: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct RawCell {
+  pub ch: u32,
+  pub fg: u16,
+  pub bg: u16,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+// Set by the SIGWINCH handler and cleared when the resize event is delivered.
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_sigwinch (_: c_int) {
+  RESIZED.store(true, Ordering::SeqCst);
+}
+
+fn lock () -> MutexGuard<'static, Option<State>> {
+  match STATE.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  }
+}
+
+fn is_cursor_hidden (x: c_int, y: c_int) -> bool {
+  x == TB_HIDE_CURSOR || y == TB_HIDE_CURSOR
+}
+
+fn same_cell (a: &RawCell, b: &RawCell) -> bool {
+  a.ch == b.ch && a.fg == b.fg && a.bg == b.bg
+}
+
+
+//
+// State
+//
+
+
+struct State {
+  tty_in: File,
+  tty_out: File,
+  orig_termios: libc::termios,
+  orig_sigwinch: libc::sigaction,
+  width: c_int,
+  height: c_int,
+  back: Vec<RawCell>,
+  front: Vec<RawCell>,
+  clear_fg: u16,
+  clear_bg: u16,
+  cursor_x: c_int,
+  cursor_y: c_int,
+  input_mode: c_int,
+  output_mode: c_int,
+  escape_delay_ms: c_int,
+  // Input sequences registered with `tb_register_key_sequence`, with the keys they decode as.
+  key_sequences: Vec<(Vec<u8>, u16)>,
+  last_attr: Option<(u16, u16)>,
+  last_pos: Option<(c_int, c_int)>,
+  out: Vec<u8>,
+  // Size at which `out` is flushed while presenting, or zero to flush only once done.
+  out_capacity: usize,
+  // Total number of bytes written to the terminal, and the number written by the last call to
+  // `present`.
+  written: usize,
+  present_bytes: usize,
+  input: Vec<u8>,
+  raw: Vec<u8>,
+  raw_pending: bool,
+  unknown: Vec<u8>,
+}
+
+impl State {
+  fn blank (&self) -> RawCell {
+    RawCell { ch: ' ' as u32, fg: self.clear_fg, bg: self.clear_bg }
+  }
+
+  fn flush (&mut self) {
+    let _ = self.try_flush();
+  }
+
+  fn index (&self, x: c_int, y: c_int) -> Option<usize> {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      None
+    } else {
+      Some((y * self.width + x) as usize)
+    }
+  }
+
+  fn present (&mut self) {
+    self.last_pos = None;
+    let written = self.written;
+
+    for y in 0..self.height {
+      let mut x = 0;
+      while x < self.width {
+        let index = (y * self.width + x) as usize;
+        let back = self.back[index];
+        let w = char::from_u32(back.ch).map(width::char_width).unwrap_or(1).max(1) as c_int;
+
+        if same_cell(&back, &self.front[index]) {
+          x += w;
+          continue;
+        }
+
+        self.front[index] = back;
+        self.send_attr(back.fg, back.bg);
+
+        if w > 1 && x + w - 1 >= self.width {
+          // A wide character that doesn't fit at the end of the line is replaced with spaces.
+          for i in x..self.width {
+            self.send_char(i, y, ' ' as u32);
+          }
+        } else {
+          self.send_char(x, y, back.ch);
+          for i in 1..w {
+            self.front[index + i as usize] = RawCell { ch: 0, fg: back.fg, bg: back.bg };
+          }
+        }
+
+        x += w;
+        if self.out_capacity > 0 && self.out.len() >= self.out_capacity {
+          self.flush();
+        }
+      }
+    }
+
+    if !is_cursor_hidden(self.cursor_x, self.cursor_y) {
+      self.write_cursor();
+    }
+    self.flush();
+    self.present_bytes = self.written - written;
+  }
+
+  fn send_attr (&mut self, fg: u16, bg: u16) {
+    if self.last_attr == Some((fg, bg)) {
+      return;
+    }
+
+    let mode = OutputMode::from_raw(self.output_mode).unwrap_or(OutputMode::Normal);
+    let mut seq = String::new();
+    sgr::write_attributes(&mut seq, fg, bg, mode);
+    self.out.extend_from_slice(seq.as_bytes());
+    self.last_attr = Some((fg, bg));
+  }
+
+  fn send_char (&mut self, x: c_int, y: c_int, ch: u32) {
+    match self.last_pos {
+      Some((lx, ly)) if lx + 1 == x && ly == y => {},
+      _ => { self.write_move(x, y); },
+    }
+    self.last_pos = Some((x, y));
+
+    let ch = match char::from_u32(ch) {
+      Some(ch) if ch >= ' ' => ch,
+      _ => ' ',
+    };
+    let mut buf = [0u8; 4];
+    self.out.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+  }
+
+  fn send_clear (&mut self) {
+    let (fg, bg) = (self.clear_fg, self.clear_bg);
+    self.send_attr(fg, bg);
+    self.out.extend_from_slice(b"\x1b[H\x1b[2J");
+    if !is_cursor_hidden(self.cursor_x, self.cursor_y) {
+      self.write_cursor();
+    }
+    self.flush();
+    self.last_pos = None;
+  }
+
+  fn try_flush (&mut self) -> io::Result<()> {
+    self.written += self.out.len();
+    let result = self.tty_out.write_all(&self.out).and_then(|_| self.tty_out.flush());
+    self.out.clear();
+    return result;
+  }
+
+  fn update_size (&mut self) {
+    let (width, height) = query_size(&self.tty_out, &self.tty_in);
+    let blank = self.blank();
+    let mut back = vec![blank; (width * height) as usize];
+
+    for y in 0..height.min(self.height) {
+      for x in 0..width.min(self.width) {
+        back[(y * width + x) as usize] = self.back[(y * self.width + x) as usize];
+      }
+    }
+
+    self.width = width;
+    self.height = height;
+    self.back = back;
+    self.front = vec![blank; (width * height) as usize];
+    self.send_clear();
+  }
+
+  // Decodes the next event from the input read so far into `event`. Returns `Ok` with the result
+  // for `tb_peek_event` if there is one, or `Err` with the descriptor to wait on and how long to
+  // wait for before trying again. Once `deadline` has passed, the terminal is still checked once if
+  // it hasn't been `polled` yet, so that a zero timeout picks up input that is already waiting.
+  fn next_event (&mut self, event: &mut RawEvent, deadline: Option<Instant>, force: bool,
+                 polled: bool)
+    -> Result<c_int, (RawFd, c_int, bool)>
+  {
+    if RESIZED.swap(false, Ordering::SeqCst) {
+      self.update_size();
+      *event = RawEvent {
+        etype: TB_EVENT_RESIZE,
+        emod: 0,
+        key: 0,
+        ch: 0,
+        w: self.width,
+        h: self.height,
+        x: 0,
+        y: 0,
+      };
+      return Ok(TB_EVENT_RESIZE as c_int);
+    }
+
+    if self.raw_pending {
+      self.raw_pending = false;
+      *event = RawEvent { etype: TB_EVENT_RAW, emod: 0, key: 0, ch: 0, w: 0, h: 0, x: 0, y: 0 };
+      return Ok(TB_EVENT_RAW as c_int);
+    }
+
+    let incomplete = match input::parse(&self.input, self.input_mode, &self.key_sequences, force) {
+      input::Parse::Event(parsed, len) => {
+        self.input.drain(..len);
+        *event = parsed;
+        return Ok(parsed.etype as c_int);
+      },
+      input::Parse::Unknown(len) => {
+        self.unknown = self.input.drain(..len).collect();
+        *event = RawEvent {
+          etype: TB_EVENT_UNKNOWN,
+          emod: 0,
+          key: 0,
+          ch: 0,
+          w: 0,
+          h: 0,
+          x: 0,
+          y: 0,
+        };
+        return Ok(TB_EVENT_UNKNOWN as c_int);
+      },
+      input::Parse::Incomplete => true,
+      input::Parse::Empty => false,
+    };
+
+    let now = Instant::now();
+    let mut wait_ms: c_int = match deadline {
+      Some(deadline) if deadline <= now => {
+        if !incomplete && polled {
+          return Ok(0);
+        }
+        0
+      },
+      Some(deadline) => (deadline - now).as_millis() as c_int,
+      None => -1,
+    };
+    if incomplete && (wait_ms < 0 || wait_ms > self.escape_delay_ms) {
+      wait_ms = self.escape_delay_ms;
+    }
+
+    return Err((self.tty_in.as_raw_fd(), wait_ms, incomplete));
+  }
+
+  // Adds bytes read from the terminal to the input, or to the raw input as the input mode asks.
+  fn receive (&mut self, bytes: &[u8]) {
+    if self.input_mode & TB_INPUT_RAW != 0 {
+      self.raw.extend_from_slice(bytes);
+      self.raw_pending = true;
+    }
+    if self.input_mode & TB_INPUT_RAW_ONLY == 0 {
+      self.input.extend_from_slice(bytes);
+    }
+  }
+
+  fn write_cursor (&mut self) {
+    let (x, y) = (self.cursor_x, self.cursor_y);
+    self.write_move(x, y);
+  }
+
+  fn write_move (&mut self, x: c_int, y: c_int) {
+    let seq = format!("\x1b[{};{}H", y + 1, x + 1);
+    self.out.extend_from_slice(seq.as_bytes());
+  }
+}
+
+// The size is taken from the output terminal if possible, since that is where cells are drawn.
+fn query_size (output: &File, input: &File) -> (c_int, c_int) {
+  for file in &[output, input] {
+    unsafe {
+      let mut size: libc::winsize = mem::zeroed();
+      if libc::ioctl(file.as_raw_fd(), libc::TIOCGWINSZ, &mut size) == 0 && size.ws_col > 0 {
+        return (size.ws_col as c_int, size.ws_row as c_int);
+      }
+    }
+  }
+  return (80, 24);
+}
+
+// Waits for an event. The state is only locked while decoding input, not while waiting for it,
+// so other threads can still draw or shut termbox down in the meantime.
+fn wait_event (event: &mut RawEvent, timeout: Option<c_int>) -> c_int {
+  let deadline = timeout.map(|ms| Instant::now() + Duration::from_millis(ms.max(0) as u64));
+  let mut force = false;
+  let mut polled = false;
+
+  loop {
+    let (fd, wait_ms, incomplete) = match *lock() {
+      Some(ref mut state) => {
+        match state.next_event(event, deadline, force, polled) {
+          Ok(result) => { return result; },
+          Err(wait) => wait,
+        }
+      },
+      None => { return -1; },
+    };
+
+    let mut pfd = libc::pollfd { fd: fd, events: libc::POLLIN, revents: 0 };
+    polled = true;
+    match unsafe { libc::poll(&mut pfd, 1, wait_ms) } {
+      0 => {
+        // Nothing else arrived, so whatever is buffered is all there is.
+        force = incomplete;
+      },
+      n if n < 0 => {
+        if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+          return -1;
+        }
+      },
+      _ => {
+        let mut buf = [0u8; 64];
+        let n = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+        if n == 0 {
+          return -1;
+        } else if n < 0 {
+          if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return -1;
+          }
+        } else {
+          match *lock() {
+            Some(ref mut state) => { state.receive(&buf[..n as usize]); },
+            None => { return -1; },
+          }
+          force = false;
+        }
+      },
+    }
+  }
+}
+
+unsafe fn init_with (input: File, output: File) -> c_int {
+  let mut state = lock();
+  if state.is_some() {
+    return TB_EFAILED_TO_OPEN_TTY;
+  }
+
+  // Raw mode only needs to be set where keys are read from.
+  let fd = input.as_raw_fd();
+  let mut orig_termios: libc::termios = mem::zeroed();
+  if libc::tcgetattr(fd, &mut orig_termios) != 0 {
+    return TB_EFAILED_TO_OPEN_TTY;
+  }
+
+  let mut raw_termios = orig_termios;
+  libc::cfmakeraw(&mut raw_termios);
+  raw_termios.c_cc[libc::VMIN] = 0;
+  raw_termios.c_cc[libc::VTIME] = 0;
+  if libc::tcsetattr(fd, libc::TCSAFLUSH, &raw_termios) != 0 {
+    return TB_EFAILED_TO_OPEN_TTY;
+  }
+
+  let mut action: libc::sigaction = mem::zeroed();
+  let mut orig_sigwinch: libc::sigaction = mem::zeroed();
+  action.sa_sigaction = on_sigwinch as extern "C" fn (c_int) as libc::sighandler_t;
+  libc::sigemptyset(&mut action.sa_mask);
+  if libc::sigaction(libc::SIGWINCH, &action, &mut orig_sigwinch) != 0 {
+    libc::tcsetattr(fd, libc::TCSAFLUSH, &orig_termios);
+    return TB_EPIPE_TRAP_ERROR;
+  }
+  RESIZED.store(false, Ordering::SeqCst);
+
+  let (width, height) = query_size(&output, &input);
+  let blank = RawCell { ch: ' ' as u32, fg: TB_DEFAULT, bg: TB_DEFAULT };
+  let mut new_state = State {
+    tty_in: input,
+    tty_out: output,
+    orig_termios: orig_termios,
+    orig_sigwinch: orig_sigwinch,
+    width: width,
+    height: height,
+    back: vec![blank; (width * height) as usize],
+    front: vec![blank; (width * height) as usize],
+    clear_fg: TB_DEFAULT,
+    clear_bg: TB_DEFAULT,
+    cursor_x: TB_HIDE_CURSOR,
+    cursor_y: TB_HIDE_CURSOR,
+    input_mode: TB_INPUT_ESC,
+    output_mode: TB_OUTPUT_NORMAL,
+    escape_delay_ms: DEFAULT_ESCAPE_DELAY_MS,
+    key_sequences: Vec::new(),
+    last_attr: None,
+    last_pos: None,
+    out: Vec::new(),
+    out_capacity: 0,
+    written: 0,
+    present_bytes: 0,
+    input: Vec::new(),
+    raw: Vec::new(),
+    raw_pending: false,
+    unknown: Vec::new(),
+  };
+
+  new_state.out.extend_from_slice(ENTER_SEQUENCE);
+  new_state.send_clear();
+  *state = Some(new_state);
+  return 0;
+}
+
+/// Initializes termbox on `/dev/tty`.
+///
+/// # Safety
+///
+/// Termbox must not be initialized while a `Termbox` exists.
+pub unsafe fn tb_init () -> c_int {
+  tb_init_file(b"/dev/tty\0".as_ptr() as *const c_char)
+}
+
+/// Initializes termbox on the terminal open as `inout`.
+///
+/// # Safety
+///
+/// Termbox must not be initialized while a `Termbox` exists. `inout` must be an open file
+/// descriptor that nothing else closes, since termbox takes ownership of it and closes it, even if
+/// initialization fails.
+pub unsafe fn tb_init_fd (inout: c_int) -> c_int {
+  let input = File::from_raw_fd(inout);
+  match input.try_clone() {
+    Ok(output) => init_with(input, output),
+    Err(_) => TB_EFAILED_TO_OPEN_TTY,
+  }
+}
+
+/// Not part of the C library. Like `tb_init_fd`, but keys are read from `input` while output is
+/// written to `output`. Takes ownership of both file descriptors.
+///
+/// # Safety
+///
+/// Termbox must not be initialized while a `Termbox` exists. `input` and `output` must be open file
+/// descriptors that nothing else closes, since termbox takes ownership of them and closes them,
+/// even if initialization fails.
+pub unsafe fn tb_init_fds (input: c_int, output: c_int) -> c_int {
+  init_with(File::from_raw_fd(input), File::from_raw_fd(output))
+}
+
+/// Initializes termbox on the terminal device at `name`.
+///
+/// # Safety
+///
+/// Termbox must not be initialized while a `Termbox` exists. `name` must point to a nul-terminated
+/// string.
+pub unsafe fn tb_init_file (name: *const c_char) -> c_int {
+  let path = ::std::ffi::OsStr::from_bytes(CStr::from_ptr(name).to_bytes());
+  let input = match OpenOptions::new().read(true).write(true).open(path) {
+    Ok(input) => input,
+    Err(_) => { return TB_EFAILED_TO_OPEN_TTY; },
+  };
+  match input.try_clone() {
+    Ok(output) => init_with(input, output),
+    Err(_) => TB_EFAILED_TO_OPEN_TTY,
+  }
+}
+
+/// Restores the terminal and shuts termbox down.
+///
+/// # Safety
+///
+/// Must not be called while a `Termbox` exists, since it would go on using the terminal.
+pub unsafe fn tb_shutdown () {
+  let _ = tb_shutdown_checked();
+}
+
+/// Not part of the C library. Like `tb_shutdown`, but reports whether the terminal could be
+/// restored. Termbox is shut down either way.
+///
+/// # Safety
+///
+/// Must not be called while a `Termbox` exists, since it would go on using the terminal.
+pub unsafe fn tb_shutdown_checked () -> Result<(), ShutdownError> {
+  let mut state = match lock().take() {
+    Some(state) => state,
+    None => { return Ok(()); },
+  };
+
+  if state.input_mode & TB_INPUT_MOUSE != 0 {
+    state.out.extend_from_slice(MOUSE_OFF_SEQUENCE);
+  }
+  if state.input_mode & TB_INPUT_PASTE != 0 {
+    state.out.extend_from_slice(PASTE_OFF_SEQUENCE);
+  }
+  if state.input_mode & TB_INPUT_KITTY != 0 {
+    state.out.extend_from_slice(KITTY_OFF_SEQUENCE);
+  }
+  state.out.extend_from_slice(EXIT_SEQUENCE);
+  let written = state.try_flush().map_err(ShutdownError::Write);
+
+  let fd = state.tty_in.as_raw_fd();
+  let restored = if libc::tcsetattr(fd, libc::TCSAFLUSH, &state.orig_termios) == 0 {
+    Ok(())
+  } else {
+    Err(ShutdownError::Settings(io::Error::last_os_error()))
+  };
+  libc::sigaction(libc::SIGWINCH, &state.orig_sigwinch, ::std::ptr::null_mut());
+
+  return written.and(restored);
+}
+
+/// Returns the width of the terminal, or -1 if termbox is not initialized.
+///
+/// # Safety
+///
+/// None. Termbox's state is only read, under a lock. The function is unsafe to match the C library.
+pub unsafe fn tb_width () -> c_int {
+  lock().as_ref().map(|state| state.width).unwrap_or(-1)
+}
+
+/// Returns the height of the terminal, or -1 if termbox is not initialized.
+///
+/// # Safety
+///
+/// None. Termbox's state is only read, under a lock. The function is unsafe to match the C library.
+pub unsafe fn tb_height () -> c_int {
+  lock().as_ref().map(|state| state.height).unwrap_or(-1)
+}
+
+/// Fills the back buffer with the clear attributes.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_clear () {
+  if let Some(ref mut state) = *lock() {
+    let blank = state.blank();
+    for cell in state.back.iter_mut() {
+      *cell = blank;
+    }
+  }
+}
+
+/// Writes the cells of the back buffer that changed since the last call.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_present () {
+  if let Some(ref mut state) = *lock() {
+    state.present();
+  }
+}
+
+/// Not part of the C library. Writes any output that has been collected but not yet written, such
+/// as cursor changes.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_flush () -> io::Result<()> {
+  match *lock() {
+    Some(ref mut state) => state.try_flush(),
+    None => Ok(()),
+  }
+}
+
+/// Not part of the C library. Returns the number of bytes written to the terminal by the last call
+/// to tb_present.
+///
+/// # Safety
+///
+/// None. Termbox's state is only read, under a lock. The function is unsafe to match the rest of
+/// this module.
+pub unsafe fn tb_present_bytes () -> usize {
+  match *lock() {
+    Some(ref state) => state.present_bytes,
+    None => 0,
+  }
+}
+
+/// Moves the cursor, or hides it if either coordinate is `TB_HIDE_CURSOR`.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_set_cursor (cx: c_int, cy: c_int) {
+  if let Some(ref mut state) = *lock() {
+    let was_hidden = is_cursor_hidden(state.cursor_x, state.cursor_y);
+    let hidden = is_cursor_hidden(cx, cy);
+
+    if was_hidden && !hidden {
+      state.out.extend_from_slice(b"\x1b[?25h");
+    } else if !was_hidden && hidden {
+      state.out.extend_from_slice(b"\x1b[?25l");
+    }
+
+    state.cursor_x = cx;
+    state.cursor_y = cy;
+    if !hidden {
+      state.write_cursor();
+    }
+  }
+}
+
+/// Sets a cell of the back buffer. Cells outside of it are ignored.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state. `ch` must be a Unicode scalar value.
+pub unsafe fn tb_change_cell (x: c_int, y: c_int, ch: u32, fg: u16, bg: u16) {
+  tb_put_cell(x, y, &RawCell { ch: ch, fg: fg, bg: bg });
+}
+
+/// Sets a cell of the back buffer. Cells outside of it are ignored.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state. `cell` must point to a `RawCell` whose `ch` is
+/// a Unicode scalar value.
+pub unsafe fn tb_put_cell (x: c_int, y: c_int, cell: *const RawCell) {
+  if let Some(ref mut state) = *lock() {
+    if let Some(index) = state.index(x, y) {
+      state.back[index] = *cell;
+    }
+  }
+}
+
+/// Copies a `w` by `h` rectangle of cells, row by row, into the back buffer at (`x`, `y`).
+/// Cells outside of the buffer are ignored.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state. `cells` must point to `w * h` `RawCell`s whose
+/// `ch` are Unicode scalar values.
+pub unsafe fn tb_blit (x: c_int, y: c_int, w: c_int, h: c_int, cells: *const RawCell) {
+  if let Some(ref mut state) = *lock() {
+    for row in 0..h {
+      for col in 0..w {
+        if let Some(index) = state.index(x + col, y + row) {
+          state.back[index] = *cells.offset((row * w + col) as isize);
+        }
+      }
+    }
+  }
+}
+
+/// Returns the back buffer, row by row, or null if termbox is not initialized.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state. The pointer is invalidated when the terminal
+/// is resized or termbox is shut down, and every `ch` written through it must be a Unicode scalar
+/// value. It must not be used while another function of this module runs on another thread.
+pub unsafe fn tb_cell_buffer () -> *mut RawCell {
+  match *lock() {
+    Some(ref mut state) => state.back.as_mut_ptr(),
+    None => ::std::ptr::null_mut(),
+  }
+}
+
+/// Not part of the C library. Returns the sequences registered with tb_register_key_sequence and
+/// the keys they decode as.
+///
+/// # Safety
+///
+/// None. Termbox's state is only read, under a lock. The function is unsafe to match the rest of
+/// this module.
+pub unsafe fn tb_key_sequences () -> Vec<(Vec<u8>, u16)> {
+  match *lock() {
+    Some(ref state) => state.key_sequences.clone(),
+    None => Vec::new(),
+  }
+}
+
+/// Not part of the C library. Waits up to `timeout` milliseconds for the terminal to send an escape
+/// sequence for which `is_reply` returns true, and removes and returns it. Other input read while
+/// waiting is kept for tb_peek_event and tb_poll_event.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state. The reply is removed from the input that
+/// `Termbox` reads events from.
+pub unsafe fn tb_read_reply (timeout: c_int, is_reply: &dyn Fn(&[u8]) -> bool) -> Option<Vec<u8>> {
+  let deadline = Instant::now() + Duration::from_millis(timeout.max(0) as u64);
+
+  loop {
+    let fd = match *lock() {
+      Some(ref mut state) => {
+        if let Some((start, len)) = tty::find_sequence(&state.input, is_reply) {
+          return Some(state.input.drain(start..start + len).collect());
+        }
+        state.tty_in.as_raw_fd()
+      },
+      None => { return None; },
+    };
+
+    let now = Instant::now();
+    if now >= deadline {
+      return None;
+    }
+
+    let mut pfd = libc::pollfd { fd: fd, events: libc::POLLIN, revents: 0 };
+    match libc::poll(&mut pfd, 1, (deadline - now).as_millis() as c_int) {
+      0 => { return None; },
+      n if n < 0 => {
+        if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+          return None;
+        }
+      },
+      _ => {
+        let mut buf = [0u8; 64];
+        let n = libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len());
+        if n == 0 {
+          return None;
+        } else if n < 0 {
+          if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return None;
+          }
+        } else {
+          match *lock() {
+            Some(ref mut state) => { state.receive(&buf[..n as usize]); },
+            None => { return None; },
+          }
+        }
+      },
+    }
+  }
+}
+
+/// Not part of the C library. Makes the input sequence `seq` decode as `key`, ahead of the
+/// built-in sequences. A sequence that is already registered is mapped to the new key.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_register_key_sequence (seq: &[u8], key: u16) {
+  if let Some(ref mut state) = *lock() {
+    match state.key_sequences.iter_mut().find(|entry| entry.0 == seq) {
+      Some(entry) => { entry.1 = key; },
+      None => { state.key_sequences.push((seq.to_vec(), key)); },
+    }
+  }
+}
+
+/// Not part of the C library. Sets how many milliseconds to wait for the rest of an escape
+/// sequence before reporting a lone Esc, unless `ms` is negative. Returns the current delay.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_select_escape_delay (ms: c_int) -> c_int {
+  match *lock() {
+    Some(ref mut state) => {
+      if ms >= 0 {
+        state.escape_delay_ms = ms;
+      }
+      state.escape_delay_ms
+    },
+    None => 0,
+  }
+}
+
+/// Not part of the C library. Sets the number of bytes of output that are collected while
+/// presenting before they are written to the terminal, unless `bytes` is negative. Zero writes the
+/// whole frame at once. Returns the current size.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_select_output_buffer_capacity (bytes: c_int) -> c_int {
+  match *lock() {
+    Some(ref mut state) => {
+      if bytes >= 0 {
+        state.out_capacity = bytes as usize;
+        let additional = state.out_capacity.saturating_sub(state.out.len());
+        state.out.reserve(additional);
+      }
+      state.out_capacity as c_int
+    },
+    None => 0,
+  }
+}
+
+/// Sets the input mode, unless `mode` is `TB_INPUT_CURRENT`. Returns the current mode.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state. Modes set here that `InputMode` can't
+/// represent make `Termbox::input_mode` panic.
+pub unsafe fn tb_select_input_mode (mode: c_int) -> c_int {
+  match *lock() {
+    Some(ref mut state) => {
+      if mode != TB_INPUT_CURRENT {
+        let mut mode = mode;
+        if mode & (TB_INPUT_ESC | TB_INPUT_ALT) == 0 {
+          mode |= TB_INPUT_ESC;
+        }
+        if mode & TB_INPUT_ESC != 0 && mode & TB_INPUT_ALT != 0 {
+          mode &= !TB_INPUT_ALT;
+        }
+        if mode & TB_INPUT_RAW_ONLY != 0 {
+          mode |= TB_INPUT_RAW;
+        }
+        if mode & TB_INPUT_RAW == 0 {
+          state.raw.clear();
+          state.raw_pending = false;
+        }
+
+        if mode & TB_INPUT_MOUSE != state.input_mode & TB_INPUT_MOUSE {
+          if mode & TB_INPUT_MOUSE != 0 {
+            state.out.extend_from_slice(MOUSE_ON_SEQUENCE);
+          } else {
+            state.out.extend_from_slice(MOUSE_OFF_SEQUENCE);
+          }
+          state.flush();
+        }
+
+        if mode & TB_INPUT_PASTE != state.input_mode & TB_INPUT_PASTE {
+          if mode & TB_INPUT_PASTE != 0 {
+            state.out.extend_from_slice(PASTE_ON_SEQUENCE);
+          } else {
+            state.out.extend_from_slice(PASTE_OFF_SEQUENCE);
+          }
+          state.flush();
+        }
+
+        if mode & TB_INPUT_KITTY != state.input_mode & TB_INPUT_KITTY {
+          if mode & TB_INPUT_KITTY != 0 {
+            state.out.extend_from_slice(KITTY_ON_SEQUENCE);
+          } else {
+            state.out.extend_from_slice(KITTY_OFF_SEQUENCE);
+          }
+          state.flush();
+        }
+
+        state.input_mode = mode;
+      }
+      state.input_mode
+    },
+    None => 0,
+  }
+}
+
+/// Sets the output mode, unless `mode` is `TB_OUTPUT_CURRENT`. Returns the current mode.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state. Modes set here that `OutputMode` can't
+/// represent make `Termbox::output_mode` panic.
+pub unsafe fn tb_select_output_mode (mode: c_int) -> c_int {
+  match *lock() {
+    Some(ref mut state) => {
+      if mode != TB_OUTPUT_CURRENT {
+        state.output_mode = mode;
+        state.last_attr = None;
+      }
+      state.output_mode
+    },
+    None => 0,
+  }
+}
+
+/// Sets the attributes that `tb_clear` fills the back buffer with.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_set_clear_attributes (fg: u16, bg: u16) {
+  if let Some(ref mut state) = *lock() {
+    state.clear_fg = fg;
+    state.clear_bg = bg;
+  }
+}
+
+/// Waits up to `timeout` milliseconds for an event and stores it in `event`. Returns its
+/// type, 0 if none arrived, or -1 on error.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state. `event` must point to a writable `RawEvent`.
+pub unsafe fn tb_peek_event (event: *mut RawEvent, timeout: c_int) -> c_int {
+  wait_event(&mut *event, Some(timeout))
+}
+
+/// Waits for an event and stores it in `event`. Returns its type, or -1 on error.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state. `event` must point to a writable `RawEvent`.
+pub unsafe fn tb_poll_event (event: *mut RawEvent) -> c_int {
+  wait_event(&mut *event, None)
+}
+
+/// Not part of the C library. Removes and returns the bytes read from the terminal since the last
+/// call, if TB_INPUT_RAW is set.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_take_raw_input () -> Vec<u8> {
+  match *lock() {
+    Some(ref mut state) => mem::replace(&mut state.raw, Vec::new()),
+    None => Vec::new(),
+  }
+}
+
+/// Not part of the C library. Removes and returns the escape sequence of the last TB_EVENT_UNKNOWN.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_take_unknown_sequence () -> Vec<u8> {
+  match *lock() {
+    Some(ref mut state) => mem::replace(&mut state.unknown, Vec::new()),
+    None => Vec::new(),
+  }
+}
+
+/// Not part of the C library. Removes a sequence registered with tb_register_key_sequence. Returns
+/// 0 if it was not registered.
+///
+/// # Safety
+///
+/// Must only be called as described in the `raw` module documentation, since `Termbox` relies on
+/// being the only code that changes termbox's state.
+pub unsafe fn tb_unregister_key_sequence (seq: &[u8]) -> c_int {
+  match *lock() {
+    Some(ref mut state) => {
+      let len = state.key_sequences.len();
+      state.key_sequences.retain(|entry| entry.0 != seq);
+      (state.key_sequences.len() != len) as c_int
+    },
+    None => 0,
+  }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Direct access to the termbox C library. This is only available with the `unsafe-ffi` feature.
+//!
+//! Everything from `termbox-sys` is re-exported here, so crates that need the raw bindings can
+//! depend on the version used by this crate rather than on `termbox-sys` directly. With the
+//! `pure-rust` feature, the same functions, types and constants come from the pure Rust backend
+//! instead.
+//!
+//! # Safety
+//!
+//! Termbox keeps all of its state in global variables, and `Termbox` relies on being the only
+//! code that changes that state. In particular:
+//!
+//! * `tb_init*` and `tb_shutdown` must not be called while a `Termbox` exists. Doing so will
+//!   corrupt the terminal or cause termbox to abort the process.
+//! * Drawing functions (`tb_change_cell`, `tb_blit`, etc.) may be called while a `Termbox`
+//!   exists, but only from the thread that owns it.
+//! * `tb_cell_buffer` returns a pointer that is invalidated when the terminal is resized or
+//!   termbox is shut down. Every `ch` written through it must be a valid Unicode scalar value.
+//! * `tb_select_input_mode` and `tb_select_output_mode` may be used to set modes that the safe API
+//!   cannot represent, in which case `Termbox::input_mode` and `Termbox::output_mode` may panic.
+
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
+use std::path::Path;
+
+use Error;
+
+#[cfg(not(feature = "pure-rust"))]
+pub use ffi::*;
+
+// The pure Rust backend has functions and constants of its own, which are left out so that this
+// module provides the same items with either backend.
+#[cfg(feature = "pure-rust")]
+pub use ffi::{
+  tb_blit,
+  tb_cell_buffer,
+  tb_change_cell,
+  tb_clear,
+  tb_height,
+  tb_init,
+  tb_init_fd,
+  tb_init_file,
+  tb_peek_event,
+  tb_poll_event,
+  tb_present,
+  tb_put_cell,
+  tb_select_input_mode,
+  tb_select_output_mode,
+  tb_set_clear_attributes,
+  tb_set_cursor,
+  tb_shutdown,
+  tb_width,
+  RawCell,
+  RawEvent,
+  TB_KEY_F1,
+  TB_KEY_F2,
+  TB_KEY_F3,
+  TB_KEY_F4,
+  TB_KEY_F5,
+  TB_KEY_F6,
+  TB_KEY_F7,
+  TB_KEY_F8,
+  TB_KEY_F9,
+  TB_KEY_F10,
+  TB_KEY_F11,
+  TB_KEY_F12,
+  TB_KEY_INSERT,
+  TB_KEY_DELETE,
+  TB_KEY_HOME,
+  TB_KEY_END,
+  TB_KEY_PGUP,
+  TB_KEY_PGDN,
+  TB_KEY_ARROW_UP,
+  TB_KEY_ARROW_DOWN,
+  TB_KEY_ARROW_LEFT,
+  TB_KEY_ARROW_RIGHT,
+  TB_KEY_MOUSE_LEFT,
+  TB_KEY_MOUSE_RIGHT,
+  TB_KEY_MOUSE_MIDDLE,
+  TB_KEY_MOUSE_RELEASE,
+  TB_KEY_MOUSE_WHEEL_UP,
+  TB_KEY_MOUSE_WHEEL_DOWN,
+  TB_KEY_CTRL_TILDE,
+  TB_KEY_CTRL_2,
+  TB_KEY_CTRL_A,
+  TB_KEY_CTRL_B,
+  TB_KEY_CTRL_C,
+  TB_KEY_CTRL_D,
+  TB_KEY_CTRL_E,
+  TB_KEY_CTRL_F,
+  TB_KEY_CTRL_G,
+  TB_KEY_BACKSPACE,
+  TB_KEY_CTRL_H,
+  TB_KEY_TAB,
+  TB_KEY_CTRL_I,
+  TB_KEY_CTRL_J,
+  TB_KEY_CTRL_K,
+  TB_KEY_CTRL_L,
+  TB_KEY_ENTER,
+  TB_KEY_CTRL_M,
+  TB_KEY_CTRL_N,
+  TB_KEY_CTRL_O,
+  TB_KEY_CTRL_P,
+  TB_KEY_CTRL_Q,
+  TB_KEY_CTRL_R,
+  TB_KEY_CTRL_S,
+  TB_KEY_CTRL_T,
+  TB_KEY_CTRL_U,
+  TB_KEY_CTRL_V,
+  TB_KEY_CTRL_W,
+  TB_KEY_CTRL_X,
+  TB_KEY_CTRL_Y,
+  TB_KEY_CTRL_Z,
+  TB_KEY_ESC,
+  TB_KEY_CTRL_LSQ_BRACKET,
+  TB_KEY_CTRL_3,
+  TB_KEY_CTRL_4,
+  TB_KEY_CTRL_BACKSLASH,
+  TB_KEY_CTRL_5,
+  TB_KEY_CTRL_RSQ_BRACKET,
+  TB_KEY_CTRL_6,
+  TB_KEY_CTRL_7,
+  TB_KEY_CTRL_SLASH,
+  TB_KEY_CTRL_UNDERSCORE,
+  TB_KEY_SPACE,
+  TB_KEY_BACKSPACE2,
+  TB_KEY_CTRL_8,
+  TB_MOD_ALT,
+  TB_DEFAULT,
+  TB_BLACK,
+  TB_RED,
+  TB_GREEN,
+  TB_YELLOW,
+  TB_BLUE,
+  TB_MAGENTA,
+  TB_CYAN,
+  TB_WHITE,
+  TB_BOLD,
+  TB_UNDERLINE,
+  TB_REVERSE,
+  TB_EVENT_KEY,
+  TB_EVENT_RESIZE,
+  TB_EVENT_MOUSE,
+  TB_EUNSUPPORTED_TERMINAL,
+  TB_EFAILED_TO_OPEN_TTY,
+  TB_EPIPE_TRAP_ERROR,
+  TB_HIDE_CURSOR,
+  TB_INPUT_CURRENT,
+  TB_INPUT_ESC,
+  TB_INPUT_ALT,
+  TB_INPUT_MOUSE,
+  TB_OUTPUT_CURRENT,
+  TB_OUTPUT_NORMAL,
+  TB_OUTPUT_256,
+  TB_OUTPUT_216,
+  TB_OUTPUT_GRAYSCALE,
+  TB_EOF,
+};
+
+/// Initializes termbox on the terminal device at `path` instead of `/dev/tty`.
+///
+/// # Safety
+///
+/// See the module documentation. Termbox must not already be initialized.
+pub unsafe fn init_file (path: &Path) -> Result<(), Error> {
+  let name = match CString::new(path.as_os_str().as_bytes()) {
+    Ok(name) => name,
+    Err(_) => { return Err(Error::FailedToOpenTty); },
+  };
+
+  match tb_init_file(name.as_ptr()) {
+    0 => Ok(()),
+    n => Err(Error::from_raw(n).unwrap()),
+  }
+}
+
+/// Initializes termbox on an already open terminal file descriptor. Termbox takes ownership of
+/// `fd` and closes it when shut down.
+///
+/// # Safety
+///
+/// See the module documentation. Termbox must not already be initialized, and `fd` must be a valid
+/// file descriptor that is not closed by anything else.
+pub unsafe fn init_fd (fd: RawFd) -> Result<(), Error> {
+  match tb_init_fd(fd) {
+    0 => Ok(()),
+    n => Err(Error::from_raw(n).unwrap()),
+  }
+}
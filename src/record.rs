@@ -0,0 +1,265 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Recording and playback of input events, for reproducing bugs and scripting demos. See
+//! `Termbox::start_recording` and `Termbox::replay`.
+//!
+//! Recordings are plain text with one event per line, preceded by the number of milliseconds since
+//! recording started:
+//!
+//! ~~~text
+//! 0 resize 80 24
+//! 1520 key 0 104 0 press
+//! 1610 key 13 0 0 press
+//! 2300 mouse left 10 4
+//! ~~~
+//!
+//! Key events list the key code, the character code (zero if none), whether Alt was held and the
+//! key state. Mouse events list the button, prefixed by any held modifiers as in `shift+wheel-up`,
+//! and the position, followed by the click count if it is more than one. Empty lines and lines
+//! starting with `#` are ignored. Task, custom, raw input and unknown sequence events carry values
+//! that can't be written down, so they are left out of recordings.
+//!
+//! ~~~
+//! use termbox::{Event, ResizeEvent};
+//! use termbox::record;
+//!
+//! let line = record::format_event(2500, &Event::Resize(ResizeEvent { w: 80, h: 24 })).unwrap();
+//! assert_eq!(line, "2500 resize 80 24");
+//! assert!(record::parse_event(&line).is_some());
+//! ~~~
+
+use std::char;
+use std::collections::VecDeque;
+use std::io::{
+  self,
+  BufRead,
+  BufReader,
+  Read,
+  Write,
+};
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use {
+  Event,
+  KeyEvent,
+  KeyState,
+  MouseButton,
+  MouseEvent,
+  ResizeEvent,
+  Signal,
+};
+
+
+//
+// Player
+//
+
+
+/// Feeds recorded events back to the event loop at the times they were recorded.
+pub(crate) struct Player {
+  start: Instant,
+  events: VecDeque<(u64, Event)>,
+}
+
+impl Player {
+  /// Returns when the next event is due, or `None` if the recording is finished.
+  pub fn next_deadline (&self) -> Option<Instant> {
+    self.events.front().map(|&(millis, _)| self.start + Duration::from_millis(millis))
+  }
+
+  /// Reads a whole recording. Playback starts immediately.
+  pub fn open<R: Read> (reader: R) -> io::Result<Player> {
+    let mut events = VecDeque::new();
+
+    for (index, line) in BufReader::new(reader).lines().enumerate() {
+      let line = line?;
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      match parse_event(line) {
+        Some(event) => { events.push_back(event); },
+        None => {
+          let message = format!("invalid event on line {}", index + 1);
+          return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        },
+      }
+    }
+
+    return Ok(Player {
+      start: Instant::now(),
+      events: events,
+    });
+  }
+
+  /// Returns the next event if it is due.
+  pub fn poll (&mut self) -> Option<Event> {
+    match self.next_deadline() {
+      Some(deadline) if deadline <= Instant::now() => {
+        self.events.pop_front().map(|(_, event)| event)
+      },
+      _ => None,
+    }
+  }
+}
+
+
+//
+// Recorder
+//
+
+
+/// Writes events to a recording as they are reported.
+pub(crate) struct Recorder {
+  writer: Box<dyn Write>,
+  start: Instant,
+}
+
+impl Recorder {
+  pub fn new (writer: Box<dyn Write>) -> Recorder {
+    Recorder {
+      writer: writer,
+      start: Instant::now(),
+    }
+  }
+
+  /// Appends an event to the recording. Events that can't be recorded are skipped.
+  pub fn record (&mut self, event: &Event) -> io::Result<()> {
+    let elapsed = self.start.elapsed();
+    let millis = elapsed.as_secs() * 1000 + elapsed.subsec_millis() as u64;
+
+    if let Some(line) = format_event(millis, event) {
+      writeln!(self.writer, "{}", line)?;
+      self.writer.flush()?;
+    }
+    return Ok(());
+  }
+}
+
+
+//
+// Functions
+//
+
+
+/// Formats an event as a line of a recording, without the line break. Returns `None` for events
+/// that can't be recorded.
+pub fn format_event (millis: u64, event: &Event) -> Option<String> {
+  let body = match *event {
+    Event::Key(key) => {
+      let state = match key.state {
+        KeyState::Press => "press",
+        KeyState::Repeat => "repeat",
+        KeyState::Release => "release",
+      };
+      let ch = key.ch.map(|ch| ch as u32).unwrap_or(0);
+      format!("key {} {} {} {}", key.key, ch, key.alt as u8, state)
+    },
+    Event::Resize(resize) => format!("resize {} {}", resize.w, resize.h),
+    Event::Mouse(mouse) => {
+      let button = match mouse.button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+        MouseButton::Release => "release",
+        MouseButton::WheelUp => "wheel-up",
+        MouseButton::WheelDown => "wheel-down",
+        MouseButton::WheelLeft => "wheel-left",
+        MouseButton::WheelRight => "wheel-right",
+      };
+      let mut button = button.to_string();
+      for &(held, name) in &[(mouse.ctrl, "ctrl+"), (mouse.alt, "alt+"), (mouse.shift, "shift+")] {
+        if held {
+          button.insert_str(0, name);
+        }
+      }
+      if mouse.clicks == 1 {
+        format!("mouse {} {} {}", button, mouse.x, mouse.y)
+      } else {
+        format!("mouse {} {} {} {}", button, mouse.x, mouse.y, mouse.clicks)
+      }
+    },
+    Event::PasteStart => String::from("paste-start"),
+    Event::PasteEnd => String::from("paste-end"),
+    Event::Signal(Signal::Interrupt) => String::from("signal interrupt"),
+    Event::Signal(Signal::Terminate) => String::from("signal terminate"),
+    Event::Timer(id) => format!("timer {}", id),
+    Event::Resumed => String::from("resumed"),
+    Event::ResumeFailed => String::from("resume-failed"),
+    _ => { return None; },
+  };
+
+  return Some(format!("{} {}", millis, body));
+}
+
+/// Parses a line of a recording. Returns the time in milliseconds and the event, or `None` if the
+/// line is not valid.
+pub fn parse_event (line: &str) -> Option<(u64, Event)> {
+  let fields: Vec<&str> = line.split_whitespace().collect();
+  let millis = fields.get(0)?.parse().ok()?;
+
+  let event = match fields[1..] {
+    ["key", key, ch, alt, state] => {
+      let ch: u32 = ch.parse().ok()?;
+      Event::Key(KeyEvent {
+        key: key.parse().ok()?,
+        ch: if ch == 0 { None } else { Some(char::from_u32(ch)?) },
+        alt: alt == "1",
+        state: match state {
+          "press" => KeyState::Press,
+          "repeat" => KeyState::Repeat,
+          "release" => KeyState::Release,
+          _ => { return None; },
+        },
+      })
+    },
+    ["resize", w, h] => Event::Resize(ResizeEvent { w: w.parse().ok()?, h: h.parse().ok()? }),
+    ["mouse", button, x, y, ref clicks @ ..] => {
+      let clicks = match *clicks {
+        [] => 1,
+        [clicks] => clicks.parse().ok()?,
+        _ => { return None; },
+      };
+      let mut names: Vec<&str> = button.split('+').collect();
+      let button = names.pop()?;
+      if names.iter().any(|&name| name != "shift" && name != "alt" && name != "ctrl") {
+        return None;
+      }
+      let held = |modifier| names.iter().any(|&name| name == modifier);
+      Event::Mouse(MouseEvent {
+        button: match button {
+          "left" => MouseButton::Left,
+          "right" => MouseButton::Right,
+          "middle" => MouseButton::Middle,
+          "release" => MouseButton::Release,
+          "wheel-up" => MouseButton::WheelUp,
+          "wheel-down" => MouseButton::WheelDown,
+          "wheel-left" => MouseButton::WheelLeft,
+          "wheel-right" => MouseButton::WheelRight,
+          _ => { return None; },
+        },
+        x: x.parse().ok()?,
+        y: y.parse().ok()?,
+        clicks: clicks,
+        shift: held("shift"),
+        alt: held("alt"),
+        ctrl: held("ctrl"),
+      })
+    },
+    ["paste-start"] => Event::PasteStart,
+    ["paste-end"] => Event::PasteEnd,
+    ["signal", "interrupt"] => Event::Signal(Signal::Interrupt),
+    ["signal", "terminate"] => Event::Signal(Signal::Terminate),
+    ["timer", id] => Event::Timer(id.parse().ok()?),
+    ["resumed"] => Event::Resumed,
+    ["resume-failed"] => Event::ResumeFailed,
+    _ => { return None; },
+  };
+
+  return Some((millis, event));
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use {
+  Coord,
+  Rect,
+};
+
+/// Identifies a region registered with `Termbox::register_region`. Ids are chosen by the
+/// application.
+pub type RegionId = u64;
+
+
+//
+// Regions
+//
+
+
+/// Maps screen positions to the regions registered over them, for hit-testing mouse events.
+pub struct Regions {
+  // In the order they were registered, so later regions are on top.
+  regions: Vec<(RegionId, Rect)>,
+}
+
+impl Regions {
+  pub fn clear (&mut self) {
+    self.regions.clear();
+  }
+
+  /// Returns the id of the topmost region containing the cell at `x`, `y`.
+  pub fn hit_test (&self, x: Coord, y: Coord) -> Option<RegionId> {
+    self.regions.iter().rev().find(|&&(_, rect)| rect.contains(x, y)).map(|&(id, _)| id)
+  }
+
+  pub fn new () -> Regions {
+    Regions {
+      regions: Vec::new(),
+    }
+  }
+
+  /// Adds a region on top of all others. A region with the same id is replaced.
+  pub fn register (&mut self, id: RegionId, rect: Rect) {
+    self.unregister(id);
+    self.regions.push((id, rect));
+  }
+
+  /// Removes a region. Returns false if there was no region with the given id.
+  pub fn unregister (&mut self, id: RegionId) -> bool {
+    let len = self.regions.len();
+    self.regions.retain(|&(region, _)| region != id);
+    self.regions.len() != len
+  }
+}
@@ -0,0 +1,528 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Remote rendering over a socket, so that a program can run without a terminal, for example as a
+//! daemon on a server, and be viewed and controlled from a terminal elsewhere. The program draws
+//! to a `RemoteTermbox`, and a viewer displays it on any other backend with `view`.
+//!
+//! The protocol is plain text with one message per line. The viewer sends events in the format
+//! used by recordings (see the `record` module), starting with a resize event giving the size of
+//! its screen. The program sends the changes made to its output buffer each time it presents a
+//! frame:
+//!
+//! ~~~text
+//! output normal
+//! input esc
+//! mouse off
+//! cell 0 0 104 8 0
+//! cell 1 0 105 8 0
+//! cursor 2 0
+//! present
+//! ~~~
+//!
+//! Cell messages give the position, the character code and the foreground and background
+//! attributes. The cursor is either a position or `hide`. Output mode, input mode and mouse
+//! messages are only sent when the setting changes. Unknown messages are ignored.
+//!
+//! ~~~no_run
+//! use std::net::TcpListener;
+//! use termbox::{Backend, Event, DEFAULT};
+//! use termbox::remote::RemoteTermbox;
+//!
+//! let listener = TcpListener::bind("127.0.0.1:7070").unwrap();
+//! let (stream, _) = listener.accept().unwrap();
+//! let mut tb = RemoteTermbox::from_tcp(stream).unwrap();
+//! tb.put_str(0, 0, "Hello from the server", DEFAULT, DEFAULT);
+//! tb.present();
+//! while let Event::Resize(_) = tb.poll_event() {}
+//! ~~~
+//!
+//! The viewer, in another process:
+//!
+//! ~~~no_run
+//! use std::net::TcpStream;
+//! use termbox::Termbox;
+//! use termbox::remote;
+//!
+//! let stream = TcpStream::connect("127.0.0.1:7070").unwrap();
+//! let mut tb = Termbox::open().unwrap();
+//! remote::view(&mut tb, stream.try_clone().unwrap(), stream).unwrap();
+//! ~~~
+
+use std::io::{
+  self,
+  BufRead,
+  BufReader,
+  BufWriter,
+  Read,
+  Write,
+};
+use std::net::{
+  Shutdown,
+  TcpStream,
+};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc::{
+  self,
+  Receiver,
+  RecvTimeoutError,
+  TryRecvError,
+};
+use std::thread;
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use backend;
+use record;
+use {
+  Attribute,
+  Backend,
+  Cell,
+  Coord,
+  Event,
+  InputMode,
+  OutputMode,
+  ResizeEvent,
+  Signal,
+  Time,
+  DEFAULT,
+};
+
+// The largest screen a viewer may report, so that a bogus size can't exhaust memory.
+const MAX_CELLS: usize = 1 << 22;
+
+// How long `view` waits for an event before checking for messages from the program.
+const VIEW_POLL_MILLIS: Time = 10;
+
+
+//
+// RemoteTermbox
+//
+
+
+/// A backend that sends what is drawn to a viewer over a stream, and receives its events. See the
+/// module documentation for the protocol.
+///
+/// The output buffer takes the size of the viewer's screen, and is resized when a resize event is
+/// received from it. When the viewer disconnects or reports an unreasonably large size,
+/// `Event::Signal(Signal::Terminate)` is reported in place of further events, so that the program
+/// can quit as it would if its terminal was closed.
+pub struct RemoteTermbox {
+  width: Coord,
+  height: Coord,
+  cells: Vec<Cell>,
+  // The cells as of the last frame sent to the viewer. Empty when the whole buffer must be sent.
+  sent: Vec<Cell>,
+  clear_fg: Attribute,
+  clear_bg: Attribute,
+  cursor: Option<(Coord, Coord)>,
+  sent_cursor: Option<Option<(Coord, Coord)>>,
+  input_mode: InputMode,
+  mouse_enabled: bool,
+  output_mode: OutputMode,
+  modes_changed: bool,
+  events: Receiver<Event>,
+  writer: BufWriter<Box<dyn Write + Send>>,
+  connected: bool,
+  // Shuts the socket down when dropped, since the event thread holds a handle to it.
+  shutdown: Option<Box<dyn Fn() + Send>>,
+}
+
+impl RemoteTermbox {
+  /// Serves a viewer connected over TCP.
+  pub fn from_tcp (stream: TcpStream) -> io::Result<RemoteTermbox> {
+    stream.set_nodelay(true)?;
+    let reader = stream.try_clone()?;
+    let closer = stream.try_clone()?;
+    RemoteTermbox::serve(reader, stream, Some(Box::new(move || {
+      let _ = closer.shutdown(Shutdown::Both);
+    })))
+  }
+
+  /// Serves a viewer connected over a Unix domain socket.
+  #[cfg(unix)]
+  pub fn from_unix (stream: UnixStream) -> io::Result<RemoteTermbox> {
+    let reader = stream.try_clone()?;
+    let closer = stream.try_clone()?;
+    RemoteTermbox::serve(reader, stream, Some(Box::new(move || {
+      let _ = closer.shutdown(Shutdown::Both);
+    })))
+  }
+
+  /// Returns false once the viewer has disconnected or the connection has failed.
+  pub fn is_connected (&self) -> bool {
+    self.connected
+  }
+
+  /// Serves a viewer that sends events to `reader` and is sent frames through `writer`. Waits for
+  /// the viewer to report the size of its screen. Events are read on a separate thread, which owns
+  /// `reader` until the viewer closes its end, so for sockets use `from_tcp` or `from_unix`, which
+  /// shut the socket down when the `RemoteTermbox` is dropped.
+  pub fn new<R, W> (reader: R, writer: W) -> io::Result<RemoteTermbox>
+    where R: Read + Send + 'static, W: Write + Send + 'static
+  {
+    RemoteTermbox::serve(reader, writer, None)
+  }
+
+  // Called when the event thread has stopped.
+  fn disconnected (&mut self) -> Event {
+    self.connected = false;
+    return Event::Signal(Signal::Terminate);
+  }
+
+  // Resizes the output buffer to the viewer's size. Returns false if the size is too large, in
+  // which case the buffer is left as it is.
+  fn resize_buffer (&mut self, width: Coord, height: Coord) -> bool {
+    let width = width.max(0);
+    let height = height.max(0);
+    let len = match (width as usize).checked_mul(height as usize) {
+      Some(len) if len <= MAX_CELLS => len,
+      _ => { return false; },
+    };
+    let blank = Cell::new(' ', self.clear_fg, self.clear_bg);
+    let mut cells = vec![blank; len];
+
+    for y in 0..height.min(self.height) {
+      for x in 0..width.min(self.width) {
+        cells[(y * width + x) as usize] = self.cells[(y * self.width + x) as usize];
+      }
+    }
+
+    self.width = width;
+    self.height = height;
+    self.cells = cells;
+    // The viewer's screen has been cleared by the resize.
+    self.sent.clear();
+    self.sent_cursor = None;
+    return true;
+  }
+
+  // Writes the changes since the last frame.
+  fn send_frame (&mut self) -> io::Result<()> {
+    if self.modes_changed {
+      writeln!(self.writer, "output {}", output_mode_name(self.output_mode))?;
+      writeln!(self.writer, "input {}", input_mode_name(self.input_mode))?;
+      writeln!(self.writer, "mouse {}", if self.mouse_enabled { "on" } else { "off" })?;
+      self.modes_changed = false;
+    }
+
+    let full = self.sent.len() != self.cells.len();
+    for (i, cell) in self.cells.iter().enumerate() {
+      if full || self.sent[i] != *cell {
+        let (x, y) = (i as Coord % self.width, i as Coord / self.width);
+        writeln!(self.writer, "cell {} {} {} {} {}", x, y, cell.ch() as u32, cell.fg(), cell.bg())?;
+      }
+    }
+    self.sent = self.cells.clone();
+
+    if self.sent_cursor != Some(self.cursor) {
+      match self.cursor {
+        Some((x, y)) => { writeln!(self.writer, "cursor {} {}", x, y)?; },
+        None => { writeln!(self.writer, "cursor hide")?; },
+      }
+      self.sent_cursor = Some(self.cursor);
+    }
+
+    writeln!(self.writer, "present")?;
+    return self.writer.flush();
+  }
+
+  fn serve<R, W> (reader: R, writer: W, shutdown: Option<Box<dyn Fn() + Send>>)
+    -> io::Result<RemoteTermbox>
+    where R: Read + Send + 'static, W: Write + Send + 'static
+  {
+    let (sender, events) = mpsc::channel();
+    thread::spawn(move || {
+      for line in BufReader::new(reader).lines() {
+        let line = match line {
+          Ok(line) => line,
+          Err(_) => { break; },
+        };
+        if let Some((_, event)) = record::parse_event(line.trim()) {
+          if sender.send(event).is_err() {
+            break;
+          }
+        }
+      }
+    });
+
+    let size = match events.recv() {
+      Ok(Event::Resize(event)) => event,
+      Ok(_) => {
+        let message = "the viewer must send its size first";
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+      },
+      Err(_) => {
+        let message = "the viewer disconnected before sending its size";
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, message));
+      },
+    };
+
+    let mut tb = RemoteTermbox {
+      width: 0,
+      height: 0,
+      cells: Vec::new(),
+      sent: Vec::new(),
+      clear_fg: DEFAULT,
+      clear_bg: DEFAULT,
+      cursor: None,
+      sent_cursor: None,
+      input_mode: InputMode::Esc,
+      mouse_enabled: false,
+      output_mode: OutputMode::Normal,
+      modes_changed: true,
+      events: events,
+      writer: BufWriter::new(Box::new(writer)),
+      connected: true,
+      shutdown: shutdown,
+    };
+    if !tb.resize_buffer(size.w, size.h) {
+      let message = "the viewer sent a size that is too large";
+      return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+    return Ok(tb);
+  }
+}
+
+impl Drop for RemoteTermbox {
+  fn drop (&mut self) {
+    let _ = self.writer.flush();
+    if let Some(ref shutdown) = self.shutdown {
+      shutdown();
+    }
+  }
+}
+
+impl Backend for RemoteTermbox {
+  fn cell_buffer (&self) -> &[Cell] {
+    &self.cells
+  }
+
+  fn cell_buffer_mut (&mut self) -> &mut [Cell] {
+    &mut self.cells
+  }
+
+  fn clear (&mut self) {
+    let blank = Cell::new(' ', self.clear_fg, self.clear_bg);
+    for cell in self.cells.iter_mut() {
+      *cell = blank;
+    }
+  }
+
+  fn cursor (&self) -> Option<(Coord, Coord)> {
+    self.cursor
+  }
+
+  fn height (&self) -> Coord {
+    self.height
+  }
+
+  fn hide_cursor (&mut self) {
+    self.cursor = None;
+  }
+
+  fn input_mode (&self) -> InputMode {
+    self.input_mode
+  }
+
+  fn is_mouse_enabled (&self) -> bool {
+    self.mouse_enabled
+  }
+
+  fn output_mode (&self) -> OutputMode {
+    self.output_mode
+  }
+
+  fn peek_event (&mut self, timeout: Time) -> Option<Event> {
+    let event = match self.events.recv_timeout(Duration::from_millis(timeout.max(0) as u64)) {
+      Ok(event) => event,
+      Err(RecvTimeoutError::Timeout) => { return None; },
+      Err(RecvTimeoutError::Disconnected) => self.disconnected(),
+    };
+    if let Event::Resize(ref event) = event {
+      if !self.resize_buffer(event.w, event.h) {
+        return Some(self.disconnected());
+      }
+    }
+    return Some(event);
+  }
+
+  fn poll_event (&mut self) -> Event {
+    let event = match self.events.recv() {
+      Ok(event) => event,
+      Err(_) => self.disconnected(),
+    };
+    if let Event::Resize(ref event) = event {
+      if !self.resize_buffer(event.w, event.h) {
+        return self.disconnected();
+      }
+    }
+    return event;
+  }
+
+  /// Sends the changes made to the output buffer since the last call to the viewer. Errors
+  /// writing to the viewer mark it as disconnected.
+  fn present (&mut self) {
+    if self.connected && self.send_frame().is_err() {
+      self.connected = false;
+    }
+  }
+
+  fn put_cell (&mut self, x: Coord, y: Coord, cell: Cell) {
+    backend::debug_check_bounds(x, y, self.width, self.height);
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return;
+    }
+    self.cells[(y * self.width + x) as usize] = cell;
+  }
+
+  fn set_clear_attributes (&mut self, fg: Attribute, bg: Attribute) {
+    self.clear_fg = fg;
+    self.clear_bg = bg;
+  }
+
+  fn set_cursor (&mut self, x: Coord, y: Coord) -> Option<(Coord, Coord)> {
+    let previous = self.cursor;
+    if x < 0 || y < 0 {
+      self.cursor = None;
+    } else {
+      self.cursor = Some((x, y));
+    }
+    return previous;
+  }
+
+  fn set_input_mode (&mut self, mode: InputMode) {
+    self.modes_changed |= mode != self.input_mode;
+    self.input_mode = mode;
+  }
+
+  fn set_mouse_enabled (&mut self, enabled: bool) {
+    self.modes_changed |= enabled != self.mouse_enabled;
+    self.mouse_enabled = enabled;
+  }
+
+  fn set_output_mode (&mut self, mode: OutputMode) {
+    self.modes_changed |= mode != self.output_mode;
+    self.output_mode = mode;
+  }
+
+  fn width (&self) -> Coord {
+    self.width
+  }
+}
+
+
+//
+// Functions
+//
+
+
+/// Displays a program served by a `RemoteTermbox` on `tb`, sending it the events received by `tb`,
+/// starting with the size of `tb`. The program's output mode, input mode and mouse setting are
+/// applied to `tb`. Returns when the program closes the connection.
+pub fn view<B, R, W> (tb: &mut B, reader: R, writer: W) -> io::Result<()>
+  where B: Backend, R: Read + Send + 'static, W: Write
+{
+  let mut writer = BufWriter::new(writer);
+  let start = Instant::now();
+  let size = Event::Resize(ResizeEvent { w: tb.width(), h: tb.height() });
+  if let Some(line) = record::format_event(0, &size) {
+    writeln!(writer, "{}", line)?;
+    writer.flush()?;
+  }
+
+  let (sender, messages) = mpsc::channel();
+  thread::spawn(move || {
+    for line in BufReader::new(reader).lines() {
+      match line {
+        Ok(line) => {
+          if sender.send(line).is_err() {
+            break;
+          }
+        },
+        Err(_) => { break; },
+      }
+    }
+  });
+
+  loop {
+    loop {
+      match messages.try_recv() {
+        Ok(line) => { apply_message(tb, &line); },
+        Err(TryRecvError::Empty) => { break; },
+        Err(TryRecvError::Disconnected) => { return Ok(()); },
+      }
+    }
+
+    if let Some(event) = tb.peek_event(VIEW_POLL_MILLIS) {
+      let millis = start.elapsed().as_millis() as u64;
+      if let Some(line) = record::format_event(millis, &event) {
+        writeln!(writer, "{}", line)?;
+        writer.flush()?;
+      }
+    }
+  }
+}
+
+// Carries out a message from the program. Invalid messages are ignored.
+fn apply_message<B: Backend> (tb: &mut B, line: &str) {
+  let words: Vec<&str> = line.split_whitespace().collect();
+  let number = |i: usize| words.get(i).and_then(|word| word.parse::<i64>().ok());
+
+  match words.first().cloned() {
+    Some("cell") => {
+      if let (Some(x), Some(y), Some(ch), Some(fg), Some(bg)) =
+        (number(1), number(2), number(3), number(4), number(5))
+      {
+        if let Some(ch) = char::from_u32(ch as u32) {
+          tb.put_cell(x as Coord, y as Coord, Cell::new(ch, fg as Attribute, bg as Attribute));
+        }
+      }
+    },
+    Some("cursor") => {
+      match (number(1), number(2)) {
+        (Some(x), Some(y)) => { tb.set_cursor(x as Coord, y as Coord); },
+        _ => { tb.hide_cursor(); },
+      }
+    },
+    Some("input") => {
+      match words.get(1).cloned() {
+        Some("esc") => { tb.set_input_mode(InputMode::Esc); },
+        Some("alt") => { tb.set_input_mode(InputMode::Alt); },
+        _ => {},
+      }
+    },
+    Some("mouse") => { tb.set_mouse_enabled(words.get(1) == Some(&"on")); },
+    Some("output") => {
+      match words.get(1).cloned() {
+        Some("normal") => { tb.set_output_mode(OutputMode::Normal); },
+        Some("256") => { tb.set_output_mode(OutputMode::Color256); },
+        Some("216") => { tb.set_output_mode(OutputMode::Color216); },
+        Some("grayscale") => { tb.set_output_mode(OutputMode::Grayscale); },
+        _ => {},
+      }
+    },
+    Some("present") => { tb.present(); },
+    _ => {},
+  }
+}
+
+fn input_mode_name (mode: InputMode) -> &'static str {
+  match mode {
+    InputMode::Esc => "esc",
+    InputMode::Alt => "alt",
+  }
+}
+
+fn output_mode_name (mode: OutputMode) -> &'static str {
+  match mode {
+    OutputMode::Normal => "normal",
+    OutputMode::Color256 => "256",
+    OutputMode::Color216 => "216",
+    OutputMode::Grayscale => "grayscale",
+  }
+}
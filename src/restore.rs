@@ -0,0 +1,145 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! A last resort for putting the terminal back in order if the process dies without dropping its
+//! `Termbox`. Everything done from the signal handler is async-signal-safe: a single `write` of
+//! mode resets, `tcsetattr`, `sigaction` and `raise`. SIGTERM is left to `signals`, which calls
+//! back into `restore` unless it is reporting the signal as an event.
+
+use std::mem;
+use std::os::raw::c_int;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{
+  AtomicBool,
+  AtomicIsize,
+  Ordering,
+};
+use std::sync::Once;
+
+use libc;
+
+use signals;
+
+// Disables mouse reporting, bracketed paste and the kitty keyboard protocol, resets attributes,
+// shows the cursor, leaves keypad mode and leaves the alternate screen.
+const RESET: &'static [u8] =
+  b"\x1b[?1000l\x1b[?1002l\x1b[?1006l\x1b[?2004l\x1b[<u\x1b[0m\x1b[?25h\x1b[?1l\x1b>\x1b[?1049l";
+
+// Fatal signals that can be caught. SIGKILL and SIGSTOP can't be, so nothing can be done for those.
+// SIGTERM is caught by `signals` instead.
+const SIGNALS: [c_int; 7] = [
+  libc::SIGHUP,
+  libc::SIGQUIT,
+  libc::SIGILL,
+  libc::SIGABRT,
+  libc::SIGBUS,
+  libc::SIGFPE,
+  libc::SIGSEGV,
+];
+
+// The terminal's file descriptors, or -1 while disarmed.
+static INPUT_FD: AtomicIsize = AtomicIsize::new(-1);
+static OUTPUT_FD: AtomicIsize = AtomicIsize::new(-1);
+static ARMED: AtomicBool = AtomicBool::new(false);
+static ATEXIT: Once = Once::new();
+
+// Only written while disarmed, so the signal handler never sees a partial write.
+static mut TERMIOS: Option<libc::termios> = None;
+static mut OLD_ACTIONS: Option<[libc::sigaction; 7]> = None;
+
+/// Records the terminal's settings before termbox changes them, so they can be restored later.
+pub fn save_termios (fd: RawFd) {
+  if ARMED.load(Ordering::SeqCst) {
+    return;
+  }
+
+  unsafe {
+    let mut termios: libc::termios = mem::zeroed();
+    if libc::tcgetattr(fd, &mut termios) == 0 {
+      TERMIOS = Some(termios);
+    } else {
+      TERMIOS = None;
+    }
+  }
+}
+
+/// Installs the restorer. Mode resets are written to `output`, and the saved settings are applied
+/// to `input`. Does nothing if it is already installed.
+pub fn arm (input: RawFd, output: RawFd) {
+  if ARMED.swap(true, Ordering::SeqCst) {
+    return;
+  }
+
+  unsafe {
+    let mut action: libc::sigaction = mem::zeroed();
+    let mut old_actions: [libc::sigaction; 7] = mem::zeroed();
+    action.sa_sigaction = on_signal as extern "C" fn (c_int) as libc::sighandler_t;
+    libc::sigemptyset(&mut action.sa_mask);
+
+    for (i, &signal) in SIGNALS.iter().enumerate() {
+      libc::sigaction(signal, &action, &mut old_actions[i]);
+    }
+
+    OLD_ACTIONS = Some(old_actions);
+    INPUT_FD.store(input as isize, Ordering::SeqCst);
+    OUTPUT_FD.store(output as isize, Ordering::SeqCst);
+    ATEXIT.call_once(|| { libc::atexit(on_exit); });
+  }
+  signals::set_restore_on_terminate(true);
+}
+
+/// Removes the restorer, putting back whatever signal handlers were installed before it.
+pub fn disarm () {
+  if !ARMED.load(Ordering::SeqCst) {
+    return;
+  }
+
+  signals::set_restore_on_terminate(false);
+  OUTPUT_FD.store(-1, Ordering::SeqCst);
+  INPUT_FD.store(-1, Ordering::SeqCst);
+  unsafe {
+    if let Some(ref old_actions) = OLD_ACTIONS {
+      for (i, &signal) in SIGNALS.iter().enumerate() {
+        libc::sigaction(signal, &old_actions[i], ::std::ptr::null_mut());
+      }
+    }
+    OLD_ACTIONS = None;
+  }
+  ARMED.store(false, Ordering::SeqCst);
+}
+
+/// Resets the terminal modes and settings if the restorer is installed. Async-signal-safe.
+pub fn restore () {
+  let output = OUTPUT_FD.swap(-1, Ordering::SeqCst);
+  let input = INPUT_FD.swap(-1, Ordering::SeqCst);
+  if output < 0 || input < 0 {
+    return;
+  }
+
+  unsafe {
+    libc::write(output as c_int, RESET.as_ptr() as *const libc::c_void, RESET.len());
+    if let Some(ref termios) = TERMIOS {
+      libc::tcsetattr(input as c_int, libc::TCSANOW, termios);
+    }
+  }
+}
+
+extern "C" fn on_exit () {
+  restore();
+}
+
+// Restores the terminal, then re-raises the signal with the previous handler in place so the
+// process still dies (or does whatever the program originally asked for).
+extern "C" fn on_signal (signal: c_int) {
+  restore();
+
+  unsafe {
+    if let Some(ref old_actions) = OLD_ACTIONS {
+      if let Some(i) = SIGNALS.iter().position(|&s| s == signal) {
+        libc::sigaction(signal, &old_actions[i], ::std::ptr::null_mut());
+      }
+    }
+    libc::raise(signal);
+  }
+}
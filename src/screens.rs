@@ -0,0 +1,202 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Full-screen pages that one `Termbox` switches between like tabs. The active screen is the
+//! output buffer itself, and every other screen keeps its own cells and cursor until it is
+//! switched to with `Termbox::switch_screen`. Only the active screen is presented, so switching
+//! costs no more than presenting a frame.
+//!
+//! ~~~no_run
+//! use termbox::{Termbox, DEFAULT};
+//!
+//! let mut tb = Termbox::open().unwrap();
+//! let logs = tb.add_screen();
+//! tb.put_str(0, 0, "Main page", DEFAULT, DEFAULT);
+//! tb.screen_mut(logs).unwrap().put_str(0, 0, "Log page", DEFAULT, DEFAULT);
+//!
+//! let main = tb.active_screen();
+//! tb.switch_screen(logs);
+//! tb.present();
+//! tb.switch_screen(main);
+//! tb.present();
+//! ~~~
+
+use grid::CellGrid;
+use {
+  Attribute,
+  Cell,
+  Coord,
+  DEFAULT,
+};
+
+
+//
+// ScreenId
+//
+
+
+/// Identifies a screen of a `Termbox`. See `Termbox::add_screen`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ScreenId(pub(crate) u64);
+
+
+//
+// VirtualScreen
+//
+
+
+/// The cells and cursor of a screen that isn't being shown. See `Termbox::screen_mut`.
+pub struct VirtualScreen {
+  width: Coord,
+  height: Coord,
+  cells: Vec<Cell>,
+  cursor: Option<(Coord, Coord)>,
+}
+
+impl VirtualScreen {
+  /// Returns the cells of the screen in row-major order.
+  pub fn cell_buffer (&self) -> &[Cell] {
+    &self.cells
+  }
+
+  /// Returns the cells of the screen in row-major order for modification.
+  pub fn cell_buffer_mut (&mut self) -> &mut [Cell] {
+    &mut self.cells
+  }
+
+  /// Returns the screen as a grid indexed by `(x, y)`.
+  pub fn cell_grid<'a> (&'a self) -> CellGrid<&'a [Cell]> {
+    CellGrid::new(&self.cells[..], self.width, self.height)
+  }
+
+  /// Returns the screen as a mutable grid indexed by `(x, y)`.
+  pub fn cell_grid_mut<'a> (&'a mut self) -> CellGrid<&'a mut [Cell]> {
+    CellGrid::new(&mut self.cells[..], self.width, self.height)
+  }
+
+  /// Changes a single cell. Cells outside of the screen are ignored.
+  pub fn change_cell (&mut self, x: Coord, y: Coord, ch: char, fg: Attribute, bg: Attribute) {
+    self.put_cell(x, y, Cell::new(ch, fg, bg));
+  }
+
+  /// Fills the screen with default-colored spaces.
+  pub fn clear (&mut self) {
+    for cell in self.cells.iter_mut() {
+      *cell = blank();
+    }
+  }
+
+  /// Returns the cursor position, or `None` if the cursor is hidden.
+  pub fn cursor (&self) -> Option<(Coord, Coord)> {
+    self.cursor
+  }
+
+  /// Returns the cell at the given position, or `None` if it is out of bounds.
+  pub fn get (&self, x: Coord, y: Coord) -> Option<&Cell> {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return None;
+    }
+    self.cells.get((y * self.width + x) as usize)
+  }
+
+  /// Returns the height of the screen in character cells.
+  pub fn height (&self) -> Coord {
+    self.height
+  }
+
+  /// Hides the cursor while the screen is shown.
+  pub fn hide_cursor (&mut self) {
+    self.cursor = None;
+  }
+
+  /// Creates a blank screen with the cursor hidden.
+  pub fn new (width: Coord, height: Coord) -> VirtualScreen {
+    let mut screen = VirtualScreen {
+      width: 0,
+      height: 0,
+      cells: Vec::new(),
+      cursor: None,
+    };
+    screen.resize(width, height);
+    return screen;
+  }
+
+  /// Changes a single cell. Cells outside of the screen are ignored.
+  pub fn put_cell (&mut self, x: Coord, y: Coord, cell: Cell) {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return;
+    }
+    self.cells[(y * self.width + x) as usize] = cell;
+  }
+
+  /// Writes a horizontal sequence of character cells without wrapping. Returns the number of
+  /// cells that were inside the screen.
+  pub fn put_str (&mut self, x: Coord, y: Coord, msg: &str, fg: Attribute, bg: Attribute) -> Coord {
+    let mut x = x;
+    let mut written = 0;
+
+    for ch in msg.chars() {
+      if self.get(x, y).is_some() {
+        written += 1;
+      }
+      self.change_cell(x, y, ch, fg, bg);
+      x += 1;
+    }
+
+    return written;
+  }
+
+  /// Changes the size of the screen. Cells inside both the old and new sizes are kept, and new
+  /// cells are cleared. Screens are resized to the output buffer when they are switched to, so
+  /// this is only needed to draw a screen at its new size before then.
+  pub fn resize (&mut self, width: Coord, height: Coord) {
+    let (width, height) = (width.max(0), height.max(0));
+    let mut cells = vec![blank(); width as usize * height as usize];
+
+    for y in 0..height.min(self.height) {
+      for x in 0..width.min(self.width) {
+        cells[(y * width + x) as usize] = self.cells[(y * self.width + x) as usize];
+      }
+    }
+
+    self.width = width;
+    self.height = height;
+    self.cells = cells;
+  }
+
+  /// Sets the position of the cursor while the screen is shown. If invalid coordinates are
+  /// provided, the cursor is hidden. Returns the previous position, or `None` if the cursor was
+  /// hidden.
+  pub fn set_cursor (&mut self, x: Coord, y: Coord) -> Option<(Coord, Coord)> {
+    let previous = self.cursor;
+    if x < 0 || y < 0 {
+      self.cursor = None;
+    } else {
+      self.cursor = Some((x, y));
+    }
+    return previous;
+  }
+
+  /// Returns the width of the screen in character cells.
+  pub fn width (&self) -> Coord {
+    self.width
+  }
+
+  // Creates a screen holding a copy of the output buffer.
+  pub(crate) fn from_cells (width: Coord, height: Coord, cells: &[Cell],
+                            cursor: Option<(Coord, Coord)>)
+    -> VirtualScreen
+  {
+    VirtualScreen {
+      width: width,
+      height: height,
+      cells: cells.to_vec(),
+      cursor: cursor,
+    }
+  }
+}
+
+fn blank () -> Cell {
+  Cell::new(' ', DEFAULT, DEFAULT)
+}
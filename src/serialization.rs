@@ -0,0 +1,60 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Serde support for `Snapshot`, which checks on deserialization that the cells fit its size.
+
+use serde::de::Error as DeError;
+use serde::ser::SerializeStruct;
+use serde::{
+  Deserialize,
+  Deserializer,
+  Serialize,
+  Serializer,
+};
+
+use {
+  Cell,
+  Coord,
+  OutputMode,
+  Snapshot,
+};
+
+
+//
+// Snapshot
+//
+
+
+impl Serialize for Snapshot {
+  fn serialize<S: Serializer> (&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut state = serializer.serialize_struct("Snapshot", 4)?;
+    state.serialize_field("width", &self.width())?;
+    state.serialize_field("height", &self.height())?;
+    state.serialize_field("mode", &self.output_mode())?;
+    state.serialize_field("cells", self.cells())?;
+    state.end()
+  }
+}
+
+impl<'de> Deserialize<'de> for Snapshot {
+  fn deserialize<D: Deserializer<'de>> (deserializer: D) -> Result<Snapshot, D::Error> {
+    let repr = SnapshotRepr::deserialize(deserializer)?;
+    if repr.width < 0 || repr.height < 0
+       || repr.cells.len() != repr.width as usize * repr.height as usize
+    {
+      return Err(D::Error::custom(format!("{} cells don't fit a {}x{} snapshot",
+                                          repr.cells.len(), repr.width, repr.height)));
+    }
+    return Ok(Snapshot::new(repr.width, repr.height, repr.mode, repr.cells));
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Snapshot")]
+struct SnapshotRepr {
+  width: Coord,
+  height: Coord,
+  mode: OutputMode,
+  cells: Vec<Cell>,
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Encodes cell attributes as SGR escape sequences the same way termbox does, so that text
+//! written behind termbox's back looks like the text termbox writes itself.
+
+use std::fmt::Write;
+
+use {
+  Attribute,
+  OutputMode,
+  BLINK,
+  BOLD,
+  DEFAULT,
+  DIM,
+  ITALIC,
+  REVERSE,
+  STRIKETHROUGH,
+  UNDERLINE,
+};
+
+/// Returns the color component of an attribute after applying the same range adjustments as
+/// termbox. `DEFAULT` means the terminal's default color in every mode.
+pub fn color (attr: Attribute, mode: OutputMode) -> Attribute {
+  match mode {
+    OutputMode::Normal => attr & 0x0f,
+    OutputMode::Color256 => attr & 0xff,
+    OutputMode::Color216 => {
+      let col = attr & 0xff;
+      if col > 215 { 7 + 0x10 } else { col + 0x10 }
+    },
+    OutputMode::Grayscale => {
+      let col = attr & 0xff;
+      if col > 23 { 23 + 0xe8 } else { col + 0xe8 }
+    },
+  }
+}
+
+/// Returns the index of an attribute's color in the xterm 256 color palette, or `None` if it uses
+/// the terminal's default color.
+pub fn palette_index (attr: Attribute, mode: OutputMode) -> Option<u8> {
+  match (color(attr, mode), mode) {
+    (DEFAULT, _) => None,
+    (col, OutputMode::Normal) => Some((col - 1) as u8),
+    (col, _) => Some(col as u8),
+  }
+}
+
+/// Appends an SGR sequence that resets all attributes and then selects `fg` and `bg`.
+pub fn write_attributes (out: &mut String, fg: Attribute, bg: Attribute, mode: OutputMode) {
+  let fgcol = color(fg, mode);
+  let bgcol = color(bg, mode);
+
+  out.push_str("\x1b[0");
+  if fg & BOLD != 0 {
+    out.push_str(";1");
+  }
+  if fg & DIM != 0 {
+    out.push_str(";2");
+  }
+  if fg & ITALIC != 0 {
+    out.push_str(";3");
+  }
+  if (bg & BOLD) != 0 || (fg & BLINK) != 0 {
+    out.push_str(";5");
+  }
+  if fg & UNDERLINE != 0 {
+    out.push_str(";4");
+  }
+  if (fg & REVERSE) != 0 || (bg & REVERSE) != 0 {
+    out.push_str(";7");
+  }
+  if fg & STRIKETHROUGH != 0 {
+    out.push_str(";9");
+  }
+
+  match mode {
+    OutputMode::Normal => {
+      if fgcol != DEFAULT {
+        let _ = write!(out, ";3{}", fgcol - 1);
+      }
+      if bgcol != DEFAULT {
+        let _ = write!(out, ";4{}", bgcol - 1);
+      }
+    },
+    _ => {
+      if fgcol != DEFAULT {
+        let _ = write!(out, ";38;5;{}", fgcol);
+      }
+      if bgcol != DEFAULT {
+        let _ = write!(out, ";48;5;{}", bgcol);
+      }
+    },
+  }
+
+  out.push('m');
+}
@@ -0,0 +1,202 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Catches termination and stop signals so they can be handled by the event loop instead of
+//! killing or stopping the process. The handlers only set a flag, which the event loop checks
+//! after being woken.
+//!
+//! SIGTERM is owned by this module alone, since it is wanted both for events and by the terminal
+//! restorer. While signal events are enabled it is reported as an event, and otherwise it is
+//! passed to the restorer before the process dies.
+
+use std::mem;
+use std::os::raw::c_int;
+use std::sync::atomic::{
+  AtomicBool,
+  Ordering,
+};
+
+use libc;
+
+use restore;
+use wake;
+use Signal;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static TERMINATED: AtomicBool = AtomicBool::new(false);
+static RESTORE_ON_TERMINATE: AtomicBool = AtomicBool::new(false);
+static TERMINATE_INSTALLED: AtomicBool = AtomicBool::new(false);
+static STOP_ENABLED: AtomicBool = AtomicBool::new(false);
+static STOPPED: AtomicBool = AtomicBool::new(false);
+
+// Only written while the corresponding handler is not installed.
+static mut OLD_INTERRUPT_ACTION: Option<libc::sigaction> = None;
+static mut OLD_TERMINATE_ACTION: Option<libc::sigaction> = None;
+static mut OLD_STOP_ACTION: Option<libc::sigaction> = None;
+
+/// Determines whether signals are being caught.
+pub fn is_enabled () -> bool {
+  ENABLED.load(Ordering::SeqCst)
+}
+
+/// Starts catching SIGINT and SIGTERM. Does nothing if they are already caught.
+pub fn enable () {
+  if ENABLED.swap(true, Ordering::SeqCst) {
+    return;
+  }
+
+  unsafe {
+    OLD_INTERRUPT_ACTION = Some(install(libc::SIGINT, on_signal));
+  }
+  update_terminate();
+}
+
+/// Stops catching signals, putting back the handlers that were installed before `enable`. Signals
+/// caught but not yet taken are discarded. SIGTERM stays caught if the restorer still wants it.
+pub fn disable () {
+  if !ENABLED.load(Ordering::SeqCst) {
+    return;
+  }
+
+  unsafe {
+    if let Some(ref old_action) = OLD_INTERRUPT_ACTION {
+      libc::sigaction(libc::SIGINT, old_action, ::std::ptr::null_mut());
+    }
+    OLD_INTERRUPT_ACTION = None;
+  }
+
+  ENABLED.store(false, Ordering::SeqCst);
+  update_terminate();
+  INTERRUPTED.store(false, Ordering::SeqCst);
+  TERMINATED.store(false, Ordering::SeqCst);
+}
+
+/// Starts catching SIGTSTP. Does nothing if it is already caught.
+pub fn enable_stop () {
+  if STOP_ENABLED.swap(true, Ordering::SeqCst) {
+    return;
+  }
+
+  unsafe {
+    OLD_STOP_ACTION = Some(install(libc::SIGTSTP, on_stop));
+  }
+}
+
+/// Stops catching SIGTSTP, putting back the handler that was installed before `enable_stop`.
+pub fn disable_stop () {
+  if !STOP_ENABLED.load(Ordering::SeqCst) {
+    return;
+  }
+
+  unsafe {
+    if let Some(ref old_action) = OLD_STOP_ACTION {
+      libc::sigaction(libc::SIGTSTP, old_action, ::std::ptr::null_mut());
+    }
+    OLD_STOP_ACTION = None;
+  }
+
+  STOPPED.store(false, Ordering::SeqCst);
+  STOP_ENABLED.store(false, Ordering::SeqCst);
+}
+
+/// Determines whether SIGTSTP is being caught.
+pub fn is_stop_enabled () -> bool {
+  STOP_ENABLED.load(Ordering::SeqCst)
+}
+
+/// Sets whether SIGTERM should restore the terminal and kill the process while signal events are
+/// disabled. Used by the restorer in place of installing its own handler.
+pub fn set_restore_on_terminate (enabled: bool) {
+  RESTORE_ON_TERMINATE.store(enabled, Ordering::SeqCst);
+  update_terminate();
+}
+
+/// Stops the process until it is continued, as if SIGTSTP had not been caught.
+pub fn stop () {
+  unsafe {
+    let mut action: libc::sigaction = mem::zeroed();
+    let mut old_action: libc::sigaction = mem::zeroed();
+    action.sa_sigaction = libc::SIG_DFL;
+    libc::sigemptyset(&mut action.sa_mask);
+    libc::sigaction(libc::SIGTSTP, &action, &mut old_action);
+    libc::raise(libc::SIGTSTP);
+    libc::sigaction(libc::SIGTSTP, &old_action, ::std::ptr::null_mut());
+  }
+}
+
+/// Returns a signal that was caught since the last call, if any.
+pub fn take () -> Option<Signal> {
+  if INTERRUPTED.swap(false, Ordering::SeqCst) {
+    Some(Signal::Interrupt)
+  } else if TERMINATED.swap(false, Ordering::SeqCst) {
+    Some(Signal::Terminate)
+  } else {
+    None
+  }
+}
+
+/// Determines whether SIGTSTP was caught since the last call.
+pub fn take_stop () -> bool {
+  STOPPED.swap(false, Ordering::SeqCst)
+}
+
+// Installs `handler` for `signal`, returning the action it replaced.
+unsafe fn install (signal: c_int, handler: extern "C" fn (c_int)) -> libc::sigaction {
+  let mut action: libc::sigaction = mem::zeroed();
+  let mut old_action: libc::sigaction = mem::zeroed();
+  action.sa_sigaction = handler as extern "C" fn (c_int) as libc::sighandler_t;
+  libc::sigemptyset(&mut action.sa_mask);
+  libc::sigaction(signal, &action, &mut old_action);
+  return old_action;
+}
+
+// Installs the SIGTERM handler while either signal events or the restorer want it, and puts back
+// the previous handler once neither does.
+fn update_terminate () {
+  let wanted = ENABLED.load(Ordering::SeqCst) || RESTORE_ON_TERMINATE.load(Ordering::SeqCst);
+  if wanted == TERMINATE_INSTALLED.load(Ordering::SeqCst) {
+    return;
+  }
+
+  unsafe {
+    if wanted {
+      OLD_TERMINATE_ACTION = Some(install(libc::SIGTERM, on_signal));
+    } else {
+      if let Some(ref old_action) = OLD_TERMINATE_ACTION {
+        libc::sigaction(libc::SIGTERM, old_action, ::std::ptr::null_mut());
+      }
+      OLD_TERMINATE_ACTION = None;
+    }
+  }
+  TERMINATE_INSTALLED.store(wanted, Ordering::SeqCst);
+}
+
+extern "C" fn on_stop (_: c_int) {
+  STOPPED.store(true, Ordering::SeqCst);
+  wake::wake();
+}
+
+extern "C" fn on_signal (signal: c_int) {
+  if signal == libc::SIGINT {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    wake::wake();
+  } else if signal == libc::SIGTERM {
+    if ENABLED.load(Ordering::SeqCst) {
+      TERMINATED.store(true, Ordering::SeqCst);
+      wake::wake();
+      return;
+    }
+
+    // Nobody is listening for the event, so restore the terminal and die with the previous
+    // handler in place, as the restorer does for other fatal signals.
+    restore::restore();
+    unsafe {
+      if let Some(ref old_action) = OLD_TERMINATE_ACTION {
+        libc::sigaction(signal, old_action, ::std::ptr::null_mut());
+      }
+      libc::raise(signal);
+    }
+  }
+}
@@ -0,0 +1,223 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::char;
+use std::fmt::Write;
+
+use palette;
+use sgr;
+use {
+  Attribute,
+  Cell,
+  Coord,
+  OutputMode,
+  BOLD,
+  DIM,
+  ITALIC,
+  REVERSE,
+  STRIKETHROUGH,
+  UNDERLINE,
+};
+
+
+//
+// Snapshot
+//
+
+
+/// An owned copy of the output buffer, taken with `Termbox::snapshot`. Snapshots can be converted
+/// to text for bug reports, documentation or tests.
+#[derive(Clone)]
+pub struct Snapshot {
+  width: Coord,
+  height: Coord,
+  mode: OutputMode,
+  cells: Vec<Cell>,
+}
+
+impl Snapshot {
+  /// Returns the cells of the snapshot in row-major order.
+  pub fn cells (&self) -> &[Cell] {
+    &self.cells
+  }
+
+  /// Returns the cell at the given position, or `None` if it is out of bounds.
+  pub fn get (&self, x: Coord, y: Coord) -> Option<&Cell> {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return None;
+    }
+    self.cells.get((y * self.width + x) as usize)
+  }
+
+  /// Returns the height of the snapshot in character cells.
+  pub fn height (&self) -> Coord {
+    self.height
+  }
+
+  /// Returns the output mode that was used to interpret attributes when the snapshot was taken.
+  pub fn output_mode (&self) -> OutputMode {
+    self.mode
+  }
+
+  /// Renders the snapshot as text containing SGR escape sequences, suitable for printing to
+  /// another terminal.
+  pub fn to_ansi (&self) -> String {
+    let mut out = String::new();
+
+    for row in self.rows() {
+      let mut last: Option<(Attribute, Attribute)> = None;
+
+      for cell in row {
+        if last != Some((cell.fg, cell.bg)) {
+          sgr::write_attributes(&mut out, cell.fg, cell.bg, self.mode);
+          last = Some((cell.fg, cell.bg));
+        }
+        out.push(cell_char(cell));
+      }
+
+      out.push_str("\x1b[0m\n");
+    }
+
+    return out;
+  }
+
+  /// Renders the snapshot as an HTML `<pre>` element with inline styles. Colors are converted
+  /// using the default xterm palette.
+  pub fn to_html (&self) -> String {
+    let mut out = String::from("<pre style=\"font-family: monospace\">");
+
+    for row in self.rows() {
+      let mut last: Option<(Attribute, Attribute)> = None;
+
+      for cell in row {
+        if last != Some((cell.fg, cell.bg)) {
+          if last.is_some() {
+            out.push_str("</span>");
+          }
+          self.write_html_style(&mut out, cell.fg, cell.bg);
+          last = Some((cell.fg, cell.bg));
+        }
+
+        match cell_char(cell) {
+          '<' => { out.push_str("&lt;"); },
+          '>' => { out.push_str("&gt;"); },
+          '&' => { out.push_str("&amp;"); },
+          '"' => { out.push_str("&quot;"); },
+          ch => { out.push(ch); },
+        }
+      }
+
+      if last.is_some() {
+        out.push_str("</span>");
+      }
+      out.push('\n');
+    }
+
+    out.push_str("</pre>\n");
+    return out;
+  }
+
+  /// Renders the snapshot as plain text, discarding attributes. Trailing whitespace is removed
+  /// from each line.
+  pub fn to_plain_text (&self) -> String {
+    let mut out = String::new();
+
+    for row in self.rows() {
+      let line: String = row.iter().map(cell_char).collect();
+      out.push_str(line.trim_end());
+      out.push('\n');
+    }
+
+    return out;
+  }
+
+  /// Returns the width of the snapshot in character cells.
+  pub fn width (&self) -> Coord {
+    self.width
+  }
+
+  fn rows<'a> (&'a self) -> ::std::slice::Chunks<'a, Cell> {
+    self.cells.chunks(self.width.max(1) as usize)
+  }
+
+  fn write_html_style (&self, out: &mut String, fg: Attribute, bg: Attribute) {
+    let mut fgcol = sgr::palette_index(fg, self.mode).map(palette::rgb);
+    let mut bgcol = sgr::palette_index(bg, self.mode).map(palette::rgb);
+
+    if (fg & REVERSE) != 0 || (bg & REVERSE) != 0 {
+      // The default colors are unknown, so assume a light-on-dark terminal.
+      let fg_default = (0xe5, 0xe5, 0xe5);
+      let bg_default = (0x00, 0x00, 0x00);
+      let new_fg = Some(bgcol.unwrap_or(bg_default));
+      bgcol = Some(fgcol.unwrap_or(fg_default));
+      fgcol = new_fg;
+    }
+
+    out.push_str("<span style=\"");
+    if let Some((r, g, b)) = fgcol {
+      let _ = write!(out, "color: #{:02x}{:02x}{:02x}; ", r, g, b);
+    }
+    if let Some((r, g, b)) = bgcol {
+      let _ = write!(out, "background-color: #{:02x}{:02x}{:02x}; ", r, g, b);
+    }
+    if fg & BOLD != 0 {
+      out.push_str("font-weight: bold; ");
+    }
+    if fg & DIM != 0 {
+      out.push_str("opacity: 0.5; ");
+    }
+    if fg & ITALIC != 0 {
+      out.push_str("font-style: italic; ");
+    }
+    match (fg & UNDERLINE != 0, fg & STRIKETHROUGH != 0) {
+      (true, true) => { out.push_str("text-decoration: underline line-through; "); },
+      (true, false) => { out.push_str("text-decoration: underline; "); },
+      (false, true) => { out.push_str("text-decoration: line-through; "); },
+      (false, false) => {},
+    }
+    out.push_str("\">");
+  }
+}
+
+impl Snapshot {
+  pub(crate) fn new (width: Coord, height: Coord, mode: OutputMode, cells: Vec<Cell>) -> Snapshot {
+    Snapshot {
+      width: width,
+      height: height,
+      mode: mode,
+      cells: cells,
+    }
+  }
+
+  /// Renders the snapshot as a full-screen redraw that positions every row absolutely, so it can
+  /// be replayed over whatever was displayed before.
+  pub(crate) fn to_ansi_screen (&self) -> String {
+    let mut out = String::from("\x1b[0m\x1b[H\x1b[2J");
+
+    for (y, row) in self.rows().enumerate() {
+      let mut last: Option<(Attribute, Attribute)> = None;
+      let _ = write!(out, "\x1b[{};1H", y + 1);
+
+      for cell in row {
+        if last != Some((cell.fg, cell.bg)) {
+          sgr::write_attributes(&mut out, cell.fg, cell.bg, self.mode);
+          last = Some((cell.fg, cell.bg));
+        }
+        out.push(cell_char(cell));
+      }
+
+      out.push_str("\x1b[0m");
+    }
+
+    return out;
+  }
+}
+
+// Cells that were never written are displayed as spaces.
+fn cell_char (cell: &Cell) -> char {
+  match cell.ch() {
+    '\0' => ' ',
+    ch => ch,
+  }
+}
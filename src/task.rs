@@ -0,0 +1,111 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+#[cfg(feature = "async")]
+use std::any::Any;
+#[cfg(feature = "async")]
+use std::panic::{
+  self,
+  AssertUnwindSafe,
+};
+#[cfg(feature = "async")]
+use std::sync::mpsc::{
+  self,
+  Receiver,
+  Sender,
+};
+#[cfg(feature = "async")]
+use std::thread;
+
+#[cfg(feature = "async")]
+use internal;
+use payload::Payload;
+#[cfg(feature = "async")]
+use wake;
+
+/// The value returned by a task, or `Err` with the value it panicked with. See
+/// `Event::TaskDone`.
+pub type TaskResult = Result<Payload, Payload>;
+
+
+//
+// TaskId
+//
+
+
+/// Identifies a task started with `Termbox::spawn_task`. See `Event::TaskDone`. Tasks are only
+/// available with the `async` feature.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TaskId(u64);
+
+impl TaskId {
+  /// Returns the number of the task. Tasks are numbered from zero in the order they were started.
+  pub fn to_u64 (self) -> u64 {
+    self.0
+  }
+}
+
+
+//
+// Tasks
+//
+
+
+/// Runs tasks on background threads and collects their results for the event loop.
+#[cfg(feature = "async")]
+pub struct Tasks {
+  next_id: u64,
+  running: usize,
+  sender: Sender<(TaskId, TaskResult)>,
+  receiver: Receiver<(TaskId, TaskResult)>,
+}
+
+#[cfg(feature = "async")]
+impl Tasks {
+  /// Determines whether any task has not yet been reported by `poll`.
+  pub fn is_busy (&self) -> bool {
+    self.running > 0
+  }
+
+  pub fn new () -> Tasks {
+    let (sender, receiver) = mpsc::channel();
+
+    Tasks {
+      next_id: 0,
+      running: 0,
+      sender: sender,
+      receiver: receiver,
+    }
+  }
+
+  /// Returns the id and result of a finished task, if any.
+  pub fn poll (&mut self) -> Option<(TaskId, TaskResult)> {
+    let finished = self.receiver.try_recv().ok();
+    if finished.is_some() {
+      self.running -= 1;
+    }
+    return finished;
+  }
+
+  pub fn spawn<F, T> (&mut self, f: F) -> TaskId
+    where F: FnOnce() -> T + Send + 'static, T: Any + Send
+  {
+    let id = TaskId(self.next_id);
+    let sender = self.sender.clone();
+    self.next_id += 1;
+    self.running += 1;
+
+    thread::spawn(move || {
+      internal::set_task_thread();
+      let result = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => Ok(Payload::new(value)),
+        Err(panic) => Err(Payload::from_box(panic)),
+      };
+      let _ = sender.send((id, result));
+      wake::wake();
+    });
+
+    return id;
+  }
+}
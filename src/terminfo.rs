@@ -0,0 +1,174 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! A minimal reader for compiled terminfo entries. Only the standard capabilities are read;
+//! extended capabilities are ignored.
+
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+
+// Indices of the capabilities termbox looks at, in the order defined by term.h.
+pub const HAS_STATUS_LINE: usize = 9;
+pub const MAX_COLORS: usize = 13;
+pub const KEY_MOUSE: usize = 355;
+
+const MAGIC_16BIT: i16 = 0o432;
+const MAGIC_32BIT: i16 = 0o1036;
+
+// Entries larger than this are assumed to be corrupt.
+const MAX_ENTRY_SIZE: u64 = 0x10000;
+
+const DEFAULT_DIRS: &'static [&'static str] = &["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"];
+
+
+//
+// Terminfo
+//
+
+
+pub struct Terminfo {
+  flags: Vec<bool>,
+  numbers: Vec<i32>,
+  strings: Vec<Option<Vec<u8>>>,
+}
+
+impl Terminfo {
+  /// Returns a boolean capability, which is false if it is absent.
+  pub fn flag (&self, index: usize) -> bool {
+    self.flags.get(index).cloned().unwrap_or(false)
+  }
+
+  /// Finds and parses the entry for `term` in the usual terminfo directories.
+  pub fn load (term: &str) -> Option<Terminfo> {
+    let first = match term.bytes().next() {
+      Some(b) if !term.contains('/') => b,
+      _ => { return None; },
+    };
+
+    for dir in search_dirs() {
+      // Most systems use the first letter of the name as the subdirectory, but some use its
+      // hexadecimal code.
+      for sub in &[(first as char).to_string(), format!("{:02x}", first)] {
+        let path = dir.join(sub).join(term);
+        if let Ok(file) = File::open(&path) {
+          let mut data = Vec::new();
+          if file.take(MAX_ENTRY_SIZE).read_to_end(&mut data).is_ok() {
+            if let Some(info) = Terminfo::parse(&data) {
+              return Some(info);
+            }
+          }
+        }
+      }
+    }
+
+    return None;
+  }
+
+  /// Returns a numeric capability, if present.
+  pub fn number (&self, index: usize) -> Option<i32> {
+    match self.numbers.get(index) {
+      Some(&n) if n >= 0 => Some(n),
+      _ => None,
+    }
+  }
+
+  /// Parses a compiled terminfo entry.
+  pub fn parse (data: &[u8]) -> Option<Terminfo> {
+    let header: Vec<i16> = (0..6).filter_map(|i| read_i16(data, i * 2)).collect();
+    if header.len() != 6 || header[1..].iter().any(|&n| n < 0) {
+      return None;
+    }
+    let number_size = match header[0] {
+      MAGIC_16BIT => 2,
+      MAGIC_32BIT => 4,
+      _ => { return None; },
+    };
+    let (names_size, flag_count, number_count, string_count, table_size) =
+      (header[1] as usize, header[2] as usize, header[3] as usize, header[4] as usize,
+       header[5] as usize);
+
+    let mut pos = 12 + names_size;
+    let flags = data.get(pos..pos + flag_count)?.iter().map(|&b| b == 1).collect();
+    pos += flag_count;
+    if pos % 2 != 0 {
+      pos += 1;
+    }
+
+    let mut numbers = Vec::with_capacity(number_count);
+    for i in 0..number_count {
+      let offset = pos + i * number_size;
+      numbers.push(match number_size {
+        2 => read_i16(data, offset)? as i32,
+        _ => read_i32(data, offset)?,
+      });
+    }
+    pos += number_count * number_size;
+
+    let table_start = pos + string_count * 2;
+    let table = data.get(table_start..table_start + table_size)?;
+    let mut strings = Vec::with_capacity(string_count);
+    for i in 0..string_count {
+      let offset = read_i16(data, pos + i * 2)?;
+      strings.push(if offset < 0 {
+        None
+      } else {
+        let rest = table.get(offset as usize..)?;
+        let len = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        Some(rest[..len].to_vec())
+      });
+    }
+
+    return Some(Terminfo {
+      flags: flags,
+      numbers: numbers,
+      strings: strings,
+    });
+  }
+
+  /// Returns a string capability, if present.
+  pub fn string (&self, index: usize) -> Option<&[u8]> {
+    match self.strings.get(index) {
+      Some(&Some(ref s)) => Some(s),
+      _ => None,
+    }
+  }
+}
+
+fn read_i16 (data: &[u8], offset: usize) -> Option<i16> {
+  let bytes = data.get(offset..offset + 2)?;
+  return Some(bytes[0] as i16 | (bytes[1] as i16) << 8);
+}
+
+fn read_i32 (data: &[u8], offset: usize) -> Option<i32> {
+  let bytes = data.get(offset..offset + 4)?;
+  return Some(bytes[0] as i32 | (bytes[1] as i32) << 8 | (bytes[2] as i32) << 16
+              | (bytes[3] as i32) << 24);
+}
+
+// Returns the directories to search, in the order used by ncurses.
+fn search_dirs () -> Vec<PathBuf> {
+  let mut dirs = Vec::new();
+
+  if let Some(dir) = env::var_os("TERMINFO") {
+    dirs.push(PathBuf::from(dir));
+  }
+  if let Some(home) = env::var_os("HOME") {
+    dirs.push(PathBuf::from(home).join(".terminfo"));
+  }
+  if let Some(list) = env::var_os("TERMINFO_DIRS") {
+    for dir in env::split_paths(&list) {
+      // An empty entry stands for the default directories.
+      if dir.as_os_str().is_empty() {
+        dirs.extend(DEFAULT_DIRS.iter().map(PathBuf::from));
+      } else {
+        dirs.push(dir);
+      }
+    }
+  }
+  dirs.extend(DEFAULT_DIRS.iter().map(PathBuf::from));
+
+  return dirs;
+}
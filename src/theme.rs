@@ -0,0 +1,214 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::collections::HashMap;
+
+use {
+  Attribute,
+  Color,
+  OutputMode,
+  BLACK,
+  BLUE,
+  BOLD,
+  CYAN,
+  DEFAULT,
+  GREEN,
+  MAGENTA,
+  RED,
+  REVERSE,
+  UNDERLINE,
+  WHITE,
+  YELLOW,
+};
+
+// Bits of an attribute that select a color rather than a style.
+const COLOR_MASK: Attribute = 0x00ff;
+
+
+//
+// Role
+//
+
+
+/// The purpose of a piece of text, which a `Theme` maps to colors.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Role {
+  /// Ordinary text.
+  Normal,
+  /// Text that is less important than normal text, such as hints.
+  Muted,
+  /// Text drawing attention to itself, such as search matches.
+  Highlight,
+  /// The selected item of a list or menu.
+  Selection,
+  /// Headings and window titles.
+  Title,
+  /// Borders and separators.
+  Border,
+  /// Status bars.
+  Status,
+  Success,
+  Warning,
+  Error,
+}
+
+
+//
+// Style
+//
+
+
+/// Colors and style flags for one role.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Style {
+  pub fg: Attribute,
+  pub bg: Attribute,
+  /// Style flags such as `BOLD` and `UNDERLINE`, applied to the foreground.
+  pub flags: Attribute,
+}
+
+impl Style {
+  /// Returns the foreground and background attributes to pass to drawing calls.
+  pub fn attributes (self) -> (Attribute, Attribute) {
+    (self.fg | self.flags, self.bg)
+  }
+
+  pub fn new (fg: Attribute, bg: Attribute, flags: Attribute) -> Style {
+    Style {
+      fg: fg,
+      bg: bg,
+      flags: flags,
+    }
+  }
+}
+
+
+//
+// Theme
+//
+
+
+/// Maps semantic roles to styles, so an application can switch between color schemes without
+/// scattering color constants through its drawing code. Styles are given for
+/// `OutputMode::Normal`, and converted to the closest colors in other modes unless a style is set
+/// for that mode with `set_for_mode`.
+///
+/// ~~~
+/// use termbox::{OutputMode, BOLD, DEFAULT, RED};
+/// use termbox::theme::{Role, Style, Theme};
+///
+/// let mut theme = Theme::dark();
+/// theme.set(Role::Error, Style::new(RED, DEFAULT, BOLD));
+/// assert_eq!(theme.get(Role::Error, OutputMode::Normal).attributes(), (RED | BOLD, DEFAULT));
+/// assert_eq!(theme.get(Role::Error, OutputMode::Color256).attributes(), (9 | BOLD, DEFAULT));
+/// ~~~
+#[derive(Clone, Debug)]
+pub struct Theme {
+  styles: HashMap<Role, Style>,
+  mode_styles: HashMap<(OutputMode, Role), Style>,
+}
+
+impl Theme {
+  /// Returns a theme for terminals with a dark background.
+  pub fn dark () -> Theme {
+    let mut theme = Theme::new();
+    theme.set(Role::Normal, Style::new(DEFAULT, DEFAULT, 0));
+    theme.set(Role::Muted, Style::new(BLACK, DEFAULT, BOLD));
+    theme.set(Role::Highlight, Style::new(YELLOW, DEFAULT, BOLD));
+    theme.set(Role::Selection, Style::new(BLACK, CYAN, 0));
+    theme.set(Role::Title, Style::new(WHITE, DEFAULT, BOLD | UNDERLINE));
+    theme.set(Role::Border, Style::new(BLUE, DEFAULT, 0));
+    theme.set(Role::Status, Style::new(BLACK, WHITE, 0));
+    theme.set(Role::Success, Style::new(GREEN, DEFAULT, 0));
+    theme.set(Role::Warning, Style::new(YELLOW, DEFAULT, 0));
+    theme.set(Role::Error, Style::new(RED, DEFAULT, BOLD));
+    return theme;
+  }
+
+  /// Returns the style for `role` in `mode`. Roles without a style use the `Normal` style, or the
+  /// terminal's default colors if that isn't set either.
+  pub fn get (&self, role: Role, mode: OutputMode) -> Style {
+    if let Some(&style) = self.mode_styles.get(&(mode, role)) {
+      return style;
+    }
+
+    let style = match self.styles.get(&role).or_else(|| self.styles.get(&Role::Normal)) {
+      Some(&style) => style,
+      None => { return Style::new(DEFAULT, DEFAULT, 0); },
+    };
+    return convert(style, mode);
+  }
+
+  /// Returns a theme for terminals with a light background.
+  pub fn light () -> Theme {
+    let mut theme = Theme::new();
+    theme.set(Role::Normal, Style::new(DEFAULT, DEFAULT, 0));
+    theme.set(Role::Muted, Style::new(BLACK, DEFAULT, BOLD));
+    theme.set(Role::Highlight, Style::new(MAGENTA, DEFAULT, BOLD));
+    theme.set(Role::Selection, Style::new(WHITE, BLUE, BOLD));
+    theme.set(Role::Title, Style::new(BLACK, DEFAULT, UNDERLINE));
+    theme.set(Role::Border, Style::new(BLUE, DEFAULT, 0));
+    theme.set(Role::Status, Style::new(WHITE, BLACK, 0));
+    theme.set(Role::Success, Style::new(GREEN, DEFAULT, 0));
+    theme.set(Role::Warning, Style::new(YELLOW, DEFAULT, REVERSE));
+    theme.set(Role::Error, Style::new(RED, DEFAULT, 0));
+    return theme;
+  }
+
+  /// Returns a theme with no styles, which draws every role in the terminal's default colors.
+  pub fn new () -> Theme {
+    Theme {
+      styles: HashMap::new(),
+      mode_styles: HashMap::new(),
+    }
+  }
+
+  /// Sets the style for `role`, using the color constants of `OutputMode::Normal`.
+  pub fn set (&mut self, role: Role, style: Style) {
+    self.styles.insert(role, style);
+  }
+
+  /// Sets the style for `role` in one output mode only, with colors as that mode interprets them.
+  pub fn set_for_mode (&mut self, mode: OutputMode, role: Role, style: Style) {
+    self.mode_styles.insert((mode, role), style);
+  }
+}
+
+impl Default for Theme {
+  fn default () -> Theme {
+    Theme::dark()
+  }
+}
+
+// Converts a style for `OutputMode::Normal` to `mode`. In `OutputMode::Normal`, `BOLD` on the
+// foreground selects the bright version of a color, so it's kept as a flag and also used to pick
+// the bright color.
+fn convert (style: Style, mode: OutputMode) -> Style {
+  if mode == OutputMode::Normal {
+    return style;
+  }
+
+  let bright = style.flags & BOLD != 0;
+  return Style {
+    fg: convert_color(style.fg & COLOR_MASK, bright, mode) | (style.fg & !COLOR_MASK),
+    bg: convert_color(style.bg & COLOR_MASK, false, mode) | (style.bg & !COLOR_MASK),
+    flags: style.flags,
+  };
+}
+
+fn convert_color (color: Attribute, bright: bool, mode: OutputMode) -> Attribute {
+  if color == DEFAULT || color > WHITE {
+    return color;
+  }
+
+  let index = (color - 1) as u8 + if bright { 8 } else { 0 };
+  let color = Color::indexed(index);
+  match color.attribute(mode) {
+    Some(attribute) => attribute,
+    None => {
+      let (r, g, b) = color.to_rgb();
+      Color::from_rgb_approx(r, g, b, mode)
+    },
+  }
+}
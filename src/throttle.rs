@@ -0,0 +1,125 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::sync::mpsc::{
+  Receiver,
+  TryRecvError,
+};
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use Time;
+
+
+//
+// ThrottledReceiver
+//
+
+
+/// Wraps a channel receiver so that bursts of messages are handled in batches at a bounded rate.
+/// This keeps a flood of messages (log lines, metrics) from starving the event loop of time to
+/// redraw and respond to input.
+///
+/// ~~~no_run
+/// use std::sync::mpsc;
+/// use termbox::Termbox;
+/// use termbox::throttle::ThrottledReceiver;
+///
+/// let (sender, receiver) = mpsc::channel::<String>();
+/// let mut lines = ThrottledReceiver::new(receiver, 30);
+/// let mut tb = Termbox::open().unwrap();
+/// let mut log = Vec::new();
+///
+/// loop {
+///   if let Some(event) = tb.peek_event(lines.timeout()) {
+///     // Handle input...
+///   }
+///   if lines.pump(|batch| log.extend(batch)) {
+///     // Redraw with the new lines...
+///     tb.present();
+///   }
+/// }
+/// ~~~
+pub struct ThrottledReceiver<T> {
+  receiver: Receiver<T>,
+  interval: Duration,
+  last_update: Option<Instant>,
+  batch: Vec<T>,
+  disconnected: bool,
+}
+
+impl<T> ThrottledReceiver<T> {
+  /// Returns the number of messages received but not yet passed to an update.
+  pub fn buffered (&self) -> usize {
+    self.batch.len()
+  }
+
+  /// Determines whether every sender has been dropped. Messages may still be buffered.
+  pub fn is_disconnected (&self) -> bool {
+    self.disconnected
+  }
+
+  /// Wraps `receiver`, allowing at most `max_updates_per_second` updates per second. A rate of
+  /// zero is treated as one.
+  pub fn new (receiver: Receiver<T>, max_updates_per_second: u32) -> ThrottledReceiver<T> {
+    ThrottledReceiver {
+      receiver: receiver,
+      interval: Duration::from_secs(1) / max_updates_per_second.max(1),
+      last_update: None,
+      batch: Vec::new(),
+      disconnected: false,
+    }
+  }
+
+  /// Receives all messages waiting in the channel. If any messages are buffered and enough time has
+  /// passed since the last update, they are passed to `update` in the order they were sent and
+  /// `true` is returned.
+  pub fn pump<F> (&mut self, update: F) -> bool
+    where F: FnOnce(Vec<T>)
+  {
+    loop {
+      match self.receiver.try_recv() {
+        Ok(message) => { self.batch.push(message); },
+        Err(TryRecvError::Empty) => { break; },
+        Err(TryRecvError::Disconnected) => {
+          self.disconnected = true;
+          break;
+        },
+      }
+    }
+
+    if self.batch.is_empty() || self.update_delay() > Duration::from_millis(0) {
+      return false;
+    }
+
+    self.last_update = Some(Instant::now());
+    update(self.batch.split_off(0));
+    return true;
+  }
+
+  /// Returns how long the event loop should wait for input before calling `pump` again, in
+  /// milliseconds. Suitable as the timeout for `Termbox::peek_event`.
+  pub fn timeout (&self) -> Time {
+    let delay = if self.batch.is_empty() {
+      self.interval
+    } else {
+      self.update_delay()
+    };
+
+    return (delay.as_secs() * 1000 + delay.subsec_millis() as u64) as Time;
+  }
+
+  // Returns how long until the next update is allowed.
+  fn update_delay (&self) -> Duration {
+    match self.last_update {
+      Some(last) => {
+        let elapsed = last.elapsed();
+        if elapsed < self.interval { self.interval - elapsed } else { Duration::from_millis(0) }
+      },
+      None => Duration::from_millis(0),
+    }
+  }
+}
@@ -0,0 +1,89 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::collections::HashMap;
+use std::time::{
+  Duration,
+  Instant,
+};
+
+/// Identifies a timer set with `Termbox::set_timer`. Ids are chosen by the application.
+pub type TimerId = u64;
+
+struct Timer {
+  deadline: Instant,
+  interval: Option<Duration>,
+}
+
+
+//
+// Timers
+//
+
+
+/// Keeps track of timers for the event loop.
+pub struct Timers {
+  timers: HashMap<TimerId, Timer>,
+}
+
+impl Timers {
+  /// Removes a timer. Returns false if there was no timer with the given id.
+  pub fn cancel (&mut self, id: TimerId) -> bool {
+    self.timers.remove(&id).is_some()
+  }
+
+  pub fn new () -> Timers {
+    Timers {
+      timers: HashMap::new(),
+    }
+  }
+
+  /// Returns the earliest deadline of any timer.
+  pub fn next_deadline (&self) -> Option<Instant> {
+    self.timers.values().map(|timer| timer.deadline).min()
+  }
+
+  /// Returns the id of the timer with the earliest deadline that has passed, if any. Repeating
+  /// timers are rescheduled and others are removed.
+  pub fn poll (&mut self) -> Option<TimerId> {
+    let now = Instant::now();
+    let id = match self.timers.iter()
+      .filter(|&(_, timer)| timer.deadline <= now)
+      .min_by_key(|&(&id, timer)| (timer.deadline, id))
+    {
+      Some((&id, _)) => id,
+      None => { return None; },
+    };
+
+    let repeat = {
+      let timer = self.timers.get_mut(&id).unwrap();
+      match timer.interval {
+        Some(interval) => {
+          // A repeating timer that has fallen more than a whole interval behind, for example while
+          // the program was busy, skips the missed ticks instead of firing in a burst.
+          timer.deadline += interval;
+          if timer.deadline <= now {
+            timer.deadline = now + interval;
+          }
+          true
+        },
+        None => false,
+      }
+    };
+    if !repeat {
+      self.timers.remove(&id);
+    }
+
+    return Some(id);
+  }
+
+  /// Starts or restarts a timer.
+  pub fn set (&mut self, id: TimerId, delay: Duration, repeating: bool) {
+    let timer = Timer {
+      deadline: Instant::now() + delay,
+      interval: if repeating { Some(delay) } else { None },
+    };
+    self.timers.insert(id, timer);
+  }
+}
@@ -0,0 +1,113 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Replaces characters that the terminal can't show as the output buffer is presented. Termbox
+//! always writes UTF-8, which shows up as garbage on terminals using a legacy (non-UTF-8) character
+//! set, and fonts may lack box-drawing and block characters. When the locale isn't UTF-8 or the
+//! ASCII glyph profile is selected, `Termbox` passes every character outside of ASCII through its
+//! transliterator before sending it, so applications don't need fallbacks for each string.
+//!
+//! ~~~no_run
+//! use termbox::Termbox;
+//! use termbox::transliterate::{AsciiTransliterator, Transliterator};
+//!
+//! // Shows check marks as `+`, and everything else as the default approximations.
+//! struct Checks;
+//!
+//! impl Transliterator for Checks {
+//!   fn transliterate (&self, ch: char) -> char {
+//!     match ch {
+//!       '✓' => '+',
+//!       _ => AsciiTransliterator.transliterate(ch),
+//!     }
+//!   }
+//! }
+//!
+//! let mut tb = Termbox::open().unwrap();
+//! tb.set_transliterator(Checks);
+//! ~~~
+
+
+//
+// AsciiTransliterator
+//
+
+
+/// The default transliterator. Approximates common punctuation, arrows, box-drawing and block
+/// characters and accented Latin letters with ASCII, and replaces anything else with `?`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsciiTransliterator;
+
+impl Transliterator for AsciiTransliterator {
+  fn transliterate (&self, ch: char) -> char {
+    to_ascii(ch)
+  }
+}
+
+
+//
+// Transliterator
+//
+
+
+/// Chooses replacements for characters that can't be sent to the terminal.
+pub trait Transliterator {
+  /// Returns the character to display instead of `ch`, which is never ASCII. The result should
+  /// be ASCII and the same width as `ch`, or it may still not display correctly.
+  fn transliterate (&self, ch: char) -> char;
+}
+
+
+//
+// Functions
+//
+
+
+fn to_ascii (ch: char) -> char {
+  if ch.is_ascii() {
+    return ch;
+  }
+
+  match ch {
+    '\u{a0}' | '\u{2002}' ..= '\u{200a}' => ' ',
+    '‘' | '’' | '‚' | '′' => '\'',
+    '“' | '”' | '„' | '″' | '«' | '»' => '"',
+    '‐' ..= '―' | '−' => '-',
+    '•' | '·' | '∙' | '●' | '○' | '◦' => '*',
+    '…' => '.',
+    '×' => 'x',
+    '÷' => '/',
+    '←' | '◀' | '◂' => '<',
+    '→' | '▶' | '▸' => '>',
+    '↑' | '▲' | '▴' => '^',
+    '↓' | '▼' | '▾' => 'v',
+    '✓' | '✔' => 'v',
+    '✗' | '✘' => 'x',
+    '─' | '━' | '═' | '╌' | '╍' | '┄' | '┅' | '┈' | '┉' | '╴' | '╶' | '╸' | '╺' | '╼' | '╾' => '-',
+    '│' | '┃' | '║' | '╎' | '╏' | '┆' | '┇' | '┊' | '┋' | '╵' | '╷' | '╹' | '╻' | '╽' | '╿' => '|',
+    '\u{2500}' ..= '\u{257f}' => '+',
+    '\u{2580}' ..= '\u{259f}' | '■' | '□' => '#',
+    '\u{2800}' ..= '\u{28ff}' => '.',
+    'À' ..= 'Å' => 'A',
+    'Ç' => 'C',
+    'È' ..= 'Ë' => 'E',
+    'Ì' ..= 'Ï' => 'I',
+    'Ð' => 'D',
+    'Ñ' => 'N',
+    'Ò' ..= 'Ö' | 'Ø' => 'O',
+    'Ù' ..= 'Ü' => 'U',
+    'Ý' => 'Y',
+    'ß' => 's',
+    'à' ..= 'å' => 'a',
+    'ç' => 'c',
+    'è' ..= 'ë' => 'e',
+    'ì' ..= 'ï' => 'i',
+    'ð' => 'd',
+    'ñ' => 'n',
+    'ò' ..= 'ö' | 'ø' => 'o',
+    'ù' ..= 'ü' => 'u',
+    'ý' | 'ÿ' => 'y',
+    _ => '?',
+  }
+}
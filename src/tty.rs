@@ -0,0 +1,220 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::fs::{
+  File,
+  OpenOptions,
+};
+use std::io::{
+  self,
+  Write,
+};
+#[cfg(not(feature = "pure-rust"))]
+use std::io::Read;
+use std::mem;
+use std::os::unix::io::{
+  AsRawFd,
+  RawFd,
+};
+use std::path::Path;
+#[cfg(not(feature = "pure-rust"))]
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use libc;
+
+use Coord;
+#[cfg(not(feature = "pure-rust"))]
+use Time;
+
+// Termbox opens the controlling terminal by this name.
+const TTY_PATH: &'static str = "/dev/tty";
+
+
+//
+// Tty
+//
+
+
+/// A second handle to the terminal used by termbox. This is used to send escape sequences that
+/// termbox itself does not know about, and to read the terminal's replies to them.
+pub struct Tty {
+  input: File,
+  output: File,
+}
+
+impl Tty {
+  pub fn from_file (file: File) -> io::Result<Tty> {
+    let output = file.try_clone()?;
+    return Ok(Tty::from_files(file, output));
+  }
+
+  pub fn from_files (input: File, output: File) -> Tty {
+    Tty {
+      input: input,
+      output: output,
+    }
+  }
+
+  pub fn input_fd (&self) -> RawFd {
+    self.input.as_raw_fd()
+  }
+
+  pub fn open () -> io::Result<Tty> {
+    Tty::open_path(Path::new(TTY_PATH))
+  }
+
+  pub fn open_path (path: &Path) -> io::Result<Tty> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    return Tty::from_file(file);
+  }
+
+  pub fn output_fd (&self) -> RawFd {
+    self.output.as_raw_fd()
+  }
+
+  /// Returns the current size of the terminal in cells, or `None` if it can't be determined.
+  pub fn size (&self) -> Option<(Coord, Coord)> {
+    unsafe {
+      let mut size: libc::winsize = mem::zeroed();
+      if libc::ioctl(self.output.as_raw_fd(), libc::TIOCGWINSZ, &mut size) != 0 || size.ws_col == 0 {
+        return None;
+      }
+      return Some((size.ws_col as Coord, size.ws_row as Coord));
+    }
+  }
+
+  /// Returns the size of the terminal window in pixels followed by its size in cells, as reported
+  /// by the kernel. Many terminals leave the pixel size as zero, in which case `None` is returned.
+  pub fn pixel_size (&self) -> Option<(u16, u16, u16, u16)> {
+    unsafe {
+      let mut size: libc::winsize = mem::zeroed();
+      if libc::ioctl(self.output.as_raw_fd(), libc::TIOCGWINSZ, &mut size) != 0
+        || size.ws_xpixel == 0 || size.ws_ypixel == 0 || size.ws_col == 0 || size.ws_row == 0
+      {
+        return None;
+      }
+      return Some((size.ws_xpixel, size.ws_ypixel, size.ws_col, size.ws_row));
+    }
+  }
+
+  /// Reads input until it contains a complete escape sequence for which `is_reply` returns true,
+  /// and returns that sequence, or `None` if none arrives within `timeout` milliseconds. The rest
+  /// of the input read while waiting is returned as well, since it belongs to the program.
+  #[cfg(not(feature = "pure-rust"))]
+  pub fn read_reply<F> (&mut self, timeout: Time, is_reply: F) -> (Option<Vec<u8>>, Vec<u8>)
+    where F: Fn(&[u8]) -> bool
+  {
+    let deadline = Instant::now() + Duration::from_millis(timeout.max(0) as u64);
+    let mut input = Vec::new();
+
+    loop {
+      if let Some((start, len)) = find_sequence(&input, &is_reply) {
+        let reply = input.drain(start..start + len).collect();
+        return (Some(reply), input);
+      }
+
+      let now = Instant::now();
+      if now >= deadline {
+        return (None, input);
+      }
+
+      let remaining = (deadline - now).as_millis() as Time;
+      let mut pfd = libc::pollfd {
+        fd: self.input.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+      };
+
+      match unsafe { libc::poll(&mut pfd, 1, remaining) } {
+        0 => { return (None, input); },
+        n if n < 0 => {
+          if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue;
+          }
+          return (None, input);
+        },
+        _ => {},
+      }
+
+      let mut buf = [0u8; 64];
+      match self.input.read(&mut buf) {
+        Ok(n) if n > 0 => { input.extend_from_slice(&buf[..n]); },
+        _ => { return (None, input); },
+      }
+    }
+  }
+
+  /// Writes raw bytes to the terminal, bypassing termbox's output buffer.
+  pub fn write (&mut self, bytes: &[u8]) -> io::Result<()> {
+    self.output.write_all(bytes)?;
+    return self.output.flush();
+  }
+}
+
+
+//
+// Escape sequences
+//
+
+
+/// Finds the first complete escape sequence in `input` for which `is_reply` returns true. Returns
+/// its offset and length.
+pub fn find_sequence<F> (input: &[u8], is_reply: F) -> Option<(usize, usize)>
+  where F: Fn(&[u8]) -> bool
+{
+  for start in 0..input.len() {
+    if input[start] != 0x1b {
+      continue;
+    }
+    if let Some(len) = sequence_len(&input[start..]) {
+      if is_reply(&input[start..start + len]) {
+        return Some((start, len));
+      }
+    }
+  }
+  return None;
+}
+
+/// Returns the length of the escape sequence at the start of `input`, or `None` if it is
+/// incomplete. Control sequences end with a final byte, strings (OSC, DCS, APC, PM and SOS) with
+/// `BEL` or `ESC \`, and anything else after two bytes, or three for SS3.
+pub fn sequence_len (input: &[u8]) -> Option<usize> {
+  if input.len() < 2 {
+    return None;
+  }
+
+  match input[1] {
+    b'[' => {
+      for (i, &byte) in input.iter().enumerate().skip(2) {
+        match byte {
+          0x20..=0x3f => {},
+          0x40..=0x7e => { return Some(i + 1); },
+          // Malformed, so the sequence ends before the offending byte.
+          _ => { return Some(i); },
+        }
+      }
+      return None;
+    },
+    b']' | b'P' | b'_' | b'^' | b'X' => {
+      for (i, &byte) in input.iter().enumerate().skip(2) {
+        if byte == 0x07 {
+          return Some(i + 1);
+        }
+        if byte == 0x1b && input.get(i + 1) == Some(&b'\\') {
+          return Some(i + 2);
+        }
+      }
+      return None;
+    },
+    b'O' => {
+      return if input.len() >= 3 { Some(3) } else { None };
+    },
+    _ => {
+      return Some(2);
+    },
+  }
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! A self-pipe that wakes the event loop when something other than the terminal has an event for
+//! it: a value sent by an `EventInjector`, a finished task or a caught signal. Termbox can only
+//! wait for the terminal, so while any of these can happen, the event loop waits for the terminal
+//! and the pipe itself. Waking is async-signal-safe.
+
+use std::os::raw::c_int;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{
+  AtomicIsize,
+  Ordering,
+};
+use std::sync::Once;
+
+use libc;
+
+use Time;
+
+// The ends of the pipe, or -1 before it has been created. The pipe lasts for the whole process.
+static READ_FD: AtomicIsize = AtomicIsize::new(-1);
+static WRITE_FD: AtomicIsize = AtomicIsize::new(-1);
+static OPEN: Once = Once::new();
+
+/// Creates the pipe if it doesn't exist yet. Wakes before this are lost, so it must be called
+/// before anything that can wake the event loop is set up.
+pub fn open () {
+  OPEN.call_once(|| {
+    unsafe {
+      let mut fds: [c_int; 2] = [-1, -1];
+      if libc::pipe(fds.as_mut_ptr()) != 0 {
+        return;
+      }
+      for &fd in &fds {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+        libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+      }
+      READ_FD.store(fds[0] as isize, Ordering::SeqCst);
+      WRITE_FD.store(fds[1] as isize, Ordering::SeqCst);
+    }
+  });
+}
+
+/// Waits until `input` is readable, the event loop is woken or `timeout` milliseconds pass. Waits
+/// indefinitely if `timeout` is `None`. Any wakes are consumed.
+pub fn wait (input: RawFd, timeout: Option<Time>) {
+  let pipe = READ_FD.load(Ordering::SeqCst) as c_int;
+  let mut fds = [
+    libc::pollfd { fd: input, events: libc::POLLIN, revents: 0 },
+    libc::pollfd { fd: pipe, events: libc::POLLIN, revents: 0 },
+  ];
+  let count = if pipe < 0 { 1 } else { 2 };
+
+  unsafe {
+    libc::poll(fds.as_mut_ptr(), count, timeout.map_or(-1, |timeout| timeout.max(0)));
+  }
+
+  if pipe >= 0 && fds[1].revents != 0 {
+    let mut buf = [0u8; 64];
+    unsafe {
+      while libc::read(pipe, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) > 0 {}
+    }
+  }
+}
+
+/// Wakes the event loop if it is waiting, or makes its next wait return immediately.
+pub fn wake () {
+  let fd = WRITE_FD.load(Ordering::SeqCst);
+  if fd < 0 {
+    return;
+  }
+
+  // A full pipe already wakes the event loop, so a failed write can be ignored.
+  unsafe {
+    libc::write(fd as c_int, b"\0".as_ptr() as *const libc::c_void, 1);
+  }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use glyphs;
+use {
+  Attribute,
+  Backend,
+  Coord,
+  Rect,
+  REVERSE,
+};
+
+/// Draws a progress bar like `progress_bar`, with `label` centered on its middle row. The part of
+/// the label over the filled part of the bar is drawn in reverse video so that it stays readable.
+///
+/// ~~~
+/// use termbox::{Backend, Rect, DEFAULT};
+/// use termbox::headless::TestTermbox;
+/// use termbox::widgets;
+///
+/// let mut tb = TestTermbox::new(10, 1);
+/// widgets::gauge(&mut tb, Rect::new(0, 0, 10, 1), 0.0, "42%", DEFAULT, DEFAULT);
+/// assert_eq!(tb.snapshot().to_plain_text(), "   42%\n");
+/// ~~~
+pub fn gauge<B: Backend> (tb: &mut B, rect: Rect, fraction: f32, label: &str, fg: Attribute,
+                          bg: Attribute)
+{
+  progress_bar(tb, rect, fraction, fg, bg);
+  if rect.h <= 0 {
+    return;
+  }
+
+  let fraction = clamp_fraction(fraction);
+  let filled = (fraction * rect.w as f32).round() as Coord;
+  let len = label.chars().count() as Coord;
+  let start = (rect.w - len).max(0) / 2;
+  let y = rect.y + rect.h / 2;
+
+  for (i, ch) in label.chars().take(rect.w.max(0) as usize).enumerate() {
+    let col = start + i as Coord;
+    let fg = if col < filled { fg | REVERSE } else { fg };
+    tb.change_cell(rect.x + col, y, ch, fg, bg);
+  }
+}
+
+/// Draws a horizontal bar across every row of `rect`, filled from the left according to
+/// `fraction`, which is clamped to `0.0..=1.0`. With the Unicode glyph profile, the last filled
+/// cell shows eighths of a cell.
+///
+/// ~~~
+/// use termbox::{Backend, Rect, DEFAULT};
+/// use termbox::headless::TestTermbox;
+/// use termbox::widgets;
+///
+/// let mut tb = TestTermbox::new(4, 1);
+/// widgets::progress_bar(&mut tb, Rect::new(0, 0, 4, 1), 0.5625, DEFAULT, DEFAULT);
+/// assert_eq!(tb.snapshot().to_plain_text(), "██▎\n");
+/// ~~~
+pub fn progress_bar<B: Backend> (tb: &mut B, rect: Rect, fraction: f32, fg: Attribute,
+                                 bg: Attribute)
+{
+  let glyphs = glyphs::current();
+  let fraction = clamp_fraction(fraction);
+  let steps = glyphs.gauge_partial.len() as Coord + 1;
+  let filled = (fraction * (rect.w * steps) as f32).round() as Coord;
+
+  for col in 0..rect.w {
+    let ch = match filled - col * steps {
+      n if n >= steps => glyphs.gauge_full,
+      n if n > 0 => glyphs.gauge_partial[n as usize - 1],
+      _ => glyphs.gauge_empty,
+    };
+    for row in 0..rect.h {
+      tb.change_cell(rect.x + col, rect.y + row, ch, fg, bg);
+    }
+  }
+}
+
+/// Draws a column chart of `values` in `rect`, one column per value, scaled so that the largest
+/// value fills the whole height. If there are more values than columns, only the last ones are
+/// shown, so a history that grows at the end scrolls to the left. Negative values are drawn as
+/// zero.
+///
+/// ~~~
+/// use termbox::{Backend, Rect, DEFAULT};
+/// use termbox::headless::TestTermbox;
+/// use termbox::widgets;
+///
+/// let mut tb = TestTermbox::new(4, 1);
+/// widgets::sparkline(&mut tb, Rect::new(0, 0, 4, 1), &[1.0, 2.0, 4.0, 8.0], DEFAULT, DEFAULT);
+/// assert_eq!(tb.snapshot().to_plain_text(), "▁▂▄█\n");
+/// ~~~
+pub fn sparkline<B: Backend> (tb: &mut B, rect: Rect, values: &[f64], fg: Attribute,
+                              bg: Attribute)
+{
+  if rect.w <= 0 || rect.h <= 0 {
+    return;
+  }
+
+  let levels = glyphs::current().sparkline;
+  let steps = levels.len() as Coord;
+  let shown = &values[values.len().saturating_sub(rect.w as usize)..];
+  let max = shown.iter().cloned().fold(0.0, f64::max);
+
+  for col in 0..rect.w {
+    let height = match shown.get(col as usize) {
+      Some(&value) if max > 0.0 && value > 0.0 => {
+        // Any positive value gets at least the lowest level, so it can be told apart from zero.
+        ((value / max * (rect.h * steps) as f64).round() as Coord).max(1)
+      },
+      _ => 0,
+    };
+
+    for row in 0..rect.h {
+      let ch = match height - row * steps {
+        n if n >= steps => levels[steps as usize - 1],
+        n if n > 0 => levels[n as usize - 1],
+        _ => ' ',
+      };
+      tb.change_cell(rect.x + col, rect.y + rect.h - 1 - row, ch, fg, bg);
+    }
+  }
+}
+
+/// Draws frame `frame` of the activity spinner at `(x, y)`. Frames wrap around, so a counter that
+/// is incremented on each redraw or timer tick can be passed directly.
+///
+/// ~~~
+/// use termbox::{Backend, DEFAULT};
+/// use termbox::headless::TestTermbox;
+/// use termbox::widgets;
+///
+/// let mut tb = TestTermbox::new(1, 1);
+/// widgets::spinner(&mut tb, 0, 0, 1, DEFAULT, DEFAULT);
+/// assert_eq!(tb.snapshot().to_plain_text(), "⠙\n");
+/// ~~~
+pub fn spinner<B: Backend> (tb: &mut B, x: Coord, y: Coord, frame: usize, fg: Attribute,
+                            bg: Attribute)
+{
+  let frames = glyphs::current().spinner;
+  tb.change_cell(x, y, frames[frame % frames.len()], fg, bg);
+}
+
+// Limits a fraction to `0.0..=1.0`, treating NaN as zero.
+fn clamp_fraction (fraction: f32) -> f32 {
+  if fraction.is_nan() { 0.0 } else { fraction.clamp(0.0, 1.0) }
+}
@@ -0,0 +1,321 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use keymap::KeyMap;
+use theme::Style;
+use width;
+use {
+  Backend,
+  Coord,
+  KeyEvent,
+  KeyState,
+  Rect,
+  BOLD,
+  DEFAULT,
+  UNDERLINE,
+};
+use keys::*;
+
+// Number of blank cells between the key column and the description column.
+const COLUMN_GAP: usize = 2;
+
+struct Group {
+  name: String,
+  // Pairs of keys and description. Sequences with the same description are listed together.
+  entries: Vec<(String, String)>,
+}
+
+// A row of the help, as laid out for the width it is rendered at.
+enum Row<'a> {
+  Blank,
+  Heading(&'a str),
+  // Keys, empty on the rows that continue a long description, and a piece of the description.
+  Entry(&'a str, String),
+}
+
+
+//
+// HelpView
+//
+
+
+/// A scrollable cheat sheet of the key bindings of a `KeyMap`, for a help screen or an overlay
+/// shown with `Termbox::modal`. Only sequences given a description with `KeyMap::set_help` are
+/// listed. They are grouped under a heading for each group, in the order the groups were first
+/// used, and laid out in two columns: keys on the left and descriptions, wrapped to fit, on the
+/// right. Sequences of the same group that share a description are listed on one row.
+///
+/// Supported keys are Up/Down, PgUp/PgDn, which move by the height the view was last rendered at,
+/// Space to scroll down a page, and Home/End.
+///
+/// ~~~
+/// use termbox::Rect;
+/// use termbox::headless::TestTermbox;
+/// use termbox::keymap::KeyMap;
+/// use termbox::widgets::HelpView;
+///
+/// let mut keymap = KeyMap::new();
+/// keymap.bind("q", "quit");
+/// keymap.bind("j", "down");
+/// keymap.bind("Down", "down");
+/// keymap.set_help("q", "General", "Quit");
+/// keymap.set_help("j", "Movement", "Move down");
+/// keymap.set_help("Down", "Movement", "Move down");
+///
+/// let mut help = HelpView::new(&keymap);
+/// let mut tb = TestTermbox::new(20, 5);
+/// help.render(&mut tb, Rect::new(0, 0, 20, 5));
+/// assert_eq!(tb.snapshot().to_plain_text(),
+///            "General\nq        Quit\n\nMovement\nj, Down  Move down\n");
+/// ~~~
+pub struct HelpView {
+  groups: Vec<Group>,
+  scroll: usize,
+  style: Style,
+  heading_style: Style,
+  key_style: Style,
+  // The height and number of rows the view was last rendered with.
+  height: Coord,
+  row_count: usize,
+}
+
+impl HelpView {
+  /// Scrolls from a key event. Returns `false` if the key was not used by the view. Key releases
+  /// are never used.
+  pub fn handle_key (&mut self, event: &KeyEvent) -> bool {
+    if event.alt || event.state == KeyState::Release {
+      return false;
+    }
+
+    let page = (self.height as isize).max(1);
+    match (event.ch, event.key) {
+      (None, KEY_ARROW_UP) => { self.scroll_by(-1); },
+      (None, KEY_ARROW_DOWN) => { self.scroll_by(1); },
+      (None, KEY_PGUP) => { self.scroll_by(-page); },
+      (None, KEY_PGDN) | (None, KEY_SPACE) | (Some(' '), _) => { self.scroll_by(page); },
+      (None, KEY_HOME) => { self.scroll = 0; },
+      (None, KEY_END) => { self.scroll = self.max_scroll(); },
+      _ => { return false; },
+    }
+
+    return true;
+  }
+
+  pub fn heading_style (&self) -> Style {
+    self.heading_style
+  }
+
+  /// Determines whether no bindings have a description.
+  pub fn is_empty (&self) -> bool {
+    self.groups.is_empty()
+  }
+
+  pub fn key_style (&self) -> Style {
+    self.key_style
+  }
+
+  /// Creates a view of the bindings of `keymap` that have a description. The view doesn't follow
+  /// later changes to the key map.
+  pub fn new<A: Clone> (keymap: &KeyMap<A>) -> HelpView {
+    let mut groups: Vec<Group> = Vec::new();
+
+    for (keys, _) in keymap.bindings() {
+      let (group_name, description) = match keymap.help(keys) {
+        Some(help) => help,
+        None => { continue; },
+      };
+      let keys = keys.iter().map(|key| key.to_string()).collect::<Vec<_>>().join(" ");
+
+      let index = match groups.iter().position(|group| group.name == group_name) {
+        Some(index) => index,
+        None => {
+          groups.push(Group {
+            name: group_name.to_string(),
+            entries: Vec::new(),
+          });
+          groups.len() - 1
+        },
+      };
+      let entries = &mut groups[index].entries;
+      match entries.iter_mut().find(|entry| entry.1 == description) {
+        Some(entry) => {
+          entry.0.push_str(", ");
+          entry.0.push_str(&keys);
+        },
+        None => { entries.push((keys, description.to_string())); },
+      }
+    }
+
+    HelpView {
+      groups: groups,
+      scroll: 0,
+      style: Style::new(DEFAULT, DEFAULT, 0),
+      heading_style: Style::new(DEFAULT, DEFAULT, BOLD | UNDERLINE),
+      key_style: Style::new(DEFAULT, DEFAULT, BOLD),
+      height: 0,
+      row_count: 0,
+    }
+  }
+
+  /// Draws the visible part of the help into `rect`, clearing the rest of it. The key column is as
+  /// wide as the longest keys, up to half of the width of `rect`.
+  pub fn render<B: Backend> (&mut self, tb: &mut B, rect: Rect) {
+    let (rows, key_width) = layout(&self.groups, rect.w.max(0) as usize);
+    self.height = rect.h.max(0);
+    self.row_count = rows.len();
+    self.scroll = self.scroll.min(self.row_count.saturating_sub(self.height as usize));
+
+    let right = rect.x + rect.w;
+    let description_x = rect.x + (key_width + COLUMN_GAP) as Coord;
+    let (fg, bg) = self.style.attributes();
+    for row in 0..rect.h {
+      let y = rect.y + row;
+      for x in rect.x..right {
+        tb.change_cell(x, y, ' ', fg, bg);
+      }
+
+      match rows.get(self.scroll + row as usize) {
+        Some(&Row::Heading(name)) => {
+          put_clipped(tb, rect.x, y, right, name, self.heading_style);
+        },
+        Some(&Row::Entry(keys, ref description)) => {
+          put_clipped(tb, rect.x, y, rect.x + key_width as Coord, keys, self.key_style);
+          put_clipped(tb, description_x, y, right, description, self.style);
+        },
+        Some(&Row::Blank) | None => {},
+      }
+    }
+  }
+
+  /// Returns the number of rows scrolled past at the top.
+  pub fn scroll (&self) -> usize {
+    self.scroll
+  }
+
+  /// Scrolls down by `rows`, or up if it is negative, stopping at the top and bottom.
+  pub fn scroll_by (&mut self, rows: isize) {
+    let scroll = if rows < 0 {
+      self.scroll.saturating_sub(rows.unsigned_abs())
+    } else {
+      self.scroll + rows as usize
+    };
+    self.scroll = scroll.min(self.max_scroll());
+  }
+
+  /// Sets the style of group headings. The default is bold and underlined.
+  pub fn set_heading_style (&mut self, style: Style) {
+    self.heading_style = style;
+  }
+
+  /// Sets the style of the key column. The default is bold.
+  pub fn set_key_style (&mut self, style: Style) {
+    self.key_style = style;
+  }
+
+  /// Sets the style of descriptions and of the background.
+  pub fn set_style (&mut self, style: Style) {
+    self.style = style;
+  }
+
+  pub fn style (&self) -> Style {
+    self.style
+  }
+
+  // Returns the scroll position that shows the last row at the bottom of the view.
+  fn max_scroll (&self) -> usize {
+    self.row_count.saturating_sub(self.height as usize)
+  }
+}
+
+
+//
+// Functions
+//
+
+
+// Lays out the help for a view `width` cells wide. Returns the rows and the width of the key
+// column. Groups are separated by a blank row, and the heading of a group without a name is
+// left out.
+fn layout<'a> (groups: &'a [Group], width: usize) -> (Vec<Row<'a>>, usize) {
+  let key_width = groups.iter()
+    .flat_map(|group| group.entries.iter())
+    .map(|entry| text_width(&entry.0))
+    .max()
+    .unwrap_or(0)
+    .min(width / 2);
+  let description_width = width.saturating_sub(key_width + COLUMN_GAP);
+
+  let mut rows = Vec::new();
+  for (i, group) in groups.iter().enumerate() {
+    if i > 0 {
+      rows.push(Row::Blank);
+    }
+    if !group.name.is_empty() {
+      rows.push(Row::Heading(&group.name));
+    }
+    for entry in &group.entries {
+      let mut lines = wrap(&entry.1, description_width).into_iter();
+      rows.push(Row::Entry(&entry.0, lines.next().unwrap_or_default()));
+      rows.extend(lines.map(|line| Row::Entry("", line)));
+    }
+  }
+
+  return (rows, key_width);
+}
+
+// Draws as much of `text` as fits between `x` and `right`.
+fn put_clipped<B: Backend> (tb: &mut B, x: Coord, y: Coord, right: Coord, text: &str,
+                            style: Style)
+{
+  let (fg, bg) = style.attributes();
+  let mut x = x;
+  for ch in text.chars() {
+    let ch_width = (width::char_width(ch) as Coord).max(1);
+    if x + ch_width > right {
+      break;
+    }
+    tb.change_cell(x, y, ch, fg, bg);
+    x += ch_width;
+  }
+}
+
+fn text_width (text: &str) -> usize {
+  text.chars().map(|ch| width::char_width(ch).max(1)).sum()
+}
+
+// Breaks `text` into lines at most `width` cells wide, between words where possible.
+fn wrap (text: &str, width: usize) -> Vec<String> {
+  let mut lines = Vec::new();
+  if width == 0 {
+    return lines;
+  }
+
+  let mut line = String::new();
+  let mut line_width = 0;
+  for word in text.split_whitespace() {
+    let word_width = text_width(word);
+    if line_width > 0 && line_width + 1 + word_width > width {
+      lines.push(line.split_off(0));
+      line_width = 0;
+    }
+    if line_width > 0 {
+      line.push(' ');
+      line_width += 1;
+    }
+    for ch in word.chars() {
+      let ch_width = width::char_width(ch).max(1);
+      if line_width > 0 && line_width + ch_width > width {
+        lines.push(line.split_off(0));
+        line_width = 0;
+      }
+      line.push(ch);
+      line_width += ch_width;
+    }
+  }
+  if line_width > 0 {
+    lines.push(line);
+  }
+
+  return lines;
+}
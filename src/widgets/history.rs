@@ -0,0 +1,244 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::collections::{
+  HashSet,
+  VecDeque,
+};
+use std::fs::File;
+use std::io::{
+  self,
+  BufRead,
+  BufReader,
+  Write,
+};
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+
+//
+// FileHistoryStore
+//
+
+
+/// Keeps history in a text file, one entry per line, like the history files of readline-based
+/// tools. When saving, duplicate entries are dropped in favor of their newest occurrence, and only
+/// the newest `max_entries` entries are kept. A missing file is loaded as an empty history.
+pub struct FileHistoryStore {
+  path: PathBuf,
+  max_entries: usize,
+}
+
+impl FileHistoryStore {
+  pub fn new<P: AsRef<Path>> (path: P, max_entries: usize) -> FileHistoryStore {
+    FileHistoryStore {
+      path: path.as_ref().to_path_buf(),
+      max_entries: max_entries,
+    }
+  }
+
+  pub fn path (&self) -> &Path {
+    &self.path
+  }
+}
+
+impl HistoryStore for FileHistoryStore {
+  fn load (&mut self) -> io::Result<Vec<String>> {
+    let file = match File::open(&self.path) {
+      Ok(file) => file,
+      Err(ref err) if err.kind() == io::ErrorKind::NotFound => { return Ok(Vec::new()); },
+      Err(err) => { return Err(err); },
+    };
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+      let line = line?;
+      if !line.is_empty() {
+        entries.push(line);
+      }
+    }
+    return Ok(entries);
+  }
+
+  fn save (&mut self, entries: &[&str]) -> io::Result<()> {
+    // Walk from newest to oldest so the newest copy of each duplicate is the one kept.
+    let mut seen = HashSet::new();
+    let mut kept = Vec::new();
+    for &entry in entries.iter().rev() {
+      if kept.len() >= self.max_entries {
+        break;
+      }
+      if !entry.is_empty() && !entry.contains('\n') && seen.insert(entry) {
+        kept.push(entry);
+      }
+    }
+
+    let mut out = String::new();
+    for entry in kept.iter().rev() {
+      out.push_str(entry);
+      out.push('\n');
+    }
+
+    let mut file = File::create(&self.path)?;
+    return file.write_all(out.as_bytes());
+  }
+}
+
+
+//
+// History
+//
+
+
+/// A ring of previously entered lines, with up/down style recall. When full, the oldest entry is
+/// dropped to make room for a new one.
+pub struct History {
+  entries: VecDeque<String>,
+  capacity: usize,
+  position: Option<usize>,
+  draft: String,
+  store: Option<Box<dyn HistoryStore>>,
+}
+
+impl History {
+  /// Returns the maximum number of entries kept.
+  pub fn capacity (&self) -> usize {
+    self.capacity
+  }
+
+  /// Returns the entry at `index`, where zero is the oldest entry.
+  pub fn get (&self, index: usize) -> Option<&str> {
+    self.entries.get(index).map(|entry| entry.as_str())
+  }
+
+  pub fn is_empty (&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Returns an iterator over the entries, from oldest to newest.
+  pub fn iter<'a> (&'a self) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+    Box::new(self.entries.iter().map(|entry| entry.as_str()))
+  }
+
+  pub fn len (&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Creates an empty history that keeps at most `capacity` entries.
+  pub fn new (capacity: usize) -> History {
+    History {
+      entries: VecDeque::new(),
+      capacity: capacity,
+      position: None,
+      draft: String::new(),
+      store: None,
+    }
+  }
+
+  /// Moves forward to the next newer entry and returns it. Moving past the newest entry returns
+  /// the line that was being edited before recall started. Returns `None` if recall has not
+  /// started.
+  pub fn next (&mut self) -> Option<&str> {
+    match self.position {
+      Some(position) if position + 1 < self.entries.len() => {
+        self.position = Some(position + 1);
+        return self.get(position + 1);
+      },
+      Some(_) => {
+        self.position = None;
+        return Some(&self.draft);
+      },
+      None => { return None; },
+    }
+  }
+
+  /// Moves back to the next older entry and returns it. `current` is the line being edited, which
+  /// is kept so that `next` can return to it. Returns `None` if there are no older entries.
+  pub fn previous (&mut self, current: &str) -> Option<&str> {
+    let position = match self.position {
+      Some(0) => { return None; },
+      Some(position) => position - 1,
+      None => {
+        if self.entries.is_empty() {
+          return None;
+        }
+        self.draft = current.to_string();
+        self.entries.len() - 1
+      },
+    };
+
+    self.position = Some(position);
+    return self.get(position);
+  }
+
+  /// Adds an entry as the newest one and stops recall. Empty lines and lines equal to the newest
+  /// entry are not added.
+  pub fn push (&mut self, entry: &str) {
+    self.reset();
+
+    if entry.is_empty() || self.entries.back().map_or(false, |last| last == entry) {
+      return;
+    }
+
+    if self.capacity == 0 {
+      return;
+    }
+    while self.entries.len() >= self.capacity {
+      self.entries.pop_front();
+    }
+    self.entries.push_back(entry.to_string());
+  }
+
+  /// Stops recall, so that the next call to `previous` starts from the newest entry again.
+  pub fn reset (&mut self) {
+    self.position = None;
+    self.draft.clear();
+  }
+
+  /// Writes the entries to the store set with `set_store`. Does nothing if there is no store.
+  pub fn save (&mut self) -> io::Result<()> {
+    let entries: Vec<&str> = self.entries.iter().map(|entry| entry.as_str()).collect();
+    match self.store {
+      Some(ref mut store) => store.save(&entries),
+      None => Ok(()),
+    }
+  }
+
+  /// Searches backwards for an entry containing `query`, starting just before the entry at
+  /// `before`, or from the newest entry if `before` is `None`. Returns the index of the match.
+  pub fn search (&self, query: &str, before: Option<usize>) -> Option<usize> {
+    let end = before.unwrap_or(self.entries.len()).min(self.entries.len());
+    return (0..end).rev().find(|&index| self.entries[index].contains(query));
+  }
+
+  /// Sets where the history is persisted, and loads the entries saved there. Loaded entries are
+  /// placed before any entries already in the history. Call `save` to write the history back,
+  /// usually before the program exits.
+  pub fn set_store (&mut self, mut store: Box<dyn HistoryStore>) -> io::Result<()> {
+    let loaded = store.load()?;
+    let current: Vec<String> = self.entries.drain(..).collect();
+    for entry in loaded.iter().chain(current.iter()) {
+      self.push(entry);
+    }
+    self.store = Some(store);
+    return Ok(());
+  }
+}
+
+
+//
+// HistoryStore
+//
+
+
+/// Persists a `History` between runs. See `History::set_store`.
+pub trait HistoryStore {
+  /// Returns the saved entries, from oldest to newest.
+  fn load (&mut self) -> io::Result<Vec<String>>;
+
+  /// Replaces the saved entries. `entries` are ordered from oldest to newest.
+  fn save (&mut self, entries: &[&str]) -> io::Result<()>;
+}
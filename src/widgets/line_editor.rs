@@ -0,0 +1,421 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::mem;
+
+use width;
+use {
+  Attribute,
+  Backend,
+  Coord,
+  KeyEvent,
+  KeyState,
+};
+use keys::*;
+use super::History;
+
+// Number of killed strings kept for yanking.
+const KILL_RING_CAPACITY: usize = 16;
+
+
+//
+// LineEditor
+//
+
+
+/// A single line of editable text with a cursor, with readline-style key bindings.
+///
+/// Supported keys are printable characters, Backspace, Delete, Left/Right (or Ctrl-B/Ctrl-F),
+/// Home/End (or Ctrl-A/Ctrl-E), Alt-B/Alt-F (or Alt-Left/Alt-Right) to move by words, Ctrl-U to
+/// kill up to the cursor, Ctrl-K to kill from the cursor to the end of the line, Ctrl-W to kill the
+/// previous whitespace-separated word, Alt-Backspace and Alt-D to kill the previous and next
+/// words, Ctrl-Y to yank the last killed text and Alt-Y to replace the text just yanked with older
+/// kills. Consecutive kills are joined into a single kill ring entry. If a history is attached
+/// with `set_history`, Up/Down (or Ctrl-P/Ctrl-N) recall earlier lines.
+///
+/// ~~~
+/// use termbox::{KeyEvent, KeyState};
+/// use termbox::keys::*;
+/// use termbox::widgets::LineEditor;
+///
+/// let key = |key, ch, alt| KeyEvent { key: key, ch: ch, alt: alt, state: KeyState::Press };
+/// let mut editor = LineEditor::new();
+/// editor.set_text("hello world");
+/// for &(k, ch, alt) in &[(KEY_CTRL_W, None, false), (KEY_CTRL_A, None, false),
+///                        (KEY_CTRL_Y, None, false), (0, Some(' '), false)] {
+///   editor.handle_key(&key(k, ch, alt));
+/// }
+/// assert_eq!(editor.text(), "world hello ");
+/// ~~~
+pub struct LineEditor {
+  chars: Vec<char>,
+  cursor: usize,
+  kill_ring: Vec<String>,
+  // Whether the previous key killed text, in which case the next kill is joined to it.
+  killing: bool,
+  // Set while the previous key was a yank, so that Alt-Y can replace the yanked text.
+  yank: Option<Yank>,
+  history: Option<History>,
+}
+
+impl LineEditor {
+  /// Removes all text.
+  pub fn clear (&mut self) {
+    self.chars.clear();
+    self.cursor = 0;
+  }
+
+  /// Returns the position of the cursor, counted in characters from the start of the line.
+  pub fn cursor (&self) -> usize {
+    self.cursor
+  }
+
+  /// Updates the text from a key event. Returns `false` if the key was not used by the editor.
+  /// Key releases are never used.
+  pub fn handle_key (&mut self, event: &KeyEvent) -> bool {
+    if event.state == KeyState::Release {
+      return false;
+    }
+
+    let killing = mem::replace(&mut self.killing, false);
+    let yank = self.yank.take();
+
+    if event.alt {
+      return self.handle_alt_key(event, killing, yank);
+    }
+
+    if let Some(ch) = event.ch {
+      self.insert(ch);
+      return true;
+    }
+
+    match event.key {
+      KEY_SPACE => { self.insert(' '); },
+      KEY_BACKSPACE | KEY_BACKSPACE2 => {
+        if self.cursor > 0 {
+          self.cursor -= 1;
+          self.chars.remove(self.cursor);
+        }
+      },
+      KEY_DELETE => {
+        if self.cursor < self.chars.len() {
+          self.chars.remove(self.cursor);
+        }
+      },
+      KEY_ARROW_LEFT | KEY_CTRL_B => {
+        if self.cursor > 0 {
+          self.cursor -= 1;
+        }
+      },
+      KEY_ARROW_RIGHT | KEY_CTRL_F => {
+        if self.cursor < self.chars.len() {
+          self.cursor += 1;
+        }
+      },
+      KEY_ARROW_UP | KEY_CTRL_P if self.history.is_some() => {
+        let text = self.text();
+        let entry = self.history.as_mut().and_then(|history| history.previous(&text))
+          .map(|entry| entry.to_string());
+        if let Some(entry) = entry {
+          self.set_text(&entry);
+        }
+      },
+      KEY_ARROW_DOWN | KEY_CTRL_N if self.history.is_some() => {
+        let entry = self.history.as_mut().and_then(|history| history.next())
+          .map(|entry| entry.to_string());
+        if let Some(entry) = entry {
+          self.set_text(&entry);
+        }
+      },
+      KEY_HOME | KEY_CTRL_A => { self.cursor = 0; },
+      KEY_END | KEY_CTRL_E => { self.cursor = self.chars.len(); },
+      KEY_CTRL_U => {
+        let cursor = self.cursor;
+        self.kill(0, cursor, killing);
+      },
+      KEY_CTRL_K => {
+        let (cursor, len) = (self.cursor, self.chars.len());
+        self.kill(cursor, len, killing);
+      },
+      KEY_CTRL_W => {
+        let mut start = self.cursor;
+        while start > 0 && self.chars[start - 1].is_whitespace() {
+          start -= 1;
+        }
+        while start > 0 && !self.chars[start - 1].is_whitespace() {
+          start -= 1;
+        }
+        let cursor = self.cursor;
+        self.kill(start, cursor, killing);
+      },
+      KEY_CTRL_Y => { self.yank(0); },
+      _ => { return false; },
+    }
+
+    return true;
+  }
+
+  /// Returns the history attached with `set_history`, if any.
+  pub fn history (&self) -> Option<&History> {
+    self.history.as_ref()
+  }
+
+  pub fn history_mut (&mut self) -> Option<&mut History> {
+    self.history.as_mut()
+  }
+
+  /// Inserts a character at the cursor and moves the cursor past it.
+  pub fn insert (&mut self, ch: char) {
+    self.chars.insert(self.cursor, ch);
+    self.cursor += 1;
+  }
+
+  /// Inserts a string at the cursor and moves the cursor past it.
+  pub fn insert_str (&mut self, s: &str) {
+    for ch in s.chars() {
+      self.insert(ch);
+    }
+  }
+
+  pub fn is_empty (&self) -> bool {
+    self.chars.is_empty()
+  }
+
+  pub fn new () -> LineEditor {
+    LineEditor {
+      chars: Vec::new(),
+      cursor: 0,
+      kill_ring: Vec::new(),
+      killing: false,
+      yank: None,
+      history: None,
+    }
+  }
+
+  /// Draws the text into `w` cells starting at (`x`, `y`) and places the terminal cursor. If the
+  /// text does not fit, it is scrolled horizontally to keep the cursor visible.
+  pub fn render<B: Backend> (&self, tb: &mut B, x: Coord, y: Coord, w: Coord, fg: Attribute,
+                             bg: Attribute)
+  {
+    if w <= 0 {
+      return;
+    }
+
+    let w = w as usize;
+    let mut start = 0;
+    while start < self.cursor && columns(&self.chars[start..self.cursor]) >= w {
+      start += 1;
+    }
+
+    let mut col = 0;
+    for &ch in &self.chars[start..] {
+      let ch_width = width::char_width(ch);
+      if col + ch_width > w {
+        break;
+      }
+      tb.change_cell(x + col as Coord, y, ch, fg, bg);
+      col += ch_width;
+    }
+    while col < w {
+      tb.change_cell(x + col as Coord, y, ' ', fg, bg);
+      col += 1;
+    }
+
+    tb.set_cursor(x + columns(&self.chars[start..self.cursor]) as Coord, y);
+  }
+
+  /// Attaches a history that Up/Down recall lines from, or detaches it with `None`. Lines are
+  /// added to it by `submit`.
+  pub fn set_history (&mut self, history: Option<History>) {
+    self.history = history;
+  }
+
+  /// Restores the text, cursor and kill ring saved with `state`. The cursor is moved to the end of
+  /// the text if it is past it.
+  pub fn set_state (&mut self, state: &LineEditorState) {
+    self.chars = state.text.chars().collect();
+    self.cursor = state.cursor.min(self.chars.len());
+    let skip = state.kill_ring.len().saturating_sub(KILL_RING_CAPACITY);
+    self.kill_ring = state.kill_ring[skip..].to_vec();
+    self.killing = false;
+    self.yank = None;
+  }
+
+  /// Replaces the text and moves the cursor to the end.
+  pub fn set_text (&mut self, text: &str) {
+    self.chars = text.chars().collect();
+    self.cursor = self.chars.len();
+  }
+
+  /// Takes the text for use as an entered line, adding it to the history if there is one, and
+  /// leaves the editor empty.
+  pub fn submit (&mut self) -> String {
+    let text = self.text();
+    if let Some(ref mut history) = self.history {
+      history.push(&text);
+    }
+    self.clear();
+    return text;
+  }
+
+  /// Returns the text, cursor and kill ring, for restoring with `set_state`. The history is not
+  /// included, since it is saved by its own `HistoryStore`.
+  pub fn state (&self) -> LineEditorState {
+    LineEditorState {
+      text: self.text(),
+      cursor: self.cursor,
+      kill_ring: self.kill_ring.clone(),
+    }
+  }
+
+  /// Returns the current text.
+  pub fn text (&self) -> String {
+    self.chars.iter().cloned().collect()
+  }
+
+  fn handle_alt_key (&mut self, event: &KeyEvent, killing: bool, yank: Option<Yank>) -> bool {
+    match (event.ch, event.key) {
+      (Some('b'), _) | (None, KEY_ARROW_LEFT) => {
+        self.cursor = previous_word(&self.chars, self.cursor);
+      },
+      (Some('f'), _) | (None, KEY_ARROW_RIGHT) => {
+        self.cursor = next_word(&self.chars, self.cursor);
+      },
+      (None, KEY_BACKSPACE) | (None, KEY_BACKSPACE2) => {
+        let (start, cursor) = (previous_word(&self.chars, self.cursor), self.cursor);
+        self.kill(start, cursor, killing);
+      },
+      (Some('d'), _) => {
+        let (cursor, end) = (self.cursor, next_word(&self.chars, self.cursor));
+        self.kill(cursor, end, killing);
+      },
+      (Some('y'), _) => {
+        // Alt-Y only does something right after a yank, by swapping in the next older kill.
+        let yank = match yank {
+          Some(yank) => yank,
+          None => { return true; },
+        };
+        self.chars.drain(yank.start..yank.start + yank.len);
+        self.cursor = yank.start;
+        self.yank(yank.index + 1);
+      },
+      _ => { return false; },
+    }
+
+    return true;
+  }
+
+  // Removes the characters from `start` to `end` and saves them on the kill ring. If the previous
+  // key also killed text, the two are joined into one entry.
+  fn kill (&mut self, start: usize, end: usize, killing: bool) {
+    self.killing = true;
+    if start >= end {
+      return;
+    }
+
+    let killed: String = self.chars.drain(start..end).collect();
+    let backward = end <= self.cursor;
+    self.cursor = start;
+
+    match self.kill_ring.last_mut() {
+      Some(last) if killing => {
+        if backward {
+          last.insert_str(0, &killed);
+        } else {
+          last.push_str(&killed);
+        }
+        return;
+      },
+      _ => {},
+    }
+
+    if self.kill_ring.len() >= KILL_RING_CAPACITY {
+      self.kill_ring.remove(0);
+    }
+    self.kill_ring.push(killed);
+  }
+
+  // Inserts the kill ring entry `index` places back from the newest one, wrapping around.
+  fn yank (&mut self, index: usize) {
+    if self.kill_ring.is_empty() {
+      return;
+    }
+
+    let index = index % self.kill_ring.len();
+    let text = self.kill_ring[self.kill_ring.len() - 1 - index].clone();
+    let start = self.cursor;
+    self.insert_str(&text);
+    self.yank = Some(Yank {
+      start: start,
+      len: self.cursor - start,
+      index: index,
+    });
+  }
+}
+
+impl Default for LineEditor {
+  fn default () -> LineEditor {
+    LineEditor::new()
+  }
+}
+
+// The text inserted by the last yank.
+#[derive(Clone, Copy)]
+struct Yank {
+  start: usize,
+  len: usize,
+  // Position of the yanked entry, counted back from the newest one in the kill ring.
+  index: usize,
+}
+
+
+//
+// LineEditorState
+//
+
+
+/// The state of a `LineEditor` that changes as it is used, so that it can be saved and restored
+/// later. With the `serde` feature, it can be serialized.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LineEditorState {
+  pub text: String,
+  /// The position of the cursor, counted in characters from the start of the text.
+  pub cursor: usize,
+  /// Killed text that can be yanked, oldest first.
+  pub kill_ring: Vec<String>,
+}
+
+
+//
+// Functions
+//
+
+
+// Returns the number of terminal columns taken by `chars`.
+fn columns (chars: &[char]) -> usize {
+  chars.iter().map(|&ch| width::char_width(ch)).sum()
+}
+
+// Returns the position just past the end of the word at or after `pos`.
+fn next_word (chars: &[char], mut pos: usize) -> usize {
+  while pos < chars.len() && !chars[pos].is_alphanumeric() {
+    pos += 1;
+  }
+  while pos < chars.len() && chars[pos].is_alphanumeric() {
+    pos += 1;
+  }
+  return pos;
+}
+
+// Returns the position of the start of the word before `pos`.
+fn previous_word (chars: &[char], mut pos: usize) -> usize {
+  while pos > 0 && !chars[pos - 1].is_alphanumeric() {
+    pos -= 1;
+  }
+  while pos > 0 && chars[pos - 1].is_alphanumeric() {
+    pos -= 1;
+  }
+  return pos;
+}
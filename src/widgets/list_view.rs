@@ -0,0 +1,319 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use theme::Style;
+use width;
+use {
+  Backend,
+  Coord,
+  KeyEvent,
+  KeyState,
+  Rect,
+  DEFAULT,
+  REVERSE,
+};
+use keys::*;
+
+
+//
+// ListView
+//
+
+
+/// A list of items with a selection, for menus, file pickers and command palettes. The list
+/// scrolls to keep the selected item in view. With multi-select turned on, any number of items can
+/// also be marked, and each item is drawn with a check box in front of it.
+///
+/// Supported keys are Up/Down (or Ctrl-P/Ctrl-N), PgUp/PgDn, which move by the height the list was
+/// last rendered at, Home/End, and Space to toggle the mark on the selected item when multi-select
+/// is on.
+///
+/// ~~~
+/// use termbox::{Backend, KeyEvent, KeyState, Rect};
+/// use termbox::headless::TestTermbox;
+/// use termbox::keys::KEY_ARROW_DOWN;
+/// use termbox::widgets::ListView;
+///
+/// let mut list = ListView::new();
+/// list.set_items(vec!["open", "save", "quit"]);
+/// list.handle_key(&KeyEvent { key: KEY_ARROW_DOWN, ch: None, alt: false,
+///                             state: KeyState::Press });
+/// assert_eq!(list.selected_item(), Some("save"));
+///
+/// let mut tb = TestTermbox::new(6, 2);
+/// list.render(&mut tb, Rect::new(0, 0, 6, 2));
+/// assert_eq!(tb.snapshot().to_plain_text(), "open\nsave\n");
+/// ~~~
+pub struct ListView {
+  items: Vec<String>,
+  marked: Vec<bool>,
+  selected: usize,
+  scroll: usize,
+  multi_select: bool,
+  style: Style,
+  highlight_style: Style,
+  height: Coord,
+}
+
+impl ListView {
+  /// Removes all items.
+  pub fn clear (&mut self) {
+    self.items.clear();
+    self.marked.clear();
+    self.selected = 0;
+    self.scroll = 0;
+  }
+
+  /// Moves the selection or toggles marks from a key event. Returns `false` if the key was not
+  /// used by the list. Key releases are never used.
+  pub fn handle_key (&mut self, event: &KeyEvent) -> bool {
+    if event.alt || event.state == KeyState::Release {
+      return false;
+    }
+
+    let page = (self.height as usize).max(1);
+    match (event.ch, event.key) {
+      (None, KEY_ARROW_UP) | (None, KEY_CTRL_P) => {
+        let selected = self.selected.saturating_sub(1);
+        self.select(selected);
+      },
+      (None, KEY_ARROW_DOWN) | (None, KEY_CTRL_N) => {
+        let selected = self.selected + 1;
+        self.select(selected);
+      },
+      (None, KEY_PGUP) => {
+        let selected = self.selected.saturating_sub(page);
+        self.select(selected);
+      },
+      (None, KEY_PGDN) => {
+        let selected = self.selected + page;
+        self.select(selected);
+      },
+      (None, KEY_HOME) => { self.select(0); },
+      (None, KEY_END) => {
+        let last = self.items.len().saturating_sub(1);
+        self.select(last);
+      },
+      (None, KEY_SPACE) | (Some(' '), _) if self.multi_select && !self.items.is_empty() => {
+        let selected = self.selected;
+        self.marked[selected] = !self.marked[selected];
+      },
+      _ => { return false; },
+    }
+
+    return true;
+  }
+
+  pub fn highlight_style (&self) -> Style {
+    self.highlight_style
+  }
+
+  pub fn is_empty (&self) -> bool {
+    self.items.is_empty()
+  }
+
+  /// Determines whether the item at `index` is marked. Always `false` for items out of range.
+  pub fn is_marked (&self, index: usize) -> bool {
+    self.marked.get(index).cloned().unwrap_or(false)
+  }
+
+  pub fn items (&self) -> &[String] {
+    &self.items
+  }
+
+  pub fn len (&self) -> usize {
+    self.items.len()
+  }
+
+  /// Returns the indices of the marked items, in order.
+  pub fn marked (&self) -> Vec<usize> {
+    self.marked.iter().enumerate().filter(|&(_, &marked)| marked).map(|(i, _)| i).collect()
+  }
+
+  pub fn multi_select (&self) -> bool {
+    self.multi_select
+  }
+
+  /// Creates an empty list with multi-select turned off.
+  pub fn new () -> ListView {
+    ListView {
+      items: Vec::new(),
+      marked: Vec::new(),
+      selected: 0,
+      scroll: 0,
+      multi_select: false,
+      style: Style::new(DEFAULT, DEFAULT, 0),
+      highlight_style: Style::new(DEFAULT, DEFAULT, REVERSE),
+      height: 0,
+    }
+  }
+
+  /// Adds an item at the end of the list.
+  pub fn push (&mut self, item: &str) {
+    self.items.push(item.to_string());
+    self.marked.push(false);
+  }
+
+  /// Draws the visible items into `rect`, one per row, with the selected item in the highlight
+  /// style. Items too long for the rectangle are clipped.
+  pub fn render<B: Backend> (&mut self, tb: &mut B, rect: Rect) {
+    self.height = rect.h.max(0);
+    let height = self.height as usize;
+    if self.selected < self.scroll {
+      self.scroll = self.selected;
+    } else if height > 0 && self.selected >= self.scroll + height {
+      self.scroll = self.selected + 1 - height;
+    }
+
+    for row in 0..rect.h {
+      let y = rect.y + row;
+      let index = self.scroll + row as usize;
+      let style = if index == self.selected && index < self.items.len() {
+        self.highlight_style
+      } else {
+        self.style
+      };
+      let (fg, bg) = style.attributes();
+
+      let mut x = rect.x;
+      if let Some(item) = self.items.get(index) {
+        let check = if !self.multi_select {
+          ""
+        } else if self.marked[index] {
+          "[x] "
+        } else {
+          "[ ] "
+        };
+        for ch in check.chars().chain(item.chars()) {
+          let ch_width = (width::char_width(ch) as Coord).max(1);
+          if x + ch_width > rect.x + rect.w {
+            break;
+          }
+          tb.change_cell(x, y, ch, fg, bg);
+          x += ch_width;
+        }
+      }
+      while x < rect.x + rect.w {
+        tb.change_cell(x, y, ' ', fg, bg);
+        x += 1;
+      }
+    }
+  }
+
+  /// Selects the item at `index`, or the last item if it is out of range.
+  pub fn select (&mut self, index: usize) {
+    self.selected = index.min(self.items.len().saturating_sub(1));
+  }
+
+  /// Returns the index of the selected item, or `None` if the list is empty.
+  pub fn selected (&self) -> Option<usize> {
+    if self.items.is_empty() { None } else { Some(self.selected) }
+  }
+
+  pub fn selected_item (&self) -> Option<&str> {
+    self.items.get(self.selected).map(|item| item.as_str())
+  }
+
+  pub fn set_highlight_style (&mut self, style: Style) {
+    self.highlight_style = style;
+  }
+
+  /// Replaces the items, clearing all marks. The selection is kept where possible.
+  pub fn set_items<I, S> (&mut self, items: I)
+    where I: IntoIterator<Item = S>, S: Into<String>
+  {
+    self.items = items.into_iter().map(|item| item.into()).collect();
+    self.marked = vec![false; self.items.len()];
+    let selected = self.selected;
+    self.select(selected);
+  }
+
+  /// Marks or unmarks the item at `index`. Does nothing if it is out of range.
+  pub fn set_marked (&mut self, index: usize, marked: bool) {
+    if let Some(slot) = self.marked.get_mut(index) {
+      *slot = marked;
+    }
+  }
+
+  /// Turns multi-select on or off. Turning it off clears all marks.
+  pub fn set_multi_select (&mut self, multi_select: bool) {
+    self.multi_select = multi_select;
+    if !multi_select {
+      for marked in &mut self.marked {
+        *marked = false;
+      }
+    }
+  }
+
+  /// Restores the selection, scroll position and marks saved with `state`. Positions past the end
+  /// of the current items are ignored or moved to the last item.
+  pub fn set_state (&mut self, state: &ListViewState) {
+    self.select(state.selected);
+    self.scroll = state.scroll.min(self.selected);
+    for marked in &mut self.marked {
+      *marked = false;
+    }
+    for &index in &state.marked {
+      self.set_marked(index, true);
+    }
+  }
+
+  /// Sets the style of items other than the selected one.
+  pub fn set_style (&mut self, style: Style) {
+    self.style = style;
+  }
+
+  /// Returns the selection, scroll position and marks, for restoring with `set_state`. The items
+  /// themselves are not included.
+  pub fn state (&self) -> ListViewState {
+    ListViewState {
+      selected: self.selected,
+      scroll: self.scroll,
+      marked: self.marked(),
+    }
+  }
+
+  pub fn style (&self) -> Style {
+    self.style
+  }
+}
+
+impl Default for ListView {
+  fn default () -> ListView {
+    ListView::new()
+  }
+}
+
+
+//
+// ListViewState
+//
+
+
+/// The state of a `ListView` that changes as it is used, so that it can be saved when a program
+/// exits and restored when it starts again. With the `serde` feature, it can be serialized.
+///
+/// ~~~
+/// use termbox::widgets::ListView;
+///
+/// let mut list = ListView::new();
+/// list.set_items(vec!["open", "save", "quit"]);
+/// list.select(2);
+/// let state = list.state();
+///
+/// let mut restored = ListView::new();
+/// restored.set_items(vec!["open", "save", "quit"]);
+/// restored.set_state(&state);
+/// assert_eq!(restored.selected_item(), Some("quit"));
+/// ~~~
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ListViewState {
+  /// The index of the selected item.
+  pub selected: usize,
+  /// The index of the item at the top of the list.
+  pub scroll: usize,
+  /// The indices of the marked items, in order.
+  pub marked: Vec<usize>,
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+//! Reusable interface components. Widgets keep their own state, update it from events passed to
+//! them, and draw themselves onto any `Backend`. Simple indicators such as `progress_bar` and
+//! `sparkline` are plain functions instead, since they have no state of their own. Widgets whose
+//! state is worth keeping across runs, such as a selection or scroll position, return it from
+//! `state` and take it back with `set_state`.
+
+pub use self::gauges::{
+  gauge,
+  progress_bar,
+  sparkline,
+  spinner,
+};
+pub use self::help_view::HelpView;
+pub use self::history::{
+  FileHistoryStore,
+  History,
+  HistoryStore,
+};
+pub use self::line_editor::{
+  LineEditor,
+  LineEditorState,
+};
+pub use self::list_view::{
+  ListView,
+  ListViewState,
+};
+pub use self::prompt::{
+  Prompt,
+  PromptResult,
+};
+pub use self::table::{
+  draw_table,
+  Column,
+  ColumnWidth,
+  TableOptions,
+  TableState,
+};
+pub use self::text_view::{
+  TextView,
+  TextViewState,
+};
+
+mod gauges;
+mod help_view;
+mod history;
+mod line_editor;
+mod list_view;
+mod prompt;
+mod table;
+mod text_view;
@@ -0,0 +1,318 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::result;
+
+use width;
+use {
+  Attribute,
+  Backend,
+  Coord,
+  Event,
+  KeyEvent,
+  KeyState,
+  BOLD,
+  DEFAULT,
+  RED,
+};
+use keys::*;
+use super::{
+  History,
+  LineEditor,
+};
+
+// Number of entries kept in a prompt's history unless changed with `history_mut`.
+const DEFAULT_HISTORY_CAPACITY: usize = 100;
+
+
+//
+// PromptResult
+//
+
+
+/// Returned by `Prompt::handle_event`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PromptResult {
+  /// The prompt is still being edited.
+  Pending,
+  /// The user pressed Enter and the text passed validation.
+  Submitted(String),
+  /// The user pressed Esc or Ctrl-C.
+  Cancelled,
+}
+
+
+//
+// Prompt
+//
+
+
+// State of an incremental reverse search started with Ctrl-R.
+struct Search {
+  query: String,
+  found: Option<usize>,
+}
+
+/// A single line input with a message, validation and history, suitable for command bars.
+///
+/// Up and Down recall earlier entries, and Ctrl-R starts a reverse search through the history.
+/// While searching, typed text narrows the search, Ctrl-R finds the next older match, Esc or Ctrl-G
+/// leaves the line unchanged, and any other key accepts the match before being handled normally.
+///
+/// If bracketed paste is enabled, pasted text is inserted literally, so a pasted newline does not
+/// submit the prompt. Newlines and tabs in pasted text are inserted as spaces.
+pub struct Prompt {
+  message: String,
+  editor: LineEditor,
+  history: History,
+  validator: Option<Box<dyn Fn(&str) -> result::Result<(), String>>>,
+  error: Option<String>,
+  pasting: bool,
+  search: Option<Search>,
+}
+
+impl Prompt {
+  /// Returns the line editor holding the text being entered.
+  pub fn editor (&self) -> &LineEditor {
+    &self.editor
+  }
+
+  pub fn editor_mut (&mut self) -> &mut LineEditor {
+    &mut self.editor
+  }
+
+  /// Returns the message from the last failed validation, if any. It is cleared by the next edit.
+  pub fn error (&self) -> Option<&str> {
+    self.error.as_ref().map(|error| error.as_str())
+  }
+
+  /// Updates the prompt from an event. Events other than key and paste events, and key releases,
+  /// are ignored.
+  pub fn handle_event (&mut self, event: &Event) -> PromptResult {
+    match *event {
+      Event::PasteStart => {
+        self.finish_search(true);
+        self.pasting = true;
+      },
+      Event::PasteEnd => { self.pasting = false; },
+      Event::Key(ref key) => {
+        if key.state == KeyState::Release {
+          return PromptResult::Pending;
+        } else if self.pasting {
+          self.paste_key(key);
+        } else if self.search.is_none() || !self.search_key(key) {
+          return self.edit_key(key);
+        }
+      },
+      _ => {},
+    }
+
+    return PromptResult::Pending;
+  }
+
+  pub fn history (&self) -> &History {
+    &self.history
+  }
+
+  pub fn history_mut (&mut self) -> &mut History {
+    &mut self.history
+  }
+
+  /// Determines whether a reverse search is in progress.
+  pub fn is_searching (&self) -> bool {
+    self.search.is_some()
+  }
+
+  pub fn message (&self) -> &str {
+    &self.message
+  }
+
+  /// Creates an empty prompt that displays `message` before the input.
+  pub fn new (message: &str) -> Prompt {
+    Prompt {
+      message: message.to_string(),
+      editor: LineEditor::new(),
+      history: History::new(DEFAULT_HISTORY_CAPACITY),
+      validator: None,
+      error: None,
+      pasting: false,
+      search: None,
+    }
+  }
+
+  /// Draws the prompt into `w` cells starting at (`x`, `y`) and places the terminal cursor. A
+  /// validation error is shown in place of the message until the next edit.
+  pub fn render<B: Backend> (&self, tb: &mut B, x: Coord, y: Coord, w: Coord) {
+    if let Some(ref search) = self.search {
+      let label = format!("(reverse-i-search)`{}': ", search.query);
+      let label_width = put_clipped(tb, x, y, w, &label, DEFAULT | BOLD);
+      let found = search.found.and_then(|index| self.history.get(index)).unwrap_or("");
+      put_clipped(tb, x + label_width, y, w - label_width, found, DEFAULT);
+      let rest = label_width + str_width(found);
+      for col in rest..w {
+        tb.change_cell(x + col, y, ' ', DEFAULT, DEFAULT);
+      }
+      tb.set_cursor(x + (label_width - 3).max(0).min(w - 1), y);
+      return;
+    }
+
+    let label_width = match self.error {
+      Some(ref error) => put_clipped(tb, x, y, w, &format!("{}: ", error), RED | BOLD),
+      None => put_clipped(tb, x, y, w, &self.message, DEFAULT | BOLD),
+    };
+    self.editor.render(tb, x + label_width, y, w - label_width, DEFAULT, DEFAULT);
+  }
+
+  pub fn set_message (&mut self, message: &str) {
+    self.message = message.to_string();
+  }
+
+  /// Sets a function that checks the text before it is submitted. If it returns an error, the
+  /// prompt is not submitted and the error is shown instead of the message.
+  pub fn set_validator<F> (&mut self, validator: F)
+    where F: Fn(&str) -> result::Result<(), String> + 'static
+  {
+    self.validator = Some(Box::new(validator));
+  }
+
+  fn edit_key (&mut self, key: &KeyEvent) -> PromptResult {
+    if key.ch.is_none() && !key.alt {
+      match key.key {
+        KEY_ENTER | KEY_CTRL_J => { return self.submit(); },
+        KEY_ESC | KEY_CTRL_C => {
+          self.history.reset();
+          self.error = None;
+          return PromptResult::Cancelled;
+        },
+        KEY_ARROW_UP | KEY_CTRL_P => {
+          let text = self.editor.text();
+          if let Some(entry) = self.history.previous(&text) {
+            self.editor.set_text(entry);
+          }
+          return PromptResult::Pending;
+        },
+        KEY_ARROW_DOWN | KEY_CTRL_N => {
+          if let Some(entry) = self.history.next() {
+            self.editor.set_text(entry);
+          }
+          return PromptResult::Pending;
+        },
+        KEY_CTRL_R => {
+          self.search = Some(Search { query: String::new(), found: None });
+          return PromptResult::Pending;
+        },
+        _ => {},
+      }
+    }
+
+    if self.editor.handle_key(key) {
+      self.error = None;
+    }
+    return PromptResult::Pending;
+  }
+
+  // Ends a reverse search, copying the match into the editor if `accept` is set.
+  fn finish_search (&mut self, accept: bool) {
+    if let Some(search) = self.search.take() {
+      let history = &self.history;
+      if accept {
+        if let Some(entry) = search.found.and_then(|index| history.get(index)) {
+          self.editor.set_text(entry);
+        }
+      }
+    }
+  }
+
+  fn paste_key (&mut self, key: &KeyEvent) {
+    let ch = match (key.ch, key.key) {
+      (Some(ch), _) => ch,
+      (None, KEY_SPACE) | (None, KEY_ENTER) | (None, KEY_CTRL_J) | (None, KEY_TAB) => ' ',
+      _ => { return; },
+    };
+    self.editor.insert(ch);
+    self.error = None;
+  }
+
+  // Returns `false` if the key ended the search and should be handled normally.
+  fn search_key (&mut self, key: &KeyEvent) -> bool {
+    let query_changed;
+    {
+      let search = self.search.as_mut().unwrap();
+      match (key.ch, key.key) {
+        (Some(ch), _) => {
+          search.query.push(ch);
+          query_changed = true;
+        },
+        (None, KEY_SPACE) => {
+          search.query.push(' ');
+          query_changed = true;
+        },
+        (None, KEY_BACKSPACE) | (None, KEY_BACKSPACE2) => {
+          search.query.pop();
+          query_changed = true;
+        },
+        (None, KEY_CTRL_R) => {
+          if let Some(found) = self.history.search(&search.query, search.found) {
+            search.found = Some(found);
+          }
+          query_changed = false;
+        },
+        (None, KEY_ESC) | (None, KEY_CTRL_G) => {
+          self.search = None;
+          return true;
+        },
+        _ => {
+          self.finish_search(true);
+          return false;
+        },
+      }
+    }
+
+    if query_changed {
+      let search = self.search.as_mut().unwrap();
+      search.found = if search.query.is_empty() {
+        None
+      } else {
+        self.history.search(&search.query, None)
+      };
+    }
+    return true;
+  }
+
+  fn submit (&mut self) -> PromptResult {
+    let text = self.editor.text();
+
+    if let Some(ref validator) = self.validator {
+      if let Err(error) = validator(&text) {
+        self.error = Some(error);
+        return PromptResult::Pending;
+      }
+    }
+
+    self.history.push(&text);
+    self.editor.clear();
+    self.error = None;
+    return PromptResult::Submitted(text);
+  }
+}
+
+// Writes as much of `s` as fits in `w` cells and returns the number of cells used.
+fn put_clipped<B: Backend> (tb: &mut B, x: Coord, y: Coord, w: Coord, s: &str, fg: Attribute)
+  -> Coord
+{
+  let mut col = 0;
+  for ch in s.chars() {
+    let ch_width = width::char_width(ch) as Coord;
+    if col + ch_width > w {
+      break;
+    }
+    tb.change_cell(x + col, y, ch, fg, DEFAULT);
+    col += ch_width;
+  }
+  return col;
+}
+
+fn str_width (s: &str) -> Coord {
+  s.chars().map(|ch| width::char_width(ch) as Coord).sum()
+}
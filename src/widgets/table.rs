@@ -0,0 +1,285 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use theme::Style;
+use width;
+use {
+  Backend,
+  Coord,
+  Rect,
+  BOLD,
+  DEFAULT,
+  REVERSE,
+};
+
+
+//
+// Column
+//
+
+
+/// A column of a table drawn with `draw_table`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Column<'a> {
+  pub title: &'a str,
+  pub width: ColumnWidth,
+}
+
+impl<'a> Column<'a> {
+  pub fn new (title: &'a str, width: ColumnWidth) -> Column<'a> {
+    Column {
+      title: title,
+      width: width,
+    }
+  }
+}
+
+
+//
+// ColumnWidth
+//
+
+
+/// How `draw_table` sizes a column.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ColumnWidth {
+  /// Exactly this many cells.
+  Fixed(Coord),
+  /// As wide as the widest cell or title in the column, but at least this many cells.
+  Min(Coord),
+  /// A share of the space left over by the other columns, in proportion to the weights of all
+  /// weighted columns.
+  Weighted(u32),
+}
+
+
+//
+// TableOptions
+//
+
+
+/// Options for `draw_table`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TableOptions {
+  /// Whether the first line of the table shows the column titles.
+  pub header: bool,
+  pub header_style: Style,
+  pub row_style: Style,
+  /// The index of a row to draw with `highlight_style`, such as the selected row.
+  pub highlighted: Option<usize>,
+  pub highlight_style: Style,
+  /// Number of empty cells between columns.
+  pub spacing: Coord,
+  /// Number of cells the table is scrolled to the left, for tables wider than their rectangle.
+  pub scroll_x: Coord,
+}
+
+impl Default for TableOptions {
+  fn default () -> TableOptions {
+    TableOptions {
+      header: true,
+      header_style: Style::new(DEFAULT, DEFAULT, BOLD),
+      row_style: Style::new(DEFAULT, DEFAULT, 0),
+      highlighted: None,
+      highlight_style: Style::new(DEFAULT, DEFAULT, REVERSE),
+      spacing: 1,
+      scroll_x: 0,
+    }
+  }
+}
+
+
+//
+// TableState
+//
+
+
+/// The selection and scroll position of a table. `draw_table` keeps no state of its own, so a
+/// program that lets the user move through a table keeps one of these instead, and draws with
+/// `visible_rows` and `options`. With the `serde` feature, it can be serialized so that it can be
+/// restored when the program starts again.
+///
+/// ~~~
+/// use termbox::{Backend, Rect};
+/// use termbox::headless::TestTermbox;
+/// use termbox::widgets::{self, Column, ColumnWidth, TableOptions, TableState};
+///
+/// let columns = [Column::new("Name", ColumnWidth::Weighted(1))];
+/// let rows = [["a.txt"], ["b.txt"], ["c.txt"], ["d.txt"]];
+/// let state = TableState { selected: Some(2), scroll: 1, scroll_x: 0 };
+///
+/// let mut tb = TestTermbox::new(8, 3);
+/// let options = state.options(&TableOptions::default());
+/// widgets::draw_table(&mut tb, Rect::new(0, 0, 8, 3), &columns, state.visible_rows(&rows),
+///                     &options);
+/// assert_eq!(tb.snapshot().to_plain_text(), "Name\nb.txt\nc.txt\n");
+/// assert_eq!(options.highlighted, Some(1));
+/// ~~~
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TableState {
+  /// The index of the selected row, if any.
+  pub selected: Option<usize>,
+  /// The index of the row at the top of the table.
+  pub scroll: usize,
+  /// Number of cells the table is scrolled to the left.
+  pub scroll_x: Coord,
+}
+
+impl TableState {
+  /// Returns a copy of `options` that highlights the selected row and scrolls the table
+  /// horizontally. The highlighted index is relative to the rows returned by `visible_rows`.
+  pub fn options (&self, options: &TableOptions) -> TableOptions {
+    let mut options = *options;
+    options.highlighted = self.selected.and_then(|selected| selected.checked_sub(self.scroll));
+    options.scroll_x = self.scroll_x;
+    return options;
+  }
+
+  /// Returns the rows from the one at the top of the table onwards.
+  pub fn visible_rows<'a, R> (&self, rows: &'a [R]) -> &'a [R] {
+    &rows[self.scroll.min(rows.len())..]
+  }
+}
+
+
+//
+// Functions
+//
+
+
+/// Draws a table into `rect`: an optional header line with the column titles, followed by one line
+/// per row. Cells that don't fit in their column are clipped, and rows that don't fit in `rect`
+/// are left out, so a long table can be scrolled by passing a subslice of its rows. Returns the
+/// total width of the table, which may be more than `rect.w` when columns have fixed or minimum
+/// widths; use `TableOptions::scroll_x` to scroll the rest into view.
+///
+/// ~~~
+/// use termbox::{Backend, Rect};
+/// use termbox::headless::TestTermbox;
+/// use termbox::widgets::{self, Column, ColumnWidth, TableOptions};
+///
+/// let mut tb = TestTermbox::new(16, 3);
+/// let columns = [
+///   Column::new("Name", ColumnWidth::Weighted(1)),
+///   Column::new("Size", ColumnWidth::Min(4)),
+/// ];
+/// let rows = [["a.txt", "12"], ["notes.md", "3400"]];
+/// let options = TableOptions::default();
+/// widgets::draw_table(&mut tb, Rect::new(0, 0, 16, 3), &columns, &rows, &options);
+/// assert_eq!(tb.snapshot().to_plain_text(),
+///            "Name        Size\na.txt       12\nnotes.md    3400\n");
+/// ~~~
+pub fn draw_table<B, R, S> (tb: &mut B, rect: Rect, columns: &[Column], rows: &[R],
+                            options: &TableOptions)
+  -> Coord
+  where B: Backend, R: AsRef<[S]>, S: AsRef<str>
+{
+  let widths = column_widths(rect.w, columns, rows, options);
+  let total = widths.iter().sum::<Coord>() + options.spacing * (widths.len() as Coord - 1).max(0);
+  let mut y = rect.y;
+
+  if options.header && y < rect.y + rect.h {
+    let titles: Vec<&str> = columns.iter().map(|column| column.title).collect();
+    draw_line(tb, rect, y, &widths, &titles, options.header_style, options);
+    y += 1;
+  }
+
+  for (index, row) in rows.iter().enumerate() {
+    if y >= rect.y + rect.h {
+      break;
+    }
+    let style = if options.highlighted == Some(index) {
+      options.highlight_style
+    } else {
+      options.row_style
+    };
+    let cells: Vec<&str> = row.as_ref().iter().map(|cell| cell.as_ref()).collect();
+    draw_line(tb, rect, y, &widths, &cells, style, options);
+    y += 1;
+  }
+
+  return total;
+}
+
+// Works out the width of each column.
+fn column_widths<R, S> (available: Coord, columns: &[Column], rows: &[R],
+                        options: &TableOptions)
+  -> Vec<Coord>
+  where R: AsRef<[S]>, S: AsRef<str>
+{
+  let mut widths: Vec<Coord> = columns.iter().enumerate().map(|(i, column)| {
+    match column.width {
+      ColumnWidth::Fixed(width) => width.max(0),
+      ColumnWidth::Min(min) => {
+        let header = if options.header { text_width(column.title) } else { 0 };
+        rows.iter()
+          .filter_map(|row| row.as_ref().get(i).map(|cell| text_width(cell.as_ref())))
+          .fold(header.max(min), Coord::max)
+      },
+      ColumnWidth::Weighted(_) => 0,
+    }
+  }).collect();
+
+  let spacing = options.spacing * (columns.len() as Coord - 1).max(0);
+  let mut remaining = available - widths.iter().sum::<Coord>() - spacing;
+  let total_weight: u32 = columns.iter().map(|column| match column.width {
+    ColumnWidth::Weighted(weight) => weight,
+    _ => 0,
+  }).sum();
+
+  if remaining > 0 && total_weight > 0 {
+    let share = remaining;
+    let mut last = None;
+    for (i, column) in columns.iter().enumerate() {
+      if let ColumnWidth::Weighted(weight) = column.width {
+        widths[i] = (share as u64 * weight as u64 / total_weight as u64) as Coord;
+        remaining -= widths[i];
+        if weight > 0 {
+          last = Some(i);
+        }
+      }
+    }
+    // Rounding leftovers go to the last weighted column, so the table fills the rectangle.
+    if let Some(i) = last {
+      widths[i] += remaining;
+    }
+  }
+
+  return widths;
+}
+
+// Draws one line of the table, filling the gaps between and after the cells with `style`.
+fn draw_line<B: Backend> (tb: &mut B, rect: Rect, y: Coord, widths: &[Coord], cells: &[&str],
+                          style: Style, options: &TableOptions)
+{
+  let (fg, bg) = style.attributes();
+  for x in rect.x..rect.x + rect.w {
+    tb.change_cell(x, y, ' ', fg, bg);
+  }
+
+  let mut left = rect.x - options.scroll_x;
+  for (i, &width) in widths.iter().enumerate() {
+    let text = cells.get(i).cloned().unwrap_or("");
+    let mut x = left;
+
+    for ch in text.chars() {
+      let ch_width = (width::char_width(ch) as Coord).max(1);
+      if x + ch_width > left + width {
+        break;
+      }
+      if x >= rect.x && x + ch_width <= rect.x + rect.w {
+        tb.change_cell(x, y, ch, fg, bg);
+      }
+      x += ch_width;
+    }
+
+    left += width + options.spacing;
+  }
+}
+
+// Returns the number of cells a string takes up.
+fn text_width (s: &str) -> Coord {
+  s.chars().map(|ch| (width::char_width(ch) as Coord).max(1)).sum()
+}
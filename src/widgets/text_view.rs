@@ -0,0 +1,380 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use theme::Style;
+use width;
+use {
+  Backend,
+  Coord,
+  KeyEvent,
+  KeyState,
+  Rect,
+  DEFAULT,
+  REVERSE,
+};
+use keys::*;
+
+
+//
+// TextView
+//
+
+
+/// A scrollable view of styled lines, for pagers, log viewers and help screens. Lines are wrapped
+/// to the width of the view unless wrapping is turned off, in which case the view can also be
+/// scrolled sideways. Matches of the search query are drawn with the highlight style.
+///
+/// The scroll position is counted in screen rows, and keys that scroll by a page use the size the
+/// view was last rendered at.
+///
+/// Supported keys are Up/Down, Left/Right when not wrapping, PgUp/PgDn, Space to scroll down a
+/// page, and Home/End.
+///
+/// ~~~
+/// use termbox::{Backend, Rect, DEFAULT};
+/// use termbox::headless::TestTermbox;
+/// use termbox::theme::Style;
+/// use termbox::widgets::TextView;
+///
+/// let style = Style::new(DEFAULT, DEFAULT, 0);
+/// let mut view = TextView::new();
+/// view.push_line("the quick brown fox", style);
+/// view.push_line("jumps", style);
+///
+/// let mut tb = TestTermbox::new(10, 2);
+/// view.render(&mut tb, Rect::new(0, 0, 10, 2));
+/// assert_eq!(tb.snapshot().to_plain_text(), "the quick\nbrown fox\n");
+/// view.scroll_by(1);
+/// view.render(&mut tb, Rect::new(0, 0, 10, 2));
+/// assert_eq!(tb.snapshot().to_plain_text(), "brown fox\njumps\n");
+/// ~~~
+pub struct TextView {
+  lines: Vec<Vec<(char, Style)>>,
+  wrap: bool,
+  scroll_y: usize,
+  scroll_x: usize,
+  query: Vec<char>,
+  highlight_style: Style,
+  size: (Coord, Coord),
+}
+
+impl TextView {
+  /// Removes all lines and scrolls back to the top.
+  pub fn clear (&mut self) {
+    self.lines.clear();
+    self.scroll_y = 0;
+    self.scroll_x = 0;
+  }
+
+  /// Scrolls from a key event. Returns `false` if the key was not used by the view. Key releases
+  /// are never used.
+  pub fn handle_key (&mut self, event: &KeyEvent) -> bool {
+    if event.alt || event.state == KeyState::Release {
+      return false;
+    }
+
+    let page = (self.size.1 as isize).max(1);
+    match (event.ch, event.key) {
+      (None, KEY_ARROW_UP) => { self.scroll_by(-1); },
+      (None, KEY_ARROW_DOWN) => { self.scroll_by(1); },
+      (None, KEY_ARROW_LEFT) if !self.wrap => {
+        self.scroll_x = self.scroll_x.saturating_sub(1);
+      },
+      (None, KEY_ARROW_RIGHT) if !self.wrap => { self.scroll_x += 1; },
+      (None, KEY_PGUP) => { self.scroll_by(-page); },
+      (None, KEY_PGDN) | (None, KEY_SPACE) | (Some(' '), _) => { self.scroll_by(page); },
+      (None, KEY_HOME) => {
+        self.scroll_y = 0;
+        self.scroll_x = 0;
+      },
+      (None, KEY_END) => { self.scroll_y = self.max_scroll(); },
+      _ => { return false; },
+    }
+
+    return true;
+  }
+
+  pub fn highlight_style (&self) -> Style {
+    self.highlight_style
+  }
+
+  pub fn is_empty (&self) -> bool {
+    self.lines.is_empty()
+  }
+
+  /// Returns the number of lines, before wrapping.
+  pub fn line_count (&self) -> usize {
+    self.lines.len()
+  }
+
+  pub fn new () -> TextView {
+    TextView {
+      lines: Vec::new(),
+      wrap: true,
+      scroll_y: 0,
+      scroll_x: 0,
+      query: Vec::new(),
+      highlight_style: Style::new(DEFAULT, DEFAULT, REVERSE),
+      size: (0, 0),
+    }
+  }
+
+  /// Adds a line drawn in a single style. Tabs are not expanded and newlines are not treated
+  /// specially, so text should be split into lines first.
+  pub fn push_line (&mut self, text: &str, style: Style) {
+    self.lines.push(text.chars().map(|ch| (ch, style)).collect());
+  }
+
+  /// Adds a line made up of differently styled pieces.
+  pub fn push_spans (&mut self, spans: &[(&str, Style)]) {
+    let mut line = Vec::new();
+    for &(text, style) in spans {
+      line.extend(text.chars().map(|ch| (ch, style)));
+    }
+    self.lines.push(line);
+  }
+
+  /// Returns the search query, which is empty when nothing is highlighted.
+  pub fn query (&self) -> String {
+    self.query.iter().cloned().collect()
+  }
+
+  /// Draws the visible part of the text into `rect`. Rows below the end of the text are cleared.
+  pub fn render<B: Backend> (&mut self, tb: &mut B, rect: Rect) {
+    self.size = (rect.w.max(0), rect.h.max(0));
+    self.scroll_y = self.scroll_y.min(self.max_scroll());
+
+    let rows = self.rows();
+    for row in 0..rect.h {
+      let y = rect.y + row;
+      let (line, start, end) = match rows.get(self.scroll_y + row as usize) {
+        Some(&row) => row,
+        None => {
+          for col in 0..rect.w {
+            tb.change_cell(rect.x + col, y, ' ', DEFAULT, DEFAULT);
+          }
+          continue;
+        },
+      };
+
+      let matches = self.matches(line);
+      let skip = if self.wrap { 0 } else { self.scroll_x };
+      let mut col = 0;
+      let mut x = rect.x;
+      for (i, &(ch, style)) in self.lines[line][start..end].iter().enumerate() {
+        let ch_width = width::char_width(ch).max(1);
+        if col < skip {
+          col += ch_width;
+          continue;
+        }
+        if x + ch_width as Coord > rect.x + rect.w {
+          break;
+        }
+        let style = if matches.iter().any(|&(a, b)| start + i >= a && start + i < b) {
+          self.highlight_style
+        } else {
+          style
+        };
+        let (fg, bg) = style.attributes();
+        tb.change_cell(x, y, ch, fg, bg);
+        col += ch_width;
+        x += ch_width as Coord;
+      }
+      while x < rect.x + rect.w {
+        tb.change_cell(x, y, ' ', DEFAULT, DEFAULT);
+        x += 1;
+      }
+    }
+  }
+
+  /// Returns the number of rows scrolled past at the top.
+  pub fn scroll (&self) -> usize {
+    self.scroll_y
+  }
+
+  /// Scrolls down by `rows`, or up if it is negative, stopping at the top and bottom.
+  pub fn scroll_by (&mut self, rows: isize) {
+    let scroll = if rows < 0 {
+      self.scroll_y.saturating_sub(rows.unsigned_abs())
+    } else {
+      self.scroll_y + rows as usize
+    };
+    self.scroll_y = scroll.min(self.max_scroll());
+  }
+
+  /// Scrolls so that `row` is at the top, or as close as the end of the text allows.
+  pub fn scroll_to (&mut self, row: usize) {
+    self.scroll_y = row.min(self.max_scroll());
+  }
+
+  /// Scrolls forward to the next line after the top row that contains the search query. Returns
+  /// `false`, without scrolling, if there is no such line.
+  pub fn search_next (&mut self) -> bool {
+    let rows = self.rows();
+    let top = rows.get(self.scroll_y).map_or(0, |&(line, _, _)| line);
+    let found = (top + 1..self.lines.len()).find(|&line| !self.matches(line).is_empty());
+    return self.scroll_to_line(&rows, found);
+  }
+
+  /// Scrolls back to the previous line before the top row that contains the search query. Returns
+  /// `false`, without scrolling, if there is no such line.
+  pub fn search_previous (&mut self) -> bool {
+    let rows = self.rows();
+    let top = rows.get(self.scroll_y).map_or(self.lines.len(), |&(line, _, _)| line);
+    let found = (0..top).rev().find(|&line| !self.matches(line).is_empty());
+    return self.scroll_to_line(&rows, found);
+  }
+
+  pub fn set_highlight_style (&mut self, style: Style) {
+    self.highlight_style = style;
+  }
+
+  /// Sets the text to highlight. An empty query turns highlighting off.
+  pub fn set_query (&mut self, query: &str) {
+    self.query = query.chars().collect();
+  }
+
+  /// Restores the scroll position, wrapping and search query saved with `state`. The scroll
+  /// position is kept within the text the next time the view is rendered.
+  pub fn set_state (&mut self, state: &TextViewState) {
+    self.wrap = state.wrap;
+    self.scroll_y = state.scroll;
+    self.scroll_x = if state.wrap { 0 } else { state.scroll_x };
+    self.set_query(&state.query);
+  }
+
+  /// Turns wrapping of long lines on or off. Lines are wrapped by default.
+  pub fn set_wrap (&mut self, wrap: bool) {
+    self.wrap = wrap;
+    self.scroll_x = 0;
+    self.scroll_y = self.scroll_y.min(self.max_scroll());
+  }
+
+  /// Returns the scroll position, wrapping and search query, for restoring with `set_state`. The
+  /// lines themselves are not included.
+  pub fn state (&self) -> TextViewState {
+    TextViewState {
+      scroll: self.scroll_y,
+      scroll_x: self.scroll_x,
+      wrap: self.wrap,
+      query: self.query(),
+    }
+  }
+
+  pub fn wrap (&self) -> bool {
+    self.wrap
+  }
+
+  // Returns the scroll position that shows the last row at the bottom of the view.
+  fn max_scroll (&self) -> usize {
+    self.rows().len().saturating_sub(self.size.1 as usize)
+  }
+
+  // Returns the character ranges of `line` that match the search query.
+  fn matches (&self, line: usize) -> Vec<(usize, usize)> {
+    let chars = &self.lines[line];
+    let len = self.query.len();
+    let mut matches = Vec::new();
+    if len == 0 || len > chars.len() {
+      return matches;
+    }
+
+    let mut start = 0;
+    while start + len <= chars.len() {
+      if chars[start..start + len].iter().zip(&self.query).all(|(&(ch, _), &q)| ch == q) {
+        matches.push((start, start + len));
+        start += len;
+      } else {
+        start += 1;
+      }
+    }
+    return matches;
+  }
+
+  // Splits the lines into screen rows of (line, start, end), with character ranges. Lines are
+  // broken after the last space that fits, or anywhere if a word is too long for a row.
+  fn rows (&self) -> Vec<(usize, usize, usize)> {
+    let width = self.size.0 as usize;
+    let mut rows = Vec::new();
+
+    for (index, line) in self.lines.iter().enumerate() {
+      if !self.wrap || width == 0 {
+        rows.push((index, 0, line.len()));
+        continue;
+      }
+
+      let mut start = 0;
+      loop {
+        let mut col = 0;
+        let mut end = start;
+        let mut space = None;
+        while end < line.len() {
+          let ch_width = width::char_width(line[end].0).max(1);
+          if col + ch_width > width {
+            break;
+          }
+          if line[end].0 == ' ' {
+            space = Some(end + 1);
+          }
+          col += ch_width;
+          end += 1;
+        }
+
+        if end < line.len() {
+          if let Some(space) = space {
+            end = space;
+          }
+          // Always make progress, even if a single character is wider than the view.
+          end = end.max(start + 1);
+        }
+        rows.push((index, start, end));
+        if end >= line.len() {
+          break;
+        }
+        start = end;
+      }
+    }
+
+    return rows;
+  }
+
+  // Scrolls to the first row of `line`. Returns `false` if there is no line.
+  fn scroll_to_line (&mut self, rows: &[(usize, usize, usize)], line: Option<usize>) -> bool {
+    let line = match line {
+      Some(line) => line,
+      None => { return false; },
+    };
+    if let Some(row) = rows.iter().position(|&(index, _, _)| index == line) {
+      self.scroll_to(row);
+    }
+    return true;
+  }
+}
+
+impl Default for TextView {
+  fn default () -> TextView {
+    TextView::new()
+  }
+}
+
+
+//
+// TextViewState
+//
+
+
+/// The state of a `TextView` that changes as it is used, so that it can be saved and restored
+/// later. With the `serde` feature, it can be serialized.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextViewState {
+  /// The number of rows scrolled past at the top. Rows depend on the width of the view when lines
+  /// are wrapped, so a different line may be at the top if the width has changed.
+  pub scroll: usize,
+  /// The number of columns scrolled past on the left, when lines aren't wrapped.
+  pub scroll_x: usize,
+  pub wrap: bool,
+  /// The search query, which is empty when nothing is highlighted.
+  pub query: String,
+}
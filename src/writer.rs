@@ -0,0 +1,134 @@
+// Copyright (c) 2015, <daggerbot@gmail.com>
+// This software is available under the terms of the zlib license.
+// See COPYING.TXT for more information.
+
+use std::fmt;
+
+use theme::Style;
+use width;
+use {
+  Attribute,
+  Backend,
+  Coord,
+  Overflow,
+  PutOptions,
+};
+
+
+//
+// TermWriter
+//
+
+
+/// Writes formatted text straight into the output buffer, so that `write!` can be used instead of
+/// formatting into a `String` first. Text starts at the position the writer was created at, and
+/// each write continues where the previous one stopped. Newlines return to the starting column on
+/// the next row. Text that reaches the right edge is clipped or wrapped as selected with
+/// `options`. Created by `Termbox::writer_at`.
+///
+/// ~~~
+/// use std::fmt::Write;
+/// use termbox::{Overflow, PutOptions, TermWriter, DEFAULT};
+/// use termbox::headless::TestTermbox;
+/// use termbox::theme::Style;
+///
+/// let mut tb = TestTermbox::new(8, 3);
+/// {
+///   let options = PutOptions { overflow: Overflow::Wrap, ..PutOptions::default() };
+///   let mut writer = TermWriter::new(&mut tb, 1, 0, Style::new(DEFAULT, DEFAULT, 0))
+///     .options(options);
+///   write!(writer, "{} items", 12).unwrap();
+/// }
+/// assert_eq!(tb.snapshot().to_plain_text(), " 12 item\n s\n\n");
+/// ~~~
+pub struct TermWriter<'a, B: 'a + Backend + ?Sized> {
+  tb: &'a mut B,
+  left: Coord,
+  col: Coord,
+  row: Coord,
+  fg: Attribute,
+  bg: Attribute,
+  options: PutOptions,
+  clipped: bool,
+}
+
+impl<'a, B: 'a + Backend + ?Sized> TermWriter<'a, B> {
+  /// Creates a writer that starts at `(x, y)` and draws with `style`. Text is clipped at the right
+  /// edge until `options` says otherwise.
+  pub fn new (tb: &'a mut B, x: Coord, y: Coord, style: Style) -> TermWriter<'a, B> {
+    let (fg, bg) = style.attributes();
+
+    TermWriter {
+      tb: tb,
+      left: x,
+      col: x,
+      row: y,
+      fg: fg,
+      bg: bg,
+      options: PutOptions::default(),
+      clipped: false,
+    }
+  }
+
+  /// Sets how text reaching the right edge and tabs are handled.
+  pub fn options (mut self, options: PutOptions) -> TermWriter<'a, B> {
+    self.options = options;
+    return self;
+  }
+
+  /// Returns the position where the next character will be written.
+  pub fn position (&self) -> (Coord, Coord) {
+    (self.col, self.row)
+  }
+}
+
+impl<'a, B: 'a + Backend + ?Sized> fmt::Write for TermWriter<'a, B> {
+  fn write_str (&mut self, s: &str) -> fmt::Result {
+    let (width, height) = (self.tb.width(), self.tb.height());
+    let wrap = self.options.overflow == Overflow::Wrap;
+
+    for ch in s.chars() {
+      if ch == '\n' {
+        self.col = self.left;
+        self.row += 1;
+        self.clipped = false;
+        continue;
+      }
+      if self.clipped {
+        continue;
+      }
+
+      let tab_width = self.options.tab_width;
+      let (ch, count) = if ch == '\t' && tab_width > 0 {
+        (' ', tab_width - (self.col - self.left) % tab_width)
+      } else {
+        (ch, 1)
+      };
+      let ch_width = (width::char_width(ch) as Coord).max(1);
+
+      for _ in 0..count {
+        if self.col + ch_width > width {
+          // Drop the rest of the line if even a fresh line is too narrow for this character.
+          if !wrap || self.col == self.left {
+            self.clipped = true;
+            break;
+          }
+          self.col = self.left;
+          self.row += 1;
+        }
+        // Later lines can't be visible either, but the position is still kept up to date.
+        if self.row >= height {
+          self.col += ch_width;
+          continue;
+        }
+
+        if self.col >= 0 && self.row >= 0 {
+          self.tb.change_cell(self.col, self.row, ch, self.fg, self.bg);
+        }
+        self.col += ch_width;
+      }
+    }
+
+    return Ok(());
+  }
+}